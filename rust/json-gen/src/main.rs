@@ -143,6 +143,8 @@ fn main() {
     gen_json_schema!(PlutusV1Scripts);
     gen_json_schema!(PlutusV2Script);
     gen_json_schema!(PlutusV2Scripts);
+    gen_json_schema!(PlutusV3Script);
+    gen_json_schema!(PlutusV3Scripts);
     gen_json_schema!(CostModel);
     gen_json_schema!(Costmdls);
     gen_json_schema!(ExUnitPrices);
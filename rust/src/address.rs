@@ -2,7 +2,6 @@ use super::*;
 use cbor_event::{de::Deserializer, se::Serializer};
 use bech32::ToBase32;
 use crate::{ledger::common::{value::{to_bignum, from_bignum}}, byron::{ProtocolMagic, ByronAddress}};
-use wasm_bindgen::JsValue;
 
 // returns (Number represented, bytes read) if valid encoding
 // or None if decoding prematurely finished
@@ -71,7 +70,7 @@ pub enum StakeCredType {
 
 
 #[repr(u8)]
-#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize, JsonSchema)]
 pub enum StakeCredKind {
     Key,
     Script,
@@ -239,7 +238,7 @@ impl JsonSchema for Address {
 /// ex: base address isn't 0b0000_0000 but instead 0b0000
 /// Use `header_matches_kind` if you don't want to implement the bitwise operators yourself
 
-#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize, JsonSchema)]
 #[repr(u8)]
 pub enum AddressHeaderKind {
     BasePaymentKeyStakeKey = 0b0000,
@@ -457,7 +456,7 @@ impl Address {
         })().map_err(|e| e.annotate("Address"))
     }
 
-    pub fn to_bech32(&self, prefix: Option<String>) -> Result<String, JsError> {
+    pub fn to_bech32(&self, prefix: Option<String>) -> Result<String, Error> {
         let final_prefix = match prefix {
             Some(prefix) => prefix,
             None => {
@@ -474,11 +473,11 @@ impl Address {
             }
         };
         bech32::encode(&final_prefix, self.to_bytes().to_base32())
-            .map_err(|e| JsError::from_str(&format! {"{:?}", e}))
+            .map_err(|e| Error::from_str(&format! {"{:?}", e}))
     }
 
-    pub fn from_bech32(bech_str: &str) -> Result<Address, JsError> {
-        let (_hrp, u5data) = bech32::decode(bech_str).map_err(|e| JsError::from_str(&e.to_string()))?;
+    pub fn from_bech32(bech_str: &str) -> Result<Address, Error> {
+        let (_hrp, u5data) = bech32::decode(bech_str).map_err(|e| Error::from_str(&e.to_string()))?;
         let data: Vec<u8> = bech32::FromBase32::from_base32(&u5data).unwrap();
         Ok(Self::from_bytes_impl(data.as_ref())?)
     }
@@ -501,7 +500,7 @@ impl Address {
         Self::is_valid_bech32(bech_str) || Self::is_valid_byron(bech_str)
     }
 
-    pub fn network_id(&self) -> Result<u8, JsError> {
+    pub fn network_id(&self) -> Result<u8, Error> {
         match &self.variant {
             AddrType::Base(a) => Ok(a.network),
             AddrType::Enterprise(a) => Ok(a.network),
@@ -0,0 +1,243 @@
+//! Inspection of arbitrary address strings, in the spirit of `cardano-address address inspect`
+
+use schemars::JsonSchema;
+
+use crate::address::{Address, AddressHeaderKind, StakeCredKind, StakeCredential};
+use crate::byron::{AddrtypeKind, AddressContent, ByronAddress, StakeDistributionKind};
+use crate::error::Error;
+use crate::ledger::common::binary::*;
+use crate::ledger::common::value::from_bignum;
+
+/// How the inspected address was written
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub enum AddressEncoding {
+    Bech32,
+    Base58,
+    Hex,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub enum AddressStyle {
+    Shelley,
+    /// Byron address without a derivation path (Ae2...)
+    Icarus,
+    /// Byron address with an encrypted derivation path (Ddz...)
+    Byron,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct CredentialInspection {
+    pub kind: StakeCredKind,
+    /// hex encoded key hash or script hash
+    pub hash: String,
+}
+
+impl From<&StakeCredential> for CredentialInspection {
+    fn from(credential: &StakeCredential) -> Self {
+        let hash = match (credential.to_keyhash(), credential.to_scripthash()) {
+            (Some(key_hash), _) => key_hash.to_hex(),
+            (None, Some(script_hash)) => script_hash.to_hex(),
+            (None, None) => unreachable!("credentials are either a key hash or a script hash"),
+        };
+        Self { kind: credential.kind(), hash }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct PointerInspection {
+    pub slot: u64,
+    pub tx_index: u64,
+    pub cert_index: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct ByronInspection {
+    /// hex encoded AddressId, the hash of the spending data and attributes
+    pub address_root: String,
+    pub addr_type: AddrtypeKind,
+    /// hex encoded HD payload, encrypted with a key derived from the wallet's root public key
+    pub derivation_path: Option<String>,
+    /// as written in the attributes: None means mainnet
+    pub protocol_magic: Option<u32>,
+    pub stake_distribution: Option<StakeDistributionKind>,
+    /// hex encoded stakeholder id of a single key stake distribution
+    pub stakeholder_id: Option<String>,
+}
+
+/// Everything that can be told about an address without the chain
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct AddressInspection {
+    pub encoding: AddressEncoding,
+    /// human-readable part of bech32 addresses
+    pub bech32_prefix: Option<String>,
+    pub address_style: AddressStyle,
+    pub header: u8,
+    pub header_kind: AddressHeaderKind,
+    /// None for Byron addresses of networks other than mainnet and the public testnet
+    pub network_id: Option<u8>,
+    /// the credential of reward addresses is reported here too
+    pub payment: Option<CredentialInspection>,
+    pub stake: Option<CredentialInspection>,
+    pub pointer: Option<PointerInspection>,
+    pub byron: Option<ByronInspection>,
+    /// hex encoded bytes found after the address, which some old addresses on chain have
+    pub trailing_bytes: Option<String>,
+}
+
+to_from_json!(AddressInspection);
+
+fn header_kind(header: u8) -> Result<AddressHeaderKind, Error> {
+    let kinds = [
+        AddressHeaderKind::BasePaymentKeyStakeKey,
+        AddressHeaderKind::BasePaymentScriptStakeKey,
+        AddressHeaderKind::BasePaymentKeyStakeScript,
+        AddressHeaderKind::BasePaymentScriptStakeScript,
+        AddressHeaderKind::PointerKey,
+        AddressHeaderKind::PointerScript,
+        AddressHeaderKind::EnterpriseKey,
+        AddressHeaderKind::EnterpriseScript,
+        AddressHeaderKind::Byron,
+        AddressHeaderKind::RewardKey,
+        AddressHeaderKind::RewardScript,
+    ];
+    kinds
+        .iter()
+        .find(|kind| Address::header_matches_kind(header, **kind))
+        .cloned()
+        .ok_or_else(|| Error::from_str(&format!("unknown address header {:#010b}", header)))
+}
+
+fn inspect_byron(byron: &ByronAddress) -> Result<ByronInspection, Error> {
+    // ByronAddress::address_content panics on malformed content, which a valid CRC doesn't rule out
+    let content = AddressContent::from_bytes(byron.addr())
+        .map_err(|e| Error::from_str(&format!("invalid Byron address content: {}", e.as_string().unwrap_or_default())))?;
+    let attributes = content.addr_attr();
+    let stake_distribution = attributes.stake_distribution();
+    Ok(ByronInspection {
+        address_root: content.address_id().to_hex(),
+        addr_type: content.addr_type().kind(),
+        derivation_path: attributes.derivation_path().map(|path| hex::encode(path.0)),
+        protocol_magic: attributes.protocol_magic().map(|magic| magic.value()),
+        stake_distribution: stake_distribution.as_ref().map(|distribution| distribution.kind()),
+        stakeholder_id: stake_distribution
+            .and_then(|distribution| distribution.as_single_key_distr())
+            .map(|distribution| distribution.stakeholder_id().to_hex()),
+    })
+}
+
+impl AddressInspection {
+    /// Accepts bech32 (any prefix), Byron base58 or the hex encoded address bytes
+    pub fn inspect(address: &str) -> Result<AddressInspection, Error> {
+        let address = address.trim();
+        if let Ok((prefix, data)) = bech32::decode(address) {
+            let bytes = <Vec<u8> as bech32::FromBase32>::from_base32(&data).map_err(|e| Error::from_str(&e.to_string()))?;
+            let parsed = Address::from_bytes(bytes)
+                .map_err(|e| Error::from_str(&format!("{} is valid bech32 but not an address: {}", prefix, e.as_string().unwrap_or_default())))?;
+            return Self::from_address(&parsed, AddressEncoding::Bech32, Some(prefix));
+        }
+        if let Ok(byron) = ByronAddress::from_base58(address) {
+            return Self::from_address(&byron.to_address(), AddressEncoding::Base58, None);
+        }
+        match hex::decode(address) {
+            Ok(bytes) => {
+                let parsed = Address::from_bytes(bytes)
+                    .map_err(|e| Error::from_str(&format!("hex string is not an address: {}", e.as_string().unwrap_or_default())))?;
+                Self::from_address(&parsed, AddressEncoding::Hex, None)
+            },
+            Err(_) => Err(Error::from_str("not a bech32, Byron base58 or hex encoded address")),
+        }
+    }
+
+    pub fn from_address(address: &Address, encoding: AddressEncoding, bech32_prefix: Option<String>) -> Result<AddressInspection, Error> {
+        let header = address.header();
+        let byron = match address.as_byron() {
+            Some(byron) => Some(inspect_byron(&byron)?),
+            None => None,
+        };
+        let address_style = match &byron {
+            Some(byron) if byron.derivation_path.is_some() => AddressStyle::Byron,
+            Some(_) => AddressStyle::Icarus,
+            None => AddressStyle::Shelley,
+        };
+        Ok(AddressInspection {
+            encoding,
+            bech32_prefix,
+            address_style,
+            header,
+            header_kind: header_kind(header)?,
+            network_id: address.network_id().ok(),
+            payment: address.payment_cred().as_ref().map(CredentialInspection::from),
+            stake: address.staking_cred().as_ref().map(CredentialInspection::from),
+            pointer: address.as_pointer().map(|pointer| {
+                let pointer = pointer.stake_pointer();
+                PointerInspection {
+                    slot: from_bignum(&pointer.slot()),
+                    tx_index: from_bignum(&pointer.tx_index()),
+                    cert_index: from_bignum(&pointer.cert_index()),
+                }
+            }),
+            byron,
+            trailing_bytes: address.trailing.as_ref().map(hex::encode),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::byron::{AddrAttributes, HDAddressPayload, ProtocolMagic};
+    use crate::crypto::Bip32PrivateKey;
+
+    #[test]
+    fn inspect_shelley_addresses() {
+        let base = AddressInspection::inspect("addr1x80de0mz3m9xmgtlmqqzu06s0uvfsczskdec8k7v4jhr7077mjlk9rk2dkshlkqq9cl4qlccnps9pvmns0duet9w8ulsylzv28").unwrap();
+        assert_eq!(base.encoding, AddressEncoding::Bech32);
+        assert_eq!(base.bech32_prefix.as_deref(), Some("addr"));
+        assert_eq!(base.header_kind, AddressHeaderKind::BasePaymentScriptStakeScript);
+        assert_eq!(base.network_id, Some(1));
+        assert_eq!(base.payment.as_ref().unwrap().kind, StakeCredKind::Script);
+        assert_eq!(base.stake.as_ref().unwrap().hash, "dedcbf628eca6da17fd8002e3f507f18986050b37383dbccacae3f3f");
+        assert_eq!(AddressInspection::from_json(&base.to_json().unwrap()).unwrap(), base);
+
+        let pointer = AddressInspection::inspect(" addr1gx2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer5ph3wczvf2w8lunk\n").unwrap();
+        assert_eq!(pointer.header_kind, AddressHeaderKind::PointerKey);
+        assert_eq!(pointer.pointer, Some(PointerInspection { slot: 24157, tx_index: 177, cert_index: 42 }));
+        assert_eq!(pointer.stake, None);
+
+        let reward = Address::from_bech32("stake1uyevw2xnsc0pvn9t9r9c7qryfqfeerchgrlm3ea2nefr9hqxdekzz").unwrap();
+        let from_hex = AddressInspection::inspect(&hex::encode(reward.to_bytes())).unwrap();
+        assert_eq!(from_hex.encoding, AddressEncoding::Hex);
+        assert_eq!(from_hex.header_kind, AddressHeaderKind::RewardKey);
+        assert_eq!(from_hex.payment.unwrap().kind, StakeCredKind::Key);
+
+        assert!(AddressInspection::inspect("not an address").is_err());
+        // valid bech32 which isn't an address
+        assert!(AddressInspection::inspect("pool1pu5jlj4q9w9jlxeu370a3c9myx47md5j5m2str0naunn2q3lkdy").is_err());
+    }
+
+    #[test]
+    fn inspect_byron_addresses() {
+        let icarus = AddressInspection::inspect("Ae2tdPwUPEZHtBmjZBF4YpMkK9tMSPTE2ADEZTPN97saNkhG78TvXdp3GDk").unwrap();
+        assert_eq!(icarus.encoding, AddressEncoding::Base58);
+        assert_eq!(icarus.address_style, AddressStyle::Icarus);
+        assert_eq!(icarus.header_kind, AddressHeaderKind::Byron);
+        assert_eq!(icarus.network_id, Some(1));
+        let byron = icarus.byron.unwrap();
+        assert_eq!(byron.addr_type, AddrtypeKind::ATPubKey);
+        assert_eq!(byron.protocol_magic, None);
+        assert_eq!(byron.stake_distribution, None);
+
+        // Daedalus style address on a testnet with its own protocol magic
+        let xpub = Bip32PrivateKey::from_bip39_entropy(&[0x0c; 16], &[]).to_public();
+        let attributes = AddrAttributes::new_bootstrap_era(Some(HDAddressPayload(vec![0x0d; 28])), Some(ProtocolMagic::new(2)));
+        let content = AddressContent::new_simple(&xpub, Some(ProtocolMagic::new(2)));
+        let content = AddressContent::new(&content.address_id(), &attributes, &content.addr_type());
+        let daedalus = AddressInspection::inspect(&content.to_address().to_base58()).unwrap();
+        assert_eq!(daedalus.address_style, AddressStyle::Byron);
+        assert_eq!(daedalus.network_id, None);
+        let byron = daedalus.byron.unwrap();
+        assert_eq!(byron.derivation_path, Some(hex::encode([0x0d; 28])));
+        assert_eq!(byron.protocol_magic, Some(2));
+        assert_eq!(byron.address_root, content.address_id().to_hex());
+    }
+}
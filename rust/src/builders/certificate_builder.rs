@@ -2,6 +2,7 @@ use crate::*;
 use crate::builders::witness_builder::{InputAggregateWitnessData, PartialPlutusWitness};
 use std::collections::{HashSet};
 
+use super::error::BuilderError;
 use super::witness_builder::{RequiredWitnessSet, NativeScriptWitnessInfo};
 
 // comes from witsVKeyNeeded in the Ledger spec
@@ -45,18 +46,18 @@ pub fn cert_required_wits(cert_enum: &Certificate, required_witnesses: &mut Requ
 }
 
 // comes from witsVKeyNeeded in the Ledger spec
-pub fn add_cert_vkeys(cert_enum: &Certificate, vkeys: &mut HashSet<Ed25519KeyHash>) -> Result<(), JsError> {
+pub fn add_cert_vkeys(cert_enum: &Certificate, vkeys: &mut HashSet<Ed25519KeyHash>) -> Result<(), BuilderError> {
     match &cert_enum.0 {
         // stake key registrations do not require a witness
         CertificateEnum::StakeRegistration(_cert) => {},
         CertificateEnum::StakeDeregistration(cert) => match cert.stake_credential().kind() {
-            StakeCredKind::Script => return Err(JsError::from_str(&format!("Deregistration certificate contains script. Expected public key hash.\n{:#?}", cert.to_json()))),
+            StakeCredKind::Script => return Err(BuilderError::ScriptWitnessRequired(cert.stake_credential().to_scripthash().unwrap())),
             StakeCredKind::Key => {
                 vkeys.insert(cert.stake_credential().to_keyhash().unwrap());
             }
         },
         CertificateEnum::StakeDelegation(cert) => match cert.stake_credential().kind() {
-            StakeCredKind::Script => return Err(JsError::from_str(&format!("Delegation certificate contains script. Expected public key hash.\n{:#?}", cert.to_json()))),
+            StakeCredKind::Script => return Err(BuilderError::ScriptWitnessRequired(cert.stake_credential().to_scripthash().unwrap())),
             StakeCredKind::Key => {
                 vkeys.insert(cert.stake_credential().to_keyhash().unwrap());
             }
@@ -115,12 +116,12 @@ impl SingleCertificateBuilder {
         }
     }
 
-    pub fn payment_key(&self) -> Result<CertificateBuilderResult, JsError> {
+    pub fn payment_key(&self) -> Result<CertificateBuilderResult, BuilderError> {
         let mut required_wits = RequiredWitnessSet::default();
         cert_required_wits(&self.cert, &mut required_wits);
 
-        if !required_wits.scripts.is_empty() {
-            return Err(JsError::from_str(&format!("Certificate required a script, not a payment key: \n{:#?}", self.cert.to_json())));
+        if let Some(script_hash) = required_wits.scripts.iter().next() {
+            return Err(BuilderError::ScriptWitnessRequired(script_hash.clone()));
         }
 
 
@@ -132,7 +133,7 @@ impl SingleCertificateBuilder {
     }
 
     /** Signer keys don't have to be set. You can leave it empty and then add the required witnesses later */
-    pub fn native_script(&self, native_script: &NativeScript, witness_info: &NativeScriptWitnessInfo) -> Result<CertificateBuilderResult, JsError> {
+    pub fn native_script(&self, native_script: &NativeScript, witness_info: &NativeScriptWitnessInfo) -> Result<CertificateBuilderResult, BuilderError> {
         let mut required_wits = RequiredWitnessSet::default();
         cert_required_wits(&self.cert, &mut required_wits);
         let mut required_wits_left = required_wits.clone();
@@ -145,7 +146,7 @@ impl SingleCertificateBuilder {
         required_wits_left.scripts.remove(&native_script.hash());
 
         if !required_wits_left.scripts.is_empty() {
-            return Err(JsError::from_str(&format!("Missing the following witnesses for the certificate: \n{:#?}", required_wits_left.to_str()))); 
+            return Err(BuilderError::MissingWitnesses(Box::new(required_wits_left))); 
         }
 
         Ok(CertificateBuilderResult {
@@ -155,7 +156,7 @@ impl SingleCertificateBuilder {
         })
     }
 
-    pub fn plutus_script(&self, partial_witness: &PartialPlutusWitness, required_signers: &RequiredSigners) -> Result<CertificateBuilderResult, JsError> {
+    pub fn plutus_script(&self, partial_witness: &PartialPlutusWitness, required_signers: &RequiredSigners) -> Result<CertificateBuilderResult, BuilderError> {
         let mut required_wits = RequiredWitnessSet::default();
         required_signers.0.iter().for_each(|required_signer| required_wits.add_vkey_key_hash(required_signer));
         cert_required_wits(&self.cert, &mut required_wits);
@@ -174,7 +175,7 @@ impl SingleCertificateBuilder {
         required_wits_left.scripts.remove(&script_hash);
 
         if required_wits_left.len() > 0 {
-            return Err(JsError::from_str(&format!("Missing the following witnesses for the certificate: \n{:#?}", required_wits_left.to_str())));
+            return Err(BuilderError::MissingWitnesses(Box::new(required_wits_left)));
         }
 
         Ok(CertificateBuilderResult {
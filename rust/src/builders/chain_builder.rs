@@ -0,0 +1,267 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::*;
+use crate::ledger::common::utxo::{TransactionUnspentOutput, TransactionUnspentOutputs};
+
+use super::error::BuilderError;
+use super::input_builder::{InputBuilderResult, SingleInputBuilder};
+use super::tx_builder::{CoinSelectionStrategyCIP2, SignedTxBuilder, TransactionBuilder};
+
+#[derive(Clone, Debug)]
+struct ChainLink {
+    builder: TransactionBuilder,
+    strategy: CoinSelectionStrategyCIP2,
+    change_address: Address,
+}
+
+
+/// Builds a sequence of transactions where later ones can spend the outputs of earlier ones
+/// before any of them is submitted (e.g. batched payouts)
+///
+/// Each transaction is given as a template: a TransactionBuilder with its outputs, certs, etc. but without change.
+/// On build, the templates are completed in order: inputs are selected from the chain's UTxO pool
+/// (the UTxOs added with add_utxo plus the outputs of the previous transactions that are locked by a key
+/// and sent to the address of one of those UTxOs or to a change address),
+/// change is added and the inputs used are removed from the pool.
+/// Since templates are kept, replacing one with set_transaction and building again recomputes
+/// every transaction after it against the new outputs.
+#[derive(Clone, Debug, Default)]
+pub struct TransactionChainBuilder {
+    utxos: Vec<InputBuilderResult>,
+    links: Vec<ChainLink>,
+}
+
+
+impl TransactionChainBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a UTxO that any transaction of the chain can select
+    pub fn add_utxo(&mut self, result: &InputBuilderResult) {
+        self.utxos.push(result.clone());
+    }
+
+    /// Appends a transaction to the chain and returns its index
+    /// If no fee was set on the builder, change goes to {change_address}
+    pub fn add_transaction(&mut self, builder: &TransactionBuilder, strategy: CoinSelectionStrategyCIP2, change_address: &Address) -> usize {
+        self.links.push(ChainLink {
+            builder: builder.clone(),
+            strategy,
+            change_address: change_address.clone(),
+        });
+        self.links.len() - 1
+    }
+
+    /// Replaces the template of a transaction. The transactions after it are recomputed on the next build
+    pub fn set_transaction(&mut self, index: usize, builder: &TransactionBuilder, strategy: CoinSelectionStrategyCIP2, change_address: &Address) -> Result<(), BuilderError> {
+        let len = self.links.len();
+        let link = self.links
+            .get_mut(index)
+            .ok_or(BuilderError::ChainIndexOutOfRange { index, len })?;
+        *link = ChainLink {
+            builder: builder.clone(),
+            strategy,
+            change_address: change_address.clone(),
+        };
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    pub fn build(&self) -> Result<TransactionChain, BuilderError> {
+        let mut pool = self.utxos.clone();
+        // input -> index of the transaction of the chain spending it
+        let mut spent = BTreeMap::<TransactionInput, usize>::new();
+        let mut transactions = Vec::with_capacity(self.links.len());
+        let owned = self.utxos
+            .iter()
+            .map(|utxo| utxo.utxo_info.address.clone())
+            .chain(self.links.iter().map(|link| link.change_address.clone()))
+            .collect::<Vec<_>>();
+        for (index, link) in self.links.iter().enumerate() {
+            let mut builder = link.builder.clone();
+            // inputs added explicitly to the template must not have been spent by an earlier transaction
+            for utxo in builder.inputs.iter() {
+                if let Some(spent_by) = spent.get(&utxo.input) {
+                    return Err(BuilderError::ChainInputAlreadySpent {
+                        index,
+                        input: utxo.input.clone(),
+                        spent_by: *spent_by,
+                    });
+                }
+            }
+            let explicit_inputs = builder.inputs.iter().map(|utxo| utxo.input.clone()).collect::<BTreeSet<_>>();
+            builder.utxos.retain(|utxo| !spent.contains_key(&utxo.input));
+            builder.utxos.extend(pool.iter().filter(|utxo| !explicit_inputs.contains(&utxo.input)).cloned());
+            let in_chain = |error| BuilderError::ChainTransaction { index, error: Box::new(error) };
+            builder.select_utxos(link.strategy).map_err(in_chain)?;
+            if builder.get_fee_if_set().is_none() {
+                builder.add_change_if_needed(&link.change_address).map_err(in_chain)?;
+            }
+            let signed = builder.build()
+                .and_then(|builder| builder.build())
+                .map_err(in_chain)?;
+
+            for input in signed.body().inputs.0.iter() {
+                spent.insert(input.clone(), index);
+            }
+            pool.retain(|utxo| !spent.contains_key(&utxo.input));
+            // outputs paid to others aren't ours to spend, and script-locked ones need extra witness information
+            for utxo in signed.produced_utxos().0.iter().filter(|utxo| owned.contains(&utxo.output.address)) {
+                if let Ok(result) = SingleInputBuilder::new(&utxo.input, &utxo.output).payment_key() {
+                    pool.push(result);
+                }
+            }
+            transactions.push(signed);
+        }
+        Ok(TransactionChain {
+            transactions,
+            utxos: TransactionUnspentOutputs(
+                pool.into_iter()
+                    .map(|utxo| TransactionUnspentOutput::new(&utxo.input, &utxo.utxo_info))
+                    .collect()
+            ),
+        })
+    }
+}
+
+
+/// Transactions of a chain in the order they must be submitted in
+/// Each one still needs to be signed
+#[derive(Clone)]
+pub struct TransactionChain {
+    transactions: Vec<SignedTxBuilder>,
+    utxos: TransactionUnspentOutputs,
+}
+
+
+impl TransactionChain {
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> SignedTxBuilder {
+        self.transactions[index].clone()
+    }
+
+    /// UTxOs of the pool that are left once every transaction of the chain is on chain
+    pub fn unspent_utxos(&self) -> TransactionUnspentOutputs {
+        self.utxos.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::output_builder::TransactionOutputBuilder;
+    use crate::builders::tx_builder::{TransactionBuilderConfig, TransactionBuilderConfigBuilder};
+    use crate::ledger::alonzo::fees::LinearFee;
+    use crate::ledger::common::hash::hash_transaction;
+    use crate::ledger::common::value::{to_bignum, Value};
+    use crate::ledger::emulator::Emulator;
+    use crate::ledger::shelley::witness::make_vkey_witness;
+
+    fn config() -> TransactionBuilderConfig {
+        TransactionBuilderConfigBuilder::new()
+            .fee_algo(&LinearFee::new(&to_bignum(44), &to_bignum(155381)))
+            .pool_deposit(&to_bignum(500000000))
+            .key_deposit(&to_bignum(2000000))
+            .max_value_size(5000)
+            .max_tx_size(16384)
+            .coins_per_utxo_byte(&to_bignum(4310))
+            .ex_unit_prices(&ExUnitPrices::new(
+                &SubCoin::new(&to_bignum(577), &to_bignum(10000)),
+                &SubCoin::new(&to_bignum(721), &to_bignum(10000000)),
+            ))
+            .collateral_percentage(150)
+            .max_collateral_inputs(3)
+            .build()
+            .unwrap()
+    }
+
+    fn address(key: &PrivateKey) -> Address {
+        EnterpriseAddress::new(0, &StakeCredential::from_keyhash(&key.to_public().hash())).to_address()
+    }
+
+    fn payment(to: &Address, amount: u64) -> TransactionBuilder {
+        let mut builder = TransactionBuilder::new(&config());
+        builder.add_output(&TransactionOutputBuilder::new()
+            .with_address(to)
+            .next().unwrap()
+            .with_coin(&to_bignum(amount))
+            .build().unwrap()
+        ).unwrap();
+        builder
+    }
+
+    fn submit_all(emulator: &mut Emulator, chain: &TransactionChain, key: &PrivateKey) {
+        for i in 0..chain.len() {
+            let mut tx = chain.get(i);
+            tx.add_vkey(&make_vkey_witness(&hash_transaction(&tx.body()), key));
+            emulator.submit(&tx.build_checked().unwrap()).unwrap();
+        }
+    }
+
+    #[test]
+    fn chain_spends_previous_change() {
+        let payer = PrivateKey::from_normal_bytes(&[1; 32]).unwrap();
+        let (alice, bob) = (address(&PrivateKey::from_normal_bytes(&[2; 32]).unwrap()), address(&PrivateKey::from_normal_bytes(&[3; 32]).unwrap()));
+        let mut emulator = Emulator::new(&config(), 100).unwrap();
+        let funds = emulator.fund(&address(&payer), &Value::new(&to_bignum(100_000_000)));
+
+        let mut chain_builder = TransactionChainBuilder::new();
+        chain_builder.add_utxo(&SingleInputBuilder::new(&funds.input(), &funds.output()).payment_key().unwrap());
+        let first = chain_builder.add_transaction(&payment(&alice, 30_000_000), CoinSelectionStrategyCIP2::LargestFirst, &address(&payer));
+        chain_builder.add_transaction(&payment(&bob, 20_000_000), CoinSelectionStrategyCIP2::LargestFirst, &address(&payer));
+
+        let chain = chain_builder.build().unwrap();
+        let first_outputs = chain.get(0).produced_utxos();
+        assert_eq!(first_outputs.len(), 2);
+        assert_eq!(chain.get(1).body().inputs().get(0), first_outputs.get(1).input());
+        assert_eq!(chain.unspent_utxos().len(), 1);
+        submit_all(&mut emulator.clone(), &chain, &payer);
+
+        // changing the first payment changes its hash so the second one must be recomputed
+        chain_builder.set_transaction(first, &payment(&alice, 40_000_000), CoinSelectionStrategyCIP2::LargestFirst, &address(&payer)).unwrap();
+        let rebuilt = chain_builder.build().unwrap();
+        assert_ne!(hash_transaction(&rebuilt.get(0).body()), hash_transaction(&chain.get(0).body()));
+        assert_eq!(rebuilt.get(1).body().inputs().get(0), rebuilt.get(0).produced_utxos().get(1).input());
+        submit_all(&mut emulator, &rebuilt, &payer);
+        assert_eq!(emulator.utxos_at(&bob).get(0).output().amount().coin(), to_bignum(20_000_000));
+    }
+
+    #[test]
+    fn chain_rejects_double_spend() {
+        let payer = PrivateKey::from_normal_bytes(&[1; 32]).unwrap();
+        let input = TransactionInput::new(&TransactionHash::from([0; TransactionHash::BYTE_COUNT]), &to_bignum(0));
+        let utxo = SingleInputBuilder::new(&input, &TransactionOutput::new(&address(&payer), &Value::new(&to_bignum(100_000_000))))
+            .payment_key()
+            .unwrap();
+
+        let mut chain_builder = TransactionChainBuilder::new();
+        for _ in 0..2 {
+            let mut builder = payment(&address(&payer), 10_000_000);
+            builder.add_input(&utxo);
+            chain_builder.add_transaction(&builder, CoinSelectionStrategyCIP2::LargestFirst, &address(&payer));
+        }
+        assert!(matches!(
+            chain_builder.build(),
+            Err(BuilderError::ChainInputAlreadySpent { index: 1, spent_by: 0, .. }),
+        ));
+        assert!(matches!(
+            chain_builder.set_transaction(2, &payment(&address(&payer), 10_000_000), CoinSelectionStrategyCIP2::LargestFirst, &address(&payer)),
+            Err(BuilderError::ChainIndexOutOfRange { index: 2, len: 2 }),
+        ));
+    }
+}
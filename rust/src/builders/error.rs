@@ -0,0 +1,166 @@
+use crate::*;
+use crate::ledger::alonzo::fees::FeeError;
+use crate::ledger::common::value::{from_bignum, Coin, Value};
+
+use super::redeemer_builder::MissingExunitError;
+use super::tx_builder::CoinSelectionStrategyCIP2;
+use super::witness_builder::RequiredWitnessSet;
+
+/// Errors returned by the transaction builders
+///
+/// Variants carry what is needed to recover programmatically
+/// e.g. the value missing to balance a transaction so more inputs can be added before retrying.
+/// Converts to the crate Error (with the Display message), which the wasm bindings turn into a JS value
+#[derive(Clone, Debug)]
+pub enum BuilderError {
+    /// A builder was finalized before one of its mandatory fields was set
+    MissingField(&'static str),
+    /// The inputs (and for coin selection, the available UTxOs) don't cover the outputs, fee and deposits.
+    /// `missing` is how much more input is needed, without the fee of the extra inputs themselves
+    InsufficientBalance {
+        missing: Value,
+    },
+    /// LargestFirst and RandomImprove can't select inputs for outputs containing native assets
+    MultiAssetNotSupported(CoinSelectionStrategyCIP2),
+    /// A value is larger than the max_value_size protocol parameter when serialized
+    MaxValueSizeExceeded {
+        max: u32,
+        actual: usize,
+    },
+    /// The transaction is larger than the max_tx_size protocol parameter when serialized (including witnesses)
+    MaxTxSizeExceeded {
+        max: u32,
+        actual: usize,
+    },
+    /// An output contains less ADA than the minimum UTxO value
+    BelowMinAda {
+        required: Coin,
+        actual: Coin,
+    },
+    /// The leftover ADA can't cover the minimum UTxO value and fee of a change output holding native assets
+    InsufficientChangeAda {
+        required: Coin,
+        available: Coin,
+    },
+    /// The native assets left over can't fit in change outputs of max_value_size
+    ChangeTooLarge,
+    /// Fee was set explicitly, so change can't be computed
+    FeeAlreadySet,
+    /// Fee must be set (explicitly or with add_change_if_needed) to build the transaction
+    FeeNotSet,
+    /// Collateral can only be spent from UTxOs locked by a key
+    ScriptCollateral,
+    MaxCollateralInputsExceeded(u32),
+    /// Collateral return was set without any collateral input
+    CollateralReturnWithoutCollateral,
+    /// A payment key was given as witness but the credential is a script
+    ScriptWitnessRequired(ScriptHash),
+    /// Witnesses that still need to be provided
+    MissingWitnesses(Box<RequiredWitnessSet>),
+    /// Signatures that don't match the transaction body
+    InvalidSignatures {
+        tx_hash: TransactionHash,
+        vkeys: Vkeys,
+    },
+    MissingExunits(MissingExunitError),
+    Fee(FeeError),
+    /// A transaction chain has no transaction at {index}
+    ChainIndexOutOfRange {
+        index: usize,
+        len: usize,
+    },
+    /// An input added explicitly to transaction {index} of a chain is spent by an earlier transaction
+    ChainInputAlreadySpent {
+        index: usize,
+        input: TransactionInput,
+        spent_by: usize,
+    },
+    /// Transaction {index} of a chain could not be built
+    ChainTransaction {
+        index: usize,
+        error: Box<BuilderError>,
+    },
+    /// Errors of the ledger functions the builders rely on e.g. arithmetic overflows
+    Other(Error),
+}
+
+impl std::fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuilderError::MissingField(field) => write!(f, "uninitialized field: {}", field),
+            BuilderError::InsufficientBalance { missing } => {
+                write!(f, "UTxO Balance Insufficient: missing {} lovelace", from_bignum(&missing.coin))?;
+                if let Some(multiasset) = &missing.multiasset {
+                    for (asset_id, amount) in multiasset.iter_assets() {
+                        write!(f, ", {} of {}", from_bignum(&amount), asset_id)?;
+                    }
+                }
+                Ok(())
+            },
+            BuilderError::MultiAssetNotSupported(strategy) => write!(f, "Multiasset values not supported by {:?}. Please use {:?}MultiAsset", strategy, strategy),
+            BuilderError::MaxValueSizeExceeded { max, actual } => write!(f, "Maximum value size of {} exceeded. Found: {}", max, actual),
+            BuilderError::MaxTxSizeExceeded { max, actual } => write!(f, "Maximum transaction size of {} exceeded. Found: {}", max, actual),
+            BuilderError::BelowMinAda { required, actual } => write!(f, "Value {} less than the minimum UTXO value {}", from_bignum(actual), from_bignum(required)),
+            BuilderError::InsufficientChangeAda { required, available } => write!(
+                f,
+                "Not enough ADA leftover to include non-ADA assets in a change address: {} required, {} available",
+                from_bignum(required),
+                from_bignum(available),
+            ),
+            BuilderError::ChangeTooLarge => write!(f, "NFTs too large for change output"),
+            BuilderError::FeeAlreadySet => write!(f, "Cannot calculate change if fee was explicitly specified"),
+            BuilderError::FeeNotSet => write!(f, "Fee not specified"),
+            BuilderError::ScriptCollateral => write!(f, "Collateral can only be payment keys (scripts not allowed)"),
+            BuilderError::MaxCollateralInputsExceeded(max) => write!(f, "Max collateral input count ({}) exceeded", max),
+            BuilderError::CollateralReturnWithoutCollateral => write!(f, "Cannot use collateral return without also having collateral input"),
+            BuilderError::ScriptWitnessRequired(script_hash) => write!(f, "Credential is script {}, not a payment key", script_hash.to_hex()),
+            BuilderError::MissingWitnesses(witnesses) => write!(f, "Missing the following witnesses:\n{}", witnesses.to_str()),
+            BuilderError::InvalidSignatures { tx_hash, vkeys } => {
+                let keys = vkeys.0.iter().map(|vkey| format!("Vkey:{}", hex::encode(vkey.public_key().as_bytes()))).collect::<Vec<String>>().join(",");
+                write!(f, "Invalid signatures for transaction {}:\n{}", tx_hash.to_hex(), keys)
+            },
+            BuilderError::MissingExunits(e) => e.fmt(f),
+            BuilderError::Fee(e) => e.fmt(f),
+            BuilderError::ChainIndexOutOfRange { index, len } => write!(f, "No transaction at index {} in a chain of {}", index, len),
+            BuilderError::ChainInputAlreadySpent { index, input, spent_by } => write!(
+                f,
+                "Input {}#{} of transaction {} is already spent by transaction {} of the chain",
+                input.transaction_id.to_hex(),
+                input.index.to_str(),
+                index,
+                spent_by,
+            ),
+            BuilderError::ChainTransaction { index, error } => write!(f, "Transaction {} of the chain: {}", index, error),
+            BuilderError::Other(e) => write!(f, "{}", e.as_string().unwrap_or_default()),
+        }
+    }
+}
+
+impl std::error::Error for BuilderError {}
+
+impl From<Error> for BuilderError {
+    fn from(e: Error) -> BuilderError {
+        BuilderError::Other(e)
+    }
+}
+
+impl From<MissingExunitError> for BuilderError {
+    fn from(e: MissingExunitError) -> BuilderError {
+        BuilderError::MissingExunits(e)
+    }
+}
+
+impl From<FeeError> for BuilderError {
+    fn from(e: FeeError) -> BuilderError {
+        BuilderError::Fee(e)
+    }
+}
+
+impl From<BuilderError> for Error {
+    fn from(e: BuilderError) -> Error {
+        match e {
+            BuilderError::Other(e) => e,
+            e => Error::from_str(&e.to_string()),
+        }
+    }
+}
@@ -2,6 +2,7 @@ use crate::*;
 use crate::builders::witness_builder::{InputAggregateWitnessData, PartialPlutusWitness};
 use crate::ledger::common::hash::hash_plutus_data;
 
+use super::error::BuilderError;
 use super::witness_builder::{RequiredWitnessSet, NativeScriptWitnessInfo};
 
 pub fn input_required_wits(utxo_info: &TransactionOutput, required_witnesses: &mut RequiredWitnessSet) {
@@ -51,14 +52,11 @@ impl SingleInputBuilder {
         }
     }
 
-    pub fn payment_key(&self) -> Result<InputBuilderResult, JsError> {
+    pub fn payment_key(&self) -> Result<InputBuilderResult, BuilderError> {
         let mut required_wits = RequiredWitnessSet::default();
         input_required_wits(&self.utxo_info,&mut required_wits);
-        let required_wits_left = required_wits.clone();
-
-        
-        if !required_wits_left.scripts.is_empty() {
-            return Err(JsError::from_str(&format!("UTXO address was not a payment key: \n{:#?}", hex::encode(self.utxo_info.address.to_bytes()))));
+        if let Some(script_hash) = required_wits.scripts.iter().next() {
+            return Err(BuilderError::ScriptWitnessRequired(script_hash.clone()));
         }
 
         Ok(InputBuilderResult {
@@ -69,7 +67,7 @@ impl SingleInputBuilder {
         })
     }
 
-    pub fn native_script(&self, native_script: &NativeScript, witness_info: &NativeScriptWitnessInfo) -> Result<InputBuilderResult, JsError> {
+    pub fn native_script(&self, native_script: &NativeScript, witness_info: &NativeScriptWitnessInfo) -> Result<InputBuilderResult, BuilderError> {
         let mut required_wits = RequiredWitnessSet::default();
         input_required_wits(&self.utxo_info,&mut required_wits);
         let mut required_wits_left = required_wits.clone();
@@ -80,7 +78,7 @@ impl SingleInputBuilder {
         required_wits_left.scripts.remove(script_hash);
 
         if !required_wits_left.scripts.is_empty() {
-            return Err(JsError::from_str(&format!("Missing the following witnesses for the input: \n{:#?}", required_wits_left.to_str())));
+            return Err(BuilderError::MissingWitnesses(Box::new(required_wits_left)));
         }
 
         Ok(InputBuilderResult {
@@ -91,7 +89,7 @@ impl SingleInputBuilder {
         })
     }
 
-    pub fn plutus_script(&self, partial_witness: &PartialPlutusWitness, required_signers: &RequiredSigners, datum: &PlutusData) -> Result<InputBuilderResult, JsError> {
+    pub fn plutus_script(&self, partial_witness: &PartialPlutusWitness, required_signers: &RequiredSigners, datum: &PlutusData) -> Result<InputBuilderResult, BuilderError> {
         let mut required_wits = RequiredWitnessSet::default();
         required_signers.0.iter().for_each(|required_signer| required_wits.add_vkey_key_hash(required_signer));
         input_required_wits(&self.utxo_info,&mut required_wits);
@@ -107,7 +105,7 @@ impl SingleInputBuilder {
         required_wits_left.plutus_data.remove(&hash_plutus_data(datum));
 
         if required_wits_left.len() > 0 {
-            return Err(JsError::from_str(&format!("Missing the following witnesses for the input: \n{:#?}", required_wits_left.to_str())));
+            return Err(BuilderError::MissingWitnesses(Box::new(required_wits_left)));
         }
 
         Ok(InputBuilderResult {
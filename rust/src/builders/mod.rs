@@ -6,4 +6,7 @@ pub mod tx_builder;
 pub mod witness_builder;
 pub mod withdrawal_builder;
 pub mod mint_builder;
+pub mod chain_builder;
+pub mod pool_metadata_builder;
+pub mod error;
 pub(crate) mod utils;
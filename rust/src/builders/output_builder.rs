@@ -1,5 +1,7 @@
 use crate::{*, ledger::{babbage::min_ada::{min_ada_required, compatible_min_ada_required}, common::hash::hash_plutus_data}};
 
+use super::error::BuilderError;
+
 /// We introduce a builder-pattern format for creating transaction outputs
 /// This is because:
 /// 1. Some fields (i.e. data hash) are optional, and we can't easily expose Option<> in WASM
@@ -49,9 +51,9 @@ impl TransactionOutputBuilder {
         cfg
     }
 
-    pub fn next(&self) -> Result<TransactionOutputAmountBuilder, JsError> {
+    pub fn next(&self) -> Result<TransactionOutputAmountBuilder, BuilderError> {
         Ok(TransactionOutputAmountBuilder {
-            address: self.address.clone().ok_or_else(|| JsError::from_str("TransactionOutputBaseBuilder: Address missing"))?,
+            address: self.address.clone().ok_or(BuilderError::MissingField("address"))?,
             amount: None,
             datum: self.datum.clone(),
             script_ref: self.script_ref.clone(),
@@ -95,7 +97,7 @@ impl TransactionOutputAmountBuilder {
         cfg
     }
 
-    pub fn with_asset_and_min_required_coin(&self, multiasset: &MultiAsset, coins_per_utxo_byte: &Coin, coins_per_utxo_word: Option<Coin>) -> Result<TransactionOutputAmountBuilder, JsError> {
+    pub fn with_asset_and_min_required_coin(&self, multiasset: &MultiAsset, coins_per_utxo_byte: &Coin, coins_per_utxo_word: Option<Coin>) -> Result<TransactionOutputAmountBuilder, BuilderError> {
         let mut min_output = TransactionOutput::new(
             &self.address,
             &self.amount.clone().unwrap_or_else(|| Value::new(&to_bignum(0))),
@@ -116,12 +118,13 @@ impl TransactionOutputAmountBuilder {
         Ok(self.with_coin_and_asset(&required_coin, multiasset))
     }
 
-    pub fn build(&self) -> Result<SingleOutputBuilderResult, JsError> {
+    pub fn build(&self) -> Result<SingleOutputBuilderResult, BuilderError> {
         let output = TransactionOutput {
             address: self.address.clone(),
-            amount: self.amount.clone().ok_or_else(|| JsError::from_str("TransactionOutputAmountBuilder: amount missing"))?,
+            amount: self.amount.clone().ok_or(BuilderError::MissingField("amount"))?,
             datum_option: self.datum.clone(),
             script_ref: self.script_ref.clone(),
+            prefer_babbage_format: false,
         };
         Ok(SingleOutputBuilderResult {
             output,
@@ -162,7 +165,7 @@ impl SingleOutputBuilderResult {
     since = "1.0.0",
     note = "If you don't need to support Alonzo, you don't need this function"
 )]
-pub(crate) fn calc_min_ada(output: &TransactionOutput, coins_per_utxo_byte: &BigNum, coins_per_utxo_word: Option<&BigNum>) -> Result<BigNum, JsError> {
+pub(crate) fn calc_min_ada(output: &TransactionOutput, coins_per_utxo_byte: &BigNum, coins_per_utxo_word: Option<&BigNum>) -> Result<BigNum, Error> {
     match coins_per_utxo_word {
         Some(coins_per_utxo_word) => compatible_min_ada_required(
             output,
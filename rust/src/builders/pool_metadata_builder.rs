@@ -0,0 +1,117 @@
+use crate::*;
+use crate::error::Error;
+use crate::ledger::common::hash::hash_pool_metadata;
+use crate::pool_metadata::{ExtendedPoolMetadata, PoolMetadataJson};
+
+/// The PoolMetadata to put in a pool registration certificate along with the documents to host
+#[derive(Clone, Debug)]
+pub struct PoolMetadataBuilderResult {
+    pub(crate) pool_metadata: PoolMetadata,
+    pub(crate) metadata_bytes: Vec<u8>,
+    pub(crate) extended_bytes: Option<Vec<u8>>,
+}
+
+impl PoolMetadataBuilderResult {
+    pub fn pool_metadata(&self) -> PoolMetadata {
+        self.pool_metadata.clone()
+    }
+
+    /// To host as-is at the URL of pool_metadata: its hash is over these exact bytes
+    pub fn metadata_bytes(&self) -> Vec<u8> {
+        self.metadata_bytes.clone()
+    }
+
+    /// To host at the extended URL of the metadata, if any
+    pub fn extended_bytes(&self) -> Option<Vec<u8>> {
+        self.extended_bytes.clone()
+    }
+}
+
+#[derive(Clone, Debug)]
+enum MetadataSource {
+    Json(PoolMetadataJson),
+    /// already hosted, so it must be hashed as it is rather than re-serialized
+    Bytes(Vec<u8>),
+}
+
+#[derive(Clone, Debug)]
+pub struct PoolMetadataBuilder {
+    url: URL,
+    metadata: MetadataSource,
+    extended: Option<(URL, ExtendedPoolMetadata)>,
+}
+
+impl PoolMetadataBuilder {
+    pub fn new(url: &URL, metadata: &PoolMetadataJson) -> Self {
+        Self {
+            url: url.clone(),
+            metadata: MetadataSource::Json(metadata.clone()),
+            extended: None,
+        }
+    }
+
+    /// For metadata already hosted at `url`
+    pub fn from_bytes(url: &URL, metadata_bytes: Vec<u8>) -> Self {
+        Self {
+            url: url.clone(),
+            metadata: MetadataSource::Bytes(metadata_bytes),
+            extended: None,
+        }
+    }
+
+    /// Links the extended metadata from the `extended` field of the metadata.
+    /// Not possible with from_bytes since the hosted bytes can't be changed
+    pub fn with_extended(&mut self, url: &URL, extended: &ExtendedPoolMetadata) -> Result<(), Error> {
+        match &mut self.metadata {
+            MetadataSource::Json(metadata) => metadata.set_extended(url),
+            MetadataSource::Bytes(_) => return Err(Error::from_str("cannot add extended metadata to already serialized pool metadata")),
+        }
+        self.extended = Some((url.clone(), extended.clone()));
+        Ok(())
+    }
+
+    pub fn build(&self) -> Result<PoolMetadataBuilderResult, Error> {
+        let metadata_bytes = match &self.metadata {
+            MetadataSource::Json(metadata) => metadata.to_json_bytes()?,
+            MetadataSource::Bytes(bytes) => {
+                PoolMetadataJson::from_json_bytes(bytes)?;
+                bytes.clone()
+            },
+        };
+        let extended_bytes = match &self.extended {
+            Some((_, extended)) => Some(extended.to_json_bytes()?),
+            None => None,
+        };
+        Ok(PoolMetadataBuilderResult {
+            pool_metadata: PoolMetadata::new(&self.url, &hash_pool_metadata(&metadata_bytes)),
+            metadata_bytes,
+            extended_bytes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool_metadata::AdapoolsExtendedMetadata;
+
+    #[test]
+    fn build_pool_metadata() {
+        let url = URL::new(String::from("https://example.com/pool.json")).unwrap();
+        let metadata = PoolMetadataJson::new(String::from("Test Pool"), String::from("A pool for tests"), String::from("TEST"), String::from("https://example.com")).unwrap();
+        let mut builder = PoolMetadataBuilder::new(&url, &metadata);
+        builder.with_extended(&URL::new(String::from("https://example.com/extended.json")).unwrap(), &ExtendedPoolMetadata::Adapools(AdapoolsExtendedMetadata::default())).unwrap();
+        let result = builder.build().unwrap();
+
+        let hosted = PoolMetadataJson::from_hosted(&result.pool_metadata(), &result.metadata_bytes()).unwrap();
+        assert_eq!(hosted.extended(), Some(String::from("https://example.com/extended.json")));
+        assert_eq!(result.extended_bytes(), Some(b"{\n  \"info\": {}\n}".to_vec()));
+
+        // hosted bytes are hashed as they are
+        let bytes = b"{\"name\":\"Test Pool\",\"description\":\"\",\"ticker\":\"TEST\",\"homepage\":\"https://example.com\"}".to_vec();
+        let mut builder = PoolMetadataBuilder::from_bytes(&url, bytes.clone());
+        assert_eq!(builder.build().unwrap().pool_metadata().pool_metadata_hash(), hash_pool_metadata(&bytes));
+        assert!(builder.with_extended(&url, &ExtendedPoolMetadata::Adapools(AdapoolsExtendedMetadata::default())).is_err());
+        assert!(PoolMetadataBuilder::from_bytes(&url, b"{}".to_vec()).build().is_err());
+    }
+}
@@ -65,7 +65,7 @@ enum UntaggedRedeemerPlaceholder {
 }
 
 /// Possible errors during conversion from bytes
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum MissingExunitError {
     Key((RedeemerTag, usize, String)),
 }
@@ -135,6 +135,8 @@ impl RedeemerSetBuilder {
                 let entry = self.reward.iter_mut().nth(u64::from(key.index()) as usize).unwrap();
                 *entry.1 = replace_placeholder(entry.1)
             },
+            // votes and proposals can't be added to this builder so there is never a redeemer to update
+            RedeemerTagKind::Voting | RedeemerTagKind::Proposing => {},
         };
     }
 
@@ -283,7 +285,7 @@ mod tests {
 
         let input_result = InputBuilderResult {
             input: TransactionInput { transaction_id: TransactionHash([1; 32]), index: 1u64.into() },
-            utxo_info: TransactionOutput { address: address.clone(), amount: Value::zero(), datum_option: None, script_ref: None },
+            utxo_info: TransactionOutput { address: address.clone(), amount: Value::zero(), datum_option: None, script_ref: None, prefer_babbage_format: false },
             aggregate_witness: None,
             required_wits: RequiredWitnessSet::new(),
         };
@@ -292,7 +294,7 @@ mod tests {
 
         let input_result = InputBuilderResult {
             input: TransactionInput { transaction_id: TransactionHash([1; 32]), index: 0u64.into() },
-            utxo_info: TransactionOutput { address: address.clone(), amount: Value::zero(), datum_option: None, script_ref: None },
+            utxo_info: TransactionOutput { address: address.clone(), amount: Value::zero(), datum_option: None, script_ref: None, prefer_babbage_format: false },
             aggregate_witness: None,
             required_wits: RequiredWitnessSet::new(),
         };
@@ -301,7 +303,7 @@ mod tests {
 
         let input_result = InputBuilderResult {
             input: TransactionInput { transaction_id: TransactionHash([0; 32]), index: 0u64.into() },
-            utxo_info: TransactionOutput { address: address.clone(), amount: Value::zero(), datum_option: None, script_ref: None },
+            utxo_info: TransactionOutput { address: address.clone(), amount: Value::zero(), datum_option: None, script_ref: None, prefer_babbage_format: false },
             aggregate_witness: Some(data.clone()),
             required_wits: RequiredWitnessSet::new(),
         };
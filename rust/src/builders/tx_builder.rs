@@ -4,13 +4,14 @@ use crate::builders::output_builder::calc_min_ada;
 use crate::ledger;
 use crate::ledger::alonzo::fees::LinearFee;
 use crate::ledger::babbage::min_ada::min_pure_ada;
-use crate::ledger::common::deposit::internal_get_deposit;
-use crate::ledger::common::deposit::internal_get_implicit_input;
+use crate::ledger::common::deposit::{DepositCalculator, DepositState};
 use crate::ledger::common::hash::calc_script_data_hash;
 use crate::ledger::common::hash::hash_auxiliary_data;
-use crate::ledger::common::utxo::TransactionUnspentOutput;
-use crate::ledger::common::value::Value;
+use crate::ledger::common::hash::hash_transaction;
+use crate::ledger::common::utxo::{TransactionUnspentOutput, TransactionUnspentOutputs, get_produced_utxos};
+use crate::ledger::common::value::{Value, ValueDelta};
 use crate::ledger::common::value::from_bignum;
+use super::error::BuilderError;
 use super::input_builder::InputBuilderResult;
 use super::mint_builder::MintBuilderResult;
 use super::certificate_builder::*;
@@ -24,6 +25,7 @@ use super::witness_builder::RequiredWitnessSet;
 use super::witness_builder::TransactionWitnessSetBuilder;
 use super::witness_builder::merge_fake_witness;
 use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
 use rand::Rng;
 
 #[derive(Clone, Default, Debug)]
@@ -62,7 +64,7 @@ impl WitnessBuilders {
 // tx_body must be the result of building from tx_builder
 // constructs the rest of the Transaction using fake witness data of the correct length
 // for use in calculating the size of the final Transaction
-fn fake_full_tx(tx_builder: &TransactionBuilder, body: TransactionBody) -> Result<Transaction, JsError> {
+fn fake_full_tx(tx_builder: &TransactionBuilder, body: TransactionBody) -> Result<Transaction, BuilderError> {
     Ok(Transaction {
         body,
         witness_set: tx_builder.witness_builders.build_fake(),
@@ -71,13 +73,14 @@ fn fake_full_tx(tx_builder: &TransactionBuilder, body: TransactionBody) -> Resul
     })
 }
 
-fn min_fee(tx_builder: &TransactionBuilder) -> Result<Coin, JsError> {
+fn min_fee(tx_builder: &TransactionBuilder) -> Result<Coin, BuilderError> {
     let full_tx = fake_full_tx(tx_builder, tx_builder.build_body()?)?;
     // we can't know the of scripts yet as they can't be calculated until we build the tx
-    ledger::alonzo::fees::min_no_script_fee(&full_tx, &tx_builder.config.fee_algo)
+    Ok(ledger::alonzo::fees::min_no_script_fee(&full_tx, &tx_builder.config.fee_algo)?)
 }
 
 
+#[derive(Clone, Copy, Debug)]
 pub enum CoinSelectionStrategyCIP2 {
     /// Performs CIP2's Largest First ada-only selection. Will error if outputs contain non-ADA assets.
     LargestFirst,
@@ -92,19 +95,19 @@ pub enum CoinSelectionStrategyCIP2 {
 
 #[derive(Clone, Debug)]
 pub struct TransactionBuilderConfig {
-    fee_algo: LinearFee,
-    pool_deposit: BigNum,      // protocol parameter
-    key_deposit: BigNum,       // protocol parameter
-    max_value_size: u32,       // protocol parameter
-    max_tx_size: u32,          // protocol parameter
+    pub(crate) fee_algo: LinearFee,
+    pub(crate) pool_deposit: BigNum,      // protocol parameter
+    pub(crate) key_deposit: BigNum,       // protocol parameter
+    pub(crate) max_value_size: u32,       // protocol parameter
+    pub(crate) max_tx_size: u32,          // protocol parameter
     /// TODO: remove once Babbage is on mainnet
-    coins_per_utxo_word: Option<Coin>, // protocol parameter
-    coins_per_utxo_byte: Coin, // protocol parameter
-    ex_unit_prices: ExUnitPrices, // protocol parameter
-    costmdls: Costmdls,           // protocol parameter
-    collateral_percentage: u32,   // protocol parameter
-    max_collateral_inputs: u32,   // protocol parameter
-    prefer_pure_change: bool,
+    pub(crate) coins_per_utxo_word: Option<Coin>, // protocol parameter
+    pub(crate) coins_per_utxo_byte: Coin, // protocol parameter
+    pub(crate) ex_unit_prices: ExUnitPrices, // protocol parameter
+    pub(crate) costmdls: Costmdls,           // protocol parameter
+    pub(crate) collateral_percentage: u32,   // protocol parameter
+    pub(crate) max_collateral_inputs: u32,   // protocol parameter
+    pub(crate) prefer_pure_change: bool,
 }
 
 
@@ -209,33 +212,27 @@ impl TransactionBuilderConfigBuilder {
         cfg
     }
 
-    pub fn build(&self) -> Result<TransactionBuilderConfig, JsError> {
+    pub fn build(&self) -> Result<TransactionBuilderConfig, BuilderError> {
         let cfg = self.clone();
         Ok(TransactionBuilderConfig {
-            fee_algo: cfg.fee_algo.ok_or_else(|| JsError::from_str("uninitialized field: fee_algo"))?,
-            pool_deposit: cfg.pool_deposit.ok_or_else(|| JsError::from_str("uninitialized field: pool_deposit"))?,
-            key_deposit: cfg.key_deposit.ok_or_else(|| JsError::from_str("uninitialized field: key_deposit"))?,
-            max_value_size: cfg.max_value_size.ok_or_else(|| JsError::from_str("uninitialized field: max_value_size"))?,
-            max_tx_size: cfg.max_tx_size.ok_or_else(|| JsError::from_str("uninitialized field: max_tx_size"))?,
-            coins_per_utxo_byte: cfg.coins_per_utxo_byte.ok_or_else(|| JsError::from_str(
-                "uninitialized field: coins_per_utxo_byte",
-            ))?,
+            fee_algo: cfg.fee_algo.ok_or_else(|| BuilderError::MissingField("fee_algo"))?,
+            pool_deposit: cfg.pool_deposit.ok_or_else(|| BuilderError::MissingField("pool_deposit"))?,
+            key_deposit: cfg.key_deposit.ok_or_else(|| BuilderError::MissingField("key_deposit"))?,
+            max_value_size: cfg.max_value_size.ok_or_else(|| BuilderError::MissingField("max_value_size"))?,
+            max_tx_size: cfg.max_tx_size.ok_or_else(|| BuilderError::MissingField("max_tx_size"))?,
+            coins_per_utxo_byte: cfg.coins_per_utxo_byte.ok_or_else(|| BuilderError::MissingField("coins_per_utxo_byte"))?,
             /// TODO: remove once Babbage is on mainnet
             coins_per_utxo_word: cfg.coins_per_utxo_word,
             ex_unit_prices: cfg
                 .ex_unit_prices
-                .ok_or_else(|| JsError::from_str("uninitialized field: ex_unit_prices"))?,
+                .ok_or_else(|| BuilderError::MissingField("ex_unit_prices"))?,
             costmdls: if cfg.costmdls.is_some() {
                 cfg.costmdls.unwrap()
             } else {
                 Costmdls::new()
             },
-            collateral_percentage: cfg.collateral_percentage.ok_or_else(|| JsError::from_str(
-                "uninitialized field: collateral_percentage",
-            ))?,
-            max_collateral_inputs: cfg.max_collateral_inputs.ok_or_else(|| JsError::from_str(
-                "uninitialized field: max_collateral_inputs",
-            ))?,
+            collateral_percentage: cfg.collateral_percentage.ok_or_else(|| BuilderError::MissingField("collateral_percentage"))?,
+            max_collateral_inputs: cfg.max_collateral_inputs.ok_or_else(|| BuilderError::MissingField("max_collateral_inputs"))?,
             prefer_pure_change: cfg.prefer_pure_change,
         })
     }
@@ -245,7 +242,7 @@ impl TransactionBuilderConfigBuilder {
 #[derive(Clone, Debug)]
 pub struct TransactionBuilder {
     config: TransactionBuilderConfig,
-    inputs: Vec<TransactionUnspentOutput>,
+    pub(crate) inputs: Vec<TransactionUnspentOutput>,
     outputs: TransactionOutputs,
     fee: Option<Coin>,
     ttl: Option<Slot>, // absolute slot number
@@ -258,9 +255,10 @@ pub struct TransactionBuilder {
     required_signers: Option<RequiredSignersSet>,
     network_id: Option<NetworkId>,
     witness_builders: WitnessBuilders,
-    utxos: Vec<InputBuilderResult>,
+    pub(crate) utxos: Vec<InputBuilderResult>,
     collateral_return: Option<TransactionOutput>,
     reference_inputs: Option<Vec<TransactionUnspentOutput>>,
+    deposit_state: Option<Arc<dyn DepositState>>,
 }
 
 
@@ -272,7 +270,7 @@ impl TransactionBuilder {
     /// Adding a change output must be called after via TransactionBuilder::add_change_if_needed()
     /// This function, diverging from CIP2, takes into account fees and will attempt to add additional
     /// inputs to cover the minimum fees. This does not, however, set the txbuilder's fee.
-    pub fn select_utxos(&mut self, strategy: CoinSelectionStrategyCIP2) -> Result<(), JsError> {
+    pub fn select_utxos(&mut self, strategy: CoinSelectionStrategyCIP2) -> Result<(), BuilderError> {
         let available_inputs = &self.utxos.clone();
         let mut input_total = self.get_total_input()?;
         let mut output_total = self
@@ -281,7 +279,7 @@ impl TransactionBuilder {
         match strategy {
             CoinSelectionStrategyCIP2::LargestFirst => {
                 if self.outputs.0.iter().any(|output| output.amount.multiasset.is_some()) {
-                    return Err(JsError::from_str("Multiasset values not supported by LargestFirst. Please use LargestFirstMultiAsset"));
+                    return Err(BuilderError::MultiAssetNotSupported(strategy));
                 }
                 self.cip2_largest_first_by(
                     available_inputs,
@@ -292,7 +290,7 @@ impl TransactionBuilder {
             },
             CoinSelectionStrategyCIP2::RandomImprove => {
                 if self.outputs.0.iter().any(|output| output.amount.multiasset.is_some()) {
-                    return Err(JsError::from_str("Multiasset values not supported by RandomImprove. Please use RandomImproveMultiAsset"));
+                    return Err(BuilderError::MultiAssetNotSupported(strategy));
                 }
                 let mut rng = rand::thread_rng();
                 let mut available_indices = (0..available_inputs.len()).collect::<BTreeSet<usize>>();
@@ -308,7 +306,7 @@ impl TransactionBuilder {
                 // a specific output, so the improvement algorithm we do above does not apply here.
                 while input_total.coin < output_total.coin {
                     if available_indices.is_empty() {
-                        return Err(JsError::from_str("UTxO Balance Insufficient[x]"));
+                        return Err(self.insufficient_balance(available_inputs, &output_total));
                     }
                     let i = *available_indices.iter().nth(rng.gen_range(0..available_indices.len())).unwrap();
                     available_indices.remove(&i);
@@ -373,7 +371,7 @@ impl TransactionBuilder {
                 // a specific output, so the improvement algorithm we do above does not apply here.
                 while input_total.coin < output_total.coin {
                     if available_indices.is_empty() {
-                        return Err(JsError::from_str("UTxO Balance Insufficient[x]"));
+                        return Err(self.insufficient_balance(available_inputs, &output_total));
                     }
                     let i = *available_indices.iter().nth(rng.gen_range(0..available_indices.len())).unwrap();
                     available_indices.remove(&i);
@@ -389,13 +387,31 @@ impl TransactionBuilder {
         Ok(())
     }
 
+    /// error for when {available_inputs} can't cover {output_total} (outputs, deposits, burn and fees so far)
+    fn insufficient_balance(&self, available_inputs: &[InputBuilderResult], output_total: &Value) -> BuilderError {
+        let added = self.inputs.iter().map(|utxo| utxo.input.clone()).collect::<BTreeSet<_>>();
+        let missing = self.get_total_input()
+            .and_then(|input_total| Ok(Value::checked_sum(
+                available_inputs
+                    .iter()
+                    .filter(|utxo| !added.contains(&utxo.input))
+                    .map(|utxo| &utxo.utxo_info.amount)
+                    .chain(std::iter::once(&input_total))
+            )?))
+            .and_then(|available| Ok(ValueDelta::between(&available, output_total)?.positive_part()?));
+        match missing {
+            Ok(missing) => BuilderError::InsufficientBalance { missing },
+            Err(e) => e,
+        }
+    }
+
     fn cip2_largest_first_by<F>(
         &mut self,
         available_inputs: &[InputBuilderResult],
         available_indices: &mut Vec<usize>,
         input_total: &mut Value,
         output_total: &mut Value,
-        by: F) -> Result<(), JsError>
+        by: F) -> Result<(), BuilderError>
     where
         F: Fn(&Value) -> Option<BigNum> {
         let mut relevant_indices = available_indices.clone();
@@ -418,7 +434,7 @@ impl TransactionBuilder {
         }
 
         if by(input_total).unwrap_or_else(BigNum::zero) < by(output_total).expect("do not call on asset types that aren't in the output") {
-            return Err(JsError::from_str("UTxO Balance Insufficient"));
+            return Err(self.insufficient_balance(available_inputs, output_total));
         }
 
         Ok(())
@@ -431,7 +447,7 @@ impl TransactionBuilder {
         input_total: &mut Value,
         output_total: &mut Value,
         by: F,
-        rng: &mut R) -> Result<(), JsError>
+        rng: &mut R) -> Result<(), BuilderError>
     where
         F: Fn(&Value) -> Option<BigNum> {
         // Phase 1: Random Selection
@@ -465,7 +481,7 @@ impl TransactionBuilder {
             let needed = by(&output.amount).unwrap();
             while added < needed {
                 if relevant_indices.is_empty() {
-                    return Err(JsError::from_str("UTxO Balance Insufficient"));
+                    return Err(self.insufficient_balance(available_inputs, output_total));
                 }
                 let random_index = rng.gen_range(0..relevant_indices.len());
                 let i = relevant_indices.swap_remove(random_index);
@@ -542,7 +558,7 @@ impl TransactionBuilder {
     }
 
     /// calculates how much the fee would increase if you added a given output
-    pub fn fee_for_input(&self, result: &InputBuilderResult) -> Result<Coin, JsError> {
+    pub fn fee_for_input(&self, result: &InputBuilderResult) -> Result<Coin, BuilderError> {
         let mut self_copy = self.clone();
 
         // we need some value for these for it to be a a valid transaction
@@ -554,7 +570,7 @@ impl TransactionBuilder {
 
         self_copy.add_input(result);
         let fee_after = min_fee(&self_copy)?;
-        fee_after.checked_sub(&fee_before)
+        Ok(fee_after.checked_sub(&fee_before)?)
     }
 
     pub fn add_reference_input(&mut self, utxo: &TransactionUnspentOutput) {
@@ -577,15 +593,14 @@ impl TransactionBuilder {
 
 
     /// Add explicit output via a TransactionOutput object
-    pub fn add_output(&mut self, builder_result: &SingleOutputBuilderResult) -> Result<(), JsError> {
+    pub fn add_output(&mut self, builder_result: &SingleOutputBuilderResult) -> Result<(), BuilderError> {
         let output = &builder_result.output();
         let value_size = output.amount.to_bytes().len();
         if value_size > self.config.max_value_size as usize {
-            return Err(JsError::from_str(&format!(
-                "Maximum value size of {} exceeded. Found: {}",
-                self.config.max_value_size,
-                value_size
-            )));
+            return Err(BuilderError::MaxValueSizeExceeded {
+                max: self.config.max_value_size,
+                actual: value_size,
+            });
         }
         let min_ada = calc_min_ada(
             output,
@@ -593,11 +608,10 @@ impl TransactionBuilder {
             self.config.coins_per_utxo_word.as_ref(),
         )?;
         if output.amount().coin() < min_ada {
-            Err(JsError::from_str(&format!(
-                "Value {} less than the minimum UTXO value {}",
-                from_bignum(&output.amount().coin()),
-                from_bignum(&min_ada)
-            )))
+            Err(BuilderError::BelowMinAda {
+                required: min_ada,
+                actual: output.amount().coin(),
+            })
         } else {
             if let Some(datum) = builder_result.communication_datum().as_ref() {
                 self.witness_builders.witness_set_builder.add_plutus_datum(datum);
@@ -608,7 +622,7 @@ impl TransactionBuilder {
     }
 
     /// calculates how much the fee would increase if you added a given output
-    pub fn fee_for_output(&self, builder: &SingleOutputBuilderResult) -> Result<Coin, JsError> {
+    pub fn fee_for_output(&self, builder: &SingleOutputBuilderResult) -> Result<Coin, BuilderError> {
         let mut self_copy = self.clone();
 
         // we need some value for these for it to be a a valid transaction
@@ -620,7 +634,7 @@ impl TransactionBuilder {
 
         self_copy.add_output(builder)?;
         let fee_after = min_fee(&self_copy)?;
-        fee_after.checked_sub(&fee_before)
+        Ok(fee_after.checked_sub(&fee_before)?)
     }
 
     pub fn set_fee(&mut self, fee: &Coin) {
@@ -737,14 +751,13 @@ impl TransactionBuilder {
             utxos: Vec::new(),
             collateral_return: None,
             reference_inputs: None,
+            deposit_state: None,
         }
     }
 
-    pub fn add_collateral(&mut self, result: &InputBuilderResult) -> Result<(), JsError>  {
+    pub fn add_collateral(&mut self, result: &InputBuilderResult) -> Result<(), BuilderError>  {
         if result.aggregate_witness.is_some() {
-            return Err(JsError::from_str(
-                "Collateral can only be payment keys (scripts not allowed)",
-            ))
+            return Err(BuilderError::ScriptCollateral)
         };
         let new_input = TransactionUnspentOutput {
             input: result.input.clone(),
@@ -754,9 +767,7 @@ impl TransactionBuilder {
             None => { self.collateral = Some(vec![new_input]) },
             Some(collateral) => {
                 if self.config.max_collateral_inputs <= collateral.len().try_into().unwrap() {
-                    return Err(JsError::from_str(
-                        &format!("Max collateral input count ({}) exceeded", self.config.max_collateral_inputs),
-                    ))
+                    return Err(BuilderError::MaxCollateralInputsExceeded(self.config.max_collateral_inputs))
                 }
                 collateral.push(new_input);
             }
@@ -803,6 +814,20 @@ impl TransactionBuilder {
         self.required_signers.as_ref().map(|set| Ed25519KeyHashes(set.iter().cloned().collect()))
     }
 
+    /// Chain state used for deposits: without it, updating a registered pool is charged a deposit
+    /// and deregistrations are refunded the current key_deposit instead of what was paid
+    pub fn set_deposit_state(&mut self, state: Arc<dyn DepositState>) {
+        self.deposit_state = Some(state);
+    }
+
+    fn deposit_calculator(&self) -> DepositCalculator<'_> {
+        let calculator = DepositCalculator::new(&self.config.pool_deposit, &self.config.key_deposit);
+        match &self.deposit_state {
+            Some(state) => calculator.with_state(state.as_ref()),
+            None => calculator,
+        }
+    }
+
     pub fn set_network_id(&mut self, network_id: NetworkId) {
         self.network_id = Some(network_id)
     }
@@ -812,22 +837,17 @@ impl TransactionBuilder {
     }
 
     /// does not include refunds or withdrawals
-    pub fn get_explicit_input(&self) -> Result<Value, JsError> {
-        self.inputs
+    pub fn get_explicit_input(&self) -> Result<Value, BuilderError> {
+        Ok(self.inputs
             .iter()
             .try_fold(Value::zero(), |acc, tx_builder_input| {
                 acc.checked_add(&tx_builder_input.output.amount)
-            })
+            })?)
     }
 
     /// withdrawals and refunds
-    pub fn get_implicit_input(&self) -> Result<Value, JsError> {
-        internal_get_implicit_input(
-            &self.withdrawals,
-            &self.certs,
-            &self.config.pool_deposit,
-            &self.config.key_deposit,
-        )
+    pub fn get_implicit_input(&self) -> Result<Value, BuilderError> {
+        Ok(self.deposit_calculator().implicit_input(&self.withdrawals, &self.certs)?)
     }
 
     /// Returns mint as tuple of (mint_value, burn_value) or two zero values
@@ -839,37 +859,33 @@ impl TransactionBuilder {
     }
 
     /// Return explicit input plus implicit input plus mint
-    pub fn get_total_input(&self) -> Result<Value, JsError> {
+    pub fn get_total_input(&self) -> Result<Value, BuilderError> {
         let (mint_value, _) = self.get_mint_as_values();
-        self.get_explicit_input()?
+        Ok(self.get_explicit_input()?
             .checked_add(&self.get_implicit_input()?)?
-            .checked_add(&mint_value)
+            .checked_add(&mint_value)?)
     }
 
     /// Return explicit output plus implicit output plus burn (does not consider fee directly)
-    pub fn get_total_output(&self) -> Result<Value, JsError> {
+    pub fn get_total_output(&self) -> Result<Value, BuilderError> {
         let (_, burn_value) = self.get_mint_as_values();
-        self.get_explicit_output()?
+        Ok(self.get_explicit_output()?
             .checked_add(&Value::new(&self.get_deposit()?))?
-            .checked_add(&burn_value)
+            .checked_add(&burn_value)?)
     }
 
     /// does not include fee
-    pub fn get_explicit_output(&self) -> Result<Value, JsError> {
-        self.outputs
+    pub fn get_explicit_output(&self) -> Result<Value, BuilderError> {
+        Ok(self.outputs
             .0
             .iter()
             .try_fold(Value::new(&to_bignum(0)), |acc, output| {
                 acc.checked_add(&output.amount())
-            })
+            })?)
     }
 
-    pub fn get_deposit(&self) -> Result<Coin, JsError> {
-        internal_get_deposit(
-            &self.certs,
-            &self.config.pool_deposit,
-            &self.config.key_deposit,
-        )
+    pub fn get_deposit(&self) -> Result<Coin, BuilderError> {
+        Ok(self.deposit_calculator().deposit(&self.certs)?)
     }
 
     pub fn get_fee_if_set(&self) -> Option<Coin> {
@@ -880,14 +896,12 @@ impl TransactionBuilder {
     /// Make sure to call this function last after setting all other tx-body properties
     /// Editing inputs, outputs, mint, etc. after change been calculated
     /// might cause a mismatch in calculated fee versus the required fee
-    pub fn add_change_if_needed(&mut self, address: &Address) -> Result<bool, JsError> {
+    pub fn add_change_if_needed(&mut self, address: &Address) -> Result<bool, BuilderError> {
         let fee = match &self.fee {
             None => self.min_fee(),
             // generating the change output involves changing the fee
             Some(_x) => {
-                return Err(JsError::from_str(
-                    "Cannot calculate change if fee was explicitly specified",
-                ))
+                return Err(BuilderError::FeeAlreadySet)
             }
         }?;
 
@@ -908,7 +922,9 @@ impl TransactionBuilder {
                 self.set_fee(&input_total.checked_sub(&output_total)?.coin());
                 Ok(false)
             },
-            Some(Ordering::Less) => Err(JsError::from_str("Insufficient input in transaction")),
+            Some(Ordering::Less) | None => Err(BuilderError::InsufficientBalance {
+                missing: ValueDelta::between(&input_total, &output_total.checked_add(&Value::new(&fee))?)?.positive_part()?,
+            }),
             Some(Ordering::Greater) => {
                 fn has_assets(ma: Option<MultiAsset>) -> bool {
                     ma.map(|assets| assets.len() > 0).unwrap_or(false)
@@ -936,7 +952,7 @@ impl TransactionBuilder {
 
                         amount_clone.to_bytes().len() > max_value_size as usize
                     }
-                    fn pack_nfts_for_change(max_value_size: u32, coins_per_utxo_byte: &Coin, coins_per_utxo_word: Option<&Coin>, change_address: &Address, change_estimator: &Value, datum: Option<Datum>, script_ref: &Option<ScriptRef>, communication_datum: &Option<PlutusData>) -> Result<Vec<MultiAsset>, JsError> {
+                    fn pack_nfts_for_change(max_value_size: u32, coins_per_utxo_byte: &Coin, coins_per_utxo_word: Option<&Coin>, change_address: &Address, change_estimator: &Value, datum: Option<Datum>, script_ref: &Option<ScriptRef>, communication_datum: &Option<PlutusData>) -> Result<Vec<MultiAsset>, BuilderError> {
                         // we insert the entire available ADA temporarily here since that could potentially impact the size
                         // as it could be 1, 2 3 or 4 bytes for Coin.
                         let mut change_assets: Vec<MultiAsset> = Vec::new();
@@ -948,6 +964,7 @@ impl TransactionBuilder {
                             amount: base_coin.clone(),
                             datum_option: datum.as_ref().map(|d| d.0.clone()),
                             script_ref: script_ref.clone(),
+                            prefer_babbage_format: false,
                         };
                         // If this becomes slow on large TXs we can optimize it like the following
                         // to avoid cloning + reserializing the entire output.
@@ -1001,6 +1018,7 @@ impl TransactionBuilder {
                                         amount: base_coin.clone(),
                                         datum_option: datum.as_ref().map(|d| d.0.clone()),
                                         script_ref: script_ref.clone(),
+                                        prefer_babbage_format: false,
                                     };
 
                                     // 3. continue building the new output from the asset we stopped
@@ -1042,7 +1060,7 @@ impl TransactionBuilder {
                         let nft_changes = pack_nfts_for_change(self.config.max_value_size, &self.config.coins_per_utxo_byte, self.config.coins_per_utxo_word.as_ref(), address, &change_left, datum.clone(), &script_ref, &communication_datum)?;
                         if nft_changes.is_empty() {
                             // this likely should never happen
-                            return Err(JsError::from_str("NFTs too large for change output"));
+                            return Err(BuilderError::ChangeTooLarge);
                         }
                         // we only add the minimum needed (for now) to cover this output
                         for nft_change in nft_changes.iter() {
@@ -1059,8 +1077,12 @@ impl TransactionBuilder {
                             // increase fee
                             let fee_for_change = self.fee_for_output(&change_output)?;
                             new_fee = new_fee.checked_add(&fee_for_change)?;
-                            if change_left.coin() < change_output.output().amount.coin().checked_add(&new_fee)? {
-                                return Err(JsError::from_str("Not enough ADA leftover to include non-ADA assets in a change address"));
+                            let required = change_output.output().amount.coin().checked_add(&new_fee)?;
+                            if change_left.coin() < required {
+                                return Err(BuilderError::InsufficientChangeAda {
+                                    required,
+                                    available: change_left.coin(),
+                                });
                             }
                             change_left = change_left.checked_sub(&change_output.output().amount)?;
                             self.add_output(&change_output)?;
@@ -1075,6 +1097,7 @@ impl TransactionBuilder {
                             amount: change_left.clone(),
                             datum_option: datum.as_ref().map(|d| d.0.clone()),
                             script_ref: script_ref.clone(),
+                            prefer_babbage_format: false,
                         });
                         let additional_fee = self.fee_for_output(&pure_output)?;
                         let potential_pure_value = change_left.checked_sub(&Value::new(&additional_fee))?;
@@ -1087,6 +1110,7 @@ impl TransactionBuilder {
                                 amount: potential_pure_value.clone(),
                                 datum_option: datum.as_ref().map(|d| d.0.clone()),
                                 script_ref: script_ref.clone(),
+                                prefer_babbage_format: false,
                             }))?;
                         }
                     }
@@ -1103,12 +1127,13 @@ impl TransactionBuilder {
                             amount: change_estimator.clone(),
                             datum_option: datum.as_ref().map(|d| d.0.clone()),
                             script_ref: script_ref.clone(),
+                            prefer_babbage_format: false,
                         },
                         &self.config.coins_per_utxo_byte,
                         self.config.coins_per_utxo_word.as_ref(),
                     )?;
                     // no-asset case so we have no problem burning the rest if there is no other option
-                    fn burn_extra(builder: &mut TransactionBuilder, burn_amount: &BigNum) -> Result<bool, JsError> {
+                    fn burn_extra(builder: &mut TransactionBuilder, burn_amount: &BigNum) -> Result<bool, BuilderError> {
                         // recall: min_fee assumed the fee was the maximum possible so we definitely have enough input to cover whatever fee it ends up being
                         builder.set_fee(burn_amount);
                         Ok(false) // not enough input to covert the extra fee from adding an output so we just burn whatever is left
@@ -1122,6 +1147,7 @@ impl TransactionBuilder {
                                 amount: change_estimator.clone(),
                                 datum_option: datum.as_ref().map(|d| d.0.clone()),
                                 script_ref: script_ref.clone(),
+                                prefer_babbage_format: false,
                             }))?;
 
                             let new_fee = fee.checked_add(&fee_for_change)?;
@@ -1136,6 +1162,7 @@ impl TransactionBuilder {
                                         amount: change_estimator.checked_sub(&Value::new(&new_fee.clone()))?,
                                         datum_option: datum.as_ref().map(|d| d.0.clone()),
                                         script_ref: script_ref.clone(),
+                                        prefer_babbage_format: false,
                                     }))?;
 
                                     Ok(true)
@@ -1145,7 +1172,6 @@ impl TransactionBuilder {
                     }
                 }
             }
-            None => Err(JsError::from_str("missing input or output (possibly some native asset)")),
         }
     }
 
@@ -1153,7 +1179,7 @@ impl TransactionBuilder {
         self.collateral_return = Some(output.clone());
     }
 
-    fn calc_collateral_total(&self) -> Result<Option<Coin>, JsError> {
+    fn calc_collateral_total(&self) -> Result<Option<Coin>, BuilderError> {
         match self.collateral_return.as_ref() {
             None => Ok(None),
             Some(coll_ret) => {
@@ -1161,7 +1187,7 @@ impl TransactionBuilder {
                     Some(collateral) => {
                         collateral.iter().fold(Result::Ok(Coin::zero()), |acc, next| acc?.checked_add(&next.output.amount.coin()))
                     },
-                    None => return Err(JsError::from_str("Cannot use collateral return without also having collateral input"))
+                    None => return Err(BuilderError::CollateralReturnWithoutCollateral)
                 }?;
     
                 let coll_tot = input_sum.checked_sub(&coll_ret.amount().coin())?;
@@ -1170,8 +1196,8 @@ impl TransactionBuilder {
         }
     }
 
-    fn build_and_size(&self) -> Result<(TransactionBody, usize), JsError> {
-        let fee = self.fee.ok_or_else(|| JsError::from_str("Fee not specified"))?;
+    fn build_and_size(&self) -> Result<(TransactionBody, usize), BuilderError> {
+        let fee = self.fee.ok_or(BuilderError::FeeNotSet)?;
 
         // dummy exunits use large values
         // to avoid users accidentally spending all their ADA in tx fees,
@@ -1207,7 +1233,7 @@ impl TransactionBuilder {
         Ok((full_tx.body, full_tx_size))
     }
 
-    pub fn full_size(&self) -> Result<usize, JsError> {
+    pub fn full_size(&self) -> Result<usize, BuilderError> {
         self.build_and_size().map(|r| { r.1 })
     }
 
@@ -1216,14 +1242,13 @@ impl TransactionBuilder {
     }
 
     /// Returns object the body of the new transaction
-    fn build_body(&self) -> Result<TransactionBody, JsError> {
+    fn build_body(&self) -> Result<TransactionBody, BuilderError> {
         let (body, full_tx_size) = self.build_and_size()?;
         if full_tx_size > self.config.max_tx_size as usize {
-            Err(JsError::from_str(&format!(
-                "Maximum transaction size of {} exceeded. Found: {}",
-                self.config.max_tx_size,
-                full_tx_size
-            )))
+            Err(BuilderError::MaxTxSizeExceeded {
+                max: self.config.max_tx_size,
+                actual: full_tx_size,
+            })
         } else {
             Ok(body)
         }
@@ -1231,7 +1256,7 @@ impl TransactionBuilder {
 
     /// Builds the transaction and moves to the next step where any real witness can be added
     /// NOTE: is_valid set to true
-    pub fn build(&self) -> Result<TxRedeemerBuilder, JsError> {
+    pub fn build(&self) -> Result<TxRedeemerBuilder, BuilderError> {
         Ok(TxRedeemerBuilder {
             draft_body: self.build_body()?,
             witness_builders: self.witness_builders.clone(),
@@ -1243,11 +1268,18 @@ impl TransactionBuilder {
     /// warning: sum of all parts of a transaction must equal 0. You cannot just set the fee to the min value and forget about it
     /// warning: min_fee may be slightly larger than the actual minimum fee (ex: a few lovelaces)
     /// this is done to simplify the library code, but can be fixed later
-    pub fn min_fee(&self) -> Result<Coin, JsError> {
+    pub fn min_fee(&self) -> Result<Coin, BuilderError> {
         let mut self_copy = self.clone();
         self_copy.set_fee(&to_bignum(force_u64));
         min_fee(&self_copy)
     }
+
+    /// The outputs this transaction will create, so that dependent transactions can spend them before it is submitted
+    /// The fee and change must already be set (e.g. with add_change_if_needed)
+    /// warning: if the transaction runs plutus scripts, changing the ex units afterwards changes the transaction hash
+    pub fn produced_utxos(&self) -> Result<TransactionUnspentOutputs, BuilderError> {
+        Ok(self.build()?.build()?.produced_utxos())
+    }
 }
 
 
@@ -1263,7 +1295,7 @@ impl TxRedeemerBuilder {
     /// Builds the transaction and moves to the next step where any real witness can be added
     /// NOTE: is_valid set to true
     /// Will NOT require you to have set required signers & witnesses
-    pub fn build(&self) -> Result<SignedTxBuilder, JsError> {
+    pub fn build(&self) -> Result<SignedTxBuilder, BuilderError> {
         let mut witness_set = self.witness_builders.build_unchecked();
         // remove temporary required signers & witnesses
         witness_set.vkeys.clear();
@@ -1278,8 +1310,7 @@ impl TxRedeemerBuilder {
             })
         } else {
             let redeemers = self.witness_builders.redeemer_set_builder
-                .build(true)
-                .map_err(|err| JsError::from_str(&format!("{}", err)))?;
+                .build(true)?;
 
             let script_data_hash = calc_script_data_hash(
                 &redeemers,
@@ -1361,6 +1392,7 @@ impl TxRedeemerBuilder {
 }
 
 
+#[derive(Clone)]
 pub struct SignedTxBuilder {
     body: TransactionBody,
     witness_set: TransactionWitnessSetBuilder,
@@ -1397,7 +1429,7 @@ impl SignedTxBuilder {
         }
     }
 
-    pub fn build_checked(&self) -> Result<Transaction, JsError> {
+    pub fn build_checked(&self) -> Result<Transaction, BuilderError> {
         Ok(Transaction {
             body: self.body.clone(),
             witness_set: self.witness_set.try_build()?,
@@ -1434,6 +1466,12 @@ impl SignedTxBuilder {
         self.witness_set.add_bootstrap(bootstrap);
     }
 
+    /// Merges the witness set signed by another party of this transaction (e.g. a multisig co-signer)
+    /// Fails without adding anything if any of its signatures does not match the body hash
+    pub fn add_witness_set(&mut self, witness_set: &TransactionWitnessSet) -> Result<(), BuilderError> {
+        self.witness_set.add_existing_checked(&hash_transaction(&self.body), witness_set)
+    }
+
     pub fn body(&self) -> TransactionBody {
         self.body.clone()
     }
@@ -1449,6 +1487,11 @@ impl SignedTxBuilder {
     pub fn auxiliary_data(&self) -> Option<AuxiliaryData> {
         self.auxiliary_data.clone()
     }
+
+    /// The outputs this transaction will create, keyed by the hash of its body and the output index
+    pub fn produced_utxos(&self) -> TransactionUnspentOutputs {
+        get_produced_utxos(&self.body, self.is_valid)
+    }
 }
 
 #[cfg(test)]
@@ -2668,13 +2711,15 @@ mod tests {
         let mut output_amount = Value::new(&to_bignum(50));
         output_amount.set_multiasset(&create_multiasset().0);
 
-        assert!(tx_builder.add_output(
-            &TransactionOutputBuilder::new()
+        assert!(matches!(
+            tx_builder.add_output(&TransactionOutputBuilder::new()
                 .with_address(&output_addr)
                 .next().unwrap()
                 .with_value(&output_amount)
                 .build().unwrap()
-            ).is_err());
+            ),
+            Err(BuilderError::MaxValueSizeExceeded { max: 10, .. })
+        ));
     }
 
     #[test]
@@ -2733,7 +2778,7 @@ mod tests {
 
         let change_addr = ByronAddress::from_base58("Ae2tdPwUPEZGUEsuMAhvDcy94LKsZxDjCbgaiBBMgYpR8sKf96xJmit7Eho").unwrap().to_address();
 
-        assert!(tx_builder.add_change_if_needed(&change_addr).is_err())
+        assert!(matches!(tx_builder.add_change_if_needed(&change_addr), Err(BuilderError::InsufficientBalance { .. })));
     }
 
     fn make_input(input_hash_byte: u8, value: Value) -> InputBuilderResult {
@@ -2807,6 +2852,34 @@ mod tests {
         assert_eq!(3u8, tx.inputs().get(1).transaction_id().0[0]);
     }
 
+    #[test]
+    fn tx_builder_cip2_insufficient_balance() {
+        let mut tx_builder = create_tx_builder_with_fee(&create_linear_fee(0, 0));
+        tx_builder.add_output(
+            &TransactionOutputBuilder::new()
+                .with_address(&Address::from_bech32("addr1vyy6nhfyks7wdu3dudslys37v252w2nwhv0fw2nfawemmnqs6l44z").unwrap())
+                .next().unwrap()
+                .with_coin(&to_bignum(1200))
+                .build().unwrap()
+            ).unwrap();
+        tx_builder.add_utxo(&make_input(0u8, Value::new(&to_bignum(800))));
+        tx_builder.add_utxo(&make_input(1u8, Value::new(&to_bignum(100))));
+        let missing = match tx_builder.clone().select_utxos(CoinSelectionStrategyCIP2::LargestFirst) {
+            Err(BuilderError::InsufficientBalance { missing }) => missing,
+            other => panic!("expected insufficient balance, got {:?}", other),
+        };
+        assert_eq!(missing, Value::new(&to_bignum(300)));
+        assert_eq!(
+            tx_builder.clone().select_utxos(CoinSelectionStrategyCIP2::RandomImprove).unwrap_err().to_string(),
+            "UTxO Balance Insufficient: missing 300 lovelace",
+        );
+
+        // adding exactly what is missing is enough to balance
+        tx_builder.add_utxo(&make_input(2u8, missing));
+        tx_builder.select_utxos(CoinSelectionStrategyCIP2::LargestFirst).unwrap();
+        assert_eq!(tx_builder.get_total_input().unwrap(), Value::new(&to_bignum(1200)));
+    }
+
     #[test]
     fn tx_builder_cip2_largest_first_multiasset() {
         // we have a = 0 so we know adding inputs/outputs doesn't change the fee so we can analyze more
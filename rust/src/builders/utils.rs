@@ -24,7 +24,7 @@ impl GeneralTransactionMetadata {
         key: &TransactionMetadatumLabel,
         val: String,
         schema: MetadataJsonSchema,
-    ) -> Result<(), JsError> {
+    ) -> Result<(), Error> {
         let metadatum = encode_json_str_to_metadatum(val, schema)?;
         self.insert(key, &metadatum);
         Ok(())
@@ -58,7 +58,7 @@ impl AuxiliaryData {
         key: &TransactionMetadatumLabel,
         val: String,
         schema: MetadataJsonSchema,
-    ) -> Result<(), JsError> {
+    ) -> Result<(), Error> {
         let metadatum = encode_json_str_to_metadatum(val, schema)?;
         self.add_metadatum(key, &metadatum);
         Ok(())
@@ -106,6 +106,17 @@ impl AuxiliaryData {
                 }
             }
         };
+
+        match (self.plutus_v3_scripts.as_mut(), other.plutus_v3_scripts.as_ref()) {
+            (None, None) => {},
+            (Some(_), None) => {},
+            (None, val@Some(_)) => { self.plutus_v3_scripts = val.cloned(); },
+            (Some(data1), Some(data2)) => {
+                for script in &data2.0 {
+                    data1.add(script);
+                }
+            }
+        };
     }
 }
 
@@ -120,6 +131,7 @@ mod tests {
             native_scripts: Some(NativeScripts::new()),
             plutus_v1_scripts: Some(PlutusV1Scripts::new()),
             plutus_v2_scripts: Some(PlutusV2Scripts::new()),
+            plutus_v3_scripts: Some(PlutusV3Scripts::new()),
             prefer_alonzo_format: false,
         };
 
@@ -1,6 +1,7 @@
 use crate::*;
 use crate::builders::witness_builder::{InputAggregateWitnessData, PartialPlutusWitness};
 
+use super::error::BuilderError;
 use super::witness_builder::{RequiredWitnessSet, NativeScriptWitnessInfo};
 
 // comes from witsVKeyNeeded in the Ledger spec
@@ -40,12 +41,12 @@ impl SingleWithdrawalBuilder {
         }
     }
 
-    pub fn payment_key(&self) -> Result<WithdrawalBuilderResult, JsError> {
+    pub fn payment_key(&self) -> Result<WithdrawalBuilderResult, BuilderError> {
         let mut required_wits = RequiredWitnessSet::default();
         withdrawal_required_wits(&self.address, &mut required_wits);
 
-        if !required_wits.scripts.is_empty() {
-            return Err(JsError::from_str(&format!("Withdrawal required a script, not a payment key: \n{:#?}", self.address.to_address().to_bech32(None))));
+        if let Some(script_hash) = required_wits.scripts.iter().next() {
+            return Err(BuilderError::ScriptWitnessRequired(script_hash.clone()));
         }
 
         Ok(WithdrawalBuilderResult {
@@ -56,7 +57,7 @@ impl SingleWithdrawalBuilder {
         })
     }
 
-    pub fn native_script(&self, native_script: &NativeScript, witness_info: &NativeScriptWitnessInfo) -> Result<WithdrawalBuilderResult, JsError> {
+    pub fn native_script(&self, native_script: &NativeScript, witness_info: &NativeScriptWitnessInfo) -> Result<WithdrawalBuilderResult, BuilderError> {
         let mut required_wits = RequiredWitnessSet::default();
         withdrawal_required_wits(&self.address, &mut required_wits);
         let mut required_wits_left = required_wits.clone();
@@ -65,7 +66,7 @@ impl SingleWithdrawalBuilder {
         required_wits_left.scripts.remove(&native_script.hash());
 
         if !required_wits_left.scripts.is_empty() {
-            return Err(JsError::from_str(&format!("Missing the following witnesses for the withdrawal: \n{:#?}", required_wits_left.to_str())));
+            return Err(BuilderError::MissingWitnesses(Box::new(required_wits_left)));
         }
 
         Ok(WithdrawalBuilderResult {
@@ -76,7 +77,7 @@ impl SingleWithdrawalBuilder {
         })
     }
 
-    pub fn plutus_script(&self, partial_witness: &PartialPlutusWitness, required_signers: &RequiredSigners) -> Result<WithdrawalBuilderResult, JsError> {
+    pub fn plutus_script(&self, partial_witness: &PartialPlutusWitness, required_signers: &RequiredSigners) -> Result<WithdrawalBuilderResult, BuilderError> {
         let mut required_wits = RequiredWitnessSet::default();
         required_signers.0.iter().for_each(|required_signer| required_wits.add_vkey_key_hash(required_signer));
         withdrawal_required_wits(&self.address, &mut required_wits);
@@ -91,7 +92,7 @@ impl SingleWithdrawalBuilder {
         required_wits_left.scripts.remove(&script_hash);
 
         if required_wits_left.len() > 0 {
-            return Err(JsError::from_str(&format!("Missing the following witnesses for the withdrawal: \n{:#?}", required_wits_left.to_str())));
+            return Err(BuilderError::MissingWitnesses(Box::new(required_wits_left)));
         }
 
         Ok(WithdrawalBuilderResult {
@@ -1,6 +1,7 @@
 use std::{collections::{HashMap}, fmt::Debug};
 use crate::{*, ledger::common::hash::hash_plutus_data, byron::ByronAddress};
 
+use super::error::BuilderError;
 use super::redeemer_builder::RedeemerWitnessKey;
 
 /// A partial Plutus witness
@@ -101,8 +102,8 @@ impl RequiredWitnessSet {
         }
     }
 
-    pub fn add_plutus_script(&mut self, plutus_v1_script: &PlutusScript) {
-        self.add_script_hash(&plutus_v1_script.hash());
+    pub fn add_plutus_script(&mut self, plutus_script: &PlutusScript) {
+        self.add_script_hash(&plutus_script.hash());
     }
 
     pub fn add_plutus_datum(&mut self, plutus_datum: &PlutusData) {
@@ -127,6 +128,30 @@ impl RequiredWitnessSet {
         self.redeemers.extend(requirements.redeemers.iter().cloned());
     }
 
+    pub fn vkeys(&self) -> Ed25519KeyHashes {
+        Ed25519KeyHashes(self.vkeys.iter().cloned().collect())
+    }
+
+    pub fn bootstraps(&self) -> Vec<ByronAddress> {
+        self.bootstraps.iter().cloned().collect()
+    }
+
+    pub fn scripts(&self) -> ScriptHashes {
+        ScriptHashes(self.scripts.iter().cloned().collect())
+    }
+
+    pub fn plutus_data(&self) -> Vec<DataHash> {
+        self.plutus_data.iter().cloned().collect()
+    }
+
+    pub fn redeemers(&self) -> Vec<RedeemerWitnessKey> {
+        self.redeemers.iter().cloned().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     pub (crate) fn to_str(&self) -> String {
         let vkeys = self.vkeys.iter().map(|key| format!("Vkey:{}", hex::encode(key.to_bytes()))).collect::<Vec<String>>().join(",");
         let bootstraps = self.bootstraps.iter().map(|data| format!("Legacy Bootstraps addresses:{}", data.to_base58())).collect::<Vec<String>>().join(",");
@@ -223,6 +248,7 @@ impl TransactionWitnessSetBuilder {
             ScriptEnum::Native(native) => self.add_native_script(native),
             ScriptEnum::PlutusV1(plutus_v1) => self.add_plutus_v1_script(plutus_v1),
             ScriptEnum::PlutusV2(plutus_v2) => self.add_plutus_v2_script(plutus_v2),
+            ScriptEnum::PlutusV3(plutus_v3) => self.add_plutus_v3_script(plutus_v3),
         }
     }
 
@@ -280,6 +306,24 @@ impl TransactionWitnessSetBuilder {
         PlutusV2Scripts(scripts)
     }
 
+    pub fn add_plutus_v3_script(&mut self, plutus_v3_script: &PlutusV3Script) {
+        self.scripts.insert(plutus_v3_script.hash(), ScriptEnum::PlutusV3(plutus_v3_script.clone()));
+    }
+
+    pub fn get_plutus_v3_script(&self) -> PlutusV3Scripts {
+        let scripts: Vec<PlutusV3Script> = self.scripts
+            .iter()
+            .filter(|entry| self.required_wits.script_refs.get(entry.0).is_none())
+            .fold(
+                Vec::<PlutusV3Script>::new(),
+                |mut acc, script| match &script.1 {
+                    &ScriptEnum::PlutusV3(plutus_script) => { acc.push(plutus_script.clone()); acc },
+                    _ => acc
+                }
+            );
+        PlutusV3Scripts(scripts)
+    }
+
     pub fn add_plutus_datum(&mut self, plutus_datum: &PlutusData) {
         self.plutus_data.insert(hash_plutus_data(plutus_datum), plutus_datum.clone());
     }
@@ -331,11 +375,69 @@ impl TransactionWitnessSetBuilder {
         if let Some(plutus_scripts) = &wit_set.plutus_v2_scripts() {
             plutus_scripts.0.iter().for_each(|plutus_script| { self.add_plutus_v2_script(plutus_script); } );
         }
+        if let Some(plutus_scripts) = &wit_set.plutus_v3_scripts() {
+            plutus_scripts.0.iter().for_each(|plutus_script| { self.add_plutus_v3_script(plutus_script); } );
+        }
+        if let Some(plutus_data) = &wit_set.plutus_data() {
+            plutus_data.elems.iter().for_each(|datum| { self.add_plutus_datum(datum); } );
+        }
         if let Some(redeemers) = &wit_set.redeemers() {
             redeemers.0.iter().for_each(|redeemer| { self.add_redeemer(redeemer); } );
         }
     }
 
+    /// Adds a witness set signed by one of the parties of a transaction
+    /// Every vkey and bootstrap signature is checked against `tx_body_hash` first
+    /// and nothing is added if any of them is invalid
+    pub fn add_existing_checked(&mut self, tx_body_hash: &TransactionHash, wit_set: &TransactionWitnessSet) -> Result<(), BuilderError> {
+        let mut other = TransactionWitnessSetBuilder::new();
+        other.add_existing(wit_set);
+        let invalid = other.invalid_signatures(tx_body_hash);
+        if invalid.len() > 0 {
+            return Err(BuilderError::InvalidSignatures {
+                tx_hash: tx_body_hash.clone(),
+                vkeys: invalid,
+            });
+        }
+        self.add_all(&other);
+        Ok(())
+    }
+
+    /// Merges the witnesses and the requirements of another builder into this one
+    /// Witnesses present in both are de-duplicated
+    pub fn add_all(&mut self, other: &TransactionWitnessSetBuilder) {
+        self.vkeys.extend(other.vkeys.clone());
+        self.bootstraps.extend(other.bootstraps.clone());
+        self.scripts.extend(other.scripts.clone());
+        self.plutus_data.extend(other.plutus_data.clone());
+        self.redeemers.extend(other.redeemers.clone());
+        self.required_wits.add_all(&other.required_wits);
+    }
+
+    /// Returns the keys of all vkey and bootstrap witnesses whose signature does not match `tx_body_hash`
+    pub fn invalid_signatures(&self, tx_body_hash: &TransactionHash) -> Vkeys {
+        let vkeys = self.vkeys.values()
+            .filter(|wit| !wit.vkey().public_key().verify(tx_body_hash.to_bytes().as_ref(), &wit.signature()))
+            .map(|wit| wit.vkey());
+        let bootstraps = self.bootstraps.values()
+            .filter(|wit| !wit.vkey().public_key().verify(tx_body_hash.to_bytes().as_ref(), &wit.signature()))
+            .map(|wit| wit.vkey());
+        Vkeys(vkeys.chain(bootstraps).collect())
+    }
+
+    /// Signature status of every native script added to this builder
+    /// given the vkey witnesses collected so far
+    pub fn native_script_status(&self) -> Vec<NativeScriptWitnessStatus> {
+        let signers = self.vkeys.keys().map(|vkey| vkey.public_key().hash()).collect::<BTreeSet<_>>();
+        self.scripts
+            .values()
+            .filter_map(|script| match script {
+                ScriptEnum::Native(native_script) => Some(NativeScriptWitnessStatus::new(native_script, &signers)),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub(crate) fn add_input_aggregate_real_witness_data(&mut self, data: &InputAggregateWitnessData) {
         match data {
             InputAggregateWitnessData::NativeScript(script, _info) => {
@@ -344,7 +446,8 @@ impl TransactionWitnessSetBuilder {
             InputAggregateWitnessData::PlutusScript(witness, _info, option) => {
                 match &witness.script.0 {
                     PlutusScriptEnum::PlutusV1(script) => self.add_plutus_v1_script(script),
-                    PlutusScriptEnum::PlutusV2(script) => self.add_plutus_v2_script(script)
+                    PlutusScriptEnum::PlutusV2(script) => self.add_plutus_v2_script(script),
+                    PlutusScriptEnum::PlutusV3(script) => self.add_plutus_v3_script(script),
                 }
                 if let Some(ref data) = option {
                     self.add_plutus_datum(data);
@@ -381,6 +484,12 @@ impl TransactionWitnessSetBuilder {
                 result.set_plutus_v2_scripts(&plutus_scripts);
             }
         }
+        {
+            let plutus_scripts = self.get_plutus_v3_script();
+            if !plutus_scripts.0.is_empty() {
+                result.set_plutus_v3_scripts(&plutus_scripts);
+            }
+        }
         if !self.plutus_data.is_empty() {
             result.set_plutus_data(&self.get_plutus_datum());
         }
@@ -403,11 +512,11 @@ impl TransactionWitnessSetBuilder {
         remaining_wits
     }
 
-    pub fn try_build(&self) -> Result<TransactionWitnessSet, JsError> {
+    pub fn try_build(&self) -> Result<TransactionWitnessSet, BuilderError> {
         let remaining_wits = self.remaining_wits();
 
         if remaining_wits.len() > 0 {
-            return Err(JsError::from_str(&format!("Missing following witnesses:\n{}", remaining_wits.to_str())))
+            return Err(BuilderError::MissingWitnesses(Box::new(remaining_wits)))
         }
 
         Ok(self.build())
@@ -460,6 +569,78 @@ fn fake_key_hash(x: u8) -> Ed25519KeyHash {
     ).unwrap()
 }
 
+/// Whether a native script (and recursively each of its branches)
+/// is satisfied by a given set of signers.
+/// Timelocks do not depend on witnesses so they are always considered satisfied here
+#[derive(Clone, Debug)]
+pub struct NativeScriptWitnessStatus {
+    script: NativeScript,
+    satisfied: bool,
+    branches: Vec<NativeScriptWitnessStatus>,
+}
+
+impl NativeScriptWitnessStatus {
+    pub fn new(script: &NativeScript, signers: &RequiredSignersSet) -> Self {
+        let branches_of = |scripts: &NativeScripts| scripts.0
+            .iter()
+            .map(|script| NativeScriptWitnessStatus::new(script, signers))
+            .collect::<Vec<_>>();
+        let (satisfied, branches) = match &script.0 {
+            NativeScriptEnum::ScriptPubkey(pubkey) => (signers.contains(&pubkey.addr_keyhash), vec![]),
+            NativeScriptEnum::ScriptAll(all) => {
+                let branches = branches_of(&all.native_scripts);
+                (branches.iter().all(|branch| branch.satisfied), branches)
+            },
+            NativeScriptEnum::ScriptAny(any) => {
+                let branches = branches_of(&any.native_scripts);
+                (branches.iter().any(|branch| branch.satisfied), branches)
+            },
+            NativeScriptEnum::ScriptNOfK(n_of_k) => {
+                let branches = branches_of(&n_of_k.native_scripts);
+                (branches.iter().filter(|branch| branch.satisfied).count() >= n_of_k.n as usize, branches)
+            },
+            NativeScriptEnum::TimelockStart(_) | NativeScriptEnum::TimelockExpiry(_) => (true, vec![]),
+        };
+        Self {
+            script: script.clone(),
+            satisfied,
+            branches,
+        }
+    }
+
+    pub fn script(&self) -> NativeScript {
+        self.script.clone()
+    }
+
+    pub fn is_satisfied(&self) -> bool {
+        self.satisfied
+    }
+
+    /// Status of the direct sub-scripts of ScriptAll, ScriptAny and ScriptNOfK. Empty for other kinds
+    pub fn branches(&self) -> Vec<NativeScriptWitnessStatus> {
+        self.branches.clone()
+    }
+
+    /// Keys that could still sign to satisfy the script. Empty if the script is already satisfied
+    /// For ScriptAny and ScriptNOfK this includes the keys of all unsatisfied branches
+    /// since any large enough subset of them would do
+    pub fn missing_vkeys(&self) -> Ed25519KeyHashes {
+        let mut missing = BTreeSet::new();
+        self.collect_missing_vkeys(&mut missing);
+        Ed25519KeyHashes(missing.into_iter().collect())
+    }
+
+    fn collect_missing_vkeys(&self, missing: &mut RequiredSignersSet) {
+        if self.satisfied {
+            return;
+        }
+        match &self.script.0 {
+            NativeScriptEnum::ScriptPubkey(pubkey) => { missing.insert(pubkey.addr_keyhash()); },
+            _ => self.branches.iter().for_each(|branch| branch.collect_missing_vkeys(missing)),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum NativeScriptWitnessInfoKind {
     Count(usize),
@@ -492,6 +673,7 @@ impl NativeScriptWitnessInfo {
 #[cfg(test)]
 mod tests {
     use crate::ledger::byron::witness::make_icarus_bootstrap_witness;
+    use crate::ledger::shelley::witness::make_vkey_witness;
 
     use super::*;
 
@@ -690,6 +872,80 @@ mod tests {
         assert!(builder.try_build().is_ok());
     }
 
+    fn signed_wit_set(tx_hash: &TransactionHash, key: &PrivateKey) -> TransactionWitnessSet {
+        let mut wit_set = TransactionWitnessSet::new();
+        let mut vkeys = Vkeywitnesses::new();
+        vkeys.add(&make_vkey_witness(tx_hash, key));
+        wit_set.set_vkeys(&vkeys);
+        wit_set
+    }
+
+    #[test]
+    fn merge_signed_witness_sets() {
+        let tx_hash = TransactionHash::from([1u8; TransactionHash::BYTE_COUNT]);
+        let key1 = fake_private_key1().to_raw_key();
+        let key2 = fake_private_key2().to_raw_key();
+
+        let mut required_wits = RequiredWitnessSet::new();
+        required_wits.add_vkey_key_hash(&key1.to_public().hash());
+        required_wits.add_vkey_key_hash(&key2.to_public().hash());
+        let mut builder = TransactionWitnessSetBuilder::new();
+        builder.add_required_wits(&required_wits);
+
+        builder.add_existing_checked(&tx_hash, &signed_wit_set(&tx_hash, &key1)).unwrap();
+        // the same party sending its witness twice is de-duplicated
+        builder.add_existing_checked(&tx_hash, &signed_wit_set(&tx_hash, &key1)).unwrap();
+        assert_eq!(builder.remaining_wits().vkeys().0, vec![key2.to_public().hash()]);
+
+        builder.add_existing_checked(&tx_hash, &signed_wit_set(&tx_hash, &key2)).unwrap();
+        assert!(builder.remaining_wits().is_empty());
+        assert_eq!(builder.try_build().unwrap().vkeys().unwrap().len(), 2);
+        assert_eq!(builder.invalid_signatures(&tx_hash).len(), 0);
+    }
+
+    #[test]
+    fn merge_rejects_invalid_signature() {
+        let tx_hash = TransactionHash::from([1u8; TransactionHash::BYTE_COUNT]);
+        let other_hash = TransactionHash::from([2u8; TransactionHash::BYTE_COUNT]);
+        let key1 = fake_private_key1().to_raw_key();
+
+        let mut builder = TransactionWitnessSetBuilder::new();
+        assert!(builder.add_existing_checked(&tx_hash, &signed_wit_set(&other_hash, &key1)).is_err());
+        assert!(builder.build().vkeys().is_none());
+
+        // unchecked adds are still reported
+        builder.add_existing(&signed_wit_set(&other_hash, &key1));
+        assert_eq!(builder.invalid_signatures(&tx_hash).len(), 1);
+    }
+
+    #[test]
+    fn native_script_status_n_of_k() {
+        let tx_hash = TransactionHash::from([1u8; TransactionHash::BYTE_COUNT]);
+        let key1 = fake_private_key1().to_raw_key();
+        let key2 = fake_private_key2().to_raw_key();
+        let key3 = fake_raw_key_public(3);
+
+        let mut scripts = NativeScripts::new();
+        scripts.add(&NativeScript::new_script_pubkey(&ScriptPubkey::new(&key1.to_public().hash())));
+        scripts.add(&NativeScript::new_script_pubkey(&ScriptPubkey::new(&key2.to_public().hash())));
+        scripts.add(&NativeScript::new_script_pubkey(&ScriptPubkey::new(&key3.hash())));
+        let script = NativeScript::new_script_n_of_k(&ScriptNOfK::new(2, &scripts));
+
+        let mut builder = TransactionWitnessSetBuilder::new();
+        builder.add_native_script(&script);
+        builder.add_existing_checked(&tx_hash, &signed_wit_set(&tx_hash, &key1)).unwrap();
+
+        let status = &builder.native_script_status()[0];
+        assert!(!status.is_satisfied());
+        assert_eq!(status.branches().iter().map(|branch| branch.is_satisfied()).collect::<Vec<_>>(), vec![true, false, false]);
+        assert_eq!(status.missing_vkeys().len(), 2);
+
+        builder.add_existing_checked(&tx_hash, &signed_wit_set(&tx_hash, &key2)).unwrap();
+        let status = &builder.native_script_status()[0];
+        assert!(status.is_satisfied());
+        assert_eq!(status.missing_vkeys().len(), 0);
+    }
+
     #[test]
     fn tx_witness_set_roundtrip_test() {
         let data = "a102818458205e8379f58f0838234af67f73738f0fee0d8185232e200b8e42887f4f06544a9a5840f5cfea560d2f8645ed624b65bf08cf83346eb5168ee4df0f63ce2d0d5f677db88fef2d5d9f032f09223889b5e85504ab44dd0a0cde1f1fd8f57deefde8c2080658202d3b7d9b806f88f10f1193e94ef97e5c02370c1464f61a30a8f1ac1a46115b2d5829a201581e581c072931653330243cf126aea85d39e73c6bd04601fe77424efb9e371002451a4170cb17";
@@ -4,7 +4,7 @@ use schemars::JsonSchema;
 use crate::chain_crypto;
 use crate::chain_crypto::Ed25519;
 use crate::chain_crypto::Ed25519Bip32;
-use crate::JsError;
+use crate::Error;
 
 use crate::crypto::Bip32PublicKey;
 use crate::crypto::PublicKey;
@@ -19,7 +19,6 @@ use cbor_event::{self, de::Deserializer, se::{Serialize, Serializer}};
 use cbor_event::Type as CBORType;
 
 use cbor_event::Special as CBORSpecial;
-use wasm_bindgen::JsValue;
 
 use crate::{chain_crypto::hash::Blake2b224, crypto::impl_hash_type};
 use crate::error::{DeserializeError, DeserializeFailure};
@@ -179,11 +178,11 @@ impl ByronAddress {
         }
     }
 
-    pub fn new(addr: Vec<u8>, crc32: &Crc32) -> Result<ByronAddress, JsError> {
+    pub fn new(addr: Vec<u8>, crc32: &Crc32) -> Result<ByronAddress, Error> {
         let found_crc = crate::byron::crc32::crc32(&addr);
 
         if crc32.0 != found_crc as u32 {
-            return Err(JsError::from_str(&format!(
+            return Err(Error::from_str(&format!(
                 "Invalid CRC32: 0x{:x} but expected 0x{:x}",
                 crc32.0, found_crc
             )));
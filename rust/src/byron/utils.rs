@@ -106,7 +106,7 @@ impl AddressContent {
         }
     }
 
-    pub fn network_id(&self) -> Result<u8, JsError> {
+    pub fn network_id(&self) -> Result<u8, Error> {
         // premise: during the Byron-era, we had one mainnet (764824073) and many many testnets
         // with each testnet getting a different protocol magic
         // in Shelley, this changes so that:
@@ -121,7 +121,7 @@ impl AddressContent {
         match protocol_magic {
             magic if magic == NetworkInfo::mainnet().protocol_magic().0 => Ok(NetworkInfo::mainnet().network_id()),
             magic if magic == NetworkInfo::testnet().protocol_magic().0 => Ok(NetworkInfo::testnet().network_id()),
-            _ => Err(JsError::from_str(&format! {"Unknown network {}", protocol_magic}))
+            _ => Err(Error::from_str(&format! {"Unknown network {}", protocol_magic}))
         }
     }
 
@@ -161,13 +161,13 @@ impl ByronAddress {
         base58::encode(&self.to_bytes())
     }
 
-    pub fn from_base58(s: &str) -> Result<ByronAddress, JsError> {
+    pub fn from_base58(s: &str) -> Result<ByronAddress, Error> {
         let bytes = base58::decode(s)
             // .map_err(ParseExtendedAddrError::Base58Error)
-            .map_err(|_| JsError::from_str("ByronAddress::from_base58 failed to parse base58"))?;
+            .map_err(|_| Error::from_str("ByronAddress::from_base58 failed to parse base58"))?;
         Self::from_bytes(bytes)
             // .map_err(ParseExtendedAddrError::DeserializeError)
-            .map_err(|_| JsError::from_str("ByronAddress::from_base58 failed to parse bytes"))
+            .map_err(|_| Error::from_str("ByronAddress::from_base58 failed to parse bytes"))
     }
 
     pub fn address_content(&self) -> AddressContent {
@@ -210,7 +210,7 @@ impl fmt::Display for ByronAddress {
 
 impl ::std::str::FromStr for ByronAddress {
     // type Err = ParseExtendedAddrError;
-    type Err = JsError;
+    type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Self::from_base58(s)
     }
@@ -0,0 +1,224 @@
+use std::convert::TryFrom;
+
+use bech32::{FromBase32, ToBase32};
+
+use super::*;
+use super::crypto::blake2b160;
+use super::error::Error;
+
+/// bech32 human-readable part of CIP-14 asset fingerprints
+pub const ASSET_FINGERPRINT_HRP: &str = "asset";
+
+/// CIP-14 user-facing asset fingerprint: blake2b-160 of the policy ID followed by the asset name
+/// https://cips.cardano.org/cips/cip14/
+///
+/// Fingerprints are only meant for display: they can't be turned back into a policy ID and asset name
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct AssetFingerprint([u8; 20]);
+
+impl AssetFingerprint {
+    pub fn new(policy_id: &PolicyID, asset_name: &AssetName) -> Self {
+        let mut preimage = policy_id.to_bytes();
+        preimage.extend_from_slice(&asset_name.0);
+        Self(blake2b160(&preimage))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// e.g. asset1rjklcrnsdzqp65wjgrg55sy9723kw09mlgvlc3
+    pub fn to_bech32(&self) -> String {
+        // only fails for invalid human-readable parts
+        bech32::encode(ASSET_FINGERPRINT_HRP, self.0.to_base32()).unwrap()
+    }
+
+    pub fn from_bech32(bech_str: &str) -> Result<AssetFingerprint, Error> {
+        let (hrp, u5data) = bech32::decode(bech_str).map_err(|e| Error::from_str(&e.to_string()))?;
+        if hrp != ASSET_FINGERPRINT_HRP {
+            return Err(Error::from_str(&format!("Asset fingerprint must have the \"{}\" prefix, found \"{}\"", ASSET_FINGERPRINT_HRP, hrp)));
+        }
+        let data = Vec::<u8>::from_base32(&u5data).map_err(|e| Error::from_str(&e.to_string()))?;
+        let bytes = <[u8; 20]>::try_from(data.as_slice())
+            .map_err(|_| Error::from_str(&format!("Asset fingerprint must be 20 bytes, found {}", data.len())))?;
+        Ok(Self(bytes))
+    }
+}
+
+impl std::fmt::Display for AssetFingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_bech32())
+    }
+}
+
+
+/// Fully qualified asset: a policy ID and an asset name under it
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct AssetId {
+    pub(crate) policy_id: PolicyID,
+    pub(crate) asset_name: AssetName,
+}
+
+impl AssetId {
+    pub fn new(policy_id: &PolicyID, asset_name: &AssetName) -> Self {
+        Self {
+            policy_id: policy_id.clone(),
+            asset_name: asset_name.clone(),
+        }
+    }
+
+    pub fn policy_id(&self) -> PolicyID {
+        self.policy_id.clone()
+    }
+
+    pub fn asset_name(&self) -> AssetName {
+        self.asset_name.clone()
+    }
+
+    pub fn fingerprint(&self) -> AssetFingerprint {
+        AssetFingerprint::new(&self.policy_id, &self.asset_name)
+    }
+
+    /// Policy ID and asset name concatenated in hex, as used by most indexers (e.g. Blockfrost's "unit")
+    pub fn to_hex(&self) -> String {
+        format!("{}{}", self.policy_id.to_hex(), self.asset_name.to_hex())
+    }
+
+    /// Policy ID and asset name in hex separated by a dot, as used by cardano-cli.
+    /// The dot is omitted for an empty asset name
+    pub fn to_str(&self) -> String {
+        match self.asset_name.0.is_empty() {
+            true => self.policy_id.to_hex(),
+            false => format!("{}.{}", self.policy_id.to_hex(), self.asset_name.to_hex()),
+        }
+    }
+}
+
+/// Parses either the dotted (`policy.name`) or the concatenated hex format
+impl std::str::FromStr for AssetId {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let policy_hex_len = 2 * PolicyID::BYTE_COUNT;
+        let (policy_hex, name_hex) = match string.split_once('.') {
+            Some((policy_hex, name_hex)) => (policy_hex, name_hex),
+            None if string.len() >= policy_hex_len && string.is_char_boundary(policy_hex_len) => string.split_at(policy_hex_len),
+            None => return Err(Error::from_str(&format!("Asset ID \"{}\" is too short to contain a policy ID", string))),
+        };
+        let policy_id = PolicyID::from_hex(policy_hex)
+            .map_err(|e| Error::from_str(&format!("Invalid policy ID in asset ID \"{}\": {}", string, e.as_string().unwrap_or_default())))?;
+        let asset_name = AssetName::from_hex(name_hex)
+            .map_err(|e| Error::from_str(&format!("Invalid asset name in asset ID \"{}\": {}", string, e.as_string().unwrap_or_default())))?;
+        Ok(Self::new(&policy_id, &asset_name))
+    }
+}
+
+impl std::fmt::Display for AssetId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
+}
+
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AssetIds(pub(crate) Vec<AssetId>);
+
+vec_iter!(AssetIds, AssetId);
+
+impl AssetIds {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> AssetId {
+        self.0[index].clone()
+    }
+
+    pub fn add(&mut self, elem: &AssetId) {
+        self.0.push(elem.clone());
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::cip68::{cip68_asset_name, CIP68TokenLabel};
+    use crate::ledger::common::value::to_bignum;
+
+    // test vectors from CIP-14
+    #[test]
+    fn cip14_fingerprints() {
+        let vectors = [
+            ("7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373", "", "asset1rjklcrnsdzqp65wjgrg55sy9723kw09mlgvlc3"),
+            ("7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc37e", "", "asset1nl0puwxmhas8fawxp8nx4e2q3wekg969n2auw3"),
+            ("1e349c9bdea19fd6c147626a5260bc44b71635f398b67c59881df209", "", "asset1uyuxku60yqe57nusqzjx38aan3f2wq6s93f6ea"),
+            ("7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373", "504154415445", "asset13n25uv0yaf5kus35fm2k86cqy60z58d9xmde92"),
+            ("1e349c9bdea19fd6c147626a5260bc44b71635f398b67c59881df209", "504154415445", "asset1hv4p5tv2a837mzqrst04d0dcptdjmluqvdx9k3"),
+            ("1e349c9bdea19fd6c147626a5260bc44b71635f398b67c59881df209", "7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373", "asset1aqrdypg669jgazruv5ah07nuyqe0wxjhe2el6f"),
+            ("7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373", "1e349c9bdea19fd6c147626a5260bc44b71635f398b67c59881df209", "asset17jd78wukhtrnmjh3fngzasxm8rck0l2r4hhyyt"),
+        ];
+        for (policy_id, asset_name, fingerprint) in vectors.iter() {
+            let asset_id = AssetId::new(&PolicyID::from_hex(policy_id).unwrap(), &AssetName::from_hex(asset_name).unwrap());
+            assert_eq!(asset_id.fingerprint().to_bech32(), *fingerprint);
+            assert_eq!(AssetFingerprint::from_bech32(fingerprint).unwrap(), asset_id.fingerprint());
+        }
+        let policy = PolicyID::from_hex("7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373").unwrap();
+        assert!(AssetFingerprint::from_bech32(&bech32::encode("asset", policy.to_bytes().to_base32()).unwrap()).is_err());
+        assert!(AssetFingerprint::from_bech32(&AssetFingerprint::new(&policy, &AssetName::new(vec![]).unwrap()).to_bech32().replace("asset", "token")).is_err());
+    }
+
+    #[test]
+    fn asset_id_formats() {
+        let hex = "7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373504154415445";
+        let dotted = "7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373.504154415445";
+        let asset_id = AssetId::from_str(hex).unwrap();
+        assert_eq!(asset_id, AssetId::from_str(dotted).unwrap());
+        assert_eq!(asset_id.to_hex(), hex);
+        assert_eq!(asset_id.to_str(), dotted);
+        assert_eq!(asset_id.asset_name().to_utf8().unwrap(), "PATATE");
+
+        let ada_like = AssetId::from_str("7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373").unwrap();
+        assert!(ada_like.asset_name().name().is_empty());
+        assert_eq!(ada_like.to_str(), "7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373");
+
+        assert!(AssetId::from_str("7eae28af").is_err());
+        assert!(AssetId::from_str("7eae28af2208be856f7a119668ae52a49b73725e326dc16579dcc373.50415441544").is_err());
+        assert!(AssetId::from_str(&format!("{}{}", hex, "00".repeat(32))).is_err());
+    }
+
+    #[test]
+    fn asset_name_display() {
+        let nft = cip68_asset_name(CIP68TokenLabel::Nft, b"SpaceBud").unwrap();
+        assert_eq!(nft.cip67_label(), Some(222));
+        assert_eq!(nft.without_cip67_label().to_utf8().unwrap(), "SpaceBud");
+        assert_eq!(nft.to_display_string(), "(222) SpaceBud");
+        assert_eq!(AssetName::new(vec![0x00, 0xff]).unwrap().to_display_string(), "0x00ff");
+        assert_eq!(AssetName::new(b"PATATE".to_vec()).unwrap().to_display_string(), "PATATE");
+
+        let policy_id = PolicyID::from([1; PolicyID::BYTE_COUNT]);
+        let mut multiasset = MultiAsset::new();
+        multiasset.set_asset(&policy_id, &nft, &to_bignum(1));
+        multiasset.set_asset(&policy_id, &AssetName::new(b"PATATE".to_vec()).unwrap(), &to_bignum(5));
+        let ids = multiasset.asset_ids();
+        assert_eq!(ids.len(), 2);
+        // canonical order: shorter names first
+        assert_eq!(ids.get(0).asset_name().to_utf8().unwrap(), "PATATE");
+        assert_eq!(multiasset.get_by_id(&ids.get(1)), to_bignum(1));
+        assert_eq!(multiasset.iter_assets().map(|(_, amount)| u64::from(amount)).sum::<u64>(), 6);
+    }
+}
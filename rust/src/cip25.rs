@@ -0,0 +1,455 @@
+use std::collections::BTreeMap;
+
+use super::*;
+use super::error::Error;
+
+/// Transaction metadata label under which CIP-25 NFT metadata is stored
+pub const CIP25_METADATA_LABEL: u64 = 721;
+
+const MD_MAX_LEN: usize = 64;
+
+/// CIP-25 v1 encodes policy IDs and asset names as text (hex / UTF-8)
+/// while v2 encodes both as raw bytes
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CIP25Version {
+    V1,
+    V2,
+}
+
+/// Splits a string into chunks of at most 64 bytes (on UTF-8 boundaries)
+/// so it can be stored as metadata. Short strings are kept as a single text entry
+pub fn encode_chunked_string(text: &str) -> TransactionMetadatum {
+    if text.len() <= MD_MAX_LEN {
+        // this can't fail as we just checked the length
+        return TransactionMetadatum::new_text(text.to_owned()).unwrap();
+    }
+    let mut list = MetadataList::new();
+    let mut chunk = String::new();
+    for c in text.chars() {
+        if chunk.len() + c.len_utf8() > MD_MAX_LEN {
+            list.add(&TransactionMetadatum::new_text(std::mem::take(&mut chunk)).unwrap());
+        }
+        chunk.push(c);
+    }
+    if !chunk.is_empty() {
+        list.add(&TransactionMetadatum::new_text(chunk).unwrap());
+    }
+    TransactionMetadatum::new_list(&list)
+}
+
+/// Decodes either a single text entry or a list of text chunks
+pub fn decode_chunked_string(metadatum: &TransactionMetadatum) -> Result<String, Error> {
+    match metadatum.kind() {
+        TransactionMetadatumKind::Text => metadatum.as_text(),
+        TransactionMetadatumKind::MetadataList => {
+            let list = metadatum.as_list()?;
+            (0..list.len()).map(|i| list.get(i).as_text()).collect::<Result<Vec<String>, Error>>().map(|chunks| chunks.concat())
+        },
+        _ => Err(Error::from_str("expected a string or a list of strings")),
+    }
+}
+
+fn text_key(key: &str) -> TransactionMetadatum {
+    // keys used in this module are all well below the 64 byte limit
+    TransactionMetadatum::new_text(key.to_owned()).unwrap()
+}
+
+fn get_opt(map: &MetadataMap, key: &str) -> Option<TransactionMetadatum> {
+    map.get(&text_key(key)).ok()
+}
+
+// all entries whose key is not one of `known` (in their original order)
+fn other_entries(map: &MetadataMap, known: &[&str]) -> MetadataMap {
+    let keys = map.keys();
+    let mut other = MetadataMap::new();
+    for i in 0..keys.len() {
+        let key = keys.get(i);
+        let is_known = key.as_text().map(|text| known.contains(&text.as_str())).unwrap_or(false);
+        if !is_known {
+            other.insert(&key, &map.get(&key).unwrap());
+        }
+    }
+    other
+}
+
+fn add_other_entries(map: &mut MetadataMap, other: &MetadataMap) {
+    let keys = other.keys();
+    for i in 0..keys.len() {
+        let key = keys.get(i);
+        map.insert(&key, &other.get(&key).unwrap());
+    }
+}
+
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CIP25FilesDetails {
+    name: Option<String>,
+    media_type: String,
+    src: String,
+    other: MetadataMap,
+}
+
+impl CIP25FilesDetails {
+    pub fn new(media_type: String, src: String) -> Self {
+        Self {
+            name: None,
+            media_type,
+            src,
+            other: MetadataMap::new(),
+        }
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = Some(name)
+    }
+
+    pub fn media_type(&self) -> String {
+        self.media_type.clone()
+    }
+
+    pub fn src(&self) -> String {
+        self.src.clone()
+    }
+
+    /// Any extra properties of the file besides the standard ones
+    pub fn other(&self) -> MetadataMap {
+        self.other.clone()
+    }
+
+    pub fn insert_other(&mut self, key: &TransactionMetadatum, value: &TransactionMetadatum) {
+        self.other.insert(key, value);
+    }
+
+    pub fn to_metadatum(&self) -> TransactionMetadatum {
+        let mut map = MetadataMap::new();
+        if let Some(name) = &self.name {
+            map.insert(&text_key("name"), &encode_chunked_string(name));
+        }
+        map.insert(&text_key("mediaType"), &encode_chunked_string(&self.media_type));
+        map.insert(&text_key("src"), &encode_chunked_string(&self.src));
+        add_other_entries(&mut map, &self.other);
+        TransactionMetadatum::new_map(&map)
+    }
+
+    pub fn from_metadatum(metadatum: &TransactionMetadatum) -> Result<Self, Error> {
+        let map = metadatum.as_map()?;
+        Ok(Self {
+            name: get_opt(&map, "name").map(|name| decode_chunked_string(&name)).transpose()?,
+            media_type: decode_chunked_string(&map.get_str("mediaType")?)?,
+            src: decode_chunked_string(&map.get_str("src")?)?,
+            other: other_entries(&map, &["name", "mediaType", "src"]),
+        })
+    }
+}
+
+
+/// Metadata of a single NFT
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CIP25MetadataDetails {
+    name: String,
+    image: String,
+    media_type: Option<String>,
+    description: Option<String>,
+    files: Vec<CIP25FilesDetails>,
+    other: MetadataMap,
+}
+
+impl CIP25MetadataDetails {
+    pub fn new(name: String, image: String) -> Self {
+        Self {
+            name,
+            image,
+            media_type: None,
+            description: None,
+            files: Vec::new(),
+            other: MetadataMap::new(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn image(&self) -> String {
+        self.image.clone()
+    }
+
+    pub fn media_type(&self) -> Option<String> {
+        self.media_type.clone()
+    }
+
+    pub fn set_media_type(&mut self, media_type: String) {
+        self.media_type = Some(media_type)
+    }
+
+    pub fn description(&self) -> Option<String> {
+        self.description.clone()
+    }
+
+    pub fn set_description(&mut self, description: String) {
+        self.description = Some(description)
+    }
+
+    pub fn files(&self) -> Vec<CIP25FilesDetails> {
+        self.files.clone()
+    }
+
+    pub fn add_file(&mut self, file: &CIP25FilesDetails) {
+        self.files.push(file.clone())
+    }
+
+    /// Any extra properties of the NFT besides the standard ones
+    pub fn other(&self) -> MetadataMap {
+        self.other.clone()
+    }
+
+    pub fn insert_other(&mut self, key: &TransactionMetadatum, value: &TransactionMetadatum) {
+        self.other.insert(key, value);
+    }
+
+    pub fn to_metadatum(&self) -> TransactionMetadatum {
+        let mut map = MetadataMap::new();
+        map.insert(&text_key("name"), &encode_chunked_string(&self.name));
+        map.insert(&text_key("image"), &encode_chunked_string(&self.image));
+        if let Some(media_type) = &self.media_type {
+            map.insert(&text_key("mediaType"), &encode_chunked_string(media_type));
+        }
+        if let Some(description) = &self.description {
+            map.insert(&text_key("description"), &encode_chunked_string(description));
+        }
+        if !self.files.is_empty() {
+            let mut files = MetadataList::new();
+            self.files.iter().for_each(|file| files.add(&file.to_metadatum()));
+            map.insert(&text_key("files"), &TransactionMetadatum::new_list(&files));
+        }
+        add_other_entries(&mut map, &self.other);
+        TransactionMetadatum::new_map(&map)
+    }
+
+    pub fn from_metadatum(metadatum: &TransactionMetadatum) -> Result<Self, Error> {
+        let map = metadatum.as_map()?;
+        let files = match get_opt(&map, "files") {
+            Some(files) => {
+                let list = files.as_list()?;
+                (0..list.len()).map(|i| CIP25FilesDetails::from_metadatum(&list.get(i))).collect::<Result<Vec<_>, _>>()?
+            },
+            None => Vec::new(),
+        };
+        Ok(Self {
+            name: decode_chunked_string(&map.get_str("name")?)?,
+            image: decode_chunked_string(&map.get_str("image")?)?,
+            media_type: get_opt(&map, "mediaType").map(|media_type| decode_chunked_string(&media_type)).transpose()?,
+            description: get_opt(&map, "description").map(|description| decode_chunked_string(&description)).transpose()?,
+            files,
+            other: other_entries(&map, &["name", "image", "mediaType", "description", "files"]),
+        })
+    }
+}
+
+
+/// Typed CIP-25 metadata (the value under the 721 label)
+/// https://cips.cardano.org/cips/cip25/
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CIP25Metadata {
+    version: CIP25Version,
+    data: BTreeMap<PolicyID, BTreeMap<AssetName, CIP25MetadataDetails>>,
+}
+
+impl CIP25Metadata {
+    pub fn new(version: CIP25Version) -> Self {
+        Self {
+            version,
+            data: BTreeMap::new(),
+        }
+    }
+
+    pub fn version(&self) -> CIP25Version {
+        self.version
+    }
+
+    pub fn insert(&mut self, policy_id: &PolicyID, asset_name: &AssetName, details: &CIP25MetadataDetails) -> Option<CIP25MetadataDetails> {
+        self.data
+            .entry(policy_id.clone())
+            .or_default()
+            .insert(asset_name.clone(), details.clone())
+    }
+
+    pub fn get(&self, policy_id: &PolicyID, asset_name: &AssetName) -> Option<CIP25MetadataDetails> {
+        self.data.get(policy_id).and_then(|assets| assets.get(asset_name)).cloned()
+    }
+
+    pub fn policy_ids(&self) -> PolicyIDs {
+        ScriptHashes(self.data.keys().cloned().collect())
+    }
+
+    pub fn asset_names(&self, policy_id: &PolicyID) -> Option<AssetNames> {
+        self.data.get(policy_id).map(|assets| AssetNames(assets.keys().cloned().collect()))
+    }
+
+    pub fn to_metadatum(&self) -> Result<TransactionMetadatum, Error> {
+        let mut policies = MetadataMap::new();
+        for (policy_id, assets) in self.data.iter() {
+            let mut asset_map = MetadataMap::new();
+            for (asset_name, details) in assets.iter() {
+                let asset_key = match self.version {
+                    CIP25Version::V1 => {
+                        let name = String::from_utf8(asset_name.name())
+                            .map_err(|_| Error::from_str(&format!("CIP-25 v1 requires UTF-8 asset names, found {}. Use v2 instead", hex::encode(asset_name.name()))))?;
+                        TransactionMetadatum::new_text(name)?
+                    },
+                    CIP25Version::V2 => TransactionMetadatum::new_bytes(asset_name.name())?,
+                };
+                asset_map.insert(&asset_key, &details.to_metadatum());
+            }
+            let policy_key = match self.version {
+                CIP25Version::V1 => TransactionMetadatum::new_text(policy_id.to_hex())?,
+                CIP25Version::V2 => TransactionMetadatum::new_bytes(policy_id.to_bytes())?,
+            };
+            policies.insert(&policy_key, &TransactionMetadatum::new_map(&asset_map));
+        }
+        if self.version == CIP25Version::V2 {
+            policies.insert(&text_key("version"), &text_key("2.0"));
+        }
+        Ok(TransactionMetadatum::new_map(&policies))
+    }
+
+    /// Parses the value under the 721 label
+    /// Both text and byte keys are accepted regardless of the version for compatibility with existing NFTs
+    pub fn from_metadatum(metadatum: &TransactionMetadatum) -> Result<Self, Error> {
+        let map = metadatum.as_map()?;
+        let version = match get_opt(&map, "version") {
+            None => CIP25Version::V1,
+            Some(version) => match version.as_text()?.as_str() {
+                "1.0" => CIP25Version::V1,
+                "2.0" => CIP25Version::V2,
+                other => return Err(Error::from_str(&format!("unknown CIP-25 version {}", other))),
+            },
+        };
+        let mut result = Self::new(version);
+        let policy_keys = map.keys();
+        for i in 0..policy_keys.len() {
+            let policy_key = policy_keys.get(i);
+            if policy_key.as_text().map(|key| key == "version").unwrap_or(false) {
+                continue;
+            }
+            let policy_id = match policy_key.kind() {
+                TransactionMetadatumKind::Text => PolicyID::from_hex(&policy_key.as_text()?)?,
+                TransactionMetadatumKind::Bytes => PolicyID::from_bytes(policy_key.as_bytes()?)?,
+                _ => return Err(Error::from_str("CIP-25 policy ID must be a hex string or bytes")),
+            };
+            let assets = map.get(&policy_key)?.as_map()?;
+            let asset_keys = assets.keys();
+            for j in 0..asset_keys.len() {
+                let asset_key = asset_keys.get(j);
+                let asset_name = match asset_key.kind() {
+                    TransactionMetadatumKind::Text => AssetName::new(asset_key.as_text()?.into_bytes())?,
+                    TransactionMetadatumKind::Bytes => AssetName::new(asset_key.as_bytes()?)?,
+                    _ => return Err(Error::from_str("CIP-25 asset name must be a string or bytes")),
+                };
+                let details = CIP25MetadataDetails::from_metadatum(&assets.get(&asset_key)?)
+                    .map_err(|e| Error::from_str(&format!("invalid CIP-25 details for {}.{}: {}", policy_id.to_hex(), hex::encode(asset_name.name()), e.as_string().unwrap_or_default())))?;
+                result.insert(&policy_id, &asset_name, &details);
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn to_metadata(&self) -> Result<GeneralTransactionMetadata, Error> {
+        let mut metadata = GeneralTransactionMetadata::new();
+        metadata.insert(&to_bignum(CIP25_METADATA_LABEL), &self.to_metadatum()?);
+        Ok(metadata)
+    }
+
+    pub fn from_metadata(metadata: &GeneralTransactionMetadata) -> Result<Self, Error> {
+        match metadata.get(&to_bignum(CIP25_METADATA_LABEL)) {
+            Some(metadatum) => Self::from_metadatum(&metadatum),
+            None => Err(Error::from_str("no CIP-25 metadata (label 721) found")),
+        }
+    }
+
+    /// Auxiliary data containing only this metadata. Use `AuxiliaryData::add` to combine it with existing data
+    pub fn to_auxiliary_data(&self) -> Result<AuxiliaryData, Error> {
+        let mut aux_data = AuxiliaryData::new();
+        aux_data.set_metadata(&self.to_metadata()?);
+        Ok(aux_data)
+    }
+
+    pub fn from_auxiliary_data(aux_data: &AuxiliaryData) -> Result<Self, Error> {
+        match aux_data.metadata() {
+            Some(metadata) => Self::from_metadata(&metadata),
+            None => Err(Error::from_str("auxiliary data contains no metadata")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy_id() -> PolicyID {
+        PolicyID::from_hex("b0d07d45fe9514f80213f4020e5a61241458be626841cde717cb38a7").unwrap()
+    }
+
+    #[test]
+    fn chunked_strings() {
+        let short = "ipfs://QmSomething";
+        assert_eq!(encode_chunked_string(short).kind(), TransactionMetadatumKind::Text);
+        // multi-byte characters must not be split across chunks
+        let long = "ipfs://".to_owned() + &"é".repeat(60);
+        let encoded = encode_chunked_string(&long);
+        let chunks = encoded.as_list().unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks.get(0).as_text().unwrap().len(), 63);
+        assert_eq!(chunks.get(1).as_text().unwrap().len(), 64);
+        assert_eq!(decode_chunked_string(&encoded).unwrap(), long);
+    }
+
+    #[test]
+    fn cip25_v1_round_trip() {
+        let mut details = CIP25MetadataDetails::new("NFT 1".to_owned(), "ipfs://".to_owned() + &"a".repeat(100));
+        details.set_media_type("image/png".to_owned());
+        let mut file = CIP25FilesDetails::new("image/png".to_owned(), "ipfs://file".to_owned());
+        file.set_name("file".to_owned());
+        details.add_file(&file);
+        details.insert_other(&TransactionMetadatum::new_text("attack".to_owned()).unwrap(), &TransactionMetadatum::new_int(&Int::new_i32(5)));
+
+        let mut metadata = CIP25Metadata::new(CIP25Version::V1);
+        metadata.insert(&policy_id(), &AssetName::new(b"NFT1".to_vec()).unwrap(), &details);
+
+        let aux_data = AuxiliaryData::from_bytes(metadata.to_auxiliary_data().unwrap().to_bytes()).unwrap();
+        let decoded = CIP25Metadata::from_auxiliary_data(&aux_data).unwrap();
+        assert_eq!(decoded, metadata);
+
+        let label = aux_data.metadata().unwrap().get(&to_bignum(CIP25_METADATA_LABEL)).unwrap().as_map().unwrap();
+        assert!(label.get_str(&policy_id().to_hex()).is_ok());
+        assert!(label.get_str("version").is_err());
+    }
+
+    #[test]
+    fn cip25_v1_rejects_non_utf8_names() {
+        let mut metadata = CIP25Metadata::new(CIP25Version::V1);
+        let details = CIP25MetadataDetails::new("NFT".to_owned(), "ipfs://image".to_owned());
+        metadata.insert(&policy_id(), &AssetName::new(vec![0xff, 0xfe]).unwrap(), &details);
+        assert!(metadata.to_metadatum().is_err());
+
+        let mut metadata_v2 = CIP25Metadata::new(CIP25Version::V2);
+        metadata_v2.insert(&policy_id(), &AssetName::new(vec![0xff, 0xfe]).unwrap(), &details);
+        let decoded = CIP25Metadata::from_metadatum(&metadata_v2.to_metadatum().unwrap()).unwrap();
+        assert_eq!(decoded.version(), CIP25Version::V2);
+        assert_eq!(decoded, metadata_v2);
+    }
+
+    #[test]
+    fn cip25_parse_json() {
+        let json = r#"{"b0d07d45fe9514f80213f4020e5a61241458be626841cde717cb38a7": {"NFT1": {"name": "NFT 1", "image": ["ipfs://Qm", "Hash"], "description": "my nft"}}}"#;
+        let metadatum = encode_json_str_to_metadatum(json.to_owned(), MetadataJsonSchema::NoConversions).unwrap();
+        let metadata = CIP25Metadata::from_metadatum(&metadatum).unwrap();
+        let details = metadata.get(&policy_id(), &AssetName::new(b"NFT1".to_vec()).unwrap()).unwrap();
+        assert_eq!(details.image(), "ipfs://QmHash");
+        assert_eq!(details.description(), Some("my nft".to_owned()));
+    }
+}
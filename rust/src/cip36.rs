@@ -0,0 +1,372 @@
+use super::*;
+use super::error::Error;
+use std::convert::TryFrom;
+
+/// Transaction metadata label of the registration itself
+pub const CIP36_REGISTRATION_LABEL: u64 = 61284;
+/// Transaction metadata label of the registration's signature
+pub const CIP36_WITNESS_LABEL: u64 = 61285;
+
+/// Voting key together with the share of voting power delegated to it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CIP36Delegation {
+    voting_key: PublicKey,
+    weight: u32,
+}
+
+impl CIP36Delegation {
+    pub fn new(voting_key: &PublicKey, weight: u32) -> Self {
+        Self {
+            voting_key: voting_key.clone(),
+            weight,
+        }
+    }
+
+    pub fn voting_key(&self) -> PublicKey {
+        self.voting_key.clone()
+    }
+
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CIP36DelegationDistribution {
+    /// CIP-15: all voting power goes to a single voting key
+    Legacy(PublicKey),
+    /// CIP-36: voting power is split between several voting keys according to their weights
+    Weighted(Vec<CIP36Delegation>),
+}
+
+/// Catalyst voting registration (CIP-15 / CIP-36)
+/// https://cips.cardano.org/cips/cip36/
+#[derive(Clone, Debug)]
+pub struct VotingRegistration {
+    delegations: CIP36DelegationDistribution,
+    stake_credential: PublicKey,
+    payment_address: Address,
+    nonce: u64,
+    voting_purpose: u64,
+    // registrations are signed as they are encoded, and wallets don't all encode them the same way
+    // (key order, integer widths, CIP-15 ones have no voting purpose) so parsed ones keep their metadatum
+    metadatum: Option<TransactionMetadatum>,
+}
+
+impl PartialEq for VotingRegistration {
+    fn eq(&self, other: &Self) -> bool {
+        self.delegations == other.delegations
+            && self.stake_credential == other.stake_credential
+            && self.payment_address == other.payment_address
+            && self.nonce == other.nonce
+            && self.voting_purpose == other.voting_purpose
+    }
+}
+
+impl Eq for VotingRegistration {}
+
+impl VotingRegistration {
+    /// CIP-15 registration. `reward_address` should be a stake address
+    pub fn new_legacy(voting_key: &PublicKey, stake_credential: &PublicKey, reward_address: &Address, nonce: u64) -> Self {
+        Self {
+            delegations: CIP36DelegationDistribution::Legacy(voting_key.clone()),
+            stake_credential: stake_credential.clone(),
+            payment_address: reward_address.clone(),
+            nonce,
+            voting_purpose: 0,
+            metadatum: None,
+        }
+    }
+
+    /// CIP-36 registration. The nonce is usually the current slot number
+    pub fn new(delegations: &[CIP36Delegation], stake_credential: &PublicKey, payment_address: &Address, nonce: u64) -> Result<Self, Error> {
+        if delegations.is_empty() {
+            return Err(Error::from_str("CIP-36 registration requires at least one delegation"));
+        }
+        Ok(Self {
+            delegations: CIP36DelegationDistribution::Weighted(delegations.to_vec()),
+            stake_credential: stake_credential.clone(),
+            payment_address: payment_address.clone(),
+            nonce,
+            voting_purpose: 0,
+            metadatum: None,
+        })
+    }
+
+    pub fn delegations(&self) -> CIP36DelegationDistribution {
+        self.delegations.clone()
+    }
+
+    pub fn stake_credential(&self) -> PublicKey {
+        self.stake_credential.clone()
+    }
+
+    pub fn payment_address(&self) -> Address {
+        self.payment_address.clone()
+    }
+
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    pub fn voting_purpose(&self) -> u64 {
+        self.voting_purpose
+    }
+
+    /// Only used by CIP-36 registrations. 0 (the default) is Catalyst
+    pub fn set_voting_purpose(&mut self, voting_purpose: u64) {
+        self.voting_purpose = voting_purpose;
+        self.metadatum = None;
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        matches!(self.delegations, CIP36DelegationDistribution::Legacy(_))
+    }
+
+    /// The metadatum the registration was parsed from, if any, so that its signature stays valid
+    pub fn to_metadatum(&self) -> Result<TransactionMetadatum, Error> {
+        if let Some(metadatum) = &self.metadatum {
+            return Ok(metadatum.clone());
+        }
+        let mut map = MetadataMap::new();
+        let delegations = match &self.delegations {
+            CIP36DelegationDistribution::Legacy(voting_key) => TransactionMetadatum::new_bytes(voting_key.as_bytes())?,
+            CIP36DelegationDistribution::Weighted(delegations) => {
+                let mut list = MetadataList::new();
+                for delegation in delegations {
+                    let mut pair = MetadataList::new();
+                    pair.add(&TransactionMetadatum::new_bytes(delegation.voting_key.as_bytes())?);
+                    pair.add(&TransactionMetadatum::new_int(&Int::new(&to_bignum(delegation.weight as u64))));
+                    list.add(&TransactionMetadatum::new_list(&pair));
+                }
+                TransactionMetadatum::new_list(&list)
+            },
+        };
+        map.insert_i32(1, &delegations);
+        map.insert_i32(2, &TransactionMetadatum::new_bytes(self.stake_credential.as_bytes())?);
+        map.insert_i32(3, &TransactionMetadatum::new_bytes(self.payment_address.to_bytes())?);
+        map.insert_i32(4, &TransactionMetadatum::new_int(&Int::new(&to_bignum(self.nonce))));
+        if !self.is_legacy() {
+            map.insert_i32(5, &TransactionMetadatum::new_int(&Int::new(&to_bignum(self.voting_purpose))));
+        }
+        Ok(TransactionMetadatum::new_map(&map))
+    }
+
+    pub fn from_metadatum(metadatum: &TransactionMetadatum) -> Result<Self, Error> {
+        fn as_u64(metadatum: &TransactionMetadatum) -> Result<u64, Error> {
+            metadatum.as_int()?.as_positive().map(u64::from).ok_or_else(|| Error::from_str("expected a positive integer"))
+        }
+        let map = metadatum.as_map()?;
+        let key_1 = map.get_i32(1)?;
+        let delegations = match key_1.kind() {
+            TransactionMetadatumKind::Bytes => CIP36DelegationDistribution::Legacy(PublicKey::from_bytes(&key_1.as_bytes()?)?),
+            TransactionMetadatumKind::MetadataList => {
+                let list = key_1.as_list()?;
+                let mut delegations = Vec::new();
+                for i in 0..list.len() {
+                    let pair = list.get(i).as_list()?;
+                    if pair.len() != 2 {
+                        return Err(Error::from_str("CIP-36 delegation must be a [voting_key, weight] pair"));
+                    }
+                    let weight = u32::try_from(as_u64(&pair.get(1))?)
+                        .map_err(|_| Error::from_str("CIP-36 delegation weight must fit in 32 bits"))?;
+                    delegations.push(CIP36Delegation::new(&PublicKey::from_bytes(&pair.get(0).as_bytes()?)?, weight));
+                }
+                CIP36DelegationDistribution::Weighted(delegations)
+            },
+            _ => return Err(Error::from_str("CIP-36 delegations must be a voting key or a list of delegations")),
+        };
+        let voting_purpose = match map.get_i32(5) {
+            Ok(purpose) => as_u64(&purpose)?,
+            Err(_) => 0,
+        };
+        Ok(Self {
+            delegations,
+            stake_credential: PublicKey::from_bytes(&map.get_i32(2)?.as_bytes()?)?,
+            payment_address: Address::from_bytes(map.get_i32(3)?.as_bytes()?)?,
+            nonce: as_u64(&map.get_i32(4)?)?,
+            voting_purpose,
+            metadatum: Some(metadatum.clone()),
+        })
+    }
+
+    /// blake2b-256 of the CBOR map `{ 61284: registration }` which is what the stake key signs.
+    /// Registrations parsed from metadata are hashed as they were encoded there
+    pub fn hash_to_sign(&self) -> Result<Vec<u8>, Error> {
+        let mut metadata = GeneralTransactionMetadata::new();
+        metadata.insert(&to_bignum(CIP36_REGISTRATION_LABEL), &self.to_metadatum()?);
+        Ok(blake2b256(&metadata.to_bytes()).to_vec())
+    }
+
+    /// Signs the registration with the stake key matching `stake_credential`
+    pub fn sign(&self, stake_key: &PrivateKey) -> Result<SignedVotingRegistration, Error> {
+        if stake_key.to_public() != self.stake_credential {
+            return Err(Error::from_str("private key does not match the registration's stake credential"));
+        }
+        Ok(SignedVotingRegistration {
+            registration: self.clone(),
+            signature: stake_key.sign(&self.hash_to_sign()?),
+        })
+    }
+}
+
+/// A voting registration along with its witness, as found on chain
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignedVotingRegistration {
+    registration: VotingRegistration,
+    signature: Ed25519Signature,
+}
+
+impl SignedVotingRegistration {
+    pub fn new(registration: &VotingRegistration, signature: &Ed25519Signature) -> Self {
+        Self {
+            registration: registration.clone(),
+            signature: signature.clone(),
+        }
+    }
+
+    pub fn registration(&self) -> VotingRegistration {
+        self.registration.clone()
+    }
+
+    pub fn signature(&self) -> Ed25519Signature {
+        self.signature.clone()
+    }
+
+    /// Checks the signature was made by the registration's stake credential
+    pub fn verify(&self) -> Result<bool, Error> {
+        Ok(self.registration.stake_credential.verify(&self.registration.hash_to_sign()?, &self.signature))
+    }
+
+    /// Metadata with both the registration (61284) and its witness (61285)
+    pub fn to_metadata(&self) -> Result<GeneralTransactionMetadata, Error> {
+        let mut witness = MetadataMap::new();
+        witness.insert_i32(1, &TransactionMetadatum::new_bytes(self.signature.to_bytes())?);
+        let mut metadata = GeneralTransactionMetadata::new();
+        metadata.insert(&to_bignum(CIP36_REGISTRATION_LABEL), &self.registration.to_metadatum()?);
+        metadata.insert(&to_bignum(CIP36_WITNESS_LABEL), &TransactionMetadatum::new_map(&witness));
+        Ok(metadata)
+    }
+
+    /// Parses a registration without checking its signature. See `from_auxiliary_data` for on-chain data
+    pub fn from_metadata(metadata: &GeneralTransactionMetadata) -> Result<Self, Error> {
+        let registration = metadata.get(&to_bignum(CIP36_REGISTRATION_LABEL))
+            .ok_or_else(|| Error::from_str("no voting registration (label 61284) found"))?;
+        let witness = metadata.get(&to_bignum(CIP36_WITNESS_LABEL))
+            .ok_or_else(|| Error::from_str("no voting registration witness (label 61285) found"))?;
+        Ok(Self {
+            registration: VotingRegistration::from_metadatum(&registration)?,
+            signature: Ed25519Signature::from_bytes(witness.as_map()?.get_i32(1)?.as_bytes()?)?,
+        })
+    }
+
+    /// Auxiliary data containing only the registration. Use `AuxiliaryData::add` to combine it with existing data
+    pub fn to_auxiliary_data(&self) -> Result<AuxiliaryData, Error> {
+        let mut aux_data = AuxiliaryData::new();
+        aux_data.set_metadata(&self.to_metadata()?);
+        Ok(aux_data)
+    }
+
+    /// Parses the registration found in a transaction's auxiliary data and verifies its signature
+    pub fn from_auxiliary_data(aux_data: &AuxiliaryData) -> Result<Self, Error> {
+        let metadata = aux_data.metadata()
+            .ok_or_else(|| Error::from_str("auxiliary data contains no metadata"))?;
+        let signed = Self::from_metadata(&metadata)?;
+        if !signed.verify()? {
+            return Err(Error::from_str("invalid voting registration signature"));
+        }
+        Ok(signed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stake_key() -> PrivateKey {
+        PrivateKey::from_normal_bytes(&[1u8; 32]).unwrap()
+    }
+
+    fn voting_key(x: u8) -> PublicKey {
+        PrivateKey::from_normal_bytes(&[x; 32]).unwrap().to_public()
+    }
+
+    fn reward_address() -> Address {
+        RewardAddress::new(1, &StakeCredential::from_keyhash(&stake_key().to_public().hash())).to_address()
+    }
+
+    #[test]
+    fn cip36_round_trip() {
+        let delegations = vec![CIP36Delegation::new(&voting_key(2), 1), CIP36Delegation::new(&voting_key(3), 3)];
+        let registration = VotingRegistration::new(&delegations, &stake_key().to_public(), &reward_address(), 1234).unwrap();
+        let signed = registration.sign(&stake_key()).unwrap();
+
+        let aux_data = AuxiliaryData::from_bytes(signed.to_auxiliary_data().unwrap().to_bytes()).unwrap();
+        let decoded = SignedVotingRegistration::from_auxiliary_data(&aux_data).unwrap();
+        assert_eq!(decoded, signed);
+        assert!(!decoded.registration().is_legacy());
+        assert_eq!(decoded.registration().voting_purpose(), 0);
+    }
+
+    #[test]
+    fn cip15_round_trip() {
+        let registration = VotingRegistration::new_legacy(&voting_key(2), &stake_key().to_public(), &reward_address(), 1234);
+        let signed = registration.sign(&stake_key()).unwrap();
+        let metadata = signed.to_metadata().unwrap();
+
+        let registration_map = metadata.get(&to_bignum(CIP36_REGISTRATION_LABEL)).unwrap().as_map().unwrap();
+        assert_eq!(registration_map.get_i32(1).unwrap().kind(), TransactionMetadatumKind::Bytes);
+        assert!(!registration_map.has(&TransactionMetadatum::new_int(&Int::new_i32(5))));
+
+        let decoded = SignedVotingRegistration::from_metadata(&metadata).unwrap();
+        assert!(decoded.registration().is_legacy());
+        assert!(decoded.verify().unwrap());
+    }
+
+    #[test]
+    fn rejects_wrong_signer() {
+        let registration = VotingRegistration::new_legacy(&voting_key(2), &stake_key().to_public(), &reward_address(), 1234);
+        assert!(registration.sign(&PrivateKey::from_normal_bytes(&[9u8; 32]).unwrap()).is_err());
+
+        // signature made by another key
+        let other_key = PrivateKey::from_normal_bytes(&[9u8; 32]).unwrap();
+        let forged = SignedVotingRegistration::new(&registration, &other_key.sign(&registration.hash_to_sign().unwrap()));
+        assert!(!forged.verify().unwrap());
+        assert!(SignedVotingRegistration::from_auxiliary_data(&forged.to_auxiliary_data().unwrap()).is_err());
+    }
+
+    #[test]
+    fn cip15_test_vector() {
+        // test vector of CIP-15
+        let aux_data = AuxiliaryData::from_bytes(hex::decode("a219ef64a40158200036ef3e1f0d3f5989e2d155ea54bdb2a72c4c456ccb959af4c94868f473f5a002582086870efc99c453a873a16492ce87738ec79a0ebd064379a62e2c9cf4e119219e03581de0ae3a0a7aeda4aea522e74e4fe36759fca80789a613a58a4364f6ecef041904d219ef65a10158406c2312cd49067ecf0920df7e067199c55b3faef4ec0bce1bd2cfb99793972478c45876af2bc271ac759c5ce40ace5a398b9fdb0e359f3c333fe856648804780e").unwrap()).unwrap();
+        let signed = SignedVotingRegistration::from_auxiliary_data(&aux_data).unwrap();
+        assert!(signed.registration().is_legacy());
+        assert_eq!(signed.registration().nonce(), 1234);
+    }
+
+    #[test]
+    fn verifies_original_encoding() {
+        // keys in reverse order and the nonce as a 64-bit integer
+        let registration_bytes = [
+            hex::decode("a4041b00000000000004d203581d").unwrap(),
+            reward_address().to_bytes(),
+            hex::decode("025820").unwrap(),
+            stake_key().to_public().as_bytes(),
+            hex::decode("015820").unwrap(),
+            voting_key(2).as_bytes(),
+        ].concat();
+        let signature = stake_key().sign(&blake2b256(&[hex::decode("a119ef64").unwrap(), registration_bytes.clone()].concat()));
+        let aux_data_bytes = [
+            hex::decode("a219ef64").unwrap(),
+            registration_bytes,
+            hex::decode("19ef65a1015840").unwrap(),
+            signature.to_bytes(),
+        ].concat();
+
+        let signed = SignedVotingRegistration::from_auxiliary_data(&AuxiliaryData::from_bytes(aux_data_bytes.clone()).unwrap()).unwrap();
+        let reencoded = VotingRegistration::new_legacy(&voting_key(2), &stake_key().to_public(), &reward_address(), 1234);
+        assert_eq!(signed.registration(), reencoded);
+        assert_ne!(signed.registration().hash_to_sign().unwrap(), reencoded.hash_to_sign().unwrap());
+        assert_eq!(signed.to_auxiliary_data().unwrap().to_bytes(), aux_data_bytes);
+    }
+}
@@ -0,0 +1,282 @@
+//! CIP-5 bech32 prefixes for keys, hashes and identifiers
+//! https://cips.cardano.org/cips/cip5/
+//!
+//! The same bytes mean different things depending on their role (a key hash can be a pool id or
+//! a payment credential) so the prefix is what tells them apart: decoding checks it strictly.
+
+use std::str::FromStr;
+
+use bech32::{FromBase32, ToBase32};
+
+use super::error::Error;
+
+/// What kind of bytes a prefix is for
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Bech32PrefixKind {
+    /// 32 bytes ed25519 signing key
+    SigningKey,
+    /// 32 bytes ed25519 verification key
+    VerificationKey,
+    /// BIP32-ed25519 signing key with its chain code (96 bytes)
+    ExtendedSigningKey,
+    /// BIP32-ed25519 verification key with its chain code (64 bytes)
+    ExtendedVerificationKey,
+    KeyHash,
+    ScriptHash,
+    /// any other hash e.g. datum hashes
+    Hash,
+    /// addresses and identifiers that aren't a plain hash
+    Other,
+}
+
+macro_rules! bech32_prefixes {
+    ($($variant:ident => $hrp:literal, $kind:ident, $byte_count:expr;)*) => {
+        #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+        pub enum Bech32Prefix {
+            $($variant,)*
+        }
+
+        impl Bech32Prefix {
+            pub const ALL: &'static [Bech32Prefix] = &[$(Bech32Prefix::$variant,)*];
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(Bech32Prefix::$variant => $hrp,)*
+                }
+            }
+
+            pub fn kind(&self) -> Bech32PrefixKind {
+                match self {
+                    $(Bech32Prefix::$variant => Bech32PrefixKind::$kind,)*
+                }
+            }
+
+            /// Length of the encoded bytes, None when it varies (e.g. addresses)
+            pub fn byte_count(&self) -> Option<usize> {
+                match self {
+                    $(Bech32Prefix::$variant => $byte_count,)*
+                }
+            }
+        }
+    };
+}
+
+bech32_prefixes! {
+    // keys
+    AcctSk => "acct_sk", SigningKey, Some(32);
+    AcctVk => "acct_vk", VerificationKey, Some(32);
+    AcctXsk => "acct_xsk", ExtendedSigningKey, Some(96);
+    AcctXvk => "acct_xvk", ExtendedVerificationKey, Some(64);
+    AcctSharedSk => "acct_shared_sk", SigningKey, Some(32);
+    AcctSharedVk => "acct_shared_vk", VerificationKey, Some(32);
+    AcctSharedXsk => "acct_shared_xsk", ExtendedSigningKey, Some(96);
+    AcctSharedXvk => "acct_shared_xvk", ExtendedVerificationKey, Some(64);
+    AddrSk => "addr_sk", SigningKey, Some(32);
+    AddrVk => "addr_vk", VerificationKey, Some(32);
+    AddrXsk => "addr_xsk", ExtendedSigningKey, Some(96);
+    AddrXvk => "addr_xvk", ExtendedVerificationKey, Some(64);
+    AddrSharedSk => "addr_shared_sk", SigningKey, Some(32);
+    AddrSharedVk => "addr_shared_vk", VerificationKey, Some(32);
+    AddrSharedXsk => "addr_shared_xsk", ExtendedSigningKey, Some(96);
+    AddrSharedXvk => "addr_shared_xvk", ExtendedVerificationKey, Some(64);
+    CcColdSk => "cc_cold_sk", SigningKey, Some(32);
+    CcColdVk => "cc_cold_vk", VerificationKey, Some(32);
+    CcColdXsk => "cc_cold_xsk", ExtendedSigningKey, Some(96);
+    CcColdXvk => "cc_cold_xvk", ExtendedVerificationKey, Some(64);
+    CcHotSk => "cc_hot_sk", SigningKey, Some(32);
+    CcHotVk => "cc_hot_vk", VerificationKey, Some(32);
+    CcHotXsk => "cc_hot_xsk", ExtendedSigningKey, Some(96);
+    CcHotXvk => "cc_hot_xvk", ExtendedVerificationKey, Some(64);
+    CvoteSk => "cvote_sk", SigningKey, Some(32);
+    CvoteVk => "cvote_vk", VerificationKey, Some(32);
+    DrepSk => "drep_sk", SigningKey, Some(32);
+    DrepVk => "drep_vk", VerificationKey, Some(32);
+    DrepXsk => "drep_xsk", ExtendedSigningKey, Some(96);
+    DrepXvk => "drep_xvk", ExtendedVerificationKey, Some(64);
+    KesSk => "kes_sk", Other, None;
+    KesVk => "kes_vk", Other, Some(32);
+    PolicySk => "policy_sk", SigningKey, Some(32);
+    PolicyVk => "policy_vk", VerificationKey, Some(32);
+    PoolSk => "pool_sk", SigningKey, Some(32);
+    PoolVk => "pool_vk", VerificationKey, Some(32);
+    PoolXsk => "pool_xsk", ExtendedSigningKey, Some(96);
+    PoolXvk => "pool_xvk", ExtendedVerificationKey, Some(64);
+    RootSk => "root_sk", SigningKey, Some(32);
+    RootVk => "root_vk", VerificationKey, Some(32);
+    RootXsk => "root_xsk", ExtendedSigningKey, Some(96);
+    RootXvk => "root_xvk", ExtendedVerificationKey, Some(64);
+    RootSharedSk => "root_shared_sk", SigningKey, Some(32);
+    RootSharedVk => "root_shared_vk", VerificationKey, Some(32);
+    RootSharedXsk => "root_shared_xsk", ExtendedSigningKey, Some(96);
+    RootSharedXvk => "root_shared_xvk", ExtendedVerificationKey, Some(64);
+    StakeSk => "stake_sk", SigningKey, Some(32);
+    StakeVk => "stake_vk", VerificationKey, Some(32);
+    StakeXsk => "stake_xsk", ExtendedSigningKey, Some(96);
+    StakeXvk => "stake_xvk", ExtendedVerificationKey, Some(64);
+    StakeSharedSk => "stake_shared_sk", SigningKey, Some(32);
+    StakeSharedVk => "stake_shared_vk", VerificationKey, Some(32);
+    StakeSharedXsk => "stake_shared_xsk", ExtendedSigningKey, Some(96);
+    StakeSharedXvk => "stake_shared_xvk", ExtendedVerificationKey, Some(64);
+    VrfSk => "vrf_sk", Other, Some(64);
+    VrfVk => "vrf_vk", Other, Some(32);
+    // hashes
+    Asset => "asset", Hash, Some(20);
+    Pool => "pool", KeyHash, Some(28);
+    Script => "script", ScriptHash, Some(28);
+    AddrVkh => "addr_vkh", KeyHash, Some(28);
+    AddrSharedVkh => "addr_shared_vkh", KeyHash, Some(28);
+    PolicyVkh => "policy_vkh", KeyHash, Some(28);
+    StakeVkh => "stake_vkh", KeyHash, Some(28);
+    StakeSharedVkh => "stake_shared_vkh", KeyHash, Some(28);
+    ReqSignerVkh => "req_signer_vkh", KeyHash, Some(28);
+    VrfVkh => "vrf_vkh", Hash, Some(32);
+    Datum => "datum", Hash, Some(32);
+    ScriptData => "script_data", Hash, Some(32);
+    DrepVkh => "drep_vkh", KeyHash, Some(28);
+    DrepScript => "drep_script", ScriptHash, Some(28);
+    CcColdVkh => "cc_cold_vkh", KeyHash, Some(28);
+    CcColdScript => "cc_cold_script", ScriptHash, Some(28);
+    CcHotVkh => "cc_hot_vkh", KeyHash, Some(28);
+    CcHotScript => "cc_hot_script", ScriptHash, Some(28);
+    // governance identifiers (CIP-129): a header byte then the credential, or the tx id and index of an action
+    Drep => "drep", Other, None;
+    CcCold => "cc_cold", Other, None;
+    CcHot => "cc_hot", Other, None;
+    GovAction => "gov_action", Other, None;
+    // addresses
+    Addr => "addr", Other, None;
+    AddrTest => "addr_test", Other, None;
+    Stake => "stake", Other, Some(29);
+    StakeTest => "stake_test", Other, Some(29);
+}
+
+impl Bech32Prefix {
+    pub fn of_kind(kind: Bech32PrefixKind) -> Vec<Bech32Prefix> {
+        Self::ALL.iter().filter(|prefix| prefix.kind() == kind).cloned().collect()
+    }
+}
+
+impl FromStr for Bech32Prefix {
+    type Err = Error;
+
+    fn from_str(hrp: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|prefix| prefix.as_str() == hrp)
+            .cloned()
+            .ok_or_else(|| Error::from_str(&format!("\"{}\" is not a CIP-5 bech32 prefix", hrp)))
+    }
+}
+
+impl std::fmt::Display for Bech32Prefix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Types with CIP-5 prefixes, which their to_bech32 and from_bech32 go through
+pub trait Bech32Prefixed {
+    /// every prefix the type can be encoded with
+    const BECH32_PREFIXES: &'static [Bech32Prefix];
+    /// used when no prefix is given. None for types whose prefix depends on their role e.g. key hashes
+    const DEFAULT_BECH32_PREFIX: Option<Bech32Prefix>;
+
+    /// The prefix to encode with: `prefix` if it's one of the type's, else the default
+    fn bech32_prefix(type_name: &str, prefix: Option<Bech32Prefix>) -> Result<Bech32Prefix, Error> {
+        match prefix.or(Self::DEFAULT_BECH32_PREFIX) {
+            Some(prefix) if Self::BECH32_PREFIXES.contains(&prefix) => Ok(prefix),
+            Some(prefix) => Err(Error::from_str(&format!("{} can't be encoded with the \"{}\" prefix, expected one of {}", type_name, prefix, list(Self::BECH32_PREFIXES)))),
+            None => Err(Error::from_str(&format!("{} needs a prefix to be encoded, one of {}", type_name, list(Self::BECH32_PREFIXES)))),
+        }
+    }
+}
+
+fn list(prefixes: &[Bech32Prefix]) -> String {
+    prefixes.iter().map(|prefix| prefix.as_str()).collect::<Vec<_>>().join(", ")
+}
+
+pub fn encode(prefix: Bech32Prefix, bytes: &[u8]) -> Result<String, Error> {
+    if let Some(byte_count) = prefix.byte_count() {
+        if bytes.len() != byte_count {
+            return Err(Error::from_str(&format!("\"{}\" is for {} bytes, found {}", prefix, byte_count, bytes.len())));
+        }
+    }
+    bech32::encode(prefix.as_str(), bytes.to_base32()).map_err(|e| Error::from_str(&e.to_string()))
+}
+
+/// Decodes `bech32_str` if its prefix is among `expected`, returning which one it was
+pub fn decode(bech32_str: &str, expected: &[Bech32Prefix]) -> Result<(Bech32Prefix, Vec<u8>), Error> {
+    let (hrp, data) = bech32::decode(bech32_str).map_err(|e| Error::from_str(&e.to_string()))?;
+    let prefix = match expected.iter().find(|prefix| prefix.as_str() == hrp) {
+        Some(prefix) => *prefix,
+        None => return Err(Error::from_str(&format!("unexpected bech32 prefix \"{}\", expected one of {}", hrp, list(expected)))),
+    };
+    let bytes = Vec::<u8>::from_base32(&data).map_err(|e| Error::from_str(&e.to_string()))?;
+    if let Some(byte_count) = prefix.byte_count() {
+        if bytes.len() != byte_count {
+            return Err(Error::from_str(&format!("\"{}\" is for {} bytes, found {}", prefix, byte_count, bytes.len())));
+        }
+    }
+    Ok((prefix, bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{Bip32PrivateKey, Ed25519KeyHash, PrivateKey, PublicKey, ScriptHash, VRFKeyHash};
+
+    #[test]
+    fn registry() {
+        assert_eq!(Bech32Prefix::ALL.len(), Bech32Prefix::ALL.iter().map(|prefix| prefix.as_str()).collect::<std::collections::BTreeSet<_>>().len());
+        for prefix in Bech32Prefix::ALL {
+            assert_eq!(&Bech32Prefix::from_str(prefix.as_str()).unwrap(), prefix);
+        }
+        assert!(Bech32Prefix::from_str("ed25519_pk").is_err());
+        assert!(Bech32Prefix::of_kind(Bech32PrefixKind::ExtendedVerificationKey).contains(&Bech32Prefix::RootXvk));
+    }
+
+    #[test]
+    fn hashes() {
+        // pool id of a mainnet pool
+        let pool = "pool1pu5jlj4q9w9jlxeu370a3c9myx47md5j5m2str0naunn2q3lkdy";
+        let pool_id = Ed25519KeyHash::from_bech32(pool).unwrap();
+        assert_eq!(pool_id.to_bech32(Some(Bech32Prefix::Pool)).unwrap(), pool);
+        assert!(pool_id.to_bech32(None).is_err(), "key hashes have many roles");
+        assert!(pool_id.to_bech32(Some(Bech32Prefix::Script)).is_err());
+        assert_eq!(decode(&pool_id.to_bech32(Some(Bech32Prefix::StakeVkh)).unwrap(), &[Bech32Prefix::StakeVkh]).unwrap().0, Bech32Prefix::StakeVkh);
+        assert!(decode(pool, &[Bech32Prefix::StakeVkh]).is_err());
+
+        let script_hash = ScriptHash::from([7; ScriptHash::BYTE_COUNT]);
+        let encoded = script_hash.to_bech32(None).unwrap();
+        assert!(encoded.starts_with("script1"));
+        assert_eq!(ScriptHash::from_bech32(&encoded).unwrap(), script_hash);
+        // the same 28 bytes as a key hash
+        assert!(Ed25519KeyHash::from_bech32(&encoded).is_err());
+        assert!(VRFKeyHash::from_bech32(&bech32::encode("vrf_vkh", [1u8; 28].to_base32()).unwrap()).is_err(), "wrong length");
+    }
+
+    #[test]
+    fn keys() {
+        let root = Bip32PrivateKey::from_bip39_entropy(&[0x0c; 16], &[]);
+        let root_xsk = root.to_cip5_bech32(Bech32Prefix::RootXsk).unwrap();
+        assert!(root_xsk.starts_with("root_xsk1"));
+        assert_eq!(Bip32PrivateKey::from_cip5_bech32(&root_xsk).unwrap().as_bytes(), root.as_bytes());
+        assert!(root.to_cip5_bech32(Bech32Prefix::RootXvk).is_err());
+
+        let account = root.to_public();
+        let acct_xvk = account.to_cip5_bech32(Bech32Prefix::AcctXvk).unwrap();
+        assert_eq!(crate::crypto::Bip32PublicKey::from_cip5_bech32(&acct_xvk).unwrap().as_bytes(), account.as_bytes());
+
+        let key = account.to_raw_key();
+        let addr_vk = key.to_cip5_bech32(Bech32Prefix::AddrVk).unwrap();
+        assert_eq!(PublicKey::from_cip5_bech32(&addr_vk).unwrap(), key);
+        // an extended key isn't a plain verification key
+        assert!(PublicKey::from_cip5_bech32(&acct_xvk).is_err());
+        assert!(PublicKey::from_cip5_bech32(&key.to_bech32()).is_err());
+
+        let normal = PrivateKey::from_normal_bytes(&[3; 32]).unwrap();
+        let policy_sk = normal.to_cip5_bech32(Bech32Prefix::PolicySk).unwrap();
+        assert_eq!(PrivateKey::from_cip5_bech32(&policy_sk).unwrap().as_bytes(), normal.as_bytes());
+        assert!(root.to_raw_key().to_cip5_bech32(Bech32Prefix::AddrSk).is_err(), "no CIP-5 prefix for extended non-BIP32 keys");
+    }
+}
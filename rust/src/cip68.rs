@@ -0,0 +1,365 @@
+use super::*;
+use super::error::Error;
+use super::cip25::{CIP25MetadataDetails, CIP25FilesDetails};
+use super::ledger::common::value::BigInt;
+
+/// CIP-67 asset name labels used by CIP-68 tokens
+/// https://cips.cardano.org/cips/cip68/
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CIP68TokenLabel {
+    /// (100) reference NFT holding the datum with the metadata
+    ReferenceNft,
+    /// (222) NFT held by the user
+    Nft,
+    /// (333) fungible token held by the user
+    Ft,
+    /// (444) rich fungible token held by the user
+    Rft,
+}
+
+impl CIP68TokenLabel {
+    pub fn label(&self) -> u16 {
+        match self {
+            CIP68TokenLabel::ReferenceNft => 100,
+            CIP68TokenLabel::Nft => 222,
+            CIP68TokenLabel::Ft => 333,
+            CIP68TokenLabel::Rft => 444,
+        }
+    }
+
+    pub fn from_label(label: u16) -> Option<Self> {
+        match label {
+            100 => Some(CIP68TokenLabel::ReferenceNft),
+            222 => Some(CIP68TokenLabel::Nft),
+            333 => Some(CIP68TokenLabel::Ft),
+            444 => Some(CIP68TokenLabel::Rft),
+            _ => None,
+        }
+    }
+}
+
+// CRC-8 with polynomial 0x07 as required by CIP-67
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |crc, byte| {
+        (0..8).fold(crc ^ byte, |crc, _| if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 })
+    })
+}
+
+/// 4-byte CIP-67 prefix for `label`: [0000 | 16 bits label | 8 bits checksum | 0000]
+pub fn cip67_label_prefix(label: u16) -> [u8; 4] {
+    let checksum = crc8(&label.to_be_bytes());
+    (((label as u32) << 12) | ((checksum as u32) << 4)).to_be_bytes()
+}
+
+/// Returns the CIP-67 label the asset name starts with, if it has a valid one
+pub fn cip67_label(asset_name: &AssetName) -> Option<u16> {
+    let name = &asset_name.0;
+    if name.len() < 4 || name[0] & 0xf0 != 0 || name[3] & 0x0f != 0 {
+        return None;
+    }
+    let prefix = u32::from_be_bytes([name[0], name[1], name[2], name[3]]);
+    let label = (prefix >> 12) as u16;
+    if cip67_label_prefix(label) == prefix.to_be_bytes() {
+        Some(label)
+    } else {
+        None
+    }
+}
+
+/// Asset name for a CIP-68 token: the CIP-67 label followed by `name` (at most 28 bytes)
+pub fn cip68_asset_name(label: CIP68TokenLabel, name: &[u8]) -> Result<AssetName, Error> {
+    AssetName::new([&cip67_label_prefix(label.label())[..], name].concat())
+}
+
+/// Asset name of the (100) reference NFT matching a (222), (333) or (444) user token
+pub fn cip68_reference_asset_name(user_token: &AssetName) -> Result<AssetName, Error> {
+    match cip67_label(user_token).and_then(CIP68TokenLabel::from_label) {
+        Some(CIP68TokenLabel::ReferenceNft) => Err(Error::from_str("asset is already a CIP-68 reference NFT")),
+        Some(_) => cip68_asset_name(CIP68TokenLabel::ReferenceNft, &user_token.0[4..]),
+        None => Err(Error::from_str(&format!("{} is not a CIP-68 user token", hex::encode(&user_token.0)))),
+    }
+}
+
+fn bytes_key(key: &str) -> PlutusData {
+    PlutusData::new_bytes(key.as_bytes().to_vec())
+}
+
+fn text_value(text: &str) -> PlutusData {
+    PlutusData::new_bytes(text.as_bytes().to_vec())
+}
+
+/// Converts metadata to the equivalent Plutus data. Text is stored as UTF-8 bytes
+pub fn metadatum_to_plutus_data(metadatum: &TransactionMetadatum) -> Result<PlutusData, Error> {
+    Ok(match metadatum.kind() {
+        TransactionMetadatumKind::MetadataMap => {
+            let map = metadatum.as_map()?;
+            let keys = map.keys();
+            let mut plutus_map = PlutusMap::new();
+            for i in 0..keys.len() {
+                let key = keys.get(i);
+                plutus_map.insert(&metadatum_to_plutus_data(&key)?, &metadatum_to_plutus_data(&map.get(&key)?)?);
+            }
+            PlutusData::new_map(&plutus_map)
+        },
+        TransactionMetadatumKind::MetadataList => {
+            let list = metadatum.as_list()?;
+            let mut plutus_list = PlutusList::new();
+            for i in 0..list.len() {
+                plutus_list.add(&metadatum_to_plutus_data(&list.get(i))?);
+            }
+            PlutusData::new_list(&plutus_list)
+        },
+        TransactionMetadatumKind::Int => PlutusData::new_integer(&BigInt::from(metadatum.as_int()?.0)),
+        TransactionMetadatumKind::Bytes => PlutusData::new_bytes(metadatum.as_bytes()?),
+        TransactionMetadatumKind::Text => text_value(&metadatum.as_text()?),
+    })
+}
+
+fn insert_other_entries(map: &mut PlutusMap, other: &MetadataMap) -> Result<(), Error> {
+    let keys = other.keys();
+    for i in 0..keys.len() {
+        let key = keys.get(i);
+        map.insert(&metadatum_to_plutus_data(&key)?, &metadatum_to_plutus_data(&other.get(&key)?)?);
+    }
+    Ok(())
+}
+
+fn files_to_plutus_data(file: &CIP25FilesDetails) -> Result<PlutusData, Error> {
+    let mut map = PlutusMap::new();
+    if let Some(name) = file.name() {
+        map.insert(&bytes_key("name"), &text_value(&name));
+    }
+    map.insert(&bytes_key("mediaType"), &text_value(&file.media_type()));
+    map.insert(&bytes_key("src"), &text_value(&file.src()));
+    insert_other_entries(&mut map, &file.other())?;
+    Ok(PlutusData::new_map(&map))
+}
+
+/// Fungible token metadata stored in the reference NFT of a (333) token
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CIP68FtDetails {
+    name: String,
+    description: String,
+    ticker: Option<String>,
+    url: Option<String>,
+    decimals: Option<u64>,
+    logo: Option<String>,
+}
+
+impl CIP68FtDetails {
+    pub fn new(name: String, description: String) -> Self {
+        Self {
+            name,
+            description,
+            ticker: None,
+            url: None,
+            decimals: None,
+            logo: None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    pub fn ticker(&self) -> Option<String> {
+        self.ticker.clone()
+    }
+
+    pub fn set_ticker(&mut self, ticker: String) {
+        self.ticker = Some(ticker)
+    }
+
+    pub fn url(&self) -> Option<String> {
+        self.url.clone()
+    }
+
+    pub fn set_url(&mut self, url: String) {
+        self.url = Some(url)
+    }
+
+    pub fn decimals(&self) -> Option<u64> {
+        self.decimals
+    }
+
+    pub fn set_decimals(&mut self, decimals: u64) {
+        self.decimals = Some(decimals)
+    }
+
+    pub fn logo(&self) -> Option<String> {
+        self.logo.clone()
+    }
+
+    pub fn set_logo(&mut self, logo: String) {
+        self.logo = Some(logo)
+    }
+}
+
+/// Datum attached to the output holding a (100) reference NFT
+/// `Constr 0 [metadata, version, extra]`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CIP68Datum {
+    metadata: PlutusMap,
+    version: BigNum,
+    extra: PlutusData,
+}
+
+impl CIP68Datum {
+    pub fn new(metadata: &PlutusMap, version: &BigNum, extra: &PlutusData) -> Self {
+        Self {
+            metadata: metadata.clone(),
+            version: *version,
+            extra: extra.clone(),
+        }
+    }
+
+    /// Datum for a (222) NFT using the same fields as CIP-25
+    pub fn new_nft(details: &CIP25MetadataDetails) -> Result<Self, Error> {
+        let mut map = PlutusMap::new();
+        map.insert(&bytes_key("name"), &text_value(&details.name()));
+        map.insert(&bytes_key("image"), &text_value(&details.image()));
+        if let Some(media_type) = details.media_type() {
+            map.insert(&bytes_key("mediaType"), &text_value(&media_type));
+        }
+        if let Some(description) = details.description() {
+            map.insert(&bytes_key("description"), &text_value(&description));
+        }
+        if !details.files().is_empty() {
+            let mut files = PlutusList::new();
+            for file in details.files() {
+                files.add(&files_to_plutus_data(&file)?);
+            }
+            map.insert(&bytes_key("files"), &PlutusData::new_list(&files));
+        }
+        insert_other_entries(&mut map, &details.other())?;
+        Ok(Self::new(&map, &to_bignum(1), &Self::empty_extra()))
+    }
+
+    /// Datum for a (333) fungible token
+    pub fn new_ft(details: &CIP68FtDetails) -> Self {
+        let mut map = PlutusMap::new();
+        map.insert(&bytes_key("name"), &text_value(&details.name));
+        map.insert(&bytes_key("description"), &text_value(&details.description));
+        if let Some(ticker) = &details.ticker {
+            map.insert(&bytes_key("ticker"), &text_value(ticker));
+        }
+        if let Some(url) = &details.url {
+            map.insert(&bytes_key("url"), &text_value(url));
+        }
+        if let Some(decimals) = details.decimals {
+            map.insert(&bytes_key("decimals"), &PlutusData::new_integer(&BigInt::from(decimals)));
+        }
+        if let Some(logo) = &details.logo {
+            map.insert(&bytes_key("logo"), &text_value(logo));
+        }
+        Self::new(&map, &to_bignum(1), &Self::empty_extra())
+    }
+
+    // Constr 0 [] i.e. unit
+    fn empty_extra() -> PlutusData {
+        PlutusData::new_constr_plutus_data(&ConstrPlutusData::new(&to_bignum(0), &PlutusList::new()))
+    }
+
+    pub fn metadata(&self) -> PlutusMap {
+        self.metadata.clone()
+    }
+
+    pub fn version(&self) -> BigNum {
+        self.version
+    }
+
+    pub fn extra(&self) -> PlutusData {
+        self.extra.clone()
+    }
+
+    /// Looks up a metadata field by its (UTF-8) name
+    pub fn get(&self, key: &str) -> Option<PlutusData> {
+        // keys are compared by content since decoded data also keeps its original encoding
+        let keys = self.metadata.keys();
+        (0..keys.len())
+            .map(|i| keys.get(i))
+            .find(|k| k.as_bytes().as_deref() == Some(key.as_bytes()))
+            .and_then(|k| self.metadata.get(&k))
+    }
+
+    /// Looks up a metadata field by its (UTF-8) name and decodes it as a UTF-8 string
+    pub fn get_text(&self, key: &str) -> Option<String> {
+        self.get(key)
+            .and_then(|value| value.as_bytes())
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+    }
+
+    pub fn to_plutus_data(&self) -> PlutusData {
+        let mut fields = PlutusList::new();
+        fields.add(&PlutusData::new_map(&self.metadata));
+        fields.add(&PlutusData::new_integer(&BigInt::from(u64::from(self.version))));
+        fields.add(&self.extra);
+        PlutusData::new_constr_plutus_data(&ConstrPlutusData::new(&to_bignum(0), &fields))
+    }
+
+    pub fn from_plutus_data(data: &PlutusData) -> Result<Self, Error> {
+        let constr = data.as_constr_plutus_data()
+            .ok_or_else(|| Error::from_str("CIP-68 datum must be a constructor"))?;
+        if constr.alternative() != to_bignum(0) || constr.data().len() < 3 {
+            return Err(Error::from_str("CIP-68 datum must be Constr 0 [metadata, version, extra]"));
+        }
+        let fields = constr.data();
+        let metadata = fields.get(0).as_map()
+            .ok_or_else(|| Error::from_str("CIP-68 metadata must be a map"))?;
+        let version = fields.get(1).as_integer()
+            .and_then(|version| version.as_u64())
+            .ok_or_else(|| Error::from_str("CIP-68 version must be a positive integer"))?;
+        Ok(Self::new(&metadata, &version, &fields.get(2)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cip67_labels() {
+        assert_eq!(hex::encode(cip67_label_prefix(100)), "000643b0");
+        assert_eq!(hex::encode(cip67_label_prefix(222)), "000de140");
+        assert_eq!(hex::encode(cip67_label_prefix(333)), "0014df10");
+        assert_eq!(hex::encode(cip67_label_prefix(444)), "001bc280");
+
+        let nft = cip68_asset_name(CIP68TokenLabel::Nft, b"MyNFT").unwrap();
+        assert_eq!(cip67_label(&nft), Some(222));
+        let reference = cip68_reference_asset_name(&nft).unwrap();
+        assert_eq!(hex::encode(reference.name()), "000643b0".to_owned() + &hex::encode(b"MyNFT"));
+        assert!(cip68_reference_asset_name(&reference).is_err());
+
+        // bad checksum
+        assert_eq!(cip67_label(&AssetName::new(hex::decode("000de150").unwrap()).unwrap()), None);
+        assert_eq!(cip67_label(&AssetName::new(b"MyNFT".to_vec()).unwrap()), None);
+        // at most 28 bytes after the label
+        assert!(cip68_asset_name(CIP68TokenLabel::Nft, &[0u8; 29]).is_err());
+    }
+
+    #[test]
+    fn cip68_nft_datum() {
+        let mut details = CIP25MetadataDetails::new("NFT".to_owned(), "ipfs://".to_owned() + &"a".repeat(100));
+        details.set_description("desc".to_owned());
+        let datum = CIP68Datum::new_nft(&details).unwrap();
+        let decoded = CIP68Datum::from_plutus_data(&PlutusData::from_bytes(datum.to_plutus_data().to_bytes()).unwrap()).unwrap();
+        assert_eq!(decoded.version(), to_bignum(1));
+        assert_eq!(decoded.get_text("name"), Some("NFT".to_owned()));
+        assert_eq!(decoded.get_text("image"), Some(details.image()));
+        assert_eq!(decoded.get_text("description"), Some("desc".to_owned()));
+    }
+
+    #[test]
+    fn cip68_ft_datum() {
+        let mut details = CIP68FtDetails::new("Token".to_owned(), "A token".to_owned());
+        details.set_ticker("TKN".to_owned());
+        details.set_decimals(6);
+        let datum = CIP68Datum::from_plutus_data(&CIP68Datum::new_ft(&details).to_plutus_data()).unwrap();
+        assert_eq!(datum.get_text("ticker"), Some("TKN".to_owned()));
+        assert_eq!(datum.get("decimals").unwrap().as_integer().unwrap().as_u64(), Some(to_bignum(6)));
+    }
+}
@@ -15,9 +15,19 @@ use std::io::{BufRead, Seek, Write};
 use std::str::FromStr;
 
 use cryptoxide::blake2b::Blake2b;
+use crate::cip5::{self, Bech32Prefix, Bech32PrefixKind};
 
 use super::*;
 
+// the legacy Blake2b API only supports some output sizes so we use the const generic one
+pub (crate) fn blake2b160(data: &[u8]) -> [u8; 20] {
+    let mut out = [0; 20];
+    cryptoxide::hashing::blake2b::Context::<160>::new()
+        .update(data)
+        .finalize_at(&mut out);
+    out
+}
+
 pub (crate) fn blake2b224(data: &[u8]) -> [u8; 28] {
     let mut out = [0; 28];
     Blake2b::blake2b(&mut out, data, &[]);
@@ -34,6 +44,14 @@ pub (crate) fn blake2b256(data: &[u8]) -> [u8; 32] {
 // https://github.com/Emurgo/js-chain-libs
 
 
+/// to_bech32 of keys uses the prefixes of the underlying algorithm (ed25519_pk, xpub...), to_cip5_bech32 the one of the key's role
+fn cip5_encode(prefix: Bech32Prefix, kind: Bech32PrefixKind, bytes: &[u8]) -> Result<String, Error> {
+    if prefix.kind() != kind {
+        return Err(Error::from_str(&format!("\"{}\" is not a prefix for {:?}", prefix, kind)));
+    }
+    cip5::encode(prefix, bytes)
+}
+
 pub struct Bip32PrivateKey(crypto::SecretKey<crypto::Ed25519Bip32>);
 
 
@@ -69,7 +87,7 @@ impl Bip32PrivateKey {
     /// prv | pub | chaincode
     /// so be careful if you see the term "xprv" as it could refer to either one
     /// our library does not require the pub (instead we compute the pub key when needed)
-    pub fn from_128_xprv(bytes: &[u8]) -> Result<Bip32PrivateKey, JsError> {
+    pub fn from_128_xprv(bytes: &[u8]) -> Result<Bip32PrivateKey, Error> {
         let mut buf = [0; 96];
         buf[0..64].clone_from_slice(&bytes[0..64]);
         buf[64..96].clone_from_slice(&bytes[96..128]);
@@ -89,7 +107,7 @@ impl Bip32PrivateKey {
         buf.to_vec()
     }
 
-    pub fn generate_ed25519_bip32() -> Result<Bip32PrivateKey, JsError> {
+    pub fn generate_ed25519_bip32() -> Result<Bip32PrivateKey, Error> {
         Ok(OsRng)
             .map(crypto::SecretKey::<crypto::Ed25519Bip32>::generate)
             .map(Bip32PrivateKey)
@@ -105,9 +123,9 @@ impl Bip32PrivateKey {
         Bip32PublicKey(self.0.to_public().into())
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Result<Bip32PrivateKey, JsError> {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Bip32PrivateKey, Error> {
         crypto::SecretKey::<crypto::Ed25519Bip32>::from_binary(bytes)
-            .map_err(|e| JsError::from_str(&format!("{}", e)))
+            .map_err(|e| Error::from_str(&format!("{}", e)))
             .map(Bip32PrivateKey)
     }
 
@@ -115,16 +133,26 @@ impl Bip32PrivateKey {
         self.0.as_ref().to_vec()
     }
 
-    pub fn from_bech32(bech32_str: &str) -> Result<Bip32PrivateKey, JsError> {
+    pub fn from_bech32(bech32_str: &str) -> Result<Bip32PrivateKey, Error> {
         crypto::SecretKey::try_from_bech32_str(&bech32_str)
             .map(Bip32PrivateKey)
-            .map_err(|_| JsError::from_str("Invalid secret key"))
+            .map_err(|_| Error::from_str("Invalid secret key"))
     }
 
     pub fn to_bech32(&self) -> String {
         self.0.to_bech32_str()
     }
 
+    /// e.g. root_xsk or acct_xsk
+    pub fn to_cip5_bech32(&self, prefix: Bech32Prefix) -> Result<String, Error> {
+        cip5_encode(prefix, Bech32PrefixKind::ExtendedSigningKey, &self.as_bytes())
+    }
+
+    pub fn from_cip5_bech32(bech32_str: &str) -> Result<Bip32PrivateKey, Error> {
+        let (_prefix, bytes) = cip5::decode(bech32_str, &Bech32Prefix::of_kind(Bech32PrefixKind::ExtendedSigningKey))?;
+        Self::from_bytes(&bytes)
+    }
+
     pub fn from_bip39_entropy(entropy: &[u8], password: &[u8]) -> Bip32PrivateKey {
         Bip32PrivateKey(crypto::derive::from_bip39_entropy(&entropy, &password))
     }
@@ -166,19 +194,19 @@ impl Bip32PublicKey {
     /// This is why deriving the private key should not fail while deriving
     /// the public key may fail (if the derivation index is invalid).
     ///
-    pub fn derive(&self, index: u32) -> Result<Bip32PublicKey, JsError> {
+    pub fn derive(&self, index: u32) -> Result<Bip32PublicKey, Error> {
         crypto::derive::derive_pk_ed25519(&self.0, index)
             .map(Bip32PublicKey)
-            .map_err(|e| JsError::from_str(&format! {"{:?}", e}))
+            .map_err(|e| Error::from_str(&format! {"{:?}", e}))
     }
 
     pub fn to_raw_key(&self) -> PublicKey {
         PublicKey(crypto::derive::to_raw_pk(&self.0))
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Result<Bip32PublicKey, JsError> {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Bip32PublicKey, Error> {
         crypto::PublicKey::<crypto::Ed25519Bip32>::from_binary(bytes)
-            .map_err(|e| JsError::from_str(&format!("{}", e)))
+            .map_err(|e| Error::from_str(&format!("{}", e)))
             .map(Bip32PublicKey)
     }
 
@@ -186,16 +214,26 @@ impl Bip32PublicKey {
         self.0.as_ref().to_vec()
     }
 
-    pub fn from_bech32(bech32_str: &str) -> Result<Bip32PublicKey, JsError> {
+    pub fn from_bech32(bech32_str: &str) -> Result<Bip32PublicKey, Error> {
         crypto::PublicKey::try_from_bech32_str(&bech32_str)
             .map(Bip32PublicKey)
-            .map_err(|e| JsError::from_str(&format!("{}", e)))
+            .map_err(|e| Error::from_str(&format!("{}", e)))
     }
 
     pub fn to_bech32(&self) -> String {
         self.0.to_bech32_str()
     }
 
+    /// e.g. acct_xvk or addr_xvk
+    pub fn to_cip5_bech32(&self, prefix: Bech32Prefix) -> Result<String, Error> {
+        cip5_encode(prefix, Bech32PrefixKind::ExtendedVerificationKey, &self.as_bytes())
+    }
+
+    pub fn from_cip5_bech32(bech32_str: &str) -> Result<Bip32PublicKey, Error> {
+        let (_prefix, bytes) = cip5::decode(bech32_str, &Bech32Prefix::of_kind(Bech32PrefixKind::ExtendedVerificationKey))?;
+        Self::from_bytes(&bytes)
+    }
+
     pub fn chaincode(&self) -> Vec<u8> {
         const ED25519_PUBLIC_KEY_LENGTH: usize = 32;
         const XPUB_SIZE: usize = 64;
@@ -219,14 +257,14 @@ impl PrivateKey {
         self.0.to_public().into()
     }
 
-    pub fn generate_ed25519() -> Result<PrivateKey, JsError> {
+    pub fn generate_ed25519() -> Result<PrivateKey, Error> {
         Ok(OsRng)
             .map(crypto::SecretKey::<crypto::Ed25519>::generate)
             .map(key::EitherEd25519SecretKey::Normal)
             .map(PrivateKey)
     }
 
-    pub fn generate_ed25519extended() -> Result<PrivateKey, JsError> {
+    pub fn generate_ed25519extended() -> Result<PrivateKey, Error> {
         Ok(OsRng)
             .map(crypto::SecretKey::<crypto::Ed25519Extended>::generate)
             .map(key::EitherEd25519SecretKey::Extended)
@@ -241,7 +279,7 @@ impl PrivateKey {
     /// ```javascript
     /// PrivateKey.from_bech32(&#39;ed25519e_sk1gqwl4szuwwh6d0yk3nsqcc6xxc3fpvjlevgwvt60df59v8zd8f8prazt8ln3lmz096ux3xvhhvm3ca9wj2yctdh3pnw0szrma07rt5gl748fp&#39;);
     /// ```
-    pub fn from_bech32(bech32_str: &str) -> Result<PrivateKey, JsError> {
+    pub fn from_bech32(bech32_str: &str) -> Result<PrivateKey, Error> {
         crypto::SecretKey::try_from_bech32_str(&bech32_str)
             .map(key::EitherEd25519SecretKey::Extended)
             .or_else(|_| {
@@ -249,7 +287,7 @@ impl PrivateKey {
                     .map(key::EitherEd25519SecretKey::Normal)
             })
             .map(PrivateKey)
-            .map_err(|_| JsError::from_str("Invalid secret key"))
+            .map_err(|_| Error::from_str("Invalid secret key"))
     }
 
     pub fn to_bech32(&self) -> String {
@@ -259,6 +297,19 @@ impl PrivateKey {
         }
     }
 
+    /// e.g. addr_sk or policy_sk. CIP-5 has no prefix for extended keys without a chain code
+    pub fn to_cip5_bech32(&self, prefix: Bech32Prefix) -> Result<String, Error> {
+        match self.0 {
+            key::EitherEd25519SecretKey::Normal(ref secret) => cip5_encode(prefix, Bech32PrefixKind::SigningKey, secret.as_ref()),
+            key::EitherEd25519SecretKey::Extended(_) => Err(Error::from_str("CIP-5 signing key prefixes are for normal ed25519 keys, this one is extended")),
+        }
+    }
+
+    pub fn from_cip5_bech32(bech32_str: &str) -> Result<PrivateKey, Error> {
+        let (_prefix, bytes) = cip5::decode(bech32_str, &Bech32Prefix::of_kind(Bech32PrefixKind::SigningKey))?;
+        Self::from_normal_bytes(&bytes)
+    }
+
     pub fn as_bytes(&self) -> Vec<u8> {
         match self.0 {
             key::EitherEd25519SecretKey::Normal(ref secret) => secret.as_ref().to_vec(),
@@ -266,18 +317,18 @@ impl PrivateKey {
         }
     }
 
-    pub fn from_extended_bytes(bytes: &[u8]) -> Result<PrivateKey, JsError> {
+    pub fn from_extended_bytes(bytes: &[u8]) -> Result<PrivateKey, Error> {
         crypto::SecretKey::from_binary(bytes)
             .map(key::EitherEd25519SecretKey::Extended)
             .map(PrivateKey)
-            .map_err(|_| JsError::from_str("Invalid extended secret key"))
+            .map_err(|_| Error::from_str("Invalid extended secret key"))
     }
 
-    pub fn from_normal_bytes(bytes: &[u8]) -> Result<PrivateKey, JsError> {
+    pub fn from_normal_bytes(bytes: &[u8]) -> Result<PrivateKey, Error> {
         crypto::SecretKey::from_binary(bytes)
             .map(key::EitherEd25519SecretKey::Normal)
             .map(PrivateKey)
-            .map_err(|_| JsError::from_str("Invalid normal secret key"))
+            .map_err(|_| Error::from_str("Invalid normal secret key"))
     }
 
     pub fn sign(&self, message: &[u8]) -> Ed25519Signature {
@@ -303,23 +354,33 @@ impl PublicKey {
     /// ```javascript
     /// const pkey = PublicKey.from_bech32(&#39;ed25519_pk1dgaagyh470y66p899txcl3r0jaeaxu6yd7z2dxyk55qcycdml8gszkxze2&#39;);
     /// ```
-    pub fn from_bech32(bech32_str: &str) -> Result<PublicKey, JsError> {
+    pub fn from_bech32(bech32_str: &str) -> Result<PublicKey, Error> {
         crypto::PublicKey::try_from_bech32_str(&bech32_str)
             .map(PublicKey)
-            .map_err(|_| JsError::from_str("Malformed public key"))
+            .map_err(|_| Error::from_str("Malformed public key"))
     }
 
     pub fn to_bech32(&self) -> String {
         self.0.to_bech32_str()
     }
 
+    /// e.g. addr_vk or stake_vk
+    pub fn to_cip5_bech32(&self, prefix: Bech32Prefix) -> Result<String, Error> {
+        cip5_encode(prefix, Bech32PrefixKind::VerificationKey, &self.as_bytes())
+    }
+
+    pub fn from_cip5_bech32(bech32_str: &str) -> Result<PublicKey, Error> {
+        let (_prefix, bytes) = cip5::decode(bech32_str, &Bech32Prefix::of_kind(Bech32PrefixKind::VerificationKey))?;
+        Self::from_bytes(&bytes)
+    }
+
     pub fn as_bytes(&self) -> Vec<u8> {
         self.0.as_ref().to_vec()
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Result<PublicKey, JsError> {
+    pub fn from_bytes(bytes: &[u8]) -> Result<PublicKey, Error> {
         crypto::PublicKey::from_binary(bytes)
-            .map_err(|e| JsError::from_str(&format!("{}", e)))
+            .map_err(|e| Error::from_str(&format!("{}", e)))
             .map(PublicKey)
     }
 
@@ -365,6 +426,8 @@ impl Deserialize for Vkey {
 #[derive(Clone, Debug)]
 pub struct Vkeys(pub(crate) Vec<Vkey>);
 
+vec_iter!(Vkeys, Vkey);
+
 
 impl Vkeys {
     pub fn new() -> Self {
@@ -479,6 +542,8 @@ impl Deserialize for Vkeywitness {
 #[derive(Clone, serde::Serialize, serde::Deserialize, JsonSchema)]
 pub struct Vkeywitnesses(pub (crate) Vec<Vkeywitness>);
 
+vec_iter!(Vkeywitnesses, Vkeywitness);
+
 
 impl Vkeywitnesses {
     pub fn new() -> Self {
@@ -566,15 +631,15 @@ impl BootstrapWitness {
         }
     }
 
-    pub fn to_public_key(&self) -> Result<Bip32PublicKey, JsError> {
+    pub fn to_public_key(&self) -> Result<Bip32PublicKey, Error> {
         crypto::PublicKey::<crypto::ed25519_derive::Ed25519Bip32>::try_from(self.clone())
             .map(Bip32PublicKey)
-            .map_err(|_| JsError::from_str("Invalid public key or byte code"))
+            .map_err(|_| Error::from_str("Invalid public key or byte code"))
     }
 
-    pub fn to_address(&self) -> Result<AddressContent, JsError> {
+    pub fn to_address(&self) -> Result<AddressContent, Error> {
         AddressContent::try_from(self.clone())
-            .map_err(|_| JsError::from_str("Invalid public key or byte code"))
+            .map_err(|_| Error::from_str("Invalid public key or byte code"))
     }
 }
 
@@ -655,6 +720,8 @@ impl DeserializeEmbeddedGroup for BootstrapWitness {
 #[derive(Clone, serde::Serialize, serde::Deserialize, JsonSchema)]
 pub struct BootstrapWitnesses(pub (crate) Vec<BootstrapWitness>);
 
+vec_iter!(BootstrapWitnesses, BootstrapWitness);
+
 
 impl BootstrapWitnesses {
     pub fn new() -> Self {
@@ -705,6 +772,8 @@ impl Deserialize for BootstrapWitnesses {
 
 pub struct PublicKeys(Vec<PublicKey>);
 
+vec_iter!(PublicKeys, PublicKey);
+
 
 impl PublicKeys {
     // #[wasm_bindgen(constructor)]
@@ -745,15 +814,15 @@ macro_rules! impl_signature {
                 hex::encode(&self.0.as_ref())
             }
 
-            pub fn from_bech32(bech32_str: &str) -> Result<$name, JsError> {
+            pub fn from_bech32(bech32_str: &str) -> Result<$name, Error> {
                 crypto::Signature::try_from_bech32_str(&bech32_str)
                     .map($name)
-                    .map_err(|e| JsError::from_str(&format!("{}", e)))
+                    .map_err(|e| Error::from_str(&format!("{}", e)))
             }
 
-            pub fn from_hex(input: &str) -> Result<$name, JsError> {
+            pub fn from_hex(input: &str) -> Result<$name, Error> {
                 crypto::Signature::from_str(input)
-                    .map_err(|e| JsError::from_str(&format!("{:?}", e)))
+                    .map_err(|e| Error::from_str(&format!("{:?}", e)))
                     .map($name)
             }
         }
@@ -801,7 +870,48 @@ macro_rules! impl_signature {
 
 impl_signature!(Ed25519Signature, Vec<u8>, crypto::Ed25519);
 macro_rules! impl_hash_type {
+    // hash types without a CIP-5 prefix take any prefix
     ($name:ident, $byte_count:expr) => {
+        impl_hash_type!(@common $name, $byte_count);
+
+        impl $name {
+            pub fn to_bech32(&self, prefix: &str) -> Result<String, Error> {
+                bech32::encode(&prefix, self.to_bytes().to_base32())
+                    .map_err(|e| Error::from_str(&format! {"{:?}", e}))
+            }
+
+            pub fn from_bech32(bech_str: &str) -> Result<$name, Error> {
+                let (_hrp, u5data) = bech32::decode(bech_str).map_err(|e| Error::from_str(&e.to_string()))?;
+                let data: Vec<u8> = bech32::FromBase32::from_base32(&u5data).unwrap();
+                Ok(Self::from_bytes(data)?)
+            }
+        }
+    };
+    // hash types with CIP-5 prefixes only use those, see cip5::Bech32Prefixed
+    ($name:ident, $byte_count:expr, [$($prefix:ident),+], $default:expr) => {
+        impl_hash_type!(@common $name, $byte_count);
+
+        impl crate::cip5::Bech32Prefixed for $name {
+            const BECH32_PREFIXES: &'static [crate::cip5::Bech32Prefix] = &[$(crate::cip5::Bech32Prefix::$prefix),+];
+            const DEFAULT_BECH32_PREFIX: Option<crate::cip5::Bech32Prefix> = $default;
+        }
+
+        impl $name {
+            /// Encodes with the type's default prefix if `prefix` is None
+            pub fn to_bech32(&self, prefix: Option<crate::cip5::Bech32Prefix>) -> Result<String, Error> {
+                use crate::cip5::Bech32Prefixed;
+                crate::cip5::encode(Self::bech32_prefix(stringify!($name), prefix)?, &self.0)
+            }
+
+            /// Fails for prefixes that aren't among the type's
+            pub fn from_bech32(bech_str: &str) -> Result<$name, Error> {
+                use crate::cip5::Bech32Prefixed;
+                let (_prefix, data) = crate::cip5::decode(bech_str, Self::BECH32_PREFIXES)?;
+                Ok(Self::from_bytes(data)?)
+            }
+        }
+    };
+    (@common $name:ident, $byte_count:expr) => {
         
         #[derive(Debug, Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
         pub struct $name(pub (crate) [u8; $byte_count]);
@@ -827,24 +937,13 @@ macro_rules! impl_hash_type {
                 self.0.to_vec()
             }
 
-            pub fn to_bech32(&self, prefix: &str) -> Result<String, JsError> {
-                bech32::encode(&prefix, self.to_bytes().to_base32())
-                    .map_err(|e| JsError::from_str(&format! {"{:?}", e}))
-            }
-        
-            pub fn from_bech32(bech_str: &str) -> Result<$name, JsError> {
-                let (_hrp, u5data) = bech32::decode(bech_str).map_err(|e| JsError::from_str(&e.to_string()))?;
-                let data: Vec<u8> = bech32::FromBase32::from_base32(&u5data).unwrap();
-                Ok(Self::from_bytes(data)?)
-            }
-
             pub fn to_hex(&self) -> String {
                 hex::encode(&self.0)
             }
 
-            pub fn from_hex(hex: &str) -> Result<$name, JsError> {
-                let bytes = hex::decode(hex).map_err(|e| JsError::from_str(&format!("hex decode failed: {}", e)))?;
-                Self::from_bytes(bytes).map_err(|e| JsError::from_str(&format!("{:?}", e)))
+            pub fn from_hex(hex: &str) -> Result<$name, Error> {
+                let bytes = hex::decode(hex).map_err(|e| Error::from_str(&format!("hex decode failed: {}", e)))?;
+                Self::from_bytes(bytes).map_err(|e| Error::from_str(&format!("{:?}", e)))
             }
         }
 
@@ -915,9 +1014,9 @@ pub struct LegacyDaedalusPrivateKey(pub (crate) crypto::SecretKey<crypto::Legacy
 
 
 impl LegacyDaedalusPrivateKey {
-    pub fn from_bytes(bytes: &[u8]) -> Result<LegacyDaedalusPrivateKey, JsError> {
+    pub fn from_bytes(bytes: &[u8]) -> Result<LegacyDaedalusPrivateKey, Error> {
         crypto::SecretKey::<crypto::LegacyDaedalus>::from_binary(bytes)
-            .map_err(|e| JsError::from_str(&format!("{}", e)))
+            .map_err(|e| Error::from_str(&format!("{}", e)))
             .map(LegacyDaedalusPrivateKey)
     }
 
@@ -932,22 +1031,24 @@ impl LegacyDaedalusPrivateKey {
     }
 }
 
-impl_hash_type!(Ed25519KeyHash, 28);
-impl_hash_type!(ScriptHash, 28);
+impl_hash_type!(Ed25519KeyHash, 28, [AddrVkh, StakeVkh, Pool, PolicyVkh, ReqSignerVkh, AddrSharedVkh, StakeSharedVkh, DrepVkh, CcColdVkh, CcHotVkh], None);
+impl_hash_type!(ScriptHash, 28, [Script, DrepScript, CcColdScript, CcHotScript], Some(crate::cip5::Bech32Prefix::Script));
 // TransactionHash is either a hash of the tx CBOR or a hash of a redeem address (genesis)
 impl_hash_type!(TransactionHash, 32);
 impl_hash_type!(GenesisDelegateHash, 28);
 impl_hash_type!(GenesisHash, 28);
 impl_hash_type!(AuxiliaryDataHash, 32);
 impl_hash_type!(PoolMetadataHash, 32);
-impl_hash_type!(VRFKeyHash, 32);
+impl_hash_type!(VRFKeyHash, 32, [VrfVkh], Some(crate::cip5::Bech32Prefix::VrfVkh));
 impl_hash_type!(BlockBodyHash, 32);
 impl_hash_type!(BlockHeaderHash, 32);
-impl_hash_type!(DataHash, 32);
-impl_hash_type!(ScriptDataHash, 32);
+impl_hash_type!(DataHash, 32, [Datum], Some(crate::cip5::Bech32Prefix::Datum));
+impl_hash_type!(ScriptDataHash, 32, [ScriptData], Some(crate::cip5::Bech32Prefix::ScriptData));
+// Blake2b-256 of a Shelley, Alonzo or Conway genesis file as given to the node (e.g. ShelleyGenesisHash in its config)
+impl_hash_type!(GenesisFileHash, 32);
 // We might want to make these two vkeys normal classes later but for now it's just arbitrary bytes for us (used in block parsing)
-impl_hash_type!(VRFVKey, 32);
-impl_hash_type!(KESVKey, 32);
+impl_hash_type!(VRFVKey, 32, [VrfVk], Some(crate::cip5::Bech32Prefix::VrfVk));
+impl_hash_type!(KESVKey, 32, [KesVk], Some(crate::cip5::Bech32Prefix::KesVk));
 // same for this signature
 //impl_hash_type!(KESSignature, 448);
 // TODO: when >32 size trait implementations are out of nightly and into stable
@@ -1045,13 +1146,13 @@ impl Nonce {
         }
     }
 
-    pub fn new_from_hash(hash: Vec<u8>) -> Result<Nonce, JsError> {
+    pub fn new_from_hash(hash: Vec<u8>) -> Result<Nonce, Error> {
         use std::convert::TryInto;
         match hash[..Self::HASH_LEN].try_into() {
             Ok(bytes_correct_size) => Ok(Self {
                 hash: Some(bytes_correct_size),
             }),
-            Err(e) => Err(JsError::from_str(&e.to_string())),
+            Err(e) => Err(Error::from_str(&e.to_string())),
         }
     }
 
@@ -1140,9 +1241,9 @@ impl VRFCert {
         self.proof.clone()
     }
 
-    pub fn new(output: Vec<u8>, proof: Vec<u8>) -> Result<VRFCert, JsError> {
+    pub fn new(output: Vec<u8>, proof: Vec<u8>) -> Result<VRFCert, Error> {
         if proof.len() != Self::PROOF_LEN {
-            return Err(JsError::from_str(&format!("proof len must be {} - found {}", Self::PROOF_LEN, proof.len())));
+            return Err(Error::from_str(&format!("proof len must be {} - found {}", Self::PROOF_LEN, proof.len())));
         }
         Ok(Self {
             output: output,
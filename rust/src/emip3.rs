@@ -1,5 +1,5 @@
 use super::*;
-use super::error::JsError;
+use super::error::Error;
 
 use cryptoxide::chacha20poly1305::ChaCha20Poly1305;
 use cryptoxide::hmac::Hmac;
@@ -36,22 +36,22 @@ pub fn encrypt_with_password(
     salt: &str,
     nonce: &str,
     data: &str,
-) -> Result<String, JsError> {
+) -> Result<String, Error> {
     use password_encryption_parameter::*;
 
-    let password = hex::decode(password).map_err(|e| JsError::from_str(&e.to_string()))?;
-    let salt = hex::decode(salt).map_err(|e| JsError::from_str(&e.to_string()))?;
-    let nonce = hex::decode(nonce).map_err(|e| JsError::from_str(&e.to_string()))?;
-    let data = hex::decode(data).map_err(|e| JsError::from_str(&e.to_string()))?;
+    let password = hex::decode(password).map_err(|e| Error::from_str(&e.to_string()))?;
+    let salt = hex::decode(salt).map_err(|e| Error::from_str(&e.to_string()))?;
+    let nonce = hex::decode(nonce).map_err(|e| Error::from_str(&e.to_string()))?;
+    let data = hex::decode(data).map_err(|e| Error::from_str(&e.to_string()))?;
 
     if salt.len() != SALT_SIZE {
-        return Err(JsError::from_str(&format!("salt len must be {}, found {} bytes", SALT_SIZE, salt.len())));
+        return Err(Error::from_str(&format!("salt len must be {}, found {} bytes", SALT_SIZE, salt.len())));
     }
     if nonce.len() != NONCE_SIZE {
-        return Err(JsError::from_str(&format!("nonce len must be {}, found {} bytes", NONCE_SIZE, nonce.len())));
+        return Err(Error::from_str(&format!("nonce len must be {}, found {} bytes", NONCE_SIZE, nonce.len())));
     }
     if password.is_empty() {
-      return Err(JsError::from_str("Password len cannot be 0"));
+      return Err(Error::from_str("Password len cannot be 0"));
     }
 
     let key = {
@@ -80,14 +80,14 @@ pub fn encrypt_with_password(
 pub fn decrypt_with_password(
     password: &str,
     data: &str,
-) -> Result<String, JsError> {
+) -> Result<String, Error> {
     use password_encryption_parameter::*;
-    let password = hex::decode(password).map_err(|e| JsError::from_str(&e.to_string()))?;
-    let data = hex::decode(data).map_err(|e| JsError::from_str(&e.to_string()))?;
+    let password = hex::decode(password).map_err(|e| Error::from_str(&e.to_string()))?;
+    let data = hex::decode(data).map_err(|e| Error::from_str(&e.to_string()))?;
 
     if data.len() <= METADATA_SIZE {
         // not enough input to decrypt.
-        return Err(JsError::from_str("Missing input data"));
+        return Err(Error::from_str("Missing input data"));
     }
 
     let salt = &data[SALT_START..SALT_END];
@@ -109,7 +109,7 @@ pub fn decrypt_with_password(
     if decryption_succeed {
         Ok(decrypted.encode_hex::<String>())
     } else {
-        Err(JsError::from_str("Decryption error"))
+        Err(Error::from_str("Decryption error"))
     }
 }
 
@@ -35,7 +35,7 @@ pub enum DeserializeFailure {
         expected: Key,
     },
     MandatoryFieldMissing(Key),
-    Metadata(JsError),
+    Metadata(Error),
     NoVariantMatched,
     RangeCheck{
         found: usize,
@@ -132,9 +132,11 @@ impl std::fmt::Display for DeserializeError {
     }
 }
 
-impl From<DeserializeError> for JsError {
-    fn from(e: DeserializeError) -> JsError {
-        JsError::from_str(&e.to_string())
+impl std::error::Error for DeserializeError {}
+
+impl From<DeserializeError> for Error {
+    fn from(e: DeserializeError) -> Error {
+        Error::from_str(&e.to_string())
     }
 }
 
@@ -175,35 +177,45 @@ impl From<chain_crypto::PublicKeyError> for DeserializeError {
 }
 
 
-// Generic string error that is replaced with JsError on wasm builds but still usable from non-wasm builds
-// since JsError panics when used for non-constants in non-wasm builds even just creating one
-
-#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
-pub type JsError = wasm_bindgen::prelude::JsValue;
-
-#[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+/// Errors of the functions without a more specific error type e.g. invalid arguments or overflows
+///
+/// The JS bindings turn it into a JS value, which is the only place wasm-bindgen types are used
 #[derive(Debug, Clone)]
-pub struct JsError {
+pub struct Error {
     msg: String,
 }
 
-#[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
-impl JsError {
+impl Error {
     pub fn from_str(s: &str) -> Self {
         Self {
             msg: s.to_owned(),
         }
     }
 
-    // to match JsValue's API even though to_string() exists
+    // kept from when this was a JsValue on wasm builds, even though to_string() exists
     pub fn as_string(&self) -> Option<String> {
         Some(self.msg.clone())
     }
 }
 
-#[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
-impl std::fmt::Display for JsError {
+impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.msg)
     }
-}
\ No newline at end of file
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(feature = "wasm")]
+impl From<Error> for wasm_bindgen::JsValue {
+    fn from(e: Error) -> wasm_bindgen::JsValue {
+        wasm_bindgen::JsValue::from_str(&e.msg)
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl From<DeserializeError> for wasm_bindgen::JsValue {
+    fn from(e: DeserializeError) -> wasm_bindgen::JsValue {
+        wasm_bindgen::JsValue::from_str(&e.to_string())
+    }
+}
@@ -0,0 +1,21 @@
+//! Typed contents of the Alonzo genesis file (alonzo-genesis.json)
+
+use crate::{
+    ledger::common::value::Coin,
+    plutus::{Costmdls, ExUnitPrices, ExUnits},
+};
+
+/// The protocol parameters introduced by Alonzo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisData {
+    /// replaced by coins per UTxO byte (coins_per_utxo_word / 8) in Babbage
+    pub coins_per_utxo_word: Coin,
+    pub execution_prices: ExUnitPrices,
+    pub max_tx_ex_units: ExUnits,
+    pub max_block_ex_units: ExUnits,
+    pub max_value_size: u32,
+    pub collateral_percentage: u32,
+    pub max_collateral_inputs: u32,
+    /// PlutusV1 and, for genesis files of later node versions, PlutusV2
+    pub cost_models: Costmdls,
+}
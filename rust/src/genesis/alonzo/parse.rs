@@ -0,0 +1,47 @@
+use std::io::Read;
+
+use crate::genesis::error::GenesisError;
+use crate::genesis::json;
+use crate::ledger::common::value::to_bignum;
+use crate::plutus::{CostModel, Costmdls, ExUnitPrices, ExUnits, Language};
+
+use super::{raw, config};
+
+pub fn parse<R: Read>(json: R) -> Result<config::GenesisData, GenesisError> {
+    let data: raw::GenesisData = serde_json::from_reader(json)?;
+
+    let mut cost_models = Costmdls::new();
+    for (language, cost_model) in &data.costModels {
+        let language = match language.as_str() {
+            "PlutusV1" => Language::new_plutus_v1(),
+            "PlutusV2" => Language::new_plutus_v2(),
+            other => return Err(GenesisError::invalid("costModels", format!("unknown language {}", other))),
+        };
+        cost_models.insert(&parse_cost_model(&language, cost_model));
+    }
+
+    let ex_units = |ex_units: &raw::ExUnits| ExUnits::new(&to_bignum(ex_units.exUnitsMem), &to_bignum(ex_units.exUnitsSteps));
+
+    Ok(config::GenesisData {
+        coins_per_utxo_word: to_bignum(data.lovelacePerUTxOWord),
+        execution_prices: ExUnitPrices::new(
+            &json::unit_interval("executionPrices.prMem", &data.executionPrices.prMem)?,
+            &json::unit_interval("executionPrices.prSteps", &data.executionPrices.prSteps)?,
+        ),
+        max_tx_ex_units: ex_units(&data.maxTxExUnits),
+        max_block_ex_units: ex_units(&data.maxBlockExUnits),
+        max_value_size: data.maxValueSize,
+        collateral_percentage: data.collateralPercentage,
+        max_collateral_inputs: data.maxCollateralInputs,
+        cost_models,
+    })
+}
+
+/// Named costs (as in the original Alonzo genesis) are ordered by name, which is the on-chain order for PlutusV1
+pub(crate) fn parse_cost_model(language: &Language, cost_model: &raw::CostModel) -> CostModel {
+    let op_costs = match cost_model {
+        raw::CostModel::Array(costs) => costs.iter().map(|cost| json::int(*cost)).collect(),
+        raw::CostModel::Named(costs) => costs.values().map(|cost| json::int(*cost)).collect(),
+    };
+    CostModel::new(language, &op_costs)
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+// Field names changed between cardano-node versions, the older ones are accepted as aliases
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GenesisData {
+    #[serde(alias = "coinsPerUTxOWord")]
+    pub lovelacePerUTxOWord: u64,
+    pub executionPrices: ExecutionPrices,
+    pub maxTxExUnits: ExUnits,
+    pub maxBlockExUnits: ExUnits,
+    pub maxValueSize: u32,
+    pub collateralPercentage: u32,
+    pub maxCollateralInputs: u32,
+    pub costModels: HashMap<String, CostModel>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExecutionPrices {
+    #[serde(alias = "priceSteps")]
+    pub prSteps: serde_json::Value,
+    #[serde(alias = "priceMemory")]
+    pub prMem: serde_json::Value,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExUnits {
+    #[serde(alias = "memory")]
+    pub exUnitsMem: u64,
+    #[serde(alias = "steps")]
+    pub exUnitsSteps: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum CostModel {
+    Array(Vec<i64>),
+    /// parameter name -> cost, in the order of the names
+    Named(BTreeMap<String, i64>),
+}
@@ -10,74 +10,79 @@ use crate::chain_crypto::byron_proxy_key::ProxySecretKey;
 use crate::chain_crypto::{Ed25519, self, Ed25519Bip32, Signature, Blake2b256};
 use crate::crypto::{BlockHeaderHash, blake2b256, TransactionHash, self, Bip32PublicKey};
 use crate::ledger::alonzo::fees::LinearFee;
-use crate::ledger::common::value::{Coin, BigNum};
+use crate::genesis::error::GenesisError;
+use crate::genesis::json::field_error;
+use crate::ledger::common::utxo::{TransactionUnspentOutput, TransactionUnspentOutputs};
+use crate::ledger::common::value::{to_bignum, Coin, Value};
+use crate::{TransactionInput, TransactionOutput};
 
 use super::{raw, config};
 
-pub fn parse<R: Read>(json: R) -> config::GenesisData {
-    // FIXME: use Result
-
-    let data_value: serde_json::Value = serde_json::from_reader(json).unwrap();
-    let genesis_prev = BlockHeaderHash::from_bytes(
-        blake2b256(data_value.to_string().as_bytes()).to_vec()
-    ).unwrap();
-    let data: raw::GenesisData = serde_json::from_value(data_value.clone()).unwrap();
+pub fn parse<R: Read>(json: R) -> Result<config::GenesisData, GenesisError> {
+    let data_value: serde_json::Value = serde_json::from_reader(json)?;
+    let genesis_prev = canonical_hash(&data_value);
+    let data: raw::GenesisData = serde_json::from_value(data_value)?;
 
     let protocol_magic = ProtocolMagic::from(data.protocolConsts.protocolMagic);
 
-    let parse_fee_constant = |s: &str| -> BigNum {
-        let n = s.parse::<u64>().unwrap();
-        BigNum::from(n)
+    let parse_coin = |field: &str, s: &str| -> Result<Coin, GenesisError> {
+        s.parse::<u64>().map(Coin::from).map_err(|e| GenesisError::invalid(field, format!("{}: {}", s, e)))
     };
 
     let mut avvm_distr = BTreeMap::new();
     for (avvm, balance) in &data.avvmDistr {
+        let avvm_bytes = base64::decode_config(avvm, base64::URL_SAFE).map_err(|e| GenesisError::invalid("avvmDistr", e))?;
         avvm_distr.insert(
-            chain_crypto::PublicKey::<Ed25519>::from_binary(&base64::decode_config(avvm, base64::URL_SAFE).unwrap())
-                .unwrap(),
-            Coin::from(balance.parse::<u64>().unwrap()),
+            chain_crypto::PublicKey::<Ed25519>::from_binary(&avvm_bytes).map_err(|e| GenesisError::invalid("avvmDistr", e))?,
+            parse_coin("avvmDistr", balance)?,
         );
     }
 
     let slot_duration = {
-        let v = data.blockVersionData.slotDuration.parse::<u64>().unwrap();
+        let v = data.blockVersionData.slotDuration.parse::<u64>().map_err(|e| GenesisError::invalid("blockVersionData.slotDuration", e))?;
         Duration::from_millis(v)
     };
 
-    let start_time = {
-        let unix_displacement = Duration::from_secs(data.startTime);
-        SystemTime::UNIX_EPOCH + unix_displacement
-    };
+    let start_time = SystemTime::UNIX_EPOCH
+        .checked_add(Duration::from_secs(data.startTime))
+        .ok_or_else(|| GenesisError::invalid("startTime", format!("{} is out of range", data.startTime)))?;
 
     let mut non_avvm_balances = BTreeMap::new();
     for (address, balance) in &data.nonAvvmBalances {
         non_avvm_balances.insert(
-            ByronAddress::from_str(address).unwrap(),
-            Coin::from(balance.parse::<u64>().unwrap()),
+            ByronAddress::from_str(address).map_err(field_error("nonAvvmBalances"))?,
+            parse_coin("nonAvvmBalances", balance)?,
         );
     }
 
     let mut boot_stakeholders = BTreeMap::new();
 
     for (stakeholder_id, weight) in &data.bootStakeholders {
-        let heavy = data.heavyDelegation.get(stakeholder_id).unwrap();
+        let heavy = data.heavyDelegation.get(stakeholder_id)
+            .ok_or_else(|| GenesisError::invalid("heavyDelegation", format!("no delegation for boot stakeholder {}", stakeholder_id)))?;
 
-        let stakeholder_id = StakeholderId::from_hex(stakeholder_id).unwrap();
+        let stakeholder_id = StakeholderId::from_hex(stakeholder_id).map_err(field_error("bootStakeholders"))?;
 
+        let public_key = |field: &str, key: &str| -> Result<chain_crypto::PublicKey<Ed25519Bip32>, GenesisError> {
+            let bytes = base64::decode(key).map_err(|e| GenesisError::invalid(field, e))?;
+            chain_crypto::PublicKey::<Ed25519Bip32>::from_binary(&bytes).map_err(|e| GenesisError::invalid(field, e))
+        };
         let psk = ProxySecretKey {
             omega: 0,
-            issuer_pk: chain_crypto::PublicKey::<Ed25519Bip32>::from_binary(&base64::decode(&heavy.issuerPk).unwrap())
-                .unwrap(),
-            delegate_pk: chain_crypto::PublicKey::<Ed25519Bip32>::from_binary(&base64::decode(&heavy.delegatePk).unwrap())
-                .unwrap(),
-            cert: Signature::<(), Ed25519Bip32>::from_str(&heavy.cert).unwrap(),
+            issuer_pk: public_key("heavyDelegation.issuerPk", &heavy.issuerPk)?,
+            delegate_pk: public_key("heavyDelegation.delegatePk", &heavy.delegatePk)?,
+            cert: Signature::<(), Ed25519Bip32>::from_str(&heavy.cert).map_err(|e| GenesisError::invalid("heavyDelegation.cert", e))?,
         };
 
         // Check that the stakeholder ID corresponds to the issuer public key.
-        assert_eq!(stakeholder_id, StakeholderId::new(&Bip32PublicKey(psk.issuer_pk.clone())));
+        if stakeholder_id != StakeholderId::new(&Bip32PublicKey(psk.issuer_pk.clone())) {
+            return Err(GenesisError::invalid("heavyDelegation.issuerPk", format!("not the key of boot stakeholder {}", stakeholder_id.to_hex())));
+        }
 
         // Check that the certificate is correct.
-        assert!(psk.verify(protocol_magic));
+        if !psk.verify(protocol_magic) {
+            return Err(GenesisError::invalid("heavyDelegation.cert", format!("invalid certificate of boot stakeholder {}", stakeholder_id.to_hex())));
+        }
 
         boot_stakeholders.insert(
             stakeholder_id,
@@ -90,25 +95,44 @@ pub fn parse<R: Read>(json: R) -> config::GenesisData {
         );
     }
 
-    config::GenesisData {
+    Ok(config::GenesisData {
         genesis_prev,
         epoch_stability_depth: data.protocolConsts.k,
         protocol_magic,
         fee_policy: LinearFee::new(
-            &parse_fee_constant(&data.blockVersionData.txFeePolicy.multiplier),
-            &parse_fee_constant(&data.blockVersionData.txFeePolicy.summand),
+            &parse_coin("blockVersionData.txFeePolicy.multiplier", &data.blockVersionData.txFeePolicy.multiplier)?,
+            &parse_coin("blockVersionData.txFeePolicy.summand", &data.blockVersionData.txFeePolicy.summand)?,
         ),
         avvm_distr,
         non_avvm_balances,
         start_time,
         slot_duration,
         boot_stakeholders,
+    })
+}
+
+/// Parses the genesis file after checking its canonical hash is `expected`
+/// e.g. the hash in the node configuration or the previous hash of the first epoch boundary block
+pub fn parse_verified<R: Read>(json: R, expected: &BlockHeaderHash) -> Result<config::GenesisData, GenesisError> {
+    let genesis = parse(json)?;
+    if genesis.genesis_prev != *expected {
+        return Err(GenesisError::HashMismatch {
+            expected: expected.to_hex(),
+            actual: genesis.genesis_prev.to_hex(),
+        });
     }
+    Ok(genesis)
+}
+
+/// Keys sorted and no whitespace, which is what the Byron genesis hash is computed on
+pub fn canonicalize_json<R: Read>(json: R) -> Result<String, GenesisError> {
+    let data: serde_json::Value = serde_json::from_reader(json)?;
+    Ok(data.to_string())
 }
 
-pub fn canonicalize_json<R: Read>(json: R) -> String {
-    let data: serde_json::Value = serde_json::from_reader(json).unwrap();
-    data.to_string()
+/// Blake2b-256 of the canonical JSON
+pub fn canonical_hash(json: &serde_json::Value) -> BlockHeaderHash {
+    BlockHeaderHash::from(blake2b256(json.to_string().as_bytes()))
 }
 
 pub fn redeem_pubkey_to_txid(
@@ -121,6 +145,30 @@ pub fn redeem_pubkey_to_txid(
     (TransactionHash(*txid.as_hash_bytes()), byron_address)
 }
 
+/// Like redeem_pubkey_to_txid, non-AVVM balances are spent from the hash of their address
+pub fn address_to_txid(address: &ByronAddress) -> TransactionHash {
+    TransactionHash(*Blake2b256::new(&cbor!(address).unwrap()).as_hash_bytes())
+}
+
+/// The AVVM and non-AVVM balances as outputs at index 0 of their pseudo-transactions
+pub fn genesis_utxos(genesis: &config::GenesisData) -> TransactionUnspentOutputs {
+    let mut utxos = TransactionUnspentOutputs::new();
+    let mut add = |txid: &TransactionHash, address: &ByronAddress, coin: &Coin| {
+        utxos.add(&TransactionUnspentOutput::new(
+            &TransactionInput::new(txid, &to_bignum(0)),
+            &TransactionOutput::new(&address.to_address(), &Value::new(coin)),
+        ));
+    };
+    for (pubkey, coin) in &genesis.avvm_distr {
+        let (txid, address) = redeem_pubkey_to_txid(pubkey, Some(genesis.protocol_magic));
+        add(&txid, &address, coin);
+    }
+    for (address, coin) in &genesis.non_avvm_balances {
+        add(&address_to_txid(address), address, coin);
+    }
+    utxos
+}
+
 
 #[cfg(test)]
 mod test {
@@ -151,7 +199,7 @@ mod test {
             super::super::data::get_test_genesis_data(&genesis_hash)
                 .unwrap()
                 .as_bytes(),
-        );
+        ).unwrap();
 
         assert_eq!(genesis_data.epoch_stability_depth, 2160);
         assert_eq!(
@@ -190,7 +238,7 @@ mod test {
             super::super::data::get_test_genesis_data(&genesis_hash)
                 .unwrap()
                 .as_bytes(),
-        );
+        ).unwrap();
 
         assert_eq!(
             genesis_data
@@ -203,4 +251,43 @@ mod test {
             &Coin::from(5428571428571429)
         );
     }
+
+    #[test]
+    pub fn verify_genesis_hashes() {
+        for hash in [
+            "5f20df933584822601f9e3f8c024eb5eb252fe8cefb24d1317dc3d432e940ebb",
+            "96fceff972c2c06bd3bb5243c39215333be6d56aaf4823073dca31afe5038471",
+            "b7f76950bc4866423538ab7764fc1c7020b24a5f717a5bee3109ff2796567214",
+            "c6a004d3d178f600cd8caa10abbebe1549bef878f0665aea2903472d5abf7323",
+        ] {
+            let genesis_hash = BlockHeaderHash::from_hex(hash).unwrap();
+            let json = super::super::data::get_test_genesis_data(&genesis_hash).unwrap();
+            assert_eq!(parse_verified(json.as_bytes(), &genesis_hash).unwrap().genesis_prev, genesis_hash);
+        }
+
+        let mainnet = BlockHeaderHash::from_hex("5f20df933584822601f9e3f8c024eb5eb252fe8cefb24d1317dc3d432e940ebb").unwrap();
+        let testnet = BlockHeaderHash::from_hex("96fceff972c2c06bd3bb5243c39215333be6d56aaf4823073dca31afe5038471").unwrap();
+        let json = super::super::data::get_test_genesis_data(&mainnet).unwrap();
+        assert!(matches!(parse_verified(json.as_bytes(), &testnet), Err(GenesisError::HashMismatch { .. })));
+        assert!(matches!(parse(&json.as_bytes()[1..]), Err(GenesisError::Json(_))));
+        assert!(matches!(canonicalize_json(&b"{"[..]), Err(GenesisError::Json(_))));
+        let bad_balance = json.replacen("\"avvmDistr\":{", "\"avvmDistr\":{\"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=\": \"x\",", 1);
+        assert!(matches!(parse(bad_balance.as_bytes()), Err(GenesisError::InvalidField { .. })));
+        let bad_start_time = json.replacen("\"startTime\":1506203091", "\"startTime\":18446744073709551615", 1);
+        assert!(matches!(parse(bad_start_time.as_bytes()), Err(GenesisError::InvalidField { field, .. }) if field == "startTime"));
+    }
+
+    #[test]
+    pub fn genesis_utxos_of_balances() {
+        let genesis_hash = BlockHeaderHash::from_hex("5f20df933584822601f9e3f8c024eb5eb252fe8cefb24d1317dc3d432e940ebb").unwrap();
+        let genesis_data = parse(super::super::data::get_test_genesis_data(&genesis_hash).unwrap().as_bytes()).unwrap();
+        let utxos = genesis_utxos(&genesis_data);
+        assert_eq!(utxos.len(), genesis_data.avvm_distr.len() + genesis_data.non_avvm_balances.len());
+
+        let pubkey = chain_crypto::PublicKey::<Ed25519>::from_binary(&base64::decode_config("-0BJDi-gauylk4LptQTgjMeo7kY9lTCbZv12vwOSTZk=", base64::URL_SAFE).unwrap()).unwrap();
+        let (txid, address) = redeem_pubkey_to_txid(&pubkey, None);
+        let utxo = utxos.iter().find(|utxo| utxo.input().transaction_id() == txid).unwrap();
+        assert_eq!(utxo.output().address(), address.to_address());
+        assert_eq!(utxo.output().amount().coin(), Coin::from(9999300000000));
+    }
 }
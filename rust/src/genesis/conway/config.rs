@@ -0,0 +1,71 @@
+//! Typed contents of the Conway genesis file (conway-genesis.json)
+
+use std::collections::BTreeMap;
+
+use crate::{
+    StakeCredential, UnitInterval,
+    crypto::ScriptHash,
+    ledger::common::value::Coin,
+    plutus::CostModel,
+};
+
+/// The governance parameters introduced by Conway and the initial constitution and committee
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisData {
+    pub pool_voting_thresholds: PoolVotingThresholds,
+    pub drep_voting_thresholds: DRepVotingThresholds,
+    pub committee_min_size: u64,
+    /// in epochs
+    pub committee_max_term_length: u64,
+    /// in epochs
+    pub gov_action_lifetime: u64,
+    pub gov_action_deposit: Coin,
+    pub drep_deposit: Coin,
+    /// in epochs
+    pub drep_activity: u64,
+    /// lovelace per byte of the reference scripts spent or referenced by a transaction
+    pub min_fee_ref_script_cost_per_byte: UnitInterval,
+    pub plutus_v3_cost_model: CostModel,
+    pub constitution: Constitution,
+    pub committee: Committee,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolVotingThresholds {
+    pub motion_no_confidence: UnitInterval,
+    pub committee_normal: UnitInterval,
+    pub committee_no_confidence: UnitInterval,
+    pub hard_fork_initiation: UnitInterval,
+    pub pp_security_group: UnitInterval,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DRepVotingThresholds {
+    pub motion_no_confidence: UnitInterval,
+    pub committee_normal: UnitInterval,
+    pub committee_no_confidence: UnitInterval,
+    pub update_to_constitution: UnitInterval,
+    pub hard_fork_initiation: UnitInterval,
+    pub pp_network_group: UnitInterval,
+    pub pp_economic_group: UnitInterval,
+    pub pp_technical_group: UnitInterval,
+    pub pp_gov_group: UnitInterval,
+    pub treasury_withdrawal: UnitInterval,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constitution {
+    /// not limited to 64 bytes like the URL of pool metadata
+    pub anchor_url: String,
+    /// Blake2b-256 of the document at anchor_url
+    pub anchor_data_hash: [u8; 32],
+    /// guardrails script checking parameter changes and treasury withdrawals
+    pub script: Option<ScriptHash>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Committee {
+    /// cold credential -> epoch the membership expires
+    pub members: BTreeMap<StakeCredential, u64>,
+    pub threshold: UnitInterval,
+}
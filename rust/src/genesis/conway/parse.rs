@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::io::Read;
+
+use crate::StakeCredential;
+use crate::crypto::{Ed25519KeyHash, ScriptHash};
+use crate::genesis::error::GenesisError;
+use crate::genesis::json::{self, field_error};
+use crate::ledger::common::value::to_bignum;
+use crate::plutus::{CostModel, Language};
+
+use super::{raw, config};
+
+pub fn parse<R: Read>(json: R) -> Result<config::GenesisData, GenesisError> {
+    let data: raw::GenesisData = serde_json::from_reader(json)?;
+
+    let pool = &data.poolVotingThresholds;
+    let pool_voting_thresholds = config::PoolVotingThresholds {
+        motion_no_confidence: json::unit_interval("poolVotingThresholds.motionNoConfidence", &pool.motionNoConfidence)?,
+        committee_normal: json::unit_interval("poolVotingThresholds.committeeNormal", &pool.committeeNormal)?,
+        committee_no_confidence: json::unit_interval("poolVotingThresholds.committeeNoConfidence", &pool.committeeNoConfidence)?,
+        hard_fork_initiation: json::unit_interval("poolVotingThresholds.hardForkInitiation", &pool.hardForkInitiation)?,
+        pp_security_group: json::unit_interval("poolVotingThresholds.ppSecurityGroup", &pool.ppSecurityGroup)?,
+    };
+
+    let drep = &data.dRepVotingThresholds;
+    let drep_voting_thresholds = config::DRepVotingThresholds {
+        motion_no_confidence: json::unit_interval("dRepVotingThresholds.motionNoConfidence", &drep.motionNoConfidence)?,
+        committee_normal: json::unit_interval("dRepVotingThresholds.committeeNormal", &drep.committeeNormal)?,
+        committee_no_confidence: json::unit_interval("dRepVotingThresholds.committeeNoConfidence", &drep.committeeNoConfidence)?,
+        update_to_constitution: json::unit_interval("dRepVotingThresholds.updateToConstitution", &drep.updateToConstitution)?,
+        hard_fork_initiation: json::unit_interval("dRepVotingThresholds.hardForkInitiation", &drep.hardForkInitiation)?,
+        pp_network_group: json::unit_interval("dRepVotingThresholds.ppNetworkGroup", &drep.ppNetworkGroup)?,
+        pp_economic_group: json::unit_interval("dRepVotingThresholds.ppEconomicGroup", &drep.ppEconomicGroup)?,
+        pp_technical_group: json::unit_interval("dRepVotingThresholds.ppTechnicalGroup", &drep.ppTechnicalGroup)?,
+        pp_gov_group: json::unit_interval("dRepVotingThresholds.ppGovGroup", &drep.ppGovGroup)?,
+        treasury_withdrawal: json::unit_interval("dRepVotingThresholds.treasuryWithdrawal", &drep.treasuryWithdrawal)?,
+    };
+
+    let anchor_data_hash = json::hex_bytes("constitution.anchor.dataHash", &data.constitution.anchor.dataHash)?
+        .try_into()
+        .map_err(|_| GenesisError::invalid("constitution.anchor.dataHash", "expected 32 bytes"))?;
+    let constitution = config::Constitution {
+        anchor_url: data.constitution.anchor.url.clone(),
+        anchor_data_hash,
+        script: match &data.constitution.script {
+            Some(script) => Some(ScriptHash::from_hex(script).map_err(field_error("constitution.script"))?),
+            None => None,
+        },
+    };
+
+    let mut members = BTreeMap::new();
+    for (member, expiration) in &data.committee.members {
+        let credential = match member.split_once('-') {
+            Some(("keyHash", hash)) => StakeCredential::from_keyhash(&Ed25519KeyHash::from_hex(hash).map_err(field_error("committee.members"))?),
+            Some(("scriptHash", hash)) => StakeCredential::from_scripthash(&ScriptHash::from_hex(hash).map_err(field_error("committee.members"))?),
+            _ => return Err(GenesisError::invalid("committee.members", format!("{} is not keyHash-<hex> or scriptHash-<hex>", member))),
+        };
+        members.insert(credential, *expiration);
+    }
+
+    Ok(config::GenesisData {
+        pool_voting_thresholds,
+        drep_voting_thresholds,
+        committee_min_size: data.committeeMinSize,
+        committee_max_term_length: data.committeeMaxTermLength,
+        gov_action_lifetime: data.govActionLifetime,
+        gov_action_deposit: to_bignum(data.govActionDeposit),
+        drep_deposit: to_bignum(data.dRepDeposit),
+        drep_activity: data.dRepActivity,
+        min_fee_ref_script_cost_per_byte: json::unit_interval("minFeeRefScriptCostPerByte", &data.minFeeRefScriptCostPerByte)?,
+        plutus_v3_cost_model: CostModel::new(
+            &Language::new_plutus_v3(),
+            &data.plutusV3CostModel.iter().map(|cost| json::int(*cost)).collect(),
+        ),
+        constitution,
+        committee: config::Committee {
+            members,
+            threshold: json::unit_interval("committee.threshold", &data.committee.threshold)?,
+        },
+    })
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GenesisData {
+    pub poolVotingThresholds: PoolVotingThresholds,
+    pub dRepVotingThresholds: DRepVotingThresholds,
+    pub committeeMinSize: u64,
+    pub committeeMaxTermLength: u64,
+    pub govActionLifetime: u64,
+    pub govActionDeposit: u64,
+    pub dRepDeposit: u64,
+    pub dRepActivity: u64,
+    pub minFeeRefScriptCostPerByte: serde_json::Value,
+    pub plutusV3CostModel: Vec<i64>,
+    pub constitution: Constitution,
+    pub committee: Committee,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PoolVotingThresholds {
+    pub motionNoConfidence: serde_json::Value,
+    pub committeeNormal: serde_json::Value,
+    pub committeeNoConfidence: serde_json::Value,
+    pub hardForkInitiation: serde_json::Value,
+    pub ppSecurityGroup: serde_json::Value,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DRepVotingThresholds {
+    pub motionNoConfidence: serde_json::Value,
+    pub committeeNormal: serde_json::Value,
+    pub committeeNoConfidence: serde_json::Value,
+    pub updateToConstitution: serde_json::Value,
+    pub hardForkInitiation: serde_json::Value,
+    pub ppNetworkGroup: serde_json::Value,
+    pub ppEconomicGroup: serde_json::Value,
+    pub ppTechnicalGroup: serde_json::Value,
+    pub ppGovGroup: serde_json::Value,
+    pub treasuryWithdrawal: serde_json::Value,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Constitution {
+    pub anchor: Anchor,
+    pub script: Option<String>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Anchor {
+    pub url: String,
+    pub dataHash: String,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Committee {
+    /// keyHash-<hex> or scriptHash-<hex> -> expiration epoch
+    pub members: HashMap<String, u64>,
+    pub threshold: serde_json::Value,
+}
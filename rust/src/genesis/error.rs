@@ -0,0 +1,51 @@
+use crate::error::Error;
+
+/// Errors returned when parsing genesis files
+#[derive(Debug)]
+pub enum GenesisError {
+    /// The file isn't JSON or doesn't have the expected structure
+    Json(serde_json::Error),
+    /// A field has the expected JSON type but not a valid value e.g. a malformed hash or address
+    InvalidField {
+        field: String,
+        reason: String,
+    },
+    /// The hash of the file isn't the one expected by the caller (hex encoded)
+    HashMismatch {
+        expected: String,
+        actual: String,
+    },
+}
+
+impl GenesisError {
+    pub(crate) fn invalid(field: impl Into<String>, reason: impl std::fmt::Display) -> GenesisError {
+        GenesisError::InvalidField {
+            field: field.into(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for GenesisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenesisError::Json(e) => write!(f, "invalid genesis JSON: {}", e),
+            GenesisError::InvalidField { field, reason } => write!(f, "invalid genesis field {}: {}", field, reason),
+            GenesisError::HashMismatch { expected, actual } => write!(f, "genesis hash mismatch: expected {}, found {}", expected, actual),
+        }
+    }
+}
+
+impl std::error::Error for GenesisError {}
+
+impl From<serde_json::Error> for GenesisError {
+    fn from(e: serde_json::Error) -> GenesisError {
+        GenesisError::Json(e)
+    }
+}
+
+impl From<GenesisError> for Error {
+    fn from(e: GenesisError) -> Error {
+        Error::from_str(&e.to_string())
+    }
+}
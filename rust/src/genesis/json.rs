@@ -0,0 +1,139 @@
+//! Helpers for the value formats shared by the Shelley, Alonzo and Conway genesis files
+
+use std::time::{Duration, SystemTime};
+
+use num_integer::Integer;
+
+use crate::UnitInterval;
+use crate::error::Error;
+use crate::ledger::common::value::{to_bignum, Int};
+
+use super::error::GenesisError;
+
+/// Rationals are written either as JSON decimals (0.05, 5e-2) or as {"numerator": 1, "denominator": 20}
+pub(crate) fn rational(field: &str, value: &serde_json::Value) -> Result<(u64, u64), GenesisError> {
+    let (numerator, denominator) = match value {
+        serde_json::Value::Number(number) => decimal(field, &number.to_string())?,
+        serde_json::Value::Object(object) => {
+            let part = |name: &str| object.get(name).and_then(|n| n.as_u64()).ok_or_else(|| GenesisError::invalid(field, format!("missing {}", name)));
+            (part("numerator")?, part("denominator")?)
+        },
+        _ => return Err(GenesisError::invalid(field, "expected a number or a numerator/denominator object")),
+    };
+    if denominator == 0 {
+        return Err(GenesisError::invalid(field, "denominator is 0"));
+    }
+    let gcd = numerator.gcd(&denominator);
+    Ok((numerator / gcd, denominator / gcd))
+}
+
+fn decimal(field: &str, text: &str) -> Result<(u64, u64), GenesisError> {
+    let overflow = || GenesisError::invalid(field, format!("{} doesn't fit in a rational of u64s", text));
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(pos) => (&text[..pos], text[pos + 1..].parse::<i32>().map_err(|e| GenesisError::invalid(field, e))?),
+        None => (text, 0),
+    };
+    let (integer, fraction) = match mantissa.find('.') {
+        Some(pos) => (&mantissa[..pos], &mantissa[pos + 1..]),
+        None => (mantissa, ""),
+    };
+    let digits = format!("{}{}", integer, fraction);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(GenesisError::invalid(field, format!("{} is not a positive decimal", text)));
+    }
+    let mut numerator = digits.parse::<u64>().map_err(|_| overflow())?;
+    let mut denominator = 1u64;
+    let scale = exponent - fraction.len() as i32;
+    for _ in 0..scale.abs() {
+        if scale > 0 {
+            numerator = numerator.checked_mul(10).ok_or_else(overflow)?;
+        } else {
+            denominator = denominator.checked_mul(10).ok_or_else(overflow)?;
+        }
+    }
+    Ok((numerator, denominator))
+}
+
+pub(crate) fn unit_interval(field: &str, value: &serde_json::Value) -> Result<UnitInterval, GenesisError> {
+    let (numerator, denominator) = rational(field, value)?;
+    Ok(UnitInterval::new(&to_bignum(numerator), &to_bignum(denominator)))
+}
+
+/// Seconds as a decimal e.g. 0.2 for slotLength
+pub(crate) fn duration(field: &str, value: &serde_json::Value) -> Result<Duration, GenesisError> {
+    let (numerator, denominator) = rational(field, value)?;
+    let nanos = numerator as u128 * 1_000_000_000 / denominator as u128;
+    Ok(Duration::new((nanos / 1_000_000_000) as u64, (nanos % 1_000_000_000) as u32))
+}
+
+/// UTC timestamps as written by cardano-node e.g. 2017-09-23T21:44:51Z
+pub(crate) fn system_time(field: &str, text: &str) -> Result<SystemTime, GenesisError> {
+    let invalid = || GenesisError::invalid(field, format!("{} is not a UTC timestamp (YYYY-MM-DDTHH:MM:SS[.fff]Z)", text));
+    let text = text.strip_suffix('Z').ok_or_else(invalid)?;
+    let (date, time) = text.split_at(text.find('T').ok_or_else(invalid)?);
+    let date = date.split('-').map(|n| n.parse::<u32>()).collect::<Result<Vec<_>, _>>().map_err(|_| invalid())?;
+    let (time, fraction) = match time[1..].find('.') {
+        Some(pos) => (&time[1..pos + 1], &time[pos + 2..]),
+        None => (&time[1..], ""),
+    };
+    let time = time.split(':').map(|n| n.parse::<u32>()).collect::<Result<Vec<_>, _>>().map_err(|_| invalid())?;
+    if date.len() != 3 || time.len() != 3 || !(1..=12).contains(&date[1]) || !(1..=31).contains(&date[2]) || time[0] > 23 || time[1] > 59 || time[2] > 60 {
+        return Err(invalid());
+    }
+    let nanos = match fraction {
+        "" => 0,
+        fraction if fraction.len() <= 9 => fraction.parse::<u32>().map_err(|_| invalid())? * 10u32.pow(9 - fraction.len() as u32),
+        _ => return Err(invalid()),
+    };
+    // days since 1970-01-01 of the proleptic Gregorian calendar
+    let (year, month, day) = (date[0] as i64, date[1] as i64, date[2] as i64);
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+    let secs = days * 86400 + time[0] as i64 * 3600 + time[1] as i64 * 60 + time[2] as i64;
+    if secs < 0 {
+        return Err(invalid());
+    }
+    SystemTime::UNIX_EPOCH.checked_add(Duration::new(secs as u64, nanos)).ok_or_else(invalid)
+}
+
+pub(crate) fn int(value: i64) -> Int {
+    match value {
+        value if value < 0 => Int::new_negative(&to_bignum(value.unsigned_abs())),
+        value => Int::new(&to_bignum(value as u64)),
+    }
+}
+
+pub(crate) fn hex_bytes(field: &str, text: &str) -> Result<Vec<u8>, GenesisError> {
+    hex::decode(text).map_err(|e| GenesisError::invalid(field, e))
+}
+
+/// For the hash types' from_hex/from_bytes, which report errors as the crate Error
+pub(crate) fn field_error(field: &str) -> impl Fn(Error) -> GenesisError + '_ {
+    move |e| GenesisError::invalid(field, e.as_string().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimals_and_timestamps() {
+        let rational = |json: &str| rational("test", &serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(rational("0.05"), (1, 20));
+        assert_eq!(rational("1"), (1, 1));
+        assert_eq!(rational("7.21e-5"), (721, 10_000_000));
+        assert_eq!(rational("{\"numerator\": 577, \"denominator\": 10000}"), (577, 10000));
+        assert!(super::rational("test", &serde_json::from_str("-0.5").unwrap()).is_err());
+        assert_eq!(duration("test", &serde_json::from_str("0.2").unwrap()).unwrap(), Duration::from_millis(200));
+
+        // Byron startTime of mainnet
+        assert_eq!(system_time("test", "2017-09-23T21:44:51Z").unwrap(), SystemTime::UNIX_EPOCH + Duration::from_secs(1506203091));
+        assert_eq!(system_time("test", "2022-10-25T00:00:00.5Z").unwrap(), SystemTime::UNIX_EPOCH + Duration::from_millis(1666656000500));
+        assert!(system_time("test", "2022-13-25T00:00:00Z").is_err());
+        assert!(system_time("test", "2022-10-25 00:00:00").is_err());
+    }
+}
@@ -1,2 +1,14 @@
+use crate::crypto::{blake2b256, GenesisFileHash};
+
+pub mod alonzo;
 pub mod byron;
-pub mod network_info;
\ No newline at end of file
+pub mod conway;
+pub mod error;
+pub(crate) mod json;
+pub mod network_info;
+pub mod shelley;
+
+/// Hash of a Shelley, Alonzo or Conway genesis file. Unlike Byron's, it's computed over the file bytes as they are
+pub fn genesis_file_hash(bytes: &[u8]) -> GenesisFileHash {
+    GenesisFileHash::from(blake2b256(bytes))
+}
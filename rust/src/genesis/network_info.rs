@@ -1,4 +1,12 @@
+use std::time::{Duration, SystemTime};
+
 use crate::{byron::ProtocolMagic, plutus::{Costmdls, Language, CostModel}, ledger::common::value::Int};
+use crate::builders::tx_builder::{TransactionBuilderConfig, TransactionBuilderConfigBuilder};
+use crate::ledger::alonzo::fees::LinearFee;
+use crate::ledger::common::utxo::TransactionUnspentOutputs;
+use crate::ledger::common::value::{from_bignum, to_bignum};
+
+use super::{alonzo, conway, shelley};
 
 #[derive(Debug, Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct NetworkInfo {
@@ -33,6 +41,57 @@ impl NetworkInfo {
     }
 }
 
+/// A network started from its Shelley, Alonzo and (optionally) Conway genesis files, e.g. a local devnet,
+/// with what's needed to build transactions on it from the start
+#[derive(Debug, Clone)]
+pub struct GenesisNetwork {
+    pub network_info: NetworkInfo,
+    pub tx_builder_config: TransactionBuilderConfig,
+    /// the initial funds of the Shelley genesis, see shelley::parse::genesis_utxos
+    pub utxos: TransactionUnspentOutputs,
+    pub system_start: SystemTime,
+    pub slot_length: Duration,
+    /// in slots
+    pub epoch_length: u64,
+}
+
+impl GenesisNetwork {
+    pub fn new(
+        shelley: &shelley::config::GenesisData,
+        alonzo: &alonzo::config::GenesisData,
+        conway: Option<&conway::config::GenesisData>,
+    ) -> Self {
+        let params = &shelley.protocol_params;
+        let mut costmdls = alonzo.cost_models.clone();
+        if let Some(conway) = conway {
+            costmdls.insert(&conway.plutus_v3_cost_model);
+        }
+        let tx_builder_config = TransactionBuilderConfigBuilder::new()
+            .fee_algo(&LinearFee::new(&params.min_fee_a, &params.min_fee_b))
+            .pool_deposit(&params.pool_deposit)
+            .key_deposit(&params.key_deposit)
+            .max_value_size(alonzo.max_value_size)
+            .max_tx_size(params.max_tx_size)
+            // converted by the ledger when entering Babbage
+            .coins_per_utxo_byte(&to_bignum(from_bignum(&alonzo.coins_per_utxo_word) / 8))
+            .ex_unit_prices(&alonzo.execution_prices)
+            .costmdls(&costmdls)
+            .collateral_percentage(alonzo.collateral_percentage)
+            .max_collateral_inputs(alonzo.max_collateral_inputs)
+            .build()
+            // every field is set above
+            .unwrap();
+        Self {
+            network_info: NetworkInfo::new(shelley::parse::network_bits(&shelley.network_id), shelley.network_magic),
+            tx_builder_config,
+            utxos: shelley::parse::genesis_utxos(shelley),
+            system_start: shelley.system_start,
+            slot_length: shelley.slot_length,
+            epoch_length: shelley.epoch_length,
+        }
+    }
+}
+
 // TODO: https://github.com/dcSpark/cardano-multiplatform-lib/issues/92
 pub fn plutus_alonzo_cost_models() -> Costmdls {
     let ops = vec![197209, 0, 1, 1, 396231, 621, 0, 1, 150000, 1000, 0, 1, 150000, 32, 2477736, 29175, 4, 29773, 100, 29773, 100, 29773, 100, 29773, 100, 29773, 100, 29773, 100, 100, 100, 29773, 100, 150000, 32, 150000, 32, 150000, 32, 150000, 1000, 0, 1, 150000, 32, 150000, 1000, 0, 8, 148000, 425507, 118, 0, 1, 1, 150000, 1000, 0, 8, 150000, 112536, 247, 1, 150000, 10000, 1, 136542, 1326, 1, 1000, 150000, 1000, 1, 150000, 32, 150000, 32, 150000, 32, 1, 1, 150000, 1, 150000, 4, 103599, 248, 1, 103599, 248, 1, 145276, 1366, 1, 179690, 497, 1, 150000, 32, 150000, 32, 150000, 32, 150000, 32, 150000, 32, 150000, 32, 148000, 425507, 118, 0, 1, 1, 61516, 11218, 0, 1, 150000, 32, 148000, 425507, 118, 0, 1, 1, 148000, 425507, 118, 0, 1, 1, 2477736, 29175, 4, 0, 82363, 4, 150000, 5000, 0, 1, 150000, 32, 197209, 0, 1, 1, 150000, 32, 150000, 32, 150000, 32, 150000, 32, 150000, 32, 150000, 32, 150000, 32, 3345831, 1, 1];
@@ -43,3 +102,199 @@ pub fn plutus_alonzo_cost_models() -> Costmdls {
     );
     res
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Address, NetworkId};
+    use crate::genesis::genesis_file_hash;
+    use crate::crypto::blake2b256;
+
+    const SHELLEY: &str = r#"{
+        "activeSlotsCoeff": 0.05,
+        "epochLength": 86400,
+        "genDelegs": {
+            "12b0f443d02861948a0fce9541916b014e8402984c7b83ad70a834ce": {
+                "delegate": "7c54a168c731f2f44ced620f3cca7c2bd90731cab223d5167aa994e6",
+                "vrf": "62d546a35e1be66a2b06e29558ef33f4222f1c466adbb59b52d800964d4e60ec"
+            }
+        },
+        "initialFunds": {
+            "609783be7d3c54f11377966dfabc9284cd6c32fca1cd42ef0a4f1cc45b": 30000000000000000
+        },
+        "maxKESEvolutions": 62,
+        "maxLovelaceSupply": 45000000000000000,
+        "networkId": "Testnet",
+        "networkMagic": 42,
+        "protocolParams": {
+            "protocolVersion": { "minor": 0, "major": 8 },
+            "decentralisationParam": 0,
+            "eMax": 18,
+            "extraEntropy": { "tag": "NeutralNonce" },
+            "maxTxSize": 16384,
+            "maxBlockBodySize": 65536,
+            "maxBlockHeaderSize": 1100,
+            "minFeeA": 44,
+            "minFeeB": 155381,
+            "minUTxOValue": 1000000,
+            "poolDeposit": 500000000,
+            "minPoolCost": 340000000,
+            "keyDeposit": 2000000,
+            "nOpt": 150,
+            "rho": 0.003,
+            "tau": 0.2,
+            "a0": 0.3
+        },
+        "securityParam": 432,
+        "slotLength": 0.1,
+        "slotsPerKESPeriod": 129600,
+        "staking": {
+            "pools": {
+                "7301761068762f5900bde9eb7c1c15b09840285130f5b0f53606cc57": {
+                    "cost": 340000000,
+                    "margin": 0,
+                    "metadata": null,
+                    "owners": [],
+                    "pledge": 0,
+                    "publicKey": "7301761068762f5900bde9eb7c1c15b09840285130f5b0f53606cc57",
+                    "relays": [],
+                    "rewardAccount": {
+                        "credential": { "keyHash": "11a14edf73b08a0a27cb98b2c57eb37c780df18fcfcf6785ed5df84a" },
+                        "network": "Testnet"
+                    },
+                    "vrf": "c2b62ffa92ad18ffc117ea3abeb161a68885000a466f9c71db5e4731d6630061"
+                }
+            },
+            "stake": {
+                "295b987135610616f3c74e11c94d77b6ced5ccc93a7d719cfb135062": "7301761068762f5900bde9eb7c1c15b09840285130f5b0f53606cc57"
+            }
+        },
+        "systemStart": "2022-10-25T00:00:00Z",
+        "updateQuorum": 1
+    }"#;
+
+    const ALONZO: &str = r#"{
+        "lovelacePerUTxOWord": 34482,
+        "executionPrices": {
+            "prSteps": { "numerator": 721, "denominator": 10000000 },
+            "prMem": { "numerator": 577, "denominator": 10000 }
+        },
+        "maxTxExUnits": { "exUnitsMem": 10000000, "exUnitsSteps": 10000000000 },
+        "maxBlockExUnits": { "exUnitsMem": 50000000, "exUnitsSteps": 40000000000 },
+        "maxValueSize": 5000,
+        "collateralPercentage": 150,
+        "maxCollateralInputs": 3,
+        "costModels": {
+            "PlutusV1": { "b-cpu": 2, "a-cpu": 1, "c-mem": -3 },
+            "PlutusV2": [4, 5, 6]
+        }
+    }"#;
+
+    const CONWAY: &str = r#"{
+        "poolVotingThresholds": {
+            "committeeNormal": 0.51, "committeeNoConfidence": 0.51, "hardForkInitiation": 0.51,
+            "motionNoConfidence": 0.51, "ppSecurityGroup": 0.51
+        },
+        "dRepVotingThresholds": {
+            "motionNoConfidence": 0.67, "committeeNormal": 0.67, "committeeNoConfidence": 0.6,
+            "updateToConstitution": 0.75, "hardForkInitiation": 0.6, "ppNetworkGroup": 0.67,
+            "ppEconomicGroup": 0.67, "ppTechnicalGroup": 0.67, "ppGovGroup": 0.75, "treasuryWithdrawal": 0.67
+        },
+        "committeeMinSize": 7,
+        "committeeMaxTermLength": 146,
+        "govActionLifetime": 6,
+        "govActionDeposit": 100000000000,
+        "dRepDeposit": 500000000,
+        "dRepActivity": 20,
+        "minFeeRefScriptCostPerByte": 15,
+        "plutusV3CostModel": [7, 8, 9],
+        "constitution": {
+            "anchor": {
+                "dataHash": "ca41a91f399259bcefe57f9858e91f6d00e1a38d6d9c63d4052914ea7bd70cb2",
+                "url": "ipfs://bafkreifnwj6zpu3ixa4siz2lndqybyc5wnnt3jkwyutci4e7tulut5yxo4"
+            },
+            "script": "fa24fb305126805cf2164c161d852a0e7330cf988f1fe558cf7d4a64"
+        },
+        "committee": {
+            "members": {
+                "scriptHash-df0e83bde65416dade5b1f97e7f115cc1ff999550ad968850783fe50": 580,
+                "keyHash-b6012034ba0a7e4afbbf2c7a1432f8824aee5299a48e38e41a952686": 580
+            },
+            "threshold": { "numerator": 2, "denominator": 3 }
+        }
+    }"#;
+
+    #[test]
+    fn genesis_network() {
+        let shelley = shelley::parse::parse(SHELLEY.as_bytes()).unwrap();
+        let alonzo = alonzo::parse::parse(ALONZO.as_bytes()).unwrap();
+        let conway = conway::parse::parse(CONWAY.as_bytes()).unwrap();
+
+        assert_eq!(shelley.network_id, NetworkId::testnet());
+        assert_eq!(shelley.slot_length, Duration::from_millis(100));
+        assert_eq!(shelley.protocol_params.rho, crate::UnitInterval::new(&to_bignum(3), &to_bignum(1000)));
+        assert_eq!(shelley.staking.as_ref().unwrap().pools.len(), 1);
+        assert_eq!(shelley.staking.as_ref().unwrap().stake.len(), 1);
+        // named costs are ordered by name
+        let v1 = alonzo.cost_models.get(&Language::new_plutus_v1()).unwrap();
+        assert_eq!((v1.get(0).unwrap(), v1.get(1).unwrap(), v1.get(2).unwrap()), (Int::from(1), Int::from(2), Int::from(-3)));
+        assert_eq!(conway.committee.members.len(), 2);
+        assert_eq!(conway.committee.threshold, crate::UnitInterval::new(&to_bignum(2), &to_bignum(3)));
+        assert_eq!(genesis_file_hash(SHELLEY.as_bytes()).to_bytes(), blake2b256(SHELLEY.as_bytes()).to_vec());
+
+        let network = GenesisNetwork::new(&shelley, &alonzo, Some(&conway));
+        assert_eq!(network.network_info, NetworkInfo::new(0, ProtocolMagic::from(42)));
+        assert_eq!(network.epoch_length, 86400);
+        assert_eq!(network.system_start, SystemTime::UNIX_EPOCH + Duration::from_secs(1666656000));
+        assert_eq!(network.tx_builder_config.coins_per_utxo_byte, to_bignum(4310));
+        assert_eq!(network.tx_builder_config.costmdls.len(), 3);
+
+        assert_eq!(network.utxos.len(), 1);
+        let utxo = network.utxos.get(0);
+        let address = Address::from_bytes(hex::decode("609783be7d3c54f11377966dfabc9284cd6c32fca1cd42ef0a4f1cc45b").unwrap()).unwrap();
+        assert_eq!(utxo.input().transaction_id().to_bytes(), blake2b256(&address.to_bytes()).to_vec());
+        assert_eq!(utxo.output().address(), address);
+        assert_eq!(utxo.output().amount().coin(), to_bignum(30000000000000000));
+    }
+
+    #[test]
+    fn genesis_pool_relays_and_metadata() {
+        let with_relays = SHELLEY
+            .replace(r#""metadata": null"#, r#""metadata": {
+                "hash": "47c0c68cb57f4a5b4a87bad896fc274678e7aea98e200fa14a1cb40c0cab1d8c",
+                "url": "https://example.com/pool.json"
+            }"#)
+            .replace(r#""relays": []"#, r#""relays": [
+                { "single host address": { "IPv4": "10.0.0.1", "IPv6": null, "port": 3001 } },
+                { "single host name": { "dnsName": "relay.example.com", "port": 3001 } },
+                { "multi host name": { "dnsName": "relays.example.com" } }
+            ]"#);
+        let shelley = shelley::parse::parse(with_relays.as_bytes()).unwrap();
+        let pool = shelley.staking.unwrap().pools.into_iter().next().unwrap().1;
+        let metadata = pool.pool_metadata().unwrap();
+        assert_eq!(metadata.url().url(), "https://example.com/pool.json");
+        assert_eq!(metadata.pool_metadata_hash().to_hex(), "47c0c68cb57f4a5b4a87bad896fc274678e7aea98e200fa14a1cb40c0cab1d8c");
+        let relays = pool.relays();
+        assert_eq!(relays.len(), 3);
+        let address = relays.get(0).as_single_host_addr().unwrap();
+        assert_eq!((address.port(), address.ipv4().unwrap().ip(), address.ipv6()), (Some(3001), vec![10, 0, 0, 1], None));
+        assert_eq!(relays.get(1).as_single_host_name().unwrap().dns_name().record(), "relay.example.com");
+        assert_eq!(relays.get(2).as_multi_host_name().unwrap().dns_name().record(), "relays.example.com");
+
+        let invalid_ip = with_relays.replace("10.0.0.1", "10.0.0");
+        match shelley::parse::parse(invalid_ip.as_bytes()) {
+            Err(crate::genesis::error::GenesisError::InvalidField { field, .. }) => assert_eq!(field, "staking.pools.relays"),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn genesis_errors() {
+        let invalid_hash = SHELLEY.replace("12b0f443", "12b0f4");
+        match shelley::parse::parse(invalid_hash.as_bytes()) {
+            Err(crate::genesis::error::GenesisError::InvalidField { field, .. }) => assert_eq!(field, "genDelegs"),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(alonzo::parse::parse(&b"{}"[..]), Err(crate::genesis::error::GenesisError::Json(_))));
+    }
+}
@@ -0,0 +1,72 @@
+//! Typed contents of the Shelley genesis file (shelley-genesis.json)
+
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    Address, NetworkId, PoolParams, ProtocolVersion, UnitInterval,
+    byron::ProtocolMagic,
+    crypto::{Ed25519KeyHash, GenesisDelegateHash, GenesisHash, VRFKeyHash},
+    ledger::common::value::Coin,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisData {
+    /// fraction of the slots expected to have a block (f)
+    pub active_slots_coeff: UnitInterval,
+    /// in slots
+    pub epoch_length: u64,
+    pub gen_delegs: BTreeMap<GenesisHash, GenesisDelegation>,
+    /// ADA available in the genesis UTxO set, see parse::genesis_utxos
+    pub initial_funds: BTreeMap<Address, Coin>,
+    pub max_kes_evolutions: u64,
+    pub max_lovelace_supply: Coin,
+    pub network_id: NetworkId,
+    pub network_magic: ProtocolMagic,
+    pub protocol_params: ProtocolParams,
+    /// a.k.a. 'k'
+    pub security_param: u64,
+    pub slot_length: Duration,
+    pub slots_per_kes_period: u64,
+    pub staking: Option<Staking>,
+    pub system_start: SystemTime,
+    pub update_quorum: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisDelegation {
+    pub delegate: GenesisDelegateHash,
+    pub vrf: VRFKeyHash,
+}
+
+/// The initial protocol parameters. Later eras add theirs in their own genesis file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolParams {
+    pub protocol_version: ProtocolVersion,
+    pub decentralisation_param: UnitInterval,
+    pub e_max: u64,
+    pub max_tx_size: u32,
+    pub max_block_body_size: u32,
+    pub max_block_header_size: u32,
+    pub min_fee_a: Coin,
+    pub min_fee_b: Coin,
+    pub min_utxo_value: Coin,
+    pub pool_deposit: Coin,
+    pub min_pool_cost: Coin,
+    pub key_deposit: Coin,
+    pub n_opt: u64,
+    pub rho: UnitInterval,
+    pub tau: UnitInterval,
+    pub a0: UnitInterval,
+}
+
+/// Pools registered and stake delegated at genesis, used by devnets to produce blocks from the start
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Staking {
+    /// pool params without relays or metadata, which aren't read from the genesis file
+    pub pools: BTreeMap<Ed25519KeyHash, PoolParams>,
+    /// stake key hash -> pool id
+    pub stake: BTreeMap<Ed25519KeyHash, Ed25519KeyHash>,
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+
+use crate::{Address, DNSRecordAorAAAA, DNSRecordSRV, Ipv4, Ipv6, MultiHostName, NetworkId, PoolMetadata, PoolParams, ProtocolVersion, Relay, Relays, RewardAddress, SingleHostAddr, SingleHostName, StakeCredential, TransactionInput, TransactionOutput, URL};
+use crate::crypto::{blake2b256, Ed25519KeyHash, GenesisDelegateHash, GenesisHash, PoolMetadataHash, ScriptHash, TransactionHash, VRFKeyHash};
+use crate::genesis::error::GenesisError;
+use crate::genesis::json::{self, field_error};
+use crate::ledger::common::utxo::{TransactionUnspentOutput, TransactionUnspentOutputs};
+use crate::ledger::common::value::{to_bignum, Value};
+
+use super::{raw, config};
+
+pub fn parse<R: Read>(json: R) -> Result<config::GenesisData, GenesisError> {
+    let data: raw::GenesisData = serde_json::from_reader(json)?;
+
+    let mut gen_delegs = BTreeMap::new();
+    for (genesis_hash, delegation) in &data.genDelegs {
+        gen_delegs.insert(
+            GenesisHash::from_hex(genesis_hash).map_err(field_error("genDelegs"))?,
+            config::GenesisDelegation {
+                delegate: GenesisDelegateHash::from_hex(&delegation.delegate).map_err(field_error("genDelegs.delegate"))?,
+                vrf: VRFKeyHash::from_hex(&delegation.vrf).map_err(field_error("genDelegs.vrf"))?,
+            },
+        );
+    }
+
+    let mut initial_funds = BTreeMap::new();
+    for (address, coin) in &data.initialFunds {
+        let address = Address::from_bytes(json::hex_bytes("initialFunds", address)?)
+            .map_err(|e| GenesisError::invalid("initialFunds", e))?;
+        initial_funds.insert(address, to_bignum(*coin));
+    }
+
+    let staking = match &data.staking {
+        Some(staking) => Some(parse_staking(staking)?),
+        None => None,
+    };
+
+    let params = &data.protocolParams;
+    let protocol_params = config::ProtocolParams {
+        protocol_version: ProtocolVersion::new(params.protocolVersion.major, params.protocolVersion.minor),
+        decentralisation_param: json::unit_interval("decentralisationParam", &params.decentralisationParam)?,
+        e_max: params.eMax,
+        max_tx_size: params.maxTxSize,
+        max_block_body_size: params.maxBlockBodySize,
+        max_block_header_size: params.maxBlockHeaderSize,
+        min_fee_a: to_bignum(params.minFeeA),
+        min_fee_b: to_bignum(params.minFeeB),
+        min_utxo_value: to_bignum(params.minUTxOValue),
+        pool_deposit: to_bignum(params.poolDeposit),
+        min_pool_cost: to_bignum(params.minPoolCost),
+        key_deposit: to_bignum(params.keyDeposit),
+        n_opt: params.nOpt,
+        rho: json::unit_interval("rho", &params.rho)?,
+        tau: json::unit_interval("tau", &params.tau)?,
+        a0: json::unit_interval("a0", &params.a0)?,
+    };
+
+    Ok(config::GenesisData {
+        active_slots_coeff: json::unit_interval("activeSlotsCoeff", &data.activeSlotsCoeff)?,
+        epoch_length: data.epochLength,
+        gen_delegs,
+        initial_funds,
+        max_kes_evolutions: data.maxKESEvolutions,
+        max_lovelace_supply: to_bignum(data.maxLovelaceSupply),
+        network_id: parse_network("networkId", &data.networkId)?,
+        network_magic: data.networkMagic.into(),
+        protocol_params,
+        security_param: data.securityParam,
+        slot_length: json::duration("slotLength", &data.slotLength)?,
+        slots_per_kes_period: data.slotsPerKESPeriod,
+        staking,
+        system_start: json::system_time("systemStart", &data.systemStart)?,
+        update_quorum: data.updateQuorum,
+    })
+}
+
+fn parse_network(field: &str, network: &str) -> Result<NetworkId, GenesisError> {
+    match network {
+        "Mainnet" => Ok(NetworkId::mainnet()),
+        "Testnet" => Ok(NetworkId::testnet()),
+        other => Err(GenesisError::invalid(field, format!("unknown network {}", other))),
+    }
+}
+
+fn parse_staking(staking: &raw::Staking) -> Result<config::Staking, GenesisError> {
+    let mut pools = BTreeMap::new();
+    for (pool_id, pool) in &staking.pools {
+        let operator = Ed25519KeyHash::from_hex(&pool.publicKey).map_err(field_error("staking.pools.publicKey"))?;
+        let credential = match (&pool.rewardAccount.credential.keyHash, &pool.rewardAccount.credential.scriptHash) {
+            (Some(key_hash), None) => StakeCredential::from_keyhash(&Ed25519KeyHash::from_hex(key_hash).map_err(field_error("staking.pools.rewardAccount"))?),
+            (None, Some(script_hash)) => StakeCredential::from_scripthash(&ScriptHash::from_hex(script_hash).map_err(field_error("staking.pools.rewardAccount"))?),
+            _ => return Err(GenesisError::invalid("staking.pools.rewardAccount", "expected one of keyHash or scriptHash")),
+        };
+        let network = parse_network("staking.pools.rewardAccount.network", &pool.rewardAccount.network)?;
+        let mut owners = crate::Ed25519KeyHashes::new();
+        for owner in &pool.owners {
+            owners.add(&Ed25519KeyHash::from_hex(owner).map_err(field_error("staking.pools.owners"))?);
+        }
+        let mut relays = Relays::new();
+        for relay in &pool.relays {
+            relays.add(&parse_relay(relay)?);
+        }
+        let metadata = match &pool.metadata {
+            Some(metadata) => Some(PoolMetadata::new(
+                &URL::new(metadata.url.clone()).map_err(field_error("staking.pools.metadata.url"))?,
+                &PoolMetadataHash::from_hex(&metadata.hash).map_err(field_error("staking.pools.metadata.hash"))?,
+            )),
+            None => None,
+        };
+        pools.insert(
+            Ed25519KeyHash::from_hex(pool_id).map_err(field_error("staking.pools"))?,
+            PoolParams::new(
+                &operator,
+                &VRFKeyHash::from_hex(&pool.vrf).map_err(field_error("staking.pools.vrf"))?,
+                &to_bignum(pool.pledge),
+                &to_bignum(pool.cost),
+                &json::unit_interval("staking.pools.margin", &pool.margin)?,
+                &RewardAddress::new(network_bits(&network), &credential),
+                &owners,
+                &relays,
+                metadata,
+            ),
+        );
+    }
+
+    let mut stake = BTreeMap::new();
+    for (stake_key, pool_id) in &staking.stake {
+        stake.insert(
+            Ed25519KeyHash::from_hex(stake_key).map_err(field_error("staking.stake"))?,
+            Ed25519KeyHash::from_hex(pool_id).map_err(field_error("staking.stake"))?,
+        );
+    }
+
+    Ok(config::Staking { pools, stake })
+}
+
+fn parse_relay(relay: &raw::Relay) -> Result<Relay, GenesisError> {
+    let field = "staking.pools.relays";
+    Ok(match relay {
+        raw::Relay::SingleHostAddress { IPv4, IPv6, port } => {
+            let ipv4 = match IPv4 {
+                Some(ip) => {
+                    let ip = ip.parse::<std::net::Ipv4Addr>().map_err(|e| GenesisError::invalid(field, e))?;
+                    Some(Ipv4::new(ip.octets().to_vec()).map_err(field_error(field))?)
+                },
+                None => None,
+            };
+            let ipv6 = match IPv6 {
+                Some(ip) => {
+                    let ip = ip.parse::<std::net::Ipv6Addr>().map_err(|e| GenesisError::invalid(field, e))?;
+                    Some(Ipv6::new(ip.octets().to_vec()).map_err(field_error(field))?)
+                },
+                None => None,
+            };
+            Relay::new_single_host_addr(&SingleHostAddr::new(*port, ipv4, ipv6))
+        },
+        raw::Relay::SingleHostName { dnsName, port } => Relay::new_single_host_name(&SingleHostName::new(
+            *port,
+            &DNSRecordAorAAAA::new(dnsName.clone()).map_err(field_error(field))?,
+        )),
+        raw::Relay::MultiHostName { dnsName } => Relay::new_multi_host_name(&MultiHostName::new(
+            &DNSRecordSRV::new(dnsName.clone()).map_err(field_error(field))?,
+        )),
+    })
+}
+
+/// The network id as written in address headers
+pub(crate) fn network_bits(network_id: &NetworkId) -> u8 {
+    match network_id.kind() {
+        crate::NetworkIdKind::Testnet => 0b0000,
+        crate::NetworkIdKind::Mainnet => 0b0001,
+    }
+}
+
+/// Initial funds are spent from pseudo-inputs: the hash of the address bytes as transaction id and index 0
+pub fn initial_funds_txid(address: &Address) -> TransactionHash {
+    TransactionHash::from(blake2b256(&address.to_bytes()))
+}
+
+pub fn genesis_utxos(genesis: &config::GenesisData) -> TransactionUnspentOutputs {
+    let mut utxos = TransactionUnspentOutputs::new();
+    for (address, coin) in &genesis.initial_funds {
+        utxos.add(&TransactionUnspentOutput::new(
+            &TransactionInput::new(&initial_funds_txid(address), &to_bignum(0)),
+            &TransactionOutput::new(address, &Value::new(coin)),
+        ));
+    }
+    utxos
+}
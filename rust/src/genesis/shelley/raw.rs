@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GenesisData {
+    pub activeSlotsCoeff: serde_json::Value,
+    pub epochLength: u64,
+    pub genDelegs: HashMap<String, GenesisDelegation>,
+    #[serde(default)]
+    pub initialFunds: HashMap<String, u64>,
+    pub maxKESEvolutions: u64,
+    pub maxLovelaceSupply: u64,
+    pub networkId: String,
+    pub networkMagic: u32,
+    pub protocolParams: ProtocolParams,
+    pub securityParam: u64,
+    pub slotLength: serde_json::Value,
+    pub slotsPerKESPeriod: u64,
+    #[serde(default)]
+    pub staking: Option<Staking>,
+    pub systemStart: String,
+    pub updateQuorum: u64,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GenesisDelegation {
+    pub delegate: String,
+    pub vrf: String,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProtocolParams {
+    pub protocolVersion: ProtocolVersion,
+    pub decentralisationParam: serde_json::Value,
+    pub eMax: u64,
+    pub maxTxSize: u32,
+    pub maxBlockBodySize: u32,
+    pub maxBlockHeaderSize: u32,
+    pub minFeeA: u64,
+    pub minFeeB: u64,
+    pub minUTxOValue: u64,
+    pub poolDeposit: u64,
+    pub minPoolCost: u64,
+    pub keyDeposit: u64,
+    pub nOpt: u64,
+    pub rho: serde_json::Value,
+    pub tau: serde_json::Value,
+    pub a0: serde_json::Value,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProtocolVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Staking {
+    #[serde(default)]
+    pub pools: HashMap<String, Pool>,
+    #[serde(default)]
+    pub stake: HashMap<String, String>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Pool {
+    pub publicKey: String,
+    pub vrf: String,
+    pub pledge: u64,
+    pub cost: u64,
+    pub margin: serde_json::Value,
+    pub rewardAccount: RewardAccount,
+    #[serde(default)]
+    pub owners: Vec<String>,
+    #[serde(default)]
+    pub relays: Vec<Relay>,
+    #[serde(default)]
+    pub metadata: Option<PoolMetadata>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub enum Relay {
+    #[serde(rename = "single host address")]
+    SingleHostAddress {
+        IPv4: Option<String>,
+        IPv6: Option<String>,
+        port: Option<u16>,
+    },
+    #[serde(rename = "single host name")]
+    SingleHostName {
+        dnsName: String,
+        port: Option<u16>,
+    },
+    #[serde(rename = "multi host name")]
+    MultiHostName {
+        dnsName: String,
+    },
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PoolMetadata {
+    pub url: String,
+    pub hash: String,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RewardAccount {
+    pub network: String,
+    pub credential: Credential,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Credential {
+    pub keyHash: Option<String>,
+    pub scriptHash: Option<String>,
+}
@@ -30,29 +30,84 @@ impl LinearFee {
 }
 
 
-pub fn min_script_fee(tx: &Transaction, ex_unit_prices: &ExUnitPrices) -> Result<Coin, JsError> {
+/// Errors of the fee functions, which only fail when a fee doesn't fit in a Coin
+///
+/// Converts to the crate Error (with the Display message), which the wasm bindings turn into a JS value
+#[derive(Clone, Debug)]
+pub enum FeeError {
+    /// The execution units of the redeemers don't fit in 64 bits when summed up
+    ExUnitsOverflow,
+    /// The script fee of {ex_units}, the total execution units of the redeemers
+    ScriptFeeOverflow {
+        ex_units: ExUnits,
+        ex_unit_prices: ExUnitPrices,
+    },
+    /// The size fee of a transaction of {size} bytes
+    SizeFeeOverflow {
+        size: usize,
+        linear_fee: LinearFee,
+    },
+    /// The sum of the size fee and the script fee
+    FeeOverflow {
+        no_script_fee: Coin,
+        script_fee: Coin,
+    },
+}
+
+impl std::fmt::Display for FeeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeeError::ExUnitsOverflow => write!(f, "total execution units of the redeemers overflow"),
+            FeeError::ScriptFeeOverflow { ex_units, .. } => write!(
+                f,
+                "script fee of {} memory units and {} steps overflows",
+                ex_units.mem().to_str(),
+                ex_units.steps().to_str(),
+            ),
+            FeeError::SizeFeeOverflow { size, .. } => write!(f, "fee of a transaction of {} bytes overflows", size),
+            FeeError::FeeOverflow { no_script_fee, script_fee } => write!(
+                f,
+                "fee of {} plus script fee of {} overflows",
+                no_script_fee.to_str(),
+                script_fee.to_str(),
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FeeError {}
+
+impl From<FeeError> for Error {
+    fn from(e: FeeError) -> Error {
+        Error::from_str(&e.to_string())
+    }
+}
+
+
+pub fn min_script_fee(tx: &Transaction, ex_unit_prices: &ExUnitPrices) -> Result<Coin, FeeError> {
     if let Some(redeemers) = tx.witness_set().redeemers() {
-        let total_ex_units = redeemers.get_total_ex_units()?;
-        let script_fee = to_bignum(
+        let total_ex_units = redeemers.get_total_ex_units().map_err(|_| FeeError::ExUnitsOverflow)?;
+        let script_fee = (
             (
-                (
-                    Fraction::new(from_bignum(&total_ex_units.mem()), 1u64)
-                    * Fraction::new(
-                        from_bignum(&ex_unit_prices.mem_price().numerator),
-                        from_bignum(&ex_unit_prices.mem_price().denominator),
-                    )
+                Fraction::new(from_bignum(&total_ex_units.mem()), 1u64)
+                * Fraction::new(
+                    from_bignum(&ex_unit_prices.mem_price().numerator),
+                    from_bignum(&ex_unit_prices.mem_price().denominator),
                 )
-                +
-                (
-                    Fraction::new(from_bignum(&total_ex_units.steps()), 1u64)
-                    * Fraction::new(
-                        from_bignum(&ex_unit_prices.step_price().numerator),
-                        from_bignum(&ex_unit_prices.step_price().denominator),
-                    )
+            )
+            +
+            (
+                Fraction::new(from_bignum(&total_ex_units.steps()), 1u64)
+                * Fraction::new(
+                    from_bignum(&ex_unit_prices.step_price().numerator),
+                    from_bignum(&ex_unit_prices.step_price().denominator),
                 )
-            ).ceil().to_u64().unwrap(),
-        );
-        Ok(script_fee)
+            )
+        ).ceil().to_u64();
+        script_fee.map(to_bignum).ok_or_else(|| FeeError::ScriptFeeOverflow {
+            ex_units: total_ex_units,
+            ex_unit_prices: ex_unit_prices.clone(),
+        })
     } else {
         Ok(Coin::zero())
     }
@@ -62,10 +117,12 @@ pub fn min_script_fee(tx: &Transaction, ex_unit_prices: &ExUnitPrices) -> Result
 pub fn min_no_script_fee(
     tx: &Transaction,
     linear_fee: &LinearFee,
-) -> Result<Coin, JsError> {
-    to_bignum(tx.to_bytes().len() as u64)
-        .checked_mul(&linear_fee.coefficient())?
-        .checked_add(&linear_fee.constant())
+) -> Result<Coin, FeeError> {
+    let size = tx.to_bytes().len();
+    to_bignum(size as u64)
+        .checked_mul(&linear_fee.coefficient())
+        .and_then(|fee| fee.checked_add(&linear_fee.constant()))
+        .map_err(|_| FeeError::SizeFeeOverflow { size, linear_fee: linear_fee.clone() })
 }
 
 
@@ -73,11 +130,13 @@ pub fn min_fee(
     tx: &Transaction,
     linear_fee: &LinearFee,
     ex_unit_prices: &ExUnitPrices
-) -> Result<Coin, JsError> {
+) -> Result<Coin, FeeError> {
     // TODO: the fee should be 0 if all inputs are genesis redeem addresses
-    let mut fee = min_no_script_fee(tx, linear_fee)?;
-    fee = fee.checked_add(&min_script_fee(tx, ex_unit_prices)?)?;
-    Ok(fee)
+    let no_script_fee = min_no_script_fee(tx, linear_fee)?;
+    let script_fee = min_script_fee(tx, ex_unit_prices)?;
+    no_script_fee
+        .checked_add(&script_fee)
+        .map_err(|_| FeeError::FeeOverflow { no_script_fee, script_fee })
 }
 
 // Note: all the tests below are disabled
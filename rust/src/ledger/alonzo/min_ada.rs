@@ -63,7 +63,7 @@ pub fn min_ada_required(
     assets: &Value,
     has_data_hash: bool, // whether the output includes a data hash
     coins_per_utxo_word: &BigNum, // protocol parameter (in lovelace)
-) -> Result<BigNum, JsError> {
+) -> Result<BigNum, Error> {
     // based on https://github.com/input-output-hk/cardano-ledger-specs/blob/master/doc/explanations/min-utxo-alonzo.rst
     let data_hash_size = if has_data_hash { 10 } else { 0 }; // in words
     let utxo_entry_size_without_val = 27; // in words
@@ -86,7 +86,7 @@ pub fn min_ada_required(
     since = "0.4.0",
     note = "This calculation is no longer used in the Babbage era"
 )]
-pub fn min_pure_ada(coins_per_utxo_word: &BigNum, has_data_hash: bool) -> Result<BigNum, JsError> {
+pub fn min_pure_ada(coins_per_utxo_word: &BigNum, has_data_hash: bool) -> Result<BigNum, Error> {
     min_ada_required(
         // arbitrary value that happens to give the right number of bytes at the CBOR level
         &Value::new(&Coin::from_str("1000000")?),
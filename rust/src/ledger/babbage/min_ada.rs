@@ -1,7 +1,7 @@
 use crate::Datum;
 use crate::TransactionOutput;
 use crate::address::Address;
-use crate::error::JsError;
+use crate::error::Error;
 use crate::ledger::common::value::BigNum;
 use crate::ledger::common::value::Coin;
 use crate::ledger::common::value::Value;
@@ -18,7 +18,7 @@ pub fn compatible_min_ada_required(
     output: &TransactionOutput,
     coins_per_utxo_byte: &BigNum, // protocol parameter (in lovelace)
     coins_per_utxo_word: &BigNum, // protocol parameter (in lovelace)
-) -> Result<Coin, JsError> {
+) -> Result<Coin, Error> {
     let babbage_min = min_ada_required(output, coins_per_utxo_byte)?;
     let alonzo_min = crate::ledger::alonzo::min_ada::min_ada_required(&output.amount(), output.datum_option.is_some(), coins_per_utxo_word)?;
     Ok(std::cmp::max(babbage_min, alonzo_min))
@@ -28,7 +28,7 @@ pub fn compatible_min_ada_required(
 pub fn min_ada_required(
     output: &TransactionOutput,
     coins_per_utxo_byte: &BigNum, // protocol parameter (in lovelace)
-) -> Result<BigNum, JsError> {
+) -> Result<BigNum, Error> {
     // constant from figure 5 in Babbage spec meant to represent the size the input in a UTXO
     let constant_overhead = 160_u64;
 
@@ -64,7 +64,7 @@ pub fn min_ada_required(
 }
 
 // note: don't expose to WASM since these Option<> types would be dangerous
-pub fn min_pure_ada(coins_per_utxo_byte: &BigNum, address: &Address, datum: &Option<Datum>, script_ref: &Option<ScriptRef>) -> Result<BigNum, JsError> {
+pub fn min_pure_ada(coins_per_utxo_byte: &BigNum, address: &Address, datum: &Option<Datum>, script_ref: &Option<ScriptRef>) -> Result<BigNum, Error> {
     let mut output = TransactionOutput::new(
         address,
         // arbitrary value that happens to give the right number of bytes at the CBOR level
@@ -3,27 +3,14 @@ use std::io::{BufRead, Seek, Write};
 use cbor_event::{de::Deserializer, se::Serializer};
 use cbor_event::Special as CBORSpecial;
 use cbor_event::Type as CBORType;
-use wasm_bindgen::JsValue;
-use crate::error::{DeserializeError, DeserializeFailure, JsError};
+use crate::error::{DeserializeError, DeserializeFailure};
 
-// JsError can't be used by non-wasm targets so we use this macro to expose
-// either a DeserializeError or a JsError error depending on if we're on a
-// wasm or a non-wasm target where JsError is not available (it panics!).
 // Note: wasm-bindgen doesn't support macros inside impls, so we have to wrap these
 //       in their own impl and invoke the invoke the macro from global scope.
 // TODO: possibly write s generic version of this for other usages (e.g. PrivateKey, etc)
 macro_rules! from_bytes {
     // Custom from_bytes() code
     ($name:ident, $data: ident, $body:block) => {
-        // wasm-exposed JsError return - JsError panics when used outside wasm
-        #[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
-        impl $name {
-            pub fn from_bytes($data: Vec<u8>) -> Result<$name, JsError> {
-                Ok($body?)
-            }
-        }
-        // non-wasm exposed DeserializeError return
-        #[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
         impl $name {
             pub fn from_bytes($data: Vec<u8>) -> Result<$name, DeserializeError> $body
         }
@@ -66,25 +53,118 @@ pub(crate) use to_from_bytes;
 macro_rules! to_from_json {
     ($name:ident) => {
         impl $name {
-            pub fn to_json(&self) -> Result<String, JsError> {
+            pub fn to_json(&self) -> Result<String, Error> {
                 serde_json::to_string_pretty(&self)
-                    .map_err(|e| JsError::from_str(&format!("to_json: {}", e)))
+                    .map_err(|e| Error::from_str(&format!("to_json: {}", e)))
             }
 
-            pub fn to_js_value(&self) -> Result<JsValue, JsError> {
-                JsValue::from_serde(&self)
-                    .map_err(|e| JsError::from_str(&format!("to_js_value: {}", e)))
+            #[cfg(feature = "wasm")]
+            pub fn to_js_value(&self) -> Result<wasm_bindgen::JsValue, Error> {
+                wasm_bindgen::JsValue::from_serde(&self)
+                    .map_err(|e| Error::from_str(&format!("to_js_value: {}", e)))
             }
 
-            pub fn from_json(json: &str) -> Result<$name, JsError> {
+            pub fn from_json(json: &str) -> Result<$name, Error> {
                 serde_json::from_str(json)
-                    .map_err(|e| JsError::from_str(&format!("from_json: {}", e)))
+                    .map_err(|e| Error::from_str(&format!("from_json: {}", e)))
             }
         }
     }
 }
 pub(crate) use to_from_json;
 
+/// Iterators over collection wrappers of a Vec, and building them from owned elements
+/// len/get/add stay the JS-facing accessors since wasm_bindgen can't export iterators
+macro_rules! vec_iter {
+    ($name:ident, $elem:ty) => {
+        impl $name {
+            pub fn iter(&self) -> std::slice::Iter<'_, $elem> {
+                self.0.iter()
+            }
+        }
+
+        impl IntoIterator for $name {
+            type Item = $elem;
+            type IntoIter = std::vec::IntoIter<$elem>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.0.into_iter()
+            }
+        }
+
+        impl<'a> IntoIterator for &'a $name {
+            type Item = &'a $elem;
+            type IntoIter = std::slice::Iter<'a, $elem>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.0.iter()
+            }
+        }
+
+        impl std::iter::FromIterator<$elem> for $name {
+            fn from_iter<I: IntoIterator<Item = $elem>>(iter: I) -> Self {
+                Self(iter.into_iter().collect())
+            }
+        }
+
+        impl Extend<$elem> for $name {
+            fn extend<I: IntoIterator<Item = $elem>>(&mut self, iter: I) {
+                self.0.extend(iter)
+            }
+        }
+
+        impl From<Vec<$elem>> for $name {
+            fn from(elems: Vec<$elem>) -> Self {
+                Self(elems)
+            }
+        }
+    }
+}
+pub(crate) use vec_iter;
+
+/// Like vec_iter for collection wrappers of a map, {map} being the module of the map type
+/// e.g. std::collections::btree_map or linked_hash_map, which keeps the insertion order
+macro_rules! map_iter {
+    ($name:ident, $($map:ident)::+, $key:ty, $value:ty) => {
+        impl $name {
+            pub fn iter(&self) -> $($map)::+::Iter<'_, $key, $value> {
+                self.0.iter()
+            }
+        }
+
+        impl IntoIterator for $name {
+            type Item = ($key, $value);
+            type IntoIter = $($map)::+::IntoIter<$key, $value>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.0.into_iter()
+            }
+        }
+
+        impl<'a> IntoIterator for &'a $name {
+            type Item = (&'a $key, &'a $value);
+            type IntoIter = $($map)::+::Iter<'a, $key, $value>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.0.iter()
+            }
+        }
+
+        impl std::iter::FromIterator<($key, $value)> for $name {
+            fn from_iter<I: IntoIterator<Item = ($key, $value)>>(iter: I) -> Self {
+                Self(iter.into_iter().collect())
+            }
+        }
+
+        impl Extend<($key, $value)> for $name {
+            fn extend<I: IntoIterator<Item = ($key, $value)>>(&mut self, iter: I) {
+                self.0.extend(iter)
+            }
+        }
+    }
+}
+pub(crate) use map_iter;
+
 const BOUNDED_BYTES_CHUNK_SIZE: usize = 64;
 
 pub (crate) fn write_bounded_bytes<'se, W: Write>(serializer: &'se mut Serializer<W>, bytes: &[u8]) -> cbor_event::Result<&'se mut Serializer<W>> {
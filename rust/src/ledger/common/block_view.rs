@@ -0,0 +1,649 @@
+//! Lazy decoding of blocks for indexers.
+//!
+//! BlockView only finds where each part of a block starts and ends in its bytes, without copying
+//! them. Fields are decoded on demand, and bodies are hashed from their original bytes so the hashes
+//! are right even for non-canonical encodings, which Block can't re-encode the same way.
+
+use std::convert::TryFrom;
+use std::io::Cursor;
+
+use cbor_event::de::Deserializer;
+
+use crate::*;
+use crate::crypto::blake2b256;
+use crate::error::{DeserializeError, DeserializeFailure, Key};
+
+use super::binary::Deserialize;
+
+/// Nesting of indefinite-length items, which are the only ones skipped recursively
+const MAX_INDEFINITE_DEPTH: usize = 1024;
+
+const BREAK: u8 = 0xff;
+
+fn not_enough(found: usize, needed: usize) -> DeserializeError {
+    DeserializeFailure::CBOR(cbor_event::Error::NotEnough(found, needed)).into()
+}
+
+fn unexpected_type(expected: cbor_event::Type, major: u8) -> DeserializeError {
+    let found = match major {
+        0 => cbor_event::Type::UnsignedInteger,
+        1 => cbor_event::Type::NegativeInteger,
+        2 => cbor_event::Type::Bytes,
+        3 => cbor_event::Type::Text,
+        4 => cbor_event::Type::Array,
+        5 => cbor_event::Type::Map,
+        6 => cbor_event::Type::Tag,
+        _ => cbor_event::Type::Special,
+    };
+    DeserializeFailure::CBOR(cbor_event::Error::Expected(expected, found)).into()
+}
+
+/// The major type, argument (None for indefinite lengths) and size of the head of the item {bytes} start with
+fn item_head(bytes: &[u8]) -> Result<(u8, Option<u64>, usize), DeserializeError> {
+    let first = *bytes.first().ok_or_else(|| not_enough(0, 1))?;
+    let (major, info) = (first >> 5, first & 0x1f);
+    match info {
+        0..=23 => Ok((major, Some(info as u64), 1)),
+        24..=27 => {
+            let size = 1 << (info - 24);
+            let argument = bytes
+                .get(1..=size)
+                .ok_or_else(|| not_enough(bytes.len(), size + 1))?
+                .iter()
+                .fold(0u64, |argument, byte| argument << 8 | *byte as u64);
+            Ok((major, Some(argument), size + 1))
+        },
+        31 if (2..=5).contains(&major) => Ok((major, None, 1)),
+        _ => Err(DeserializeFailure::CBOR(cbor_event::Error::UnknownLenType(first)).into()),
+    }
+}
+
+fn item_len_at_depth(bytes: &[u8], depth: usize) -> Result<usize, DeserializeError> {
+    // items left to skip in definite-length containers, so that only indefinite ones need recursion
+    let mut pending: u64 = 1;
+    let mut pos = 0;
+    while pending > 0 {
+        pending -= 1;
+        let (major, argument, head) = item_head(&bytes[pos..])?;
+        pos += head;
+        match (major, argument) {
+            (2, Some(len)) | (3, Some(len)) => {
+                // lengths past usize (on 32-bit targets) can't be in {bytes} anyway
+                let len = usize::try_from(len).map_err(|_| not_enough(bytes.len(), usize::MAX))?;
+                pos = pos.saturating_add(len);
+            },
+            (4, Some(len)) => pending = pending.saturating_add(len),
+            (5, Some(len)) => pending = pending.saturating_add(len.saturating_mul(2)),
+            (6, _) => pending += 1,
+            (2..=5, None) => {
+                if depth >= MAX_INDEFINITE_DEPTH {
+                    return Err(DeserializeError::new("BlockView", DeserializeFailure::OutOfRange { min: 0, max: MAX_INDEFINITE_DEPTH, found: depth }));
+                }
+                let items_per_entry = if major == 5 { 2 } else { 1 };
+                while *bytes.get(pos).ok_or_else(|| not_enough(bytes.len(), pos + 1))? != BREAK {
+                    for _ in 0..items_per_entry {
+                        pos += item_len_at_depth(&bytes[pos..], depth + 1)?;
+                    }
+                }
+                pos += 1;
+            },
+            _ => (),
+        }
+        if pos > bytes.len() {
+            return Err(not_enough(bytes.len(), pos));
+        }
+    }
+    Ok(pos)
+}
+
+/// Size of the CBOR item {bytes} start with
+fn item_len(bytes: &[u8]) -> Result<usize, DeserializeError> {
+    item_len_at_depth(bytes, 0)
+}
+
+/// Splits {bytes} after its first item
+fn split_item(bytes: &[u8]) -> Result<(&[u8], &[u8]), DeserializeError> {
+    Ok(bytes.split_at(item_len(bytes)?))
+}
+
+fn decode_uint(bytes: &[u8]) -> Result<u64, DeserializeError> {
+    match item_head(bytes)? {
+        (0, Some(value), _) => Ok(value),
+        (major, _, _) => Err(unexpected_type(cbor_event::Type::UnsignedInteger, major)),
+    }
+}
+
+/// Decodes a value from a slice of the block, e.g. a TransactionOutput from TransactionBodyView::outputs
+pub fn decode<T: Deserialize>(bytes: &[u8]) -> Result<T, DeserializeError> {
+    let mut raw = Deserializer::from(Cursor::new(bytes));
+    T::deserialize(&mut raw)
+}
+
+/// The items of a CBOR array, as slices of the original bytes
+#[derive(Clone, Debug)]
+pub struct CborItems<'a> {
+    bytes: &'a [u8],
+    /// None for indefinite-length arrays, which end with a break
+    remaining: Option<u64>,
+}
+
+impl<'a> CborItems<'a> {
+    /// Also accepts arrays tagged as sets (258)
+    fn new(bytes: &'a [u8]) -> Result<Self, DeserializeError> {
+        let bytes = match item_head(bytes)? {
+            (6, Some(258), head) => &bytes[head..],
+            _ => bytes,
+        };
+        match item_head(bytes)? {
+            (4, remaining, head) => Ok(Self { bytes: &bytes[head..], remaining }),
+            (major, _, _) => Err(unexpected_type(cbor_event::Type::Array, major)),
+        }
+    }
+}
+
+impl<'a> Iterator for CborItems<'a> {
+    type Item = Result<&'a [u8], DeserializeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.remaining {
+            Some(0) => return None,
+            Some(n) => self.remaining = Some(n - 1),
+            None if self.bytes.first() == Some(&BREAK) => return None,
+            None => (),
+        }
+        match split_item(self.bytes) {
+            Ok((item, rest)) => {
+                self.bytes = rest;
+                Some(Ok(item))
+            },
+            Err(e) => {
+                // no way to find the next item after a malformed one
+                self.remaining = Some(0);
+                Some(Err(e))
+            },
+        }
+    }
+}
+
+/// The entries of a CBOR map, as slices of the original bytes
+#[derive(Clone, Debug)]
+pub struct CborEntries<'a>(CborItems<'a>);
+
+impl<'a> CborEntries<'a> {
+    fn new(bytes: &'a [u8]) -> Result<Self, DeserializeError> {
+        match item_head(bytes)? {
+            // read as an array of twice as many items
+            (5, remaining, head) => Ok(Self(CborItems {
+                bytes: &bytes[head..],
+                remaining: remaining.map(|len| len.saturating_mul(2)),
+            })),
+            (major, _, _) => Err(unexpected_type(cbor_event::Type::Map, major)),
+        }
+    }
+
+    /// The value of the first entry whose key is the unsigned integer {key}
+    fn get(self, key: u64) -> Result<Option<&'a [u8]>, DeserializeError> {
+        for entry in self {
+            let (entry_key, value) = entry?;
+            if matches!(item_head(entry_key)?, (0, Some(k), _) if k == key) {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> Iterator for CborEntries<'a> {
+    type Item = Result<(&'a [u8], &'a [u8]), DeserializeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.0.next()?;
+        let value = self.0.next().unwrap_or_else(|| Err(not_enough(0, 1)));
+        Some(key.and_then(|key| value.map(|value| (key, value))))
+    }
+}
+
+/// Looks up the entries of the auxiliary data set or the invalid transactions by transaction index
+/// while walking the transactions, so that each is read once. That only works if their keys are in
+/// ascending order, as the node writes them: otherwise every lookup goes back to a full scan
+struct AscendingKeys<I, V> {
+    /// key (None for keys that aren't transaction indices) and value of each entry
+    entries: I,
+    peeked: Option<(u64, V)>,
+    last: Option<u64>,
+    ascending: bool,
+}
+
+impl<I, V> AscendingKeys<I, V>
+where
+    I: Iterator<Item = Result<(Option<u64>, V), DeserializeError>> + Clone,
+    V: Copy,
+{
+    fn new(entries: I) -> Self {
+        // checked up front since a transaction can only be told to have no entry if no later key is its index
+        let mut last = None;
+        let mut ascending = true;
+        for entry in entries.clone() {
+            match entry {
+                Ok((None, _)) => (),
+                Ok((Some(key), _)) if !matches!(last, Some(last) if key < last) => last = Some(key),
+                // errors are left to the scans
+                _ => {
+                    ascending = false;
+                    break;
+                },
+            }
+        }
+        Self {
+            entries,
+            peeked: None,
+            last: None,
+            ascending,
+        }
+    }
+
+    /// The value of the first entry with key {index}, which must be larger than the previous one
+    fn get<F>(&mut self, index: u64, scan: F) -> Result<Option<V>, DeserializeError>
+    where
+        F: FnOnce() -> Result<Option<V>, DeserializeError>,
+    {
+        if !self.ascending {
+            return scan();
+        }
+        loop {
+            match self.peeked {
+                Some((key, value)) if key == index => {
+                    self.peeked = None;
+                    return Ok(Some(value));
+                },
+                Some((key, _)) if key > index => return Ok(None),
+                _ => (),
+            }
+            match self.entries.next().transpose()? {
+                None => return Ok(None),
+                Some((None, _)) => (),
+                // later entries with the same key are ignored
+                Some((Some(key), _)) if self.last == Some(key) => (),
+                Some((Some(key), value)) => {
+                    self.last = Some(key);
+                    self.peeked = Some((key, value));
+                },
+            }
+        }
+    }
+}
+
+/// A transaction body, hashed as it is encoded in the block
+#[derive(Clone, Copy, Debug)]
+pub struct TransactionBodyView<'a>(&'a [u8]);
+
+impl<'a> TransactionBodyView<'a> {
+    pub fn bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    pub fn hash(&self) -> TransactionHash {
+        TransactionHash::from(blake2b256(self.0))
+    }
+
+    /// The encoded value of the field {key} of the body e.g. 0 for the inputs
+    pub fn field(&self, key: u64) -> Result<Option<&'a [u8]>, DeserializeError> {
+        CborEntries::new(self.0)?.get(key)
+    }
+
+    fn mandatory_field(&self, key: u64) -> Result<&'a [u8], DeserializeError> {
+        self.field(key)?.ok_or_else(|| DeserializeFailure::MandatoryFieldMissing(Key::Uint(key)).into())
+    }
+
+    pub fn inputs(&self) -> Result<CborItems<'a>, DeserializeError> {
+        CborItems::new(self.mandatory_field(0)?)
+    }
+
+    pub fn outputs(&self) -> Result<CborItems<'a>, DeserializeError> {
+        CborItems::new(self.mandatory_field(1)?)
+    }
+
+    pub fn fee(&self) -> Result<Coin, DeserializeError> {
+        decode_uint(self.mandatory_field(2)?).map(Coin::from)
+    }
+
+    pub fn decode(&self) -> Result<TransactionBody, DeserializeError> {
+        decode(self.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TransactionWitnessSetView<'a>(&'a [u8]);
+
+impl<'a> TransactionWitnessSetView<'a> {
+    pub fn bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// The encoded value of the field {key} of the witness set e.g. 0 for the vkey witnesses
+    pub fn field(&self, key: u64) -> Result<Option<&'a [u8]>, DeserializeError> {
+        CborEntries::new(self.0)?.get(key)
+    }
+
+    /// Empty if there are none
+    pub fn vkeys(&self) -> Result<CborItems<'a>, DeserializeError> {
+        match self.field(0)? {
+            Some(vkeys) => CborItems::new(vkeys),
+            None => Ok(CborItems { bytes: &[], remaining: Some(0) }),
+        }
+    }
+
+    pub fn decode(&self) -> Result<TransactionWitnessSet, DeserializeError> {
+        decode(self.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TransactionView<'a> {
+    index: u64,
+    body: TransactionBodyView<'a>,
+    witness_set: TransactionWitnessSetView<'a>,
+    auxiliary_data: Option<&'a [u8]>,
+    is_valid: bool,
+}
+
+impl<'a> TransactionView<'a> {
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    pub fn body(&self) -> TransactionBodyView<'a> {
+        self.body
+    }
+
+    pub fn witness_set(&self) -> TransactionWitnessSetView<'a> {
+        self.witness_set
+    }
+
+    pub fn auxiliary_data_bytes(&self) -> Option<&'a [u8]> {
+        self.auxiliary_data
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.is_valid
+    }
+
+    pub fn hash(&self) -> TransactionHash {
+        self.body.hash()
+    }
+
+    pub fn decode(&self) -> Result<Transaction, DeserializeError> {
+        let auxiliary_data = match self.auxiliary_data {
+            Some(bytes) => Some(decode(bytes).map_err(|e: DeserializeError| e.annotate("auxiliary_data"))?),
+            None => None,
+        };
+        let mut transaction = Transaction::new(
+            &self.body.decode().map_err(|e| e.annotate("body"))?,
+            &self.witness_set.decode().map_err(|e| e.annotate("witness_set"))?,
+            auxiliary_data,
+        );
+        transaction.set_is_valid(self.is_valid);
+        Ok(transaction)
+    }
+}
+
+/// A block indexed without copying any of it, borrowing the bytes it was created from
+#[derive(Clone, Copy, Debug)]
+pub struct BlockView<'a> {
+    header: &'a [u8],
+    transaction_bodies: &'a [u8],
+    transaction_witness_sets: &'a [u8],
+    auxiliary_data_set: &'a [u8],
+    /// only in blocks since Alonzo
+    invalid_transactions: Option<&'a [u8]>,
+}
+
+impl<'a> BlockView<'a> {
+    /// Only checks the block is an array of 4 (before Alonzo) or 5 well-formed CBOR items
+    pub fn new(bytes: &'a [u8]) -> Result<BlockView<'a>, DeserializeError> {
+        (|| -> Result<_, DeserializeError> {
+            let (block, trailing) = split_item(bytes)?;
+            if !trailing.is_empty() {
+                return Err(DeserializeFailure::CBOR(cbor_event::Error::TrailingData).into());
+            }
+            let mut items = CborItems::new(block)?;
+            let mut next = |field: &str| items
+                .next()
+                .unwrap_or_else(|| Err(DeserializeFailure::MandatoryFieldMissing(Key::Str(String::from(field))).into()))
+                .map_err(|e| e.annotate(field));
+            let header = next("header")?;
+            let transaction_bodies = next("transaction_bodies")?;
+            let transaction_witness_sets = next("transaction_witness_sets")?;
+            let auxiliary_data_set = next("auxiliary_data_set")?;
+            let invalid_transactions = items.next().transpose().map_err(|e| e.annotate("invalid_transactions"))?;
+            if items.next().is_some() {
+                return Err(DeserializeFailure::CBOR(cbor_event::Error::TrailingData).into());
+            }
+            CborItems::new(transaction_bodies)?;
+            CborItems::new(transaction_witness_sets)?;
+            CborEntries::new(auxiliary_data_set)?;
+            if let Some(invalid_transactions) = invalid_transactions {
+                CborItems::new(invalid_transactions)?;
+            }
+            Ok(Self {
+                header,
+                transaction_bodies,
+                transaction_witness_sets,
+                auxiliary_data_set,
+                invalid_transactions,
+            })
+        })().map_err(|e| e.annotate("BlockView"))
+    }
+
+    pub fn header_bytes(&self) -> &'a [u8] {
+        self.header
+    }
+
+    pub fn header(&self) -> Result<Header, DeserializeError> {
+        decode(self.header)
+    }
+
+    /// The hash of the header as it is encoded in the block, which identifies the block
+    pub fn hash(&self) -> BlockHeaderHash {
+        BlockHeaderHash::from(blake2b256(self.header))
+    }
+
+    /// The hash the header commits to: the hash of the hashes of the other parts of the block
+    pub fn body_hash(&self) -> BlockBodyHash {
+        let mut hashes = Vec::with_capacity(4 * 32);
+        hashes.extend_from_slice(&blake2b256(self.transaction_bodies));
+        hashes.extend_from_slice(&blake2b256(self.transaction_witness_sets));
+        hashes.extend_from_slice(&blake2b256(self.auxiliary_data_set));
+        if let Some(invalid_transactions) = self.invalid_transactions {
+            hashes.extend_from_slice(&blake2b256(invalid_transactions));
+        }
+        BlockBodyHash::from(blake2b256(&hashes))
+    }
+
+    pub fn transaction_bodies(&self) -> CborItems<'a> {
+        CborItems::new(self.transaction_bodies).expect("checked in BlockView::new")
+    }
+
+    pub fn transaction_witness_sets(&self) -> CborItems<'a> {
+        CborItems::new(self.transaction_witness_sets).expect("checked in BlockView::new")
+    }
+
+    pub fn auxiliary_data(&self, index: u64) -> Result<Option<&'a [u8]>, DeserializeError> {
+        CborEntries::new(self.auxiliary_data_set)?.get(index)
+    }
+
+    pub fn is_valid(&self, index: u64) -> Result<bool, DeserializeError> {
+        let invalid_transactions = match self.invalid_transactions {
+            Some(invalid_transactions) => CborItems::new(invalid_transactions)?,
+            None => return Ok(true),
+        };
+        for invalid in invalid_transactions {
+            if decode_uint(invalid?)? == index {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// The transactions in order, each pairing its body with its witness set
+    pub fn transactions(&self) -> impl Iterator<Item = Result<TransactionView<'a>, DeserializeError>> + 'a {
+        let block = *self;
+        let mut witness_sets = self.transaction_witness_sets();
+        let mut auxiliary_data = AscendingKeys::new(
+            CborEntries::new(self.auxiliary_data_set)
+                .expect("checked in BlockView::new")
+                .map(|entry| {
+                    let (key, value) = entry?;
+                    match item_head(key)? {
+                        (0, Some(key), _) => Ok((Some(key), value)),
+                        _ => Ok((None, value)),
+                    }
+                }),
+        );
+        let mut invalid_transactions = AscendingKeys::new(
+            self.invalid_transactions
+                .map(|bytes| CborItems::new(bytes).expect("checked in BlockView::new"))
+                .into_iter()
+                .flatten()
+                .map(|item| Ok((Some(decode_uint(item?)?), ()))),
+        );
+        self.transaction_bodies().enumerate().map(move |(index, body)| {
+            let index = index as u64;
+            let witness_set = witness_sets
+                .next()
+                .unwrap_or_else(|| Err(DeserializeError::new("transaction_witness_sets", DeserializeFailure::DefiniteLenMismatch(index, Some(index + 1)))))?;
+            Ok(TransactionView {
+                index,
+                body: TransactionBodyView(body?),
+                witness_set: TransactionWitnessSetView(witness_set),
+                auxiliary_data: auxiliary_data.get(index, || block.auxiliary_data(index))?,
+                is_valid: invalid_transactions
+                    .get(index, || block.is_valid(index).map(|is_valid| if is_valid { None } else { Some(()) }))?
+                    .is_none(),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::common::hash::hash_transaction;
+
+    #[test]
+    fn item_lengths() {
+        // [1, h'abcd', {"a": [_ 1, 2]}, 24(h'00')] followed by an unrelated byte
+        let bytes = hex::decode("84014 2abcd a1 6161 9f0102ff d818 4100 07".replace(' ', "")).unwrap();
+        assert_eq!(item_len(&bytes).unwrap(), bytes.len() - 1);
+        assert_eq!(CborItems::new(&bytes).unwrap().count(), 4);
+        // chunked bytes
+        assert_eq!(item_len(&hex::decode("5f41014102ff").unwrap()).unwrap(), 6);
+        // truncated
+        assert!(item_len(&hex::decode("8301").unwrap()).is_err());
+        assert!(item_len(&hex::decode("5903").unwrap()).is_err());
+        assert!(item_len(&hex::decode("9f01").unwrap()).is_err());
+        // a break outside of an indefinite-length item
+        assert!(item_len(&hex::decode("81ff").unwrap()).is_err());
+        // huge lengths fail instead of overflowing
+        assert!(item_len(&hex::decode("9bffffffffffffffff").unwrap()).is_err());
+        assert!(item_len(&hex::decode("5bffffffffffffffff").unwrap()).is_err());
+        // lengths past 32 bits, which don't fit in usize on wasm32
+        assert!(item_len(&hex::decode("5b000000010000000100").unwrap()).is_err());
+    }
+
+    #[test]
+    fn non_canonical_block() {
+        let header = hex::decode("820102").unwrap();
+        // inputs as an indefinite-length array and the fee on more bytes than needed
+        let body = hex::decode(format!("a3009f825820{}00ff0180021a{:08x}", "11".repeat(32), 17_000)).unwrap();
+        let witness_set = hex::decode("a0").unwrap();
+        let metadata = hex::decode("a1186463616263").unwrap();
+        let mut block = vec![0x85];
+        block.extend_from_slice(&header);
+        block.push(0x82);
+        block.extend_from_slice(&body);
+        block.extend_from_slice(&body);
+        block.push(0x82);
+        block.extend_from_slice(&witness_set);
+        block.extend_from_slice(&witness_set);
+        block.push(0xa1);
+        block.push(0x01);
+        block.extend_from_slice(&metadata);
+        block.extend_from_slice(&hex::decode("8100").unwrap());
+
+        let view = BlockView::new(&block).unwrap();
+        assert_eq!(view.hash(), BlockHeaderHash::from(blake2b256(&header)));
+        assert!(view.header().is_err());
+        let transactions = view.transactions().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(transactions.len(), 2);
+        let (first, second) = (transactions[0], transactions[1]);
+        assert!(!first.is_valid() && second.is_valid());
+        assert_eq!(first.auxiliary_data_bytes(), None);
+        assert_eq!(second.auxiliary_data_bytes(), Some(&metadata[..]));
+        assert_eq!(first.body().bytes(), &body[..]);
+        assert_eq!(first.hash(), TransactionHash::from(blake2b256(&body)));
+        assert_ne!(first.hash(), hash_transaction(&first.body().decode().unwrap()));
+        assert_eq!(first.body().fee().unwrap(), Coin::from(17_000));
+        assert_eq!(first.body().inputs().unwrap().count(), 1);
+        assert_eq!(first.body().outputs().unwrap().count(), 0);
+        assert_eq!(first.witness_set().vkeys().unwrap().count(), 0);
+        let decoded = second.decode().unwrap();
+        assert_eq!(decoded.body().fee(), Coin::from(17_000));
+        assert!(decoded.auxiliary_data().is_some());
+
+        assert!(BlockView::new(&block[..block.len() - 1]).is_err());
+        let mut trailing = block.clone();
+        trailing.push(0);
+        assert!(BlockView::new(&trailing).is_err());
+    }
+
+    #[test]
+    fn unordered_auxiliary_data_and_invalid_transactions() {
+        let body = hex::decode(format!("a300818258203{}00018002182a", "3".repeat(63))).unwrap();
+        let (first_metadata, last_metadata) = (hex::decode("a10101").unwrap(), hex::decode("a10102").unwrap());
+        let mut block = hex::decode("85820102").unwrap();
+        block.push(0x83);
+        (0..3).for_each(|_| block.extend_from_slice(&body));
+        block.extend_from_slice(&hex::decode("83a0a0a0").unwrap());
+        // keys in descending order, and an index repeated
+        block.extend_from_slice(&hex::decode("a202").unwrap());
+        block.extend_from_slice(&last_metadata);
+        block.push(0x00);
+        block.extend_from_slice(&first_metadata);
+        block.extend_from_slice(&hex::decode("83020002").unwrap());
+
+        let view = BlockView::new(&block).unwrap();
+        let transactions = view.transactions().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            transactions.iter().map(|transaction| transaction.auxiliary_data_bytes()).collect::<Vec<_>>(),
+            vec![Some(&first_metadata[..]), None, Some(&last_metadata[..])],
+        );
+        assert_eq!(transactions.iter().map(|transaction| transaction.is_valid()).collect::<Vec<_>>(), vec![false, true, false]);
+        for transaction in transactions {
+            assert_eq!(transaction.auxiliary_data_bytes(), view.auxiliary_data(transaction.index()).unwrap());
+            assert_eq!(transaction.is_valid(), view.is_valid(transaction.index()).unwrap());
+        }
+    }
+
+    #[test]
+    fn pre_alonzo_block() {
+        let bytes = hex::decode("84828f1a002072a81a00ca44f0582070d6f38b4569ba062c09632127db13474f22c534e6d8097895403c431e57f12358204f4d7523e41e058a6cbdefb5538654ffc2a53416a7f5bb99f7eac699d42d5c1f58205e3d96cb8ef0291d2f1df6aa7b5a4496ac8de1dcce100c31274325625102796d82584065417914ca323d842c5861407a638e146e6af55f59aff95f1451839de2aa709151237e24e6db7bf94db97293da9c1e61e68d60c8e2b10a116d3c71067247458b5850dc36a5a88f09f0b7a0b5d5d52d87c7c3e3c20752176a426d182255df3d026392f407990f09e5858de6432263fc167bc890a97d07d2371cd5bb26b12242c1ff6fda184ec78d15493a38a3e0df1494f800825840df4e07d3bca43341e4297e2914ea38363ecea1c17ce9145294c4631e0f09f706cb23a5f27c6b71ae9ac46a7ca25af4d7c156f15444fa41814f7d6a0b6a4e57525850d6073f277ded1ef9e8bfe9f6325858c142fbbbbff4395c45d82f0861a6ef6116204965f807e8650fa4e9ac4aa04aeb03984ea66abb129155a78931d39bbcb7ad64afef3f4f55cfa4eb6c97698e88f1051905db5820c1b1fbd809dc06e0e2dc544312aae2a46c059249f86c24ea0689a0b0944a75f558207ce5ce3992b23cb2bf566c48aba8bfc39eb24c9b43354de0129b81bf9f1414b307186058403ac64d720227c18139132b499046a168eb1c5bdd3983385e3518f33fc7f52fd0be348fe3e14d17e1ba606708c30bda061cf23ea3294b0089d3e1e1d58a7aa50702005901c074d3c2c0b5e17b12ba829017186daa1f7f365bbe5b0e0c038cb0cc05e849f702afd349234353ee3cc8878fa31299e85562f04d3cdd74e1bc73591be48d2fbc0d043f6b41fa527b2f9fb3f77605eee528926e76cc18a1638283e5591170f7073462441d40d7cc2e13a38e7d247928cb15d2e5b2e74a12d07f858f7e922bbff8a91c16e9bb8f5ea101c50d96627fb48a03d8191b5035b5de00b9824867fdffb5a2493799e94676bf685db85517dd8a87a0ba2589b3a8a69d529ae8052680c520c5577adbb91cf931e906b1629e621d5bd5c30eaee77f35c5f0a714827b48afaa4e549c1756e94291f4b083aad9c375caf9a67aeac08f32c91cd0572192267960cd74a85148b5e99d0053804dcfb44785417725c56e0fc5caf2ae50fbf25b92c7b7ebe17aa9e289470041a06fd8986f6f9ebdb12e87a970f1d388963929367013e17513e83cab8c98460cab703d5fdd26eeb079e4db701996f73c694365080236901289c5fc96471e91fb75e0e58560f5d073c3ef79a8f5dd4b45ff7abf9c7d7564232f7897ca3d85ac7bb9ecaa75b7c062f27de8b20f301e5607563b2c904e3c7f113b1eeba8a4d1c82fc1a747c920bac6af9a9f4dae1744847232ea03289e25e482a50082825820478ad95cafe9b1660809d618870c86dda1295764e113886e2b8a1de2de5af17201825820f84508cc7674b663db84ceb9f0790f5527f3c70f2a05e4d7f783cd9890463b4e01018182583900ff7f04abbd3050c0b138c8fa3005d48aaf8b9700d4565758e91a95385667fab107f848cfd4b73a7407a7661600cf68f0efc969ece37665ae1a000f4240021a000f4240031a00ca60f1075820e845fe9180ac36cc0102f892a839ad1ed2ea9a52c605fb8e4e1c2774ef0bb65ba50081825820c4b5ad6873b8581c75b8ee52f58a3eded29acbbb92d874a64228a1ca4e68956700018182581d60daad04ed2b7f69e2a9be582e37091739fa036a14c1c22f88061d43c71b004aca96b58fd90c021a000f4240031a00d986900682a7581c0d06d2547ed371fdf95fb5c4c735eecdd53e6a5bb831561bd0fcfd3da10e820300581c2f56e87d67b8e5216582cfeb95dbdc9083110a3ef68faaa51bef3a80a10e820300581c2fca486b4d8f1a0432f5bf18ef473ee4294c795a1a32e3132bc6b90fa10e820300581c4ee98623920698b77c1c7f77288cbdac5f9011ff8970b1f507567d0da10e820300581c514e81afb082fce01678809eebd90eda4f7918354ec7d0433ad16274a10e820300581c581e23030b6038bae716e5d64b9e053db10541b12e6b0b4eff485454a10e820300581ce5f27655371b54aed91cc916b2569060978be80056768fee2cc5ce1ba10e820300186582a1008182582028364596385174f5eabc763031b8d54b18ed5d06967ff44b3abbdbaca9cb58a75840de49197fed8dd13716c88e68452fb314d418a24fee9cc194308bd47b057d161ae40cd8f49bf6b378e7343ee5d3a7b9bdb1f2e9efeef896adaa9eb7373fbb8502a1008882582032a954b521c0b19514408965831ef6839637de7a1a6168bcf8455c504ba93b9c5840ab2d59239499807e25dc8025940a70cb890a52e8f47f35004cfec623036ca9f5c3e925b32bd23a7d1d044cef915913e853dbb57438f9c92a5d5f9581caa67d098258207ec249d890d0aaf9a81207960c163ae2d6ac5e715ca6b96d5860e50d9f2b2b2a5840f2d8031ac5d79777076dd1176cb7ed91690fcfb6be498320e5de9afbf6ea8e8ced23bff69230d050523a4a7e03c2b0599e18e93b31959063249fb50274a02a068258204f4d7523e41e058a6cbdefb5538654ffc2a53416a7f5bb99f7eac699d42d5c1f5840c5844b849865fed81f67842a4697c3090cf4ecb50510f1e6b379b7c63b78417ca28ea653c016d2e733877e1605e8a1712c42404ca0686f67455c620431d54b07825820e764b0340d7b353f5f745891033774e4beab6aa1458a54ff29a1324c05bb9876584026c35f8ec2102ec8fcc3bd0a1a0760486952e147f44236a35c7d818a7024590e1395f097a0d046085ded24ec8c585008d3ffc0321ad040649ce08eb33614760e82582073ae41eca2be37fc15c55a50d668c8647e10bf222172c2d58abfa6e9310e596258402c3f197360294781841f0669822b0449515a5e0b77b23185652a1b0ea8354537b3e9335577a87fa19e9fe47f1039fa286aaa11859d631f3ff74564c6da14c806825820234fb2b8530114b461c6ca8242c8b86a226c95c4c27479ca850d1aea4a52d2985840ba751817e70695a041a5f455c08947fa4e3d6ffc332adeb25691fac4927bbaafd4b3f5f9855946ad9681083aec277766c7f90da7543e912f46aeae07fdd5b90a825820dfb615a61568d6867f45a85c32227f27025180d738a8a3d7fd3c929f624d72395840cc1f728cce6ce2fec21d2648011c14d244c35ba3cbd553593655f6f07d86b8bdf103d52b61143bc1701319517d4a24b778c02e983e02a0f3fd0cd558d472f009825820e5bc21a83616bcccfe343ec36b9dc4c06c90e913df1d8a0b046008651f42caa95840f85bc5e753beed04b3f9072da7a6adadcdb87769528c59e16162e86782b6ce11feacbd5de97e352121e9509a809f613d5bcebf7413fd55f89776c5606e4a9408a100a119534da261638158220a201f79b4d15fd971297a842ac6a4e953b82886df66c0d9723f5870e5725da6380b617601").unwrap();
+        let block = Block::from_bytes(bytes.clone()).unwrap();
+        let view = BlockView::new(&bytes).unwrap();
+        let header = view.header().unwrap();
+        assert_eq!(header.to_bytes(), block.header().to_bytes());
+        // the header commits to the hashes of the original bytes of the other parts
+        assert_eq!(view.body_hash(), header.header_body().block_body_hash());
+        let transactions = view.transactions().collect::<Result<Vec<_>, _>>().unwrap();
+        assert!(!transactions.is_empty());
+        assert_eq!(transactions.len(), block.transaction_bodies().len());
+        for (transaction, body) in transactions.iter().zip(block.transaction_bodies().0.iter()) {
+            assert_eq!(transaction.body().decode().unwrap().to_bytes(), body.to_bytes());
+            assert!(transaction.is_valid());
+            assert_eq!(
+                transaction.body().outputs().unwrap().map(|output| decode::<TransactionOutput>(output.unwrap()).unwrap()).collect::<Vec<_>>(),
+                body.outputs().0,
+            );
+        }
+    }
+}
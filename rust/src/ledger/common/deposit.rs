@@ -1,58 +1,121 @@
-use crate::{Withdrawals, Certificates, CertificateEnum, TransactionBody, error::JsError};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{Withdrawals, Certificates, CertificateEnum, StakeCredential, TransactionBody, crypto::Ed25519KeyHash, error::Error};
 
 use super::value::{BigNum, Value, to_bignum, Coin};
 
+/// What deposits depend on besides the transaction itself, as currently registered on chain
+///
+/// Implemented by `Emulator` and meant to be implemented on top of an indexer or node queries.
+/// Without it, every pool registration pays a deposit and refunds are assumed to be the current key_deposit
+pub trait DepositState: std::fmt::Debug + Send + Sync {
+    /// Re-registering a pool updates its parameters without paying another deposit
+    fn is_pool_registered(&self, pool: &Ed25519KeyHash) -> bool;
+    /// The deposit paid when `credential` was registered (refunded on deregistration even if key_deposit changed since)
+    /// or None if it isn't registered
+    fn stake_deposit(&self, credential: &StakeCredential) -> Option<Coin>;
+}
+
+/// Computes the deposits paid and refunded by the certificates of a transaction,
+/// following the ledger's rules when the chain state is known
+#[derive(Clone, Copy, Debug)]
+pub struct DepositCalculator<'a> {
+    pool_deposit: Coin, // protocol parameter
+    key_deposit: Coin, // protocol parameter
+    state: Option<&'a dyn DepositState>,
+}
+
+impl<'a> DepositCalculator<'a> {
+    pub fn new(pool_deposit: &Coin, key_deposit: &Coin) -> Self {
+        Self {
+            pool_deposit: *pool_deposit,
+            key_deposit: *key_deposit,
+            state: None,
+        }
+    }
+
+    pub fn with_state(self, state: &'a dyn DepositState) -> Self {
+        Self {
+            state: Some(state),
+            ..self
+        }
+    }
+
+    /// (deposit, refund) of each certificate, computed in order since they can depend on each other
+    pub fn certificate_deposits(&self, certs: &Option<Certificates>) -> Vec<(Coin, Coin)> {
+        // deposit of the credentials (de)registered earlier in this transaction
+        let mut stake_deposits = BTreeMap::<StakeCredential, Option<Coin>>::new();
+        let mut pools = BTreeSet::<Ed25519KeyHash>::new();
+        certs.iter().flat_map(|certs| certs.0.iter()).map(|cert| match &cert.0 {
+            CertificateEnum::StakeRegistration(cert) => {
+                stake_deposits.insert(cert.stake_credential.clone(), Some(self.key_deposit));
+                (self.key_deposit, Coin::zero())
+            },
+            CertificateEnum::StakeDeregistration(cert) => {
+                let paid = match stake_deposits.get(&cert.stake_credential) {
+                    Some(paid) => *paid,
+                    None => self.state.and_then(|state| state.stake_deposit(&cert.stake_credential)),
+                };
+                stake_deposits.insert(cert.stake_credential.clone(), None);
+                (Coin::zero(), paid.unwrap_or(self.key_deposit))
+            },
+            CertificateEnum::PoolRegistration(cert) => {
+                let operator = &cert.pool_params.operator;
+                let registered = pools.contains(operator) || matches!(self.state, Some(state) if state.is_pool_registered(operator));
+                pools.insert(operator.clone());
+                match registered {
+                    true => (Coin::zero(), Coin::zero()),
+                    false => (self.pool_deposit, Coin::zero()),
+                }
+            },
+            // the pool deposit goes back to its reward account when the retirement epoch starts, not in the transaction
+            _ => (Coin::zero(), Coin::zero()),
+        }).collect()
+    }
+
+    /// (deposits, refunds) of the certificates
+    fn certificate_balance(&self, certs: &Option<Certificates>) -> Result<(Coin, Coin), Error> {
+        self.certificate_deposits(certs)
+            .iter()
+            .try_fold((Coin::zero(), Coin::zero()), |(deposit, refund), (cert_deposit, cert_refund)| {
+                Ok((deposit.checked_add(cert_deposit)?, refund.checked_add(cert_refund)?))
+            })
+    }
+
+    pub fn deposit(&self, certs: &Option<Certificates>) -> Result<Coin, Error> {
+        Ok(self.certificate_balance(certs)?.0)
+    }
+
+    /// withdrawals and refunds
+    pub fn implicit_input(&self, withdrawals: &Option<Withdrawals>, certs: &Option<Certificates>) -> Result<Value, Error> {
+        let withdrawal_sum = match &withdrawals {
+            None => to_bignum(0),
+            Some(x) => x.0
+                .values()
+                .try_fold(
+                    to_bignum(0),
+                    |acc, withdrawal_amt| acc.checked_add(withdrawal_amt)
+                )?,
+        };
+        Ok(Value::new(&withdrawal_sum.checked_add(&self.certificate_balance(certs)?.1)?))
+    }
+}
+
 // wasm-bindgen can't accept Option without clearing memory, so we avoid exposing this in WASM
 pub fn internal_get_implicit_input(
     withdrawals: &Option<Withdrawals>,
     certs: &Option<Certificates>,
     pool_deposit: &BigNum, // // protocol parameter
     key_deposit: &BigNum, // protocol parameter
-) -> Result<Value, JsError> {
-    let withdrawal_sum = match &withdrawals {
-        None => to_bignum(0),
-        Some(x) => x.0
-            .values()
-            .try_fold(
-                to_bignum(0),
-                |acc, withdrawal_amt| acc.checked_add(withdrawal_amt)
-            )?,
-    };
-    let certificate_refund = match &certs {
-        None => to_bignum(0),
-        Some(certs) => certs.0
-            .iter()
-            .try_fold(
-                to_bignum(0),
-                |acc, cert| match &cert.0 {
-                    CertificateEnum::PoolRetirement(_cert) => acc.checked_add(pool_deposit),
-                    CertificateEnum::StakeDeregistration(_cert) => acc.checked_add(key_deposit),
-                    _ => Ok(acc),
-                }
-            )?
-    };
-
-    Ok(Value::new(&withdrawal_sum.checked_add(&certificate_refund)?))
+) -> Result<Value, Error> {
+    DepositCalculator::new(pool_deposit, key_deposit).implicit_input(withdrawals, certs)
 }
 pub fn internal_get_deposit(
     certs: &Option<Certificates>,
     pool_deposit: &BigNum, // // protocol parameter
     key_deposit: &BigNum, // protocol parameter
-) -> Result<Coin, JsError> {
-    let certificate_refund = match &certs {
-        None => to_bignum(0),
-        Some(certs) => certs.0
-            .iter()
-            .try_fold(
-                to_bignum(0),
-                |acc, cert| match &cert.0 {
-                    CertificateEnum::PoolRegistration(_cert) => acc.checked_add(pool_deposit),
-                    CertificateEnum::StakeRegistration(_cert) => acc.checked_add(key_deposit),
-                    _ => Ok(acc),
-                }
-            )?
-    };
-    Ok(certificate_refund)
+) -> Result<Coin, Error> {
+    DepositCalculator::new(pool_deposit, key_deposit).deposit(certs)
 }
 
 
@@ -61,7 +124,7 @@ pub fn get_implicit_input(
     txbody: &TransactionBody,
     pool_deposit: &BigNum, // // protocol parameter
     key_deposit: &BigNum, // protocol parameter
-) -> Result<Value, JsError> {
+) -> Result<Value, Error> {
     internal_get_implicit_input(
         &txbody.withdrawals,
         &txbody.certs,
@@ -75,10 +138,76 @@ pub fn get_deposit(
     txbody: &TransactionBody,
     pool_deposit: &BigNum, // // protocol parameter
     key_deposit: &BigNum, // protocol parameter
-) -> Result<Coin, JsError> {
+) -> Result<Coin, Error> {
     internal_get_deposit(
         &txbody.certs,
         pool_deposit,
         key_deposit,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[derive(Debug, Default)]
+    struct ChainState {
+        pools: BTreeSet<Ed25519KeyHash>,
+        stake: BTreeMap<StakeCredential, Coin>,
+    }
+
+    impl DepositState for ChainState {
+        fn is_pool_registered(&self, pool: &Ed25519KeyHash) -> bool {
+            self.pools.contains(pool)
+        }
+
+        fn stake_deposit(&self, credential: &StakeCredential) -> Option<Coin> {
+            self.stake.get(credential).cloned()
+        }
+    }
+
+    fn pool_registration(operator: &Ed25519KeyHash) -> Certificate {
+        Certificate::new_pool_registration(&PoolRegistration::new(&PoolParams::new(
+            operator,
+            &VRFKeyHash::from([0u8; VRFKeyHash::BYTE_COUNT]),
+            &to_bignum(0),
+            &to_bignum(340),
+            &UnitInterval::new(&to_bignum(1), &to_bignum(100)),
+            &RewardAddress::new(0, &StakeCredential::from_keyhash(operator)),
+            &Ed25519KeyHashes::new(),
+            &Relays::new(),
+            None,
+        )))
+    }
+
+    #[test]
+    fn deposits_with_chain_state() {
+        let (registered_pool, new_pool) = (Ed25519KeyHash::from([1u8; 28]), Ed25519KeyHash::from([2u8; 28]));
+        let (old_stake, new_stake) = (StakeCredential::from_keyhash(&Ed25519KeyHash::from([3u8; 28])), StakeCredential::from_keyhash(&Ed25519KeyHash::from([4u8; 28])));
+        let mut state = ChainState::default();
+        state.pools.insert(registered_pool.clone());
+        // registered when key_deposit was 2
+        state.stake.insert(old_stake.clone(), to_bignum(2));
+
+        let mut certs = Certificates::new();
+        certs.add(&pool_registration(&registered_pool));
+        certs.add(&pool_registration(&new_pool));
+        certs.add(&pool_registration(&new_pool));
+        certs.add(&Certificate::new_stake_deregistration(&StakeDeregistration::new(&old_stake)));
+        certs.add(&Certificate::new_stake_registration(&StakeRegistration::new(&new_stake)));
+        certs.add(&Certificate::new_stake_deregistration(&StakeDeregistration::new(&new_stake)));
+        certs.add(&Certificate::new_pool_retirement(&PoolRetirement::new(&registered_pool, 5)));
+        let certs = Some(certs);
+
+        let calculator = DepositCalculator::new(&to_bignum(500), &to_bignum(3));
+        // without state: pools registered before this transaction pay again, refunds are at the current key_deposit
+        // and retirements refund nothing
+        assert_eq!(calculator.deposit(&certs).unwrap(), to_bignum(500 * 2 + 3));
+        assert_eq!(calculator.implicit_input(&None, &certs).unwrap().coin(), to_bignum(3 + 3));
+
+        let calculator = calculator.with_state(&state);
+        assert_eq!(calculator.deposit(&certs).unwrap(), to_bignum(500 + 3));
+        assert_eq!(calculator.implicit_input(&None, &certs).unwrap().coin(), to_bignum(2 + 3));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{TransactionBody, metadata::AuxiliaryData, crypto::{AuxiliaryDataHash, blake2b256, DataHash, TransactionHash, ScriptDataHash, blake2b224, ScriptHash, self}, plutus::{PlutusData, Redeemers, Costmdls, PlutusList, Languages}, error::JsError};
+use crate::{TransactionBody, metadata::AuxiliaryData, crypto::{AuxiliaryDataHash, blake2b256, DataHash, PoolMetadataHash, TransactionHash, ScriptDataHash, blake2b224, ScriptHash, self}, plutus::{PlutusData, Redeemers, Costmdls, PlutusList, Languages}, error::Error};
 
 
 
@@ -10,6 +10,11 @@ pub fn hash_transaction(tx_body: &TransactionBody) -> TransactionHash {
     TransactionHash::from(crypto::blake2b256(tx_body.to_bytes().as_ref()))
 }
 
+/// Pool metadata is hashed as the exact bytes hosted at its URL, not a re-serialization of the JSON
+pub fn hash_pool_metadata(metadata_bytes: &[u8]) -> PoolMetadataHash {
+    PoolMetadataHash::from(blake2b256(metadata_bytes))
+}
+
 pub fn hash_plutus_data(plutus_data: &PlutusData) -> DataHash {
     DataHash::from(blake2b256(&plutus_data.to_bytes()))
 }
@@ -46,7 +51,7 @@ pub fn hash_script_data(redeemers: &Redeemers, cost_models: &Costmdls, datums: O
 }
 
 
-pub fn calc_script_data_hash(redeemers: &Redeemers, datums: &PlutusList, cost_models: &Costmdls, used_langs: &Languages) -> Result<Option<ScriptDataHash>, JsError> {
+pub fn calc_script_data_hash(redeemers: &Redeemers, datums: &PlutusList, cost_models: &Costmdls, used_langs: &Languages) -> Result<Option<ScriptDataHash>, Error> {
     if redeemers.len() > 0 || datums.len() > 0 {
         let mut required_costmdls = Costmdls::new();
         for lang in &used_langs.0 {
@@ -55,7 +60,7 @@ pub fn calc_script_data_hash(redeemers: &Redeemers, datums: &PlutusList, cost_mo
                 &cost_models
                     .get(lang)
                     .ok_or_else(|| {
-                        JsError::from_str(&format!("Cost model missing for plutus {:?}", lang))
+                        Error::from_str(&format!("Cost model missing for plutus {:?}", lang))
                     })?,
             );
         }
@@ -80,7 +85,8 @@ pub fn calc_script_data_hash(redeemers: &Redeemers, datums: &PlutusList, cost_mo
 pub enum ScriptHashNamespace {
     NativeScript,
     PlutusV1,
-    PlutusV2
+    PlutusV2,
+    PlutusV3,
 }
 
 pub (crate) fn hash_script(namespace: ScriptHashNamespace, script: Vec<u8>) -> ScriptHash {
@@ -0,0 +1,660 @@
+//! Checks transaction bodies against the restrictions of hardware wallets (Ledger, Trezor) before
+//! they are sent to the device, and maps their inputs and outputs to the BIP-32 paths the devices need.
+//!
+//! Devices sign in one of several modes, each only allowing some transaction contents: e.g. a pool
+//! registration must be the only certificate of its transaction, and it can only be signed in a pool
+//! registration mode. They also expect canonical CBOR, since they re-serialize the body to hash it.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+use schemars::JsonSchema;
+
+use crate::*;
+use crate::error::Error;
+
+use super::utxo::TransactionUnspentOutputs;
+
+/// How the device is asked to sign, which determines what the transaction may contain
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HwSigningMode {
+    /// spending from key addresses: no script credentials, collateral or reference inputs
+    OrdinaryTransaction,
+    /// native script wallets: certificates and withdrawals only with script credentials
+    MultisigTransaction,
+    /// the device only witnesses as one of the owners of the registered pool
+    PoolRegistrationAsOwner,
+    /// the device holds the cold key of the registered pool
+    PoolRegistrationAsOperator,
+    /// anything but pool registrations, which comes at the cost of less being shown on the device
+    PlutusTransaction,
+}
+
+impl HwSigningMode {
+    fn is_pool_registration(&self) -> bool {
+        matches!(self, HwSigningMode::PoolRegistrationAsOwner | HwSigningMode::PoolRegistrationAsOperator)
+    }
+}
+
+/// Size limits of output contents. Devices have tighter limits on what they can show than the ledger,
+/// which depend on the model and firmware version
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct HwLimits {
+    /// size of the CBOR encoded inline datum
+    pub max_inline_datum_size: usize,
+    /// size of the CBOR encoded reference script
+    pub max_reference_script_size: usize,
+}
+
+impl Default for HwLimits {
+    /// the mainnet max_tx_size, which no output can exceed anyway
+    fn default() -> Self {
+        Self {
+            max_inline_datum_size: 16384,
+            max_reference_script_size: 16384,
+        }
+    }
+}
+
+/// Where in the transaction body a violation was found
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "index", rename_all = "snake_case")]
+pub enum HwLocation {
+    Output(usize),
+    CollateralReturn,
+    Certificate(usize),
+    /// index in the canonical order of the withdrawals
+    Withdrawal(usize),
+    Mint,
+}
+
+impl fmt::Display for HwLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HwLocation::Output(index) => write!(f, "output #{}", index),
+            HwLocation::CollateralReturn => write!(f, "collateral return"),
+            HwLocation::Certificate(index) => write!(f, "certificate #{}", index),
+            HwLocation::Withdrawal(index) => write!(f, "withdrawal #{}", index),
+            HwLocation::Mint => write!(f, "mint"),
+        }
+    }
+}
+
+/// A reason for the device to reject the transaction
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HwViolation {
+    /// the bytes differ from the canonical encoding of the body, see canonicalize_transaction_body
+    NonCanonicalCbor,
+    /// a body field the signing mode doesn't allow
+    FieldNotAllowed { field: String },
+    /// certificates no device supports (genesis key delegations and MIRs)
+    UnsupportedCertificate { index: usize },
+    /// a certificate the signing mode doesn't allow, including anything next to a pool registration
+    CertificateNotAllowed { index: usize },
+    MissingPoolRegistration,
+    /// script credentials outside the multisig and Plutus modes
+    ScriptCredentialNotAllowed { location: HwLocation },
+    /// key credentials in the multisig mode
+    KeyCredentialNotAllowed { location: HwLocation },
+    DatumNotAllowed { location: HwLocation },
+    ReferenceScriptNotAllowed { location: HwLocation },
+    InlineDatumTooLarge { location: HwLocation, size: usize, max: usize },
+    ReferenceScriptTooLarge { location: HwLocation, size: usize, max: usize },
+    /// a policy without assets, which canonicalize_transaction_body removes
+    EmptyAssetGroup { location: HwLocation },
+}
+
+impl fmt::Display for HwViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HwViolation::NonCanonicalCbor => write!(f, "transaction body is not canonical CBOR"),
+            HwViolation::FieldNotAllowed { field } => write!(f, "{} not allowed in this signing mode", field),
+            HwViolation::UnsupportedCertificate { index } => write!(f, "certificate #{} is not supported by hardware wallets", index),
+            HwViolation::CertificateNotAllowed { index } => write!(f, "certificate #{} not allowed in this signing mode", index),
+            HwViolation::MissingPoolRegistration => write!(f, "pool registration signing modes require a pool registration certificate"),
+            HwViolation::ScriptCredentialNotAllowed { location } => write!(f, "script credential of {} not allowed in this signing mode", location),
+            HwViolation::KeyCredentialNotAllowed { location } => write!(f, "key credential of {} not allowed in this signing mode", location),
+            HwViolation::DatumNotAllowed { location } => write!(f, "datum of {} not allowed in this signing mode", location),
+            HwViolation::ReferenceScriptNotAllowed { location } => write!(f, "reference script of {} not allowed in this signing mode", location),
+            HwViolation::InlineDatumTooLarge { location, size, max } => write!(f, "inline datum of {} is {} bytes, more than {}", location, size, max),
+            HwViolation::ReferenceScriptTooLarge { location, size, max } => write!(f, "reference script of {} is {} bytes, more than {}", location, size, max),
+            HwViolation::EmptyAssetGroup { location } => write!(f, "{} has a policy without assets", location),
+        }
+    }
+}
+
+fn sorted_withdrawals(withdrawals: &Withdrawals) -> Vec<(RewardAddress, Coin)> {
+    let mut sorted = withdrawals.0
+        .iter()
+        .map(|(address, amount)| (address.clone(), *amount))
+        .collect::<Vec<_>>();
+    // reward addresses are all the same length, so their canonical order is the order of their bytes
+    sorted.sort_by_key(|(address, _)| address.to_address().to_bytes());
+    sorted
+}
+
+fn check_credential(mode: HwSigningMode, credential: &StakeCredential, location: HwLocation, violations: &mut Vec<HwViolation>) {
+    match (mode, credential.kind()) {
+        (HwSigningMode::MultisigTransaction, StakeCredKind::Key) => violations.push(HwViolation::KeyCredentialNotAllowed { location }),
+        (HwSigningMode::MultisigTransaction, StakeCredKind::Script) | (HwSigningMode::PlutusTransaction, _) => (),
+        (_, StakeCredKind::Script) => violations.push(HwViolation::ScriptCredentialNotAllowed { location }),
+        (_, StakeCredKind::Key) => (),
+    }
+}
+
+fn check_output(mode: HwSigningMode, limits: &HwLimits, output: &TransactionOutput, location: HwLocation, violations: &mut Vec<HwViolation>) {
+    if let Some(datum) = &output.datum_option {
+        if mode.is_pool_registration() {
+            violations.push(HwViolation::DatumNotAllowed { location: location.clone() });
+        }
+        if let DatumEnum::InlineDatum(data) = datum {
+            let size = data.to_bytes().len();
+            if size > limits.max_inline_datum_size {
+                violations.push(HwViolation::InlineDatumTooLarge { location: location.clone(), size, max: limits.max_inline_datum_size });
+            }
+        }
+    }
+    if let Some(script_ref) = &output.script_ref {
+        if mode.is_pool_registration() {
+            violations.push(HwViolation::ReferenceScriptNotAllowed { location: location.clone() });
+        }
+        let size = script_ref.to_bytes().len();
+        if size > limits.max_reference_script_size {
+            violations.push(HwViolation::ReferenceScriptTooLarge { location: location.clone(), size, max: limits.max_reference_script_size });
+        }
+    }
+    if let Some(multiasset) = &output.amount.multiasset {
+        if multiasset.0.values().any(|assets| assets.0.is_empty()) {
+            violations.push(HwViolation::EmptyAssetGroup { location });
+        }
+    }
+}
+
+fn check_certificates(mode: HwSigningMode, certs: &[Certificate], violations: &mut Vec<HwViolation>) {
+    let mut pool_registrations = 0;
+    for (index, cert) in certs.iter().enumerate() {
+        let location = HwLocation::Certificate(index);
+        let reported = violations.len();
+        match &cert.0 {
+            CertificateEnum::StakeRegistration(cert) => check_credential(mode, &cert.stake_credential, location, violations),
+            CertificateEnum::StakeDeregistration(cert) => check_credential(mode, &cert.stake_credential, location, violations),
+            CertificateEnum::StakeDelegation(cert) => check_credential(mode, &cert.stake_credential, location, violations),
+            CertificateEnum::PoolRegistration(_) => {
+                pool_registrations += 1;
+                if !mode.is_pool_registration() || pool_registrations > 1 {
+                    violations.push(HwViolation::CertificateNotAllowed { index });
+                }
+            },
+            // signed with the pool's cold key, which only the ordinary mode can do
+            CertificateEnum::PoolRetirement(_) => if mode != HwSigningMode::OrdinaryTransaction {
+                violations.push(HwViolation::CertificateNotAllowed { index });
+            },
+            CertificateEnum::GenesisKeyDelegation(_) |
+            CertificateEnum::MoveInstantaneousRewardsCert(_) => violations.push(HwViolation::UnsupportedCertificate { index }),
+        }
+        // the pool registration must be alone, which is only worth reporting for otherwise allowed certificates
+        let rejected = violations[reported..]
+            .iter()
+            .any(|violation| matches!(violation, HwViolation::CertificateNotAllowed { .. } | HwViolation::UnsupportedCertificate { .. }));
+        if mode.is_pool_registration() && !rejected && !matches!(cert.0, CertificateEnum::PoolRegistration(_)) {
+            violations.push(HwViolation::CertificateNotAllowed { index });
+        }
+    }
+    if mode.is_pool_registration() && pool_registrations == 0 {
+        violations.push(HwViolation::MissingPoolRegistration);
+    }
+}
+
+/// Every reason for a device to reject {body} when signing it in {mode}.
+/// Doesn't check the encoding, for which see check_transaction_body_bytes
+pub fn check_transaction_body(body: &TransactionBody, mode: HwSigningMode, limits: &HwLimits) -> Vec<HwViolation> {
+    let mut violations = Vec::new();
+    let mut forbid = |present: bool, field: &str| if present {
+        violations.push(HwViolation::FieldNotAllowed { field: String::from(field) });
+    };
+    forbid(body.update.is_some(), "update");
+    if mode != HwSigningMode::PlutusTransaction {
+        forbid(body.collateral.is_some(), "collateral");
+        forbid(body.collateral_return.is_some(), "collateral_return");
+        forbid(body.total_collateral.is_some(), "total_collateral");
+        forbid(body.reference_inputs.is_some(), "reference_inputs");
+    }
+    if mode.is_pool_registration() {
+        forbid(body.withdrawals.is_some(), "withdrawals");
+        forbid(body.mint.is_some(), "mint");
+        forbid(body.script_data_hash.is_some(), "script_data_hash");
+        forbid(body.required_signers.is_some(), "required_signers");
+    }
+
+    for (index, output) in body.outputs.0.iter().enumerate() {
+        check_output(mode, limits, output, HwLocation::Output(index), &mut violations);
+    }
+    if let Some(collateral_return) = &body.collateral_return {
+        check_output(mode, limits, collateral_return, HwLocation::CollateralReturn, &mut violations);
+    }
+    match &body.certs {
+        Some(certs) => check_certificates(mode, &certs.0, &mut violations),
+        None => check_certificates(mode, &[], &mut violations),
+    }
+    if let Some(withdrawals) = &body.withdrawals {
+        for (index, (address, _)) in sorted_withdrawals(withdrawals).iter().enumerate() {
+            check_credential(mode, &address.payment_cred(), HwLocation::Withdrawal(index), &mut violations);
+        }
+    }
+    if let Some(mint) = &body.mint {
+        if mint.0.values().any(|assets| assets.0.is_empty()) {
+            violations.push(HwViolation::EmptyAssetGroup { location: HwLocation::Mint });
+        }
+    }
+    violations
+}
+
+/// Like check_transaction_body, also reporting a non-canonical encoding of the body
+pub fn check_transaction_body_bytes(bytes: &[u8], mode: HwSigningMode, limits: &HwLimits) -> Result<Vec<HwViolation>, Error> {
+    let body = TransactionBody::from_bytes(bytes.to_vec())
+        .map_err(|e| Error::from_str(&format!("invalid transaction body: {}", e.as_string().unwrap_or_default())))?;
+    let mut violations = check_transaction_body(&body, mode, limits);
+    if canonicalize_transaction_body(&body).to_bytes() != bytes {
+        violations.insert(0, HwViolation::NonCanonicalCbor);
+    }
+    Ok(violations)
+}
+
+/// The body whose serialization is canonical CBOR: withdrawals sorted and empty asset groups removed.
+/// Inline datums keep their encoding, as changing it would change their hash
+pub fn canonicalize_transaction_body(body: &TransactionBody) -> TransactionBody {
+    let mut canonical = body.clone();
+    if let Some(withdrawals) = &body.withdrawals {
+        canonical.withdrawals = Some(Withdrawals(sorted_withdrawals(withdrawals).into_iter().collect()));
+    }
+    let canonical_output = |output: &mut TransactionOutput| if let Some(multiasset) = &mut output.amount.multiasset {
+        multiasset.0.retain(|_, assets| !assets.0.is_empty());
+    };
+    canonical.outputs.0.iter_mut().for_each(canonical_output);
+    canonical.collateral_return.iter_mut().for_each(canonical_output);
+    if let Some(mint) = &mut canonical.mint {
+        mint.0.retain(|_, assets| !assets.0.is_empty());
+    }
+    canonical
+}
+
+/// A BIP-32 derivation path, serialized as the array of indices that Ledger and Trezor libraries take
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct Bip32Path(Vec<u32>);
+
+to_from_json!(Bip32Path);
+
+impl Bip32Path {
+    pub const HARDENED: u32 = 0x80000000;
+
+    pub fn new(indices: Vec<u32>) -> Self {
+        Self(indices)
+    }
+
+    /// m/1852'/1815'/{account}'
+    pub fn cip1852_account(account: u32) -> Self {
+        Self(vec![1852 | Self::HARDENED, 1815 | Self::HARDENED, account | Self::HARDENED])
+    }
+
+    pub fn indices(&self) -> Vec<u32> {
+        self.0.clone()
+    }
+
+    pub fn derive(&self, index: u32) -> Self {
+        let mut indices = self.0.clone();
+        indices.push(index);
+        Self(indices)
+    }
+}
+
+impl FromStr for Bip32Path {
+    type Err = Error;
+
+    /// e.g. m/1852'/1815'/0'/0/0. Hardened indices may also end with h or H
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let mut segments = path.split('/');
+        if segments.next() != Some("m") {
+            return Err(Error::from_str(&format!("derivation path \"{}\" doesn't start with m", path)));
+        }
+        segments
+            .map(|segment| {
+                let (index, hardened) = match segment.strip_suffix(|c| c == '\'' || c == 'h' || c == 'H') {
+                    Some(index) => (index, true),
+                    None => (segment, false),
+                };
+                match index.parse::<u32>() {
+                    Ok(index) if index < Self::HARDENED => Ok(if hardened { index | Self::HARDENED } else { index }),
+                    _ => Err(Error::from_str(&format!("invalid index \"{}\" in derivation path \"{}\"", segment, path))),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+}
+
+impl fmt::Display for Bip32Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.0 {
+            match index & Self::HARDENED {
+                0 => write!(f, "/{}", index)?,
+                _ => write!(f, "/{}'", index & !Self::HARDENED)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The keys a device holds, by hash
+#[derive(Clone, Debug, Default)]
+pub struct HwKeyPaths(BTreeMap<Ed25519KeyHash, Bip32Path>);
+
+map_iter!(HwKeyPaths, std::collections::btree_map, Ed25519KeyHash, Bip32Path);
+
+impl HwKeyPaths {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, key_hash: &Ed25519KeyHash, path: &Bip32Path) -> Option<Bip32Path> {
+        self.0.insert(key_hash.clone(), path.clone())
+    }
+
+    pub fn get(&self, key_hash: &Ed25519KeyHash) -> Option<Bip32Path> {
+        self.0.get(key_hash).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The first {gap} external, internal and staking keys of the CIP-1852 account whose public key
+    /// the device exported
+    pub fn insert_account(&mut self, account_key: &Bip32PublicKey, account_path: &Bip32Path, gap: u32) -> Result<(), Error> {
+        for role in 0..=2 {
+            let role_key = account_key.derive(role)?;
+            for index in 0..gap {
+                let key_hash = role_key.derive(index)?.to_raw_key().hash();
+                self.0.insert(key_hash, account_path.derive(role).derive(index));
+            }
+        }
+        Ok(())
+    }
+
+    fn credential(&self, credential: &StakeCredential) -> Option<Bip32Path> {
+        credential.to_keyhash().and_then(|key_hash| self.get(&key_hash))
+    }
+}
+
+/// Paths of the payment and stake keys of an address, for devices to recognize their own change outputs
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct HwAddressPaths {
+    pub payment: Option<Bip32Path>,
+    pub stake: Option<Bip32Path>,
+}
+
+impl HwAddressPaths {
+    fn new(address: &Address, keys: &HwKeyPaths) -> Self {
+        Self {
+            payment: address.payment_cred().and_then(|credential| keys.credential(&credential)),
+            stake: address.staking_cred().and_then(|credential| keys.credential(&credential)),
+        }
+    }
+}
+
+/// The paths of the device's keys in a transaction body, in the order of its fields. None where the
+/// key isn't the device's or, for inputs, where their UTxO wasn't given
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct HwPathMapping {
+    pub inputs: Vec<Option<Bip32Path>>,
+    pub collateral: Vec<Option<Bip32Path>>,
+    pub outputs: Vec<HwAddressPaths>,
+    pub collateral_return: Option<HwAddressPaths>,
+    /// stake credentials, the operator of pool registrations and the pool of retirements
+    pub certificates: Vec<Option<Bip32Path>>,
+    /// in the canonical order of the withdrawals
+    pub withdrawals: Vec<Option<Bip32Path>>,
+    pub required_signers: Vec<Option<Bip32Path>>,
+    /// owners of the registered pools, in order
+    pub pool_owners: Vec<Option<Bip32Path>>,
+    /// the paths of the device's keys which witness the transaction, without duplicates
+    pub witnesses: Vec<Bip32Path>,
+}
+
+to_from_json!(HwPathMapping);
+
+impl HwPathMapping {
+    /// {utxos} must contain the outputs spent by the inputs and collateral to map them
+    pub fn new(body: &TransactionBody, utxos: &TransactionUnspentOutputs, keys: &HwKeyPaths) -> Self {
+        let resolved = utxos.0
+            .iter()
+            .map(|utxo| (&utxo.input, &utxo.output))
+            .collect::<BTreeMap<_, _>>();
+        let input_path = |input: &TransactionInput| resolved
+            .get(input)
+            .and_then(|output| output.address.payment_cred())
+            .and_then(|credential| keys.credential(&credential));
+        let mut witnesses = BTreeSet::new();
+        let mut witness = |path: Option<Bip32Path>| {
+            witnesses.extend(path.clone());
+            path
+        };
+
+        let inputs = body.inputs.0.iter().map(|input| witness(input_path(input))).collect();
+        let collateral = body.collateral
+            .iter()
+            .flat_map(|collateral| collateral.0.iter())
+            .map(|input| witness(input_path(input)))
+            .collect();
+        let mut pool_owners = Vec::new();
+        let certificates = body.certs
+            .iter()
+            .flat_map(|certs| certs.0.iter())
+            .map(|cert| match &cert.0 {
+                // registrations don't need the key's witness
+                CertificateEnum::StakeRegistration(cert) => keys.credential(&cert.stake_credential),
+                CertificateEnum::StakeDeregistration(cert) => witness(keys.credential(&cert.stake_credential)),
+                CertificateEnum::StakeDelegation(cert) => witness(keys.credential(&cert.stake_credential)),
+                CertificateEnum::PoolRegistration(cert) => {
+                    for owner in &cert.pool_params.pool_owners.0 {
+                        pool_owners.push(witness(keys.get(owner)));
+                    }
+                    witness(keys.get(&cert.pool_params.operator))
+                },
+                CertificateEnum::PoolRetirement(cert) => witness(keys.get(&cert.pool_keyhash)),
+                CertificateEnum::GenesisKeyDelegation(_) |
+                CertificateEnum::MoveInstantaneousRewardsCert(_) => None,
+            })
+            .collect();
+        let withdrawals = body.withdrawals
+            .iter()
+            .flat_map(sorted_withdrawals)
+            .map(|(address, _)| witness(keys.credential(&address.payment_cred())))
+            .collect();
+        let required_signers = body.required_signers
+            .iter()
+            .flat_map(|signers| signers.0.iter())
+            .map(|signer| witness(keys.get(signer)))
+            .collect();
+
+        Self {
+            inputs,
+            collateral,
+            outputs: body.outputs.0.iter().map(|output| HwAddressPaths::new(&output.address, keys)).collect(),
+            collateral_return: body.collateral_return.as_ref().map(|output| HwAddressPaths::new(&output.address, keys)),
+            certificates,
+            withdrawals,
+            required_signers,
+            pool_owners,
+            witnesses: witnesses.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::Bip32PrivateKey;
+    use crate::ledger::common::utxo::TransactionUnspentOutput;
+    use crate::ledger::common::value::{to_bignum, Value};
+
+    fn account() -> (Bip32PublicKey, Bip32Path) {
+        let root = Bip32PrivateKey::from_bip39_entropy(&[0x0c; 16], &[]);
+        let path = Bip32Path::cip1852_account(0);
+        let account = path.0.iter().fold(root, |key, index| key.derive(*index));
+        (account.to_public(), path)
+    }
+
+    fn key_hash(account: &Bip32PublicKey, role: u32, index: u32) -> Ed25519KeyHash {
+        account.derive(role).unwrap().derive(index).unwrap().to_raw_key().hash()
+    }
+
+    #[test]
+    fn derivation_paths() {
+        let path = Bip32Path::from_str("m/1852'/1815'/0'/2/0").unwrap();
+        assert_eq!(path, Bip32Path::cip1852_account(0).derive(2).derive(0));
+        assert_eq!(path.to_string(), "m/1852'/1815'/0'/2/0");
+        assert_eq!(Bip32Path::from_str("m/1852H/1815h/0'/2/0").unwrap(), path);
+        assert_eq!(path.to_json().unwrap(), "[\n  2147485500,\n  2147485463,\n  2147483648,\n  2,\n  0\n]");
+        assert!(Bip32Path::from_str("1852'/1815'").is_err());
+        assert!(Bip32Path::from_str("m/2147483648").is_err());
+        assert!(Bip32Path::from_str("m/x").is_err());
+    }
+
+    #[test]
+    fn ordinary_and_multisig_modes() {
+        let (account, _) = account();
+        let script_hash = ScriptHash::from([3; ScriptHash::BYTE_COUNT]);
+        let key_cred = StakeCredential::from_keyhash(&key_hash(&account, 2, 0));
+        let script_cred = StakeCredential::from_scripthash(&script_hash);
+        let address = EnterpriseAddress::new(0, &StakeCredential::from_keyhash(&key_hash(&account, 0, 0))).to_address();
+        let input = TransactionInput::new(&TransactionHash::from([0; TransactionHash::BYTE_COUNT]), &to_bignum(0));
+        let mut inputs = TransactionInputs::new();
+        inputs.add(&input);
+        let mut outputs = TransactionOutputs::new();
+        outputs.add(&TransactionOutput::new(&address, &Value::new(&to_bignum(1_000_000))));
+        let mut body = TransactionBody::new(&inputs, &outputs, &to_bignum(200_000), None);
+
+        let mut certs = Certificates::new();
+        certs.add(&Certificate::new_stake_registration(&StakeRegistration::new(&key_cred)));
+        certs.add(&Certificate::new_stake_delegation(&StakeDelegation::new(&script_cred, &Ed25519KeyHash::from([9; Ed25519KeyHash::BYTE_COUNT]))));
+        body.set_certs(&certs);
+        let mut withdrawals = Withdrawals::new();
+        withdrawals.insert(&RewardAddress::new(0, &script_cred), &to_bignum(10));
+        withdrawals.insert(&RewardAddress::new(0, &key_cred), &to_bignum(20));
+        body.set_withdrawals(&withdrawals);
+        body.set_collateral(&inputs);
+
+        let limits = HwLimits::default();
+        assert_eq!(check_transaction_body(&body, HwSigningMode::OrdinaryTransaction, &limits), vec![
+            HwViolation::FieldNotAllowed { field: String::from("collateral") },
+            HwViolation::ScriptCredentialNotAllowed { location: HwLocation::Certificate(1) },
+            HwViolation::ScriptCredentialNotAllowed { location: HwLocation::Withdrawal(1) },
+        ]);
+        assert_eq!(check_transaction_body(&body, HwSigningMode::MultisigTransaction, &limits), vec![
+            HwViolation::FieldNotAllowed { field: String::from("collateral") },
+            HwViolation::KeyCredentialNotAllowed { location: HwLocation::Certificate(0) },
+            HwViolation::KeyCredentialNotAllowed { location: HwLocation::Withdrawal(0) },
+        ]);
+        assert_eq!(check_transaction_body(&body, HwSigningMode::PlutusTransaction, &limits), vec![]);
+
+        // key reward addresses (header 0xe0) come before script ones (0xf0) in the canonical order
+        let canonical = canonicalize_transaction_body(&body);
+        assert_eq!(canonical.withdrawals().unwrap().keys().0, vec![RewardAddress::new(0, &key_cred), RewardAddress::new(0, &script_cred)]);
+        assert_eq!(check_transaction_body_bytes(&canonical.to_bytes(), HwSigningMode::PlutusTransaction, &limits).unwrap(), vec![]);
+        assert_eq!(check_transaction_body_bytes(&body.to_bytes(), HwSigningMode::PlutusTransaction, &limits).unwrap(), vec![HwViolation::NonCanonicalCbor]);
+
+        let mut keys = HwKeyPaths::new();
+        let (account, account_path) = self::account();
+        keys.insert_account(&account, &account_path, 5).unwrap();
+        assert_eq!(keys.len(), 15);
+        let mut utxos = TransactionUnspentOutputs::new();
+        utxos.add(&TransactionUnspentOutput::new(&input, &TransactionOutput::new(&address, &Value::new(&to_bignum(2_000_000)))));
+        let mapping = HwPathMapping::new(&canonical, &utxos, &keys);
+        let payment_path = account_path.derive(0).derive(0);
+        let stake_path = account_path.derive(2).derive(0);
+        assert_eq!(mapping.inputs, vec![Some(payment_path.clone())]);
+        assert_eq!(mapping.outputs, vec![HwAddressPaths { payment: Some(payment_path.clone()), stake: None }]);
+        assert_eq!(mapping.certificates, vec![Some(stake_path.clone()), None]);
+        assert_eq!(mapping.withdrawals, vec![Some(stake_path.clone()), None]);
+        assert_eq!(mapping.witnesses, vec![payment_path, stake_path]);
+        assert_eq!(HwPathMapping::from_json(&mapping.to_json().unwrap()).unwrap(), mapping);
+    }
+
+    #[test]
+    fn pool_registration_modes() {
+        let (account, account_path) = account();
+        let owner = key_hash(&account, 2, 0);
+        let operator = Ed25519KeyHash::from([1; Ed25519KeyHash::BYTE_COUNT]);
+        let mut owners = Ed25519KeyHashes::new();
+        owners.add(&owner);
+        let params = PoolParams::new(
+            &operator,
+            &VRFKeyHash::from([2; VRFKeyHash::BYTE_COUNT]),
+            &to_bignum(1_000_000),
+            &to_bignum(340_000_000),
+            &UnitInterval::new(&to_bignum(1), &to_bignum(100)),
+            &RewardAddress::new(0, &StakeCredential::from_keyhash(&owner)),
+            &owners,
+            &Relays::new(),
+            None,
+        );
+        let address = EnterpriseAddress::new(0, &StakeCredential::from_keyhash(&operator)).to_address();
+        let mut output = TransactionOutput::new(&address, &Value::new(&to_bignum(1_000_000)));
+        output.set_datum(&Datum::new_data(&PlutusData::new_bytes(vec![0; 100])));
+        let mut outputs = TransactionOutputs::new();
+        outputs.add(&output);
+        let mut body = TransactionBody::new(&TransactionInputs::new(), &outputs, &to_bignum(200_000), None);
+        let mut certs = Certificates::new();
+        certs.add(&Certificate::new_pool_registration(&PoolRegistration::new(&params)));
+        certs.add(&Certificate::new_stake_registration(&StakeRegistration::new(&StakeCredential::from_keyhash(&owner))));
+        body.set_certs(&certs);
+
+        let limits = HwLimits { max_inline_datum_size: 64, ..HwLimits::default() };
+        assert_eq!(check_transaction_body(&body, HwSigningMode::PoolRegistrationAsOwner, &limits), vec![
+            HwViolation::DatumNotAllowed { location: HwLocation::Output(0) },
+            HwViolation::InlineDatumTooLarge { location: HwLocation::Output(0), size: 106, max: 64 },
+            HwViolation::CertificateNotAllowed { index: 1 },
+        ]);
+        assert_eq!(check_transaction_body(&body, HwSigningMode::OrdinaryTransaction, &limits), vec![
+            HwViolation::InlineDatumTooLarge { location: HwLocation::Output(0), size: 106, max: 64 },
+            HwViolation::CertificateNotAllowed { index: 0 },
+        ]);
+
+        // one violation for each certificate, whatever the reason it's not allowed
+        certs.add(&Certificate::new_pool_retirement(&PoolRetirement::new(&operator, 100)));
+        certs.add(&Certificate::new_move_instantaneous_rewards_cert(&MoveInstantaneousRewardsCert::new(
+            &MoveInstantaneousReward::new_to_other_pot(MIRPot::Reserves, &to_bignum(1)),
+        )));
+        body.set_certs(&certs);
+        let certificate_violations = check_transaction_body(&body, HwSigningMode::PoolRegistrationAsOperator, &limits)
+            .into_iter()
+            .filter(|violation| matches!(violation, HwViolation::CertificateNotAllowed { .. } | HwViolation::UnsupportedCertificate { .. }))
+            .collect::<Vec<_>>();
+        assert_eq!(certificate_violations, vec![
+            HwViolation::CertificateNotAllowed { index: 1 },
+            HwViolation::CertificateNotAllowed { index: 2 },
+            HwViolation::UnsupportedCertificate { index: 3 },
+        ]);
+
+        body.set_certs(&Certificates::new());
+        assert!(check_transaction_body(&body, HwSigningMode::PoolRegistrationAsOperator, &limits).contains(&HwViolation::MissingPoolRegistration));
+
+        certs = Certificates::new();
+        certs.add(&Certificate::new_pool_registration(&PoolRegistration::new(&params)));
+        body.set_certs(&certs);
+        let mut keys = HwKeyPaths::new();
+        keys.insert_account(&account, &account_path, 1).unwrap();
+        let mapping = HwPathMapping::new(&body, &TransactionUnspentOutputs::new(), &keys);
+        assert_eq!(mapping.pool_owners, vec![Some(account_path.derive(2).derive(0))]);
+        assert_eq!(mapping.certificates, vec![None]);
+        assert_eq!(mapping.witnesses, vec![account_path.derive(2).derive(0)]);
+    }
+}
@@ -2,5 +2,9 @@ pub mod binary;
 pub mod value;
 pub mod utxo;
 pub mod native_script_json;
+pub mod native_script_eval;
 pub mod hash;
-pub mod deposit;
\ No newline at end of file
+pub mod deposit;
+pub mod tx_summary;
+pub mod hw_wallet;
+pub mod block_view;
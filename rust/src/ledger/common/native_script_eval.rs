@@ -0,0 +1,272 @@
+use crate::{NativeScript, NativeScriptEnum, NativeScripts, RequiredSignersSet, Ed25519KeyHashes};
+use crate::builders::witness_builder::NativeScriptWitnessInfo;
+use crate::crypto::Ed25519KeyHash;
+use super::value::BigNum;
+
+/// Reason why a native script can't be satisfied
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NativeScriptFailure {
+    /// ScriptPubkey whose key is not among the available signers
+    MissingSigner(Ed25519KeyHash),
+    /// TimelockStart requires the validity interval to start at or after `lock_start`
+    NotYetValid {
+        lock_start: BigNum,
+        validity_start: Option<BigNum>,
+    },
+    /// TimelockExpiry requires the validity interval to end at or before `lock_expiry`
+    Expired {
+        lock_expiry: BigNum,
+        ttl: Option<BigNum>,
+    },
+    /// ScriptAll with the failures of each unsatisfied branch
+    NotAllSatisfied(Vec<NativeScriptFailure>),
+    /// ScriptAny with the failures of every branch
+    NoneSatisfied(Vec<NativeScriptFailure>),
+    /// ScriptNOfK with fewer than `required` satisfiable branches
+    TooFewSatisfied {
+        required: u32,
+        satisfied: u32,
+        failures: Vec<NativeScriptFailure>,
+    },
+}
+
+impl std::fmt::Display for NativeScriptFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn write_branches(f: &mut std::fmt::Formatter<'_>, failures: &[NativeScriptFailure]) -> std::fmt::Result {
+            let branches = failures.iter().map(|failure| failure.to_string()).collect::<Vec<String>>().join("; ");
+            write!(f, "[{}]", branches)
+        }
+        match self {
+            NativeScriptFailure::MissingSigner(hash) => write!(f, "missing signature from {}", hash.to_hex()),
+            NativeScriptFailure::NotYetValid{ lock_start, validity_start } => match validity_start {
+                Some(start) => write!(f, "validity interval starts at slot {} but script is locked until slot {}", start.to_str(), lock_start.to_str()),
+                None => write!(f, "script is locked until slot {} but no validity interval start is set", lock_start.to_str()),
+            },
+            NativeScriptFailure::Expired{ lock_expiry, ttl } => match ttl {
+                Some(ttl) => write!(f, "validity interval ends at slot {} but script expired at slot {}", ttl.to_str(), lock_expiry.to_str()),
+                None => write!(f, "script expires at slot {} but no ttl is set", lock_expiry.to_str()),
+            },
+            NativeScriptFailure::NotAllSatisfied(failures) => {
+                write!(f, "not all branches satisfied ")?;
+                write_branches(f, failures)
+            },
+            NativeScriptFailure::NoneSatisfied(failures) => {
+                write!(f, "no branch satisfied ")?;
+                write_branches(f, failures)
+            },
+            NativeScriptFailure::TooFewSatisfied{ required, satisfied, failures } => {
+                write!(f, "{} of {} required branches satisfied ", satisfied, required)?;
+                write_branches(f, failures)
+            },
+        }
+    }
+}
+
+/// Result of evaluating a native script against a set of available signers and a validity interval
+#[derive(Clone, Debug)]
+pub struct NativeScriptEvaluation(Result<RequiredSignersSet, NativeScriptFailure>);
+
+impl NativeScriptEvaluation {
+    pub fn is_satisfiable(&self) -> bool {
+        self.0.is_ok()
+    }
+
+    /// Smallest set of the available signers that satisfies the script
+    pub fn signers(&self) -> Option<Ed25519KeyHashes> {
+        self.0.as_ref().ok().map(|signers| Ed25519KeyHashes(signers.iter().cloned().collect()))
+    }
+
+    /// Exact number of vkey witnesses to account for when estimating the fee
+    pub fn witness_count(&self) -> Option<usize> {
+        self.0.as_ref().ok().map(|signers| signers.len())
+    }
+
+    pub fn failure(&self) -> Option<NativeScriptFailure> {
+        self.0.as_ref().err().cloned()
+    }
+
+    /// Witness info to pass to the input/mint/cert builders so fees are computed for exactly these signers
+    pub fn witness_info(&self) -> Option<NativeScriptWitnessInfo> {
+        self.signers().map(|signers| NativeScriptWitnessInfo::vkeys(&signers))
+    }
+}
+
+/// Checks whether `script` can be satisfied by a transaction signed by (a subset of) `available_signers`
+/// whose validity interval is [`validity_start`, `ttl`) and computes the smallest set of signers needed
+///
+/// The signer set is minimal as long as no key appears in more than one branch of the script.
+/// Otherwise it is still valid but may contain more keys than strictly required
+pub fn evaluate_native_script(
+    script: &NativeScript,
+    available_signers: &Ed25519KeyHashes,
+    validity_start: Option<BigNum>,
+    ttl: Option<BigNum>,
+) -> NativeScriptEvaluation {
+    let available = available_signers.0.iter().cloned().collect::<RequiredSignersSet>();
+    NativeScriptEvaluation(evaluate(script, &available, validity_start, ttl))
+}
+
+fn evaluate(
+    script: &NativeScript,
+    available: &RequiredSignersSet,
+    validity_start: Option<BigNum>,
+    ttl: Option<BigNum>,
+) -> Result<RequiredSignersSet, NativeScriptFailure> {
+    let evaluate_branches = |scripts: &NativeScripts| scripts.0
+        .iter()
+        .map(|script| evaluate(script, available, validity_start, ttl))
+        .collect::<Vec<_>>();
+
+    match &script.0 {
+        NativeScriptEnum::ScriptPubkey(pubkey) => {
+            if available.contains(&pubkey.addr_keyhash()) {
+                Ok(std::iter::once(pubkey.addr_keyhash()).collect())
+            } else {
+                Err(NativeScriptFailure::MissingSigner(pubkey.addr_keyhash()))
+            }
+        },
+        NativeScriptEnum::ScriptAll(all) => {
+            let (satisfied, failures) = split_results(evaluate_branches(&all.native_scripts));
+            if failures.is_empty() {
+                Ok(satisfied.into_iter().flatten().collect())
+            } else {
+                Err(NativeScriptFailure::NotAllSatisfied(failures))
+            }
+        },
+        NativeScriptEnum::ScriptAny(any) => {
+            let (satisfied, failures) = split_results(evaluate_branches(&any.native_scripts));
+            satisfied
+                .into_iter()
+                .min_by_key(|signers| signers.len())
+                .ok_or(NativeScriptFailure::NoneSatisfied(failures))
+        },
+        NativeScriptEnum::ScriptNOfK(n_of_k) => {
+            let (mut satisfied, failures) = split_results(evaluate_branches(&n_of_k.native_scripts));
+            if satisfied.len() < n_of_k.n as usize {
+                return Err(NativeScriptFailure::TooFewSatisfied {
+                    required: n_of_k.n,
+                    satisfied: satisfied.len() as u32,
+                    failures,
+                });
+            }
+            satisfied.sort_by_key(|signers| signers.len());
+            Ok(satisfied.into_iter().take(n_of_k.n as usize).flatten().collect())
+        },
+        NativeScriptEnum::TimelockStart(start) => match validity_start {
+            Some(validity_start) if start.slot <= validity_start => Ok(RequiredSignersSet::new()),
+            _ => Err(NativeScriptFailure::NotYetValid {
+                lock_start: start.slot,
+                validity_start,
+            }),
+        },
+        NativeScriptEnum::TimelockExpiry(expiry) => match ttl {
+            Some(ttl) if ttl <= expiry.slot => Ok(RequiredSignersSet::new()),
+            _ => Err(NativeScriptFailure::Expired {
+                lock_expiry: expiry.slot,
+                ttl,
+            }),
+        },
+    }
+}
+
+fn split_results(
+    results: Vec<Result<RequiredSignersSet, NativeScriptFailure>>,
+) -> (Vec<RequiredSignersSet>, Vec<NativeScriptFailure>) {
+    let mut satisfied = Vec::new();
+    let mut failures = Vec::new();
+    for result in results {
+        match result {
+            Ok(signers) => satisfied.push(signers),
+            Err(failure) => failures.push(failure),
+        }
+    }
+    (satisfied, failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ScriptPubkey, ScriptAll, ScriptAny, ScriptNOfK, TimelockStart, TimelockExpiry};
+    use crate::ledger::common::value::to_bignum;
+
+    fn key_hash(x: u8) -> Ed25519KeyHash {
+        Ed25519KeyHash::from([x; Ed25519KeyHash::BYTE_COUNT])
+    }
+
+    fn pubkey(x: u8) -> NativeScript {
+        NativeScript::new_script_pubkey(&ScriptPubkey::new(&key_hash(x)))
+    }
+
+    fn signers(keys: &[u8]) -> Ed25519KeyHashes {
+        Ed25519KeyHashes(keys.iter().map(|x| key_hash(*x)).collect())
+    }
+
+    #[test]
+    fn n_of_k_minimal_signers() {
+        let script = NativeScript::new_script_n_of_k(&ScriptNOfK::new(
+            2,
+            &NativeScripts(vec![pubkey(1), pubkey(2), pubkey(3)]),
+        ));
+
+        let eval = evaluate_native_script(&script, &signers(&[1, 2, 3]), None, None);
+        assert_eq!(eval.witness_count(), Some(2));
+
+        let eval = evaluate_native_script(&script, &signers(&[3]), None, None);
+        assert!(!eval.is_satisfiable());
+        match eval.failure().unwrap() {
+            NativeScriptFailure::TooFewSatisfied { required, satisfied, failures } => {
+                assert_eq!(required, 2);
+                assert_eq!(satisfied, 1);
+                assert_eq!(failures.len(), 2);
+            },
+            failure => panic!("unexpected failure {}", failure),
+        }
+    }
+
+    #[test]
+    fn any_picks_cheapest_branch() {
+        let script = NativeScript::new_script_any(&ScriptAny::new(&NativeScripts(vec![
+            NativeScript::new_script_all(&ScriptAll::new(&NativeScripts(vec![pubkey(1), pubkey(2)]))),
+            pubkey(3),
+        ])));
+
+        let eval = evaluate_native_script(&script, &signers(&[1, 2, 3]), None, None);
+        assert_eq!(eval.signers().unwrap(), signers(&[3]));
+
+        let eval = evaluate_native_script(&script, &signers(&[1, 2]), None, None);
+        assert_eq!(eval.signers().unwrap(), signers(&[1, 2]));
+    }
+
+    #[test]
+    fn timelocks() {
+        let script = NativeScript::new_script_all(&ScriptAll::new(&NativeScripts(vec![
+            NativeScript::new_timelock_start(&TimelockStart::new(&to_bignum(100))),
+            NativeScript::new_timelock_expiry(&TimelockExpiry::new(&to_bignum(200))),
+            pubkey(1),
+        ])));
+
+        let eval = evaluate_native_script(&script, &signers(&[1]), Some(to_bignum(100)), Some(to_bignum(200)));
+        assert_eq!(eval.witness_count(), Some(1));
+
+        let eval = evaluate_native_script(&script, &signers(&[1]), Some(to_bignum(100)), Some(to_bignum(201)));
+        assert_eq!(
+            eval.failure(),
+            Some(NativeScriptFailure::NotAllSatisfied(vec![NativeScriptFailure::Expired {
+                lock_expiry: to_bignum(200),
+                ttl: Some(to_bignum(201)),
+            }]))
+        );
+
+        let eval = evaluate_native_script(&script, &signers(&[]), None, Some(to_bignum(150)));
+        assert_eq!(
+            eval.failure(),
+            Some(NativeScriptFailure::NotAllSatisfied(vec![
+                NativeScriptFailure::NotYetValid {
+                    lock_start: to_bignum(100),
+                    validity_start: None,
+                },
+                NativeScriptFailure::MissingSigner(key_hash(1)),
+            ]))
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use hex::FromHex;
-use crate::{NativeScript, error::JsError, crypto::Bip32PublicKey, ScriptPubkey, NativeScripts, ScriptAll, ScriptAny, ScriptNOfK, TimelockStart, TimelockExpiry};
+use crate::{NativeScript, error::Error, crypto::Bip32PublicKey, ScriptPubkey, NativeScripts, ScriptAll, ScriptAny, ScriptNOfK, TimelockStart, TimelockExpiry};
 
 
 /// Used to choose the schema for a script JSON string
@@ -23,9 +23,9 @@ pub fn encode_json_str_to_native_script(
     json: &str,
     self_xpub: &str,
     schema: ScriptSchema,
-) -> Result<NativeScript, JsError> {
+) -> Result<NativeScript, Error> {
     let value: serde_json::Value =
-        serde_json::from_str(json).map_err(|e| JsError::from_str(&e.to_string()))?;
+        serde_json::from_str(json).map_err(|e| Error::from_str(&e.to_string()))?;
 
     let native_script = match schema {
         ScriptSchema::Wallet => encode_wallet_value_to_native_script(value, self_xpub)?,
@@ -35,7 +35,7 @@ pub fn encode_json_str_to_native_script(
     Ok(native_script)
 }
 
-fn encode_wallet_value_to_native_script(value: serde_json::Value, self_xpub: &str) -> Result<NativeScript, JsError> {
+fn encode_wallet_value_to_native_script(value: serde_json::Value, self_xpub: &str) -> Result<NativeScript, Error> {
     match value {
         serde_json::Value::Object(map)
             if map.contains_key("cosigners") && map.contains_key("template") =>
@@ -51,11 +51,11 @@ fn encode_wallet_value_to_native_script(value: serde_json::Value, self_xpub: &st
                             cosigners.insert(key.to_owned(), xpub.to_owned());
                         }
                     } else {
-                        return Err(JsError::from_str("cosigner value must be a string"));
+                        return Err(Error::from_str("cosigner value must be a string"));
                     }
                 }
             } else {
-                return Err(JsError::from_str("cosigners must be a map"));
+                return Err(Error::from_str("cosigners must be a map"));
             }
 
             let template = map.get("template").unwrap();
@@ -64,7 +64,7 @@ fn encode_wallet_value_to_native_script(value: serde_json::Value, self_xpub: &st
 
             Ok(template_native_script)
         }
-        _ => Err(JsError::from_str(
+        _ => Err(Error::from_str(
             "top level must be an object. cosigners and template keys are required",
         )),
     }
@@ -73,12 +73,12 @@ fn encode_wallet_value_to_native_script(value: serde_json::Value, self_xpub: &st
 fn encode_template_to_native_script(
     template: &serde_json::Value,
     cosigners: &HashMap<String, String>,
-) -> Result<NativeScript, JsError> {
+) -> Result<NativeScript, Error> {
     match template {
         serde_json::Value::String(cosigner) => {
             if let Some(xpub) = cosigners.get(cosigner) {
                 let bytes =
-                    Vec::from_hex(xpub).map_err(|e| JsError::from_str(&e.to_string()))?;
+                    Vec::from_hex(xpub).map_err(|e| Error::from_str(&e.to_string()))?;
 
                 let public_key = Bip32PublicKey::from_bytes(&bytes)?;
 
@@ -86,7 +86,7 @@ fn encode_template_to_native_script(
                     &public_key.to_raw_key().hash(),
                 )))
             } else {
-                Err(JsError::from_str(&format!("cosigner {} not found", cosigner)))
+                Err(Error::from_str(&format!("cosigner {} not found", cosigner)))
             }
         }
         serde_json::Value::Object(map) if map.contains_key("all") => {
@@ -97,7 +97,7 @@ fn encode_template_to_native_script(
                     all.add(&encode_template_to_native_script(val, cosigners)?);
                 }
             } else {
-                return Err(JsError::from_str("all must be an array"));
+                return Err(Error::from_str("all must be an array"));
             }
 
             Ok(NativeScript::new_script_all(&ScriptAll::new(&all)))
@@ -110,7 +110,7 @@ fn encode_template_to_native_script(
                     any.add(&encode_template_to_native_script(val, cosigners)?);
                 }
             } else {
-                return Err(JsError::from_str("any must be an array"));
+                return Err(Error::from_str("any must be an array"));
             }
 
             Ok(NativeScript::new_script_any(&ScriptAny::new(&any)))
@@ -124,10 +124,10 @@ fn encode_template_to_native_script(
                         if let Some(n) = at_least.as_u64() {
                             n as u32
                         } else {
-                            return Err(JsError::from_str("at_least must be an integer"));
+                            return Err(Error::from_str("at_least must be an integer"));
                         }
                     } else {
-                        return Err(JsError::from_str("at_least must be an integer"));
+                        return Err(Error::from_str("at_least must be an integer"));
                     };
 
                     let mut from_scripts = NativeScripts::new();
@@ -138,7 +138,7 @@ fn encode_template_to_native_script(
                                 .add(&encode_template_to_native_script(val, cosigners)?);
                         }
                     } else {
-                        return Err(JsError::from_str("from must be an array"));
+                        return Err(Error::from_str("from must be an array"));
                     }
 
                     Ok(NativeScript::new_script_n_of_k(&ScriptNOfK::new(
@@ -146,10 +146,10 @@ fn encode_template_to_native_script(
                         &from_scripts,
                     )))
                 } else {
-                    Err(JsError::from_str("some must contain at_least and from"))
+                    Err(Error::from_str("some must contain at_least and from"))
                 }
             } else {
-                Err(JsError::from_str("some must be an object"))
+                Err(Error::from_str("some must be an object"))
             }
         }
         serde_json::Value::Object(map) if map.contains_key("active_from") => {
@@ -159,12 +159,12 @@ fn encode_template_to_native_script(
 
                     Ok(NativeScript::new_timelock_start(&time_lock_start))
                 } else {
-                    Err(JsError::from_str(
+                    Err(Error::from_str(
                         "active_from slot must be an integer greater than or equal to 0",
                     ))
                 }
             } else {
-                Err(JsError::from_str("active_from slot must be a number"))
+                Err(Error::from_str("active_from slot must be a number"))
             }
         }
         serde_json::Value::Object(map) if map.contains_key("active_until") => {
@@ -174,14 +174,14 @@ fn encode_template_to_native_script(
 
                     Ok(NativeScript::new_timelock_expiry(&time_lock_expiry))
                 } else {
-                    Err(JsError::from_str(
+                    Err(Error::from_str(
                         "active_until slot must be an integer greater than or equal to 0",
                     ))
                 }
             } else {
-                Err(JsError::from_str("active_until slot must be a number"))
+                Err(Error::from_str("active_until slot must be a number"))
             }
         }
-        _ => Err(JsError::from_str("invalid template format")),
+        _ => Err(Error::from_str("invalid template format")),
     }
 }
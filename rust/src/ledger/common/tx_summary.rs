@@ -0,0 +1,695 @@
+use std::collections::BTreeMap;
+
+use crate::*;
+use crate::cip14::AssetFingerprint;
+use crate::cip5::Bech32Prefix;
+use crate::error::Error;
+use crate::plutus::{decode_plutus_datum_to_json_value, PlutusDatumSchema};
+
+use super::deposit::{DepositCalculator, DepositState};
+use super::hash::{hash_plutus_data, hash_transaction};
+use super::utxo::{get_produced_utxos, TransactionUnspentOutputs};
+use super::value::{Coin, Value, ValueDelta};
+
+#[derive(Clone, Debug, serde::Serialize)]
+struct CredentialSummary {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    hash: String,
+}
+
+impl From<&StakeCredential> for CredentialSummary {
+    fn from(credential: &StakeCredential) -> Self {
+        match (credential.to_keyhash(), credential.to_scripthash()) {
+            (Some(keyhash), _) => Self { kind: "key", hash: keyhash.to_hex() },
+            (_, Some(script_hash)) => Self { kind: "script", hash: script_hash.to_hex() },
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+struct AddressBalance {
+    address: String,
+    change: ValueDelta,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+struct CredentialBalance {
+    credential: CredentialSummary,
+    change: ValueDelta,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+struct AssetAmount {
+    policy_id: PolicyID,
+    asset_name: AssetName,
+    display_name: String,
+    fingerprint: String,
+    amount: Coin,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum CertificateSummary {
+    StakeRegistration {
+        credential: CredentialSummary,
+        deposit: Coin,
+    },
+    StakeDeregistration {
+        credential: CredentialSummary,
+        refund: Coin,
+    },
+    StakeDelegation {
+        credential: CredentialSummary,
+        pool: String,
+    },
+    PoolRegistration {
+        pool: String,
+        reward_account: String,
+        owners: Vec<Ed25519KeyHash>,
+        pledge: Coin,
+        cost: Coin,
+        margin: String,
+        deposit: Coin,
+    },
+    PoolRetirement {
+        pool: String,
+        epoch: Epoch,
+    },
+    GenesisKeyDelegation {
+        genesis_hash: GenesisHash,
+        genesis_delegate_hash: GenesisDelegateHash,
+    },
+    MoveInstantaneousRewards {
+        pot: &'static str,
+    },
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+struct WithdrawalSummary {
+    reward_address: String,
+    credential: CredentialSummary,
+    amount: Coin,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+struct RedeemerSummary {
+    data: serde_json::Value,
+    mem: Coin,
+    steps: Coin,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+struct ScriptInteraction {
+    /// spend, mint, certificate or withdrawal
+    purpose: &'static str,
+    /// what the script validates: the input, policy ID, certificate index or reward address
+    target: String,
+    script_hash: ScriptHash,
+    /// native, plutus_v1, plutus_v2 or plutus_v3. None if the script isn't in the witnesses nor in a known input's script ref
+    language: Option<&'static str>,
+    /// witness, input or reference_input
+    source: Option<&'static str>,
+    redeemer: Option<RedeemerSummary>,
+    datum_hash: Option<DataHash>,
+    /// None for a datum hash whose datum isn't in the witnesses
+    datum: Option<serde_json::Value>,
+}
+
+/// What a transaction does, to show to users before they sign it
+///
+/// Balances are computed from the UTxOs consumed and produced: withdrawals, deposits and refunds
+/// don't show up in them but are listed separately. If the transaction is marked as failing phase-2
+/// validation (is_valid = false), the balances reflect the collateral being taken instead.
+/// Serializes to JSON with all amounts as strings so frontends can render it the same way
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct TransactionSummary {
+    tx_hash: TransactionHash,
+    is_valid: bool,
+    fee: Coin,
+    validity_start_interval: Option<Slot>,
+    ttl: Option<Slot>,
+    total_input: Value,
+    total_output: Value,
+    deposit: Coin,
+    implicit_input: Value,
+    by_address: Vec<AddressBalance>,
+    by_payment_credential: Vec<CredentialBalance>,
+    by_stake_credential: Vec<CredentialBalance>,
+    minted: Vec<AssetAmount>,
+    burned: Vec<AssetAmount>,
+    certificates: Vec<CertificateSummary>,
+    withdrawals: Vec<WithdrawalSummary>,
+    scripts: Vec<ScriptInteraction>,
+    required_signers: Vec<Ed25519KeyHash>,
+}
+
+fn address_to_string(address: &Address) -> String {
+    match address.as_byron() {
+        Some(byron) => byron.to_base58(),
+        // only fails for invalid network IDs which can't be deserialized anyway
+        None => address.to_bech32(None).unwrap_or_else(|_| hex::encode(address.to_bytes())),
+    }
+}
+
+fn pool_to_string(pool: &Ed25519KeyHash) -> String {
+    pool.to_bech32(Some(Bech32Prefix::Pool)).unwrap_or_else(|_| pool.to_hex())
+}
+
+fn input_to_string(input: &TransactionInput) -> String {
+    format!("{}#{}", input.transaction_id.to_hex(), input.index.to_str())
+}
+
+fn asset_amounts(multiasset: &MultiAsset) -> Vec<AssetAmount> {
+    multiasset
+        .iter_assets()
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(asset_id, amount)| AssetAmount {
+            display_name: asset_id.asset_name.to_display_string(),
+            fingerprint: AssetFingerprint::new(&asset_id.policy_id, &asset_id.asset_name).to_bech32(),
+            policy_id: asset_id.policy_id,
+            asset_name: asset_id.asset_name,
+            amount,
+        })
+        .collect()
+}
+
+fn add_change<K: Ord>(balances: &mut BTreeMap<K, ValueDelta>, key: K, change: &ValueDelta) -> Result<(), Error> {
+    let balance = balances.entry(key).or_default();
+    *balance = balance.checked_add(change)?;
+    Ok(())
+}
+
+impl TransactionSummary {
+    /// {utxos} must contain the outputs spent by the transaction (its inputs, or its collateral if is_valid is false)
+    /// Reference inputs are optional and only used to find scripts
+    /// {deposit_state} is the chain state deposits depend on (see `TransactionBuilder::set_deposit_state`). Without it,
+    /// updating a registered pool is shown paying a deposit and deregistrations are refunded the current key_deposit
+    pub fn new(
+        tx: &Transaction,
+        utxos: &TransactionUnspentOutputs,
+        pool_deposit: &Coin,
+        key_deposit: &Coin,
+        deposit_state: Option<&dyn DepositState>,
+    ) -> Result<TransactionSummary, Error> {
+        let body = &tx.body;
+        let mut deposits = DepositCalculator::new(pool_deposit, key_deposit);
+        if let Some(state) = deposit_state {
+            deposits = deposits.with_state(state);
+        }
+        let witness_set = &tx.witness_set;
+        let resolved = utxos.0
+            .iter()
+            .map(|utxo| (utxo.input.clone(), utxo.output.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let resolve = |input: &TransactionInput| resolved
+            .get(input)
+            .ok_or_else(|| Error::from_str(&format!("Missing UTxO for input {}", input_to_string(input))));
+
+        let spent = match tx.is_valid {
+            true => body.inputs.0.clone(),
+            false => body.collateral.as_ref().map(|collateral| collateral.0.clone()).unwrap_or_default(),
+        };
+        let produced = get_produced_utxos(body, tx.is_valid);
+
+        // addresses are keyed by their bytes to keep a stable order
+        let mut by_address = BTreeMap::<Vec<u8>, ValueDelta>::new();
+        let mut by_payment_credential = BTreeMap::new();
+        let mut by_stake_credential = BTreeMap::new();
+        let mut total_input = Value::zero();
+        let mut total_output = Value::zero();
+        let outputs = spent
+            .iter()
+            .map(|input| resolve(input).map(|output| (output, true)))
+            .chain(produced.0.iter().map(|utxo| Ok((&utxo.output, false))))
+            .collect::<Result<Vec<_>, Error>>()?;
+        for (output, is_spent) in outputs {
+            let change = match is_spent {
+                true => {
+                    total_input = total_input.checked_add(&output.amount)?;
+                    ValueDelta::from_value(&output.amount).negate()
+                },
+                false => {
+                    total_output = total_output.checked_add(&output.amount)?;
+                    ValueDelta::from_value(&output.amount)
+                },
+            };
+            add_change(&mut by_address, output.address.to_bytes(), &change)?;
+            if let Some(credential) = output.address.payment_cred() {
+                add_change(&mut by_payment_credential, credential, &change)?;
+            }
+            if let Some(credential) = output.address.staking_cred() {
+                add_change(&mut by_stake_credential, credential, &change)?;
+            }
+        }
+
+        let certificates = body.certs
+            .iter()
+            .flat_map(|certs| certs.0.iter())
+            .zip(deposits.certificate_deposits(&body.certs))
+            .map(|(cert, (deposit, refund))| match &cert.0 {
+                CertificateEnum::StakeRegistration(cert) => CertificateSummary::StakeRegistration {
+                    credential: (&cert.stake_credential).into(),
+                    deposit,
+                },
+                CertificateEnum::StakeDeregistration(cert) => CertificateSummary::StakeDeregistration {
+                    credential: (&cert.stake_credential).into(),
+                    refund,
+                },
+                CertificateEnum::StakeDelegation(cert) => CertificateSummary::StakeDelegation {
+                    credential: (&cert.stake_credential).into(),
+                    pool: pool_to_string(&cert.pool_keyhash),
+                },
+                CertificateEnum::PoolRegistration(cert) => CertificateSummary::PoolRegistration {
+                    pool: pool_to_string(&cert.pool_params.operator()),
+                    reward_account: address_to_string(&cert.pool_params.reward_account().to_address()),
+                    owners: cert.pool_params.pool_owners().0,
+                    pledge: cert.pool_params.pledge(),
+                    cost: cert.pool_params.cost(),
+                    margin: format!("{}/{}", cert.pool_params.margin().numerator().to_str(), cert.pool_params.margin().denominator().to_str()),
+                    deposit,
+                },
+                CertificateEnum::PoolRetirement(cert) => CertificateSummary::PoolRetirement {
+                    pool: pool_to_string(&cert.pool_keyhash),
+                    epoch: cert.epoch,
+                },
+                CertificateEnum::GenesisKeyDelegation(cert) => CertificateSummary::GenesisKeyDelegation {
+                    genesis_hash: cert.genesishash.clone(),
+                    genesis_delegate_hash: cert.genesis_delegate_hash.clone(),
+                },
+                CertificateEnum::MoveInstantaneousRewardsCert(cert) => CertificateSummary::MoveInstantaneousRewards {
+                    pot: match cert.move_instantaneous_reward.pot {
+                        MIRPot::Reserves => "reserves",
+                        MIRPot::Treasury => "treasury",
+                    },
+                },
+            })
+            .collect();
+
+        let withdrawals = body.withdrawals
+            .iter()
+            .flat_map(|withdrawals| withdrawals.0.iter())
+            .map(|(reward_address, amount)| WithdrawalSummary {
+                reward_address: address_to_string(&reward_address.to_address()),
+                credential: (&reward_address.payment_cred()).into(),
+                amount: *amount,
+            })
+            .collect();
+
+        let (minted, burned) = match &body.mint {
+            Some(mint) => (asset_amounts(&mint.as_positive_multiasset()), asset_amounts(&mint.as_negative_multiasset())),
+            None => (Vec::new(), Vec::new()),
+        };
+
+        Ok(TransactionSummary {
+            tx_hash: hash_transaction(body),
+            is_valid: tx.is_valid,
+            fee: body.fee,
+            validity_start_interval: body.validity_start_interval,
+            ttl: body.ttl,
+            total_input,
+            total_output,
+            deposit: deposits.deposit(&body.certs)?,
+            implicit_input: deposits.implicit_input(&body.withdrawals, &body.certs)?,
+            by_address: by_address
+                .into_iter()
+                .map(|(address, change)| Ok(AddressBalance {
+                    address: address_to_string(&Address::from_bytes(address)?),
+                    change,
+                }))
+                .collect::<Result<_, Error>>()?,
+            by_payment_credential: by_payment_credential
+                .into_iter()
+                .map(|(credential, change)| CredentialBalance { credential: (&credential).into(), change })
+                .collect(),
+            by_stake_credential: by_stake_credential
+                .into_iter()
+                .map(|(credential, change)| CredentialBalance { credential: (&credential).into(), change })
+                .collect(),
+            minted,
+            burned,
+            certificates,
+            withdrawals,
+            scripts: script_interactions(body, witness_set, &resolved)?,
+            required_signers: body.required_signers.as_ref().map(|signers| signers.0.clone()).unwrap_or_default(),
+        })
+    }
+
+    pub fn tx_hash(&self) -> TransactionHash {
+        self.tx_hash.clone()
+    }
+
+    pub fn fee(&self) -> Coin {
+        self.fee
+    }
+
+    /// net change of the UTxOs at {address}. Zero if the transaction doesn't touch it
+    pub fn address_change(&self, address: &Address) -> ValueDelta {
+        let address = address_to_string(address);
+        self.by_address
+            .iter()
+            .find(|balance| balance.address == address)
+            .map(|balance| balance.change.clone())
+            .unwrap_or_default()
+    }
+
+    /// net change of the UTxOs locked by {credential}
+    pub fn payment_credential_change(&self, credential: &StakeCredential) -> ValueDelta {
+        Self::find_credential(&self.by_payment_credential, credential)
+    }
+
+    /// net change of the UTxOs staked with {credential} (pointer addresses are not resolved)
+    pub fn stake_credential_change(&self, credential: &StakeCredential) -> ValueDelta {
+        Self::find_credential(&self.by_stake_credential, credential)
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(self).map_err(|e| Error::from_str(&e.to_string()))
+    }
+
+    fn find_credential(balances: &[CredentialBalance], credential: &StakeCredential) -> ValueDelta {
+        let credential = CredentialSummary::from(credential);
+        balances
+            .iter()
+            .find(|balance| balance.credential.kind == credential.kind && balance.credential.hash == credential.hash)
+            .map(|balance| balance.change.clone())
+            .unwrap_or_default()
+    }
+}
+
+type ScriptSource = (Script, &'static str);
+
+fn script_interactions(body: &TransactionBody, witness_set: &TransactionWitnessSet, resolved: &BTreeMap<TransactionInput, TransactionOutput>) -> Result<Vec<ScriptInteraction>, Error> {
+    let mut scripts = BTreeMap::<ScriptHash, ScriptSource>::new();
+    let script_refs = |inputs: &Option<TransactionInputs>, source: &'static str| -> Vec<(ScriptHash, ScriptSource)> {
+        inputs.iter()
+            .flat_map(|inputs| inputs.0.iter())
+            .filter_map(|input| resolved.get(input)?.script_ref.as_ref())
+            .map(|script_ref| (script_ref.script().hash(), (script_ref.script(), source)))
+            .collect()
+    };
+    scripts.extend(script_refs(&body.reference_inputs, "reference_input"));
+    scripts.extend(script_refs(&Some(body.inputs.clone()), "input"));
+    for script in witness_set.native_scripts.iter().flat_map(|scripts| scripts.0.iter()) {
+        scripts.insert(script.hash(), (Script::new_native(script), "witness"));
+    }
+    for script in witness_set.plutus_v1_scripts.iter().flat_map(|scripts| scripts.0.iter()) {
+        scripts.insert(script.hash(), (Script::new_plutus_v1(script), "witness"));
+    }
+    for script in witness_set.plutus_v2_scripts.iter().flat_map(|scripts| scripts.0.iter()) {
+        scripts.insert(script.hash(), (Script::new_plutus_v2(script), "witness"));
+    }
+    for script in witness_set.plutus_v3_scripts.iter().flat_map(|scripts| scripts.0.iter()) {
+        scripts.insert(script.hash(), (Script::new_plutus_v3(script), "witness"));
+    }
+    let datums = witness_set.plutus_data
+        .iter()
+        .flat_map(|datums| datums.elems.iter())
+        .map(|datum| (hash_plutus_data(datum), datum.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let redeemers = witness_set.redeemers
+        .iter()
+        .flat_map(|redeemers| redeemers.0.iter())
+        .map(|redeemer| ((redeemer.tag().kind(), u64::from(redeemer.index())), redeemer))
+        .collect::<BTreeMap<_, _>>();
+    let to_json = |datum: &PlutusData| decode_plutus_datum_to_json_value(datum, PlutusDatumSchema::DetailedSchema);
+
+    let interaction = |purpose: &'static str, tag: RedeemerTagKind, index: usize, target: String, script_hash: ScriptHash| -> Result<ScriptInteraction, Error> {
+        let (language, source) = match scripts.get(&script_hash) {
+            Some((script, source)) => {
+                let language = match script.kind() {
+                    ScriptKind::NativeScript => "native",
+                    ScriptKind::PlutusScriptV1 => "plutus_v1",
+                    ScriptKind::PlutusScriptV2 => "plutus_v2",
+                    ScriptKind::PlutusScriptV3 => "plutus_v3",
+                };
+                (Some(language), Some(*source))
+            },
+            None => (None, None),
+        };
+        let redeemer = match redeemers.get(&(tag, index as u64)) {
+            Some(redeemer) => Some(RedeemerSummary {
+                data: to_json(&redeemer.data())?,
+                mem: redeemer.ex_units().mem(),
+                steps: redeemer.ex_units().steps(),
+            }),
+            None => None,
+        };
+        Ok(ScriptInteraction { purpose, target, script_hash, language, source, redeemer, datum_hash: None, datum: None })
+    };
+
+    let mut interactions = Vec::new();
+    // redeemer indices follow the ledger's sorted order of inputs, policy IDs and reward addresses
+    let mut inputs = body.inputs.0.clone();
+    inputs.sort();
+    for (index, input) in inputs.iter().enumerate() {
+        let output = match resolved.get(input) {
+            Some(output) => output,
+            None => continue,
+        };
+        if let Some(script_hash) = output.address.payment_cred().and_then(|credential| credential.to_scripthash()) {
+            let mut spend = interaction("spend", RedeemerTagKind::Spend, index, input_to_string(input), script_hash)?;
+            if let Some(datum) = output.datum() {
+                match (datum.as_inline_data(), datum.as_data_hash()) {
+                    (Some(data), _) => spend.datum = Some(to_json(&data)?),
+                    (_, Some(data_hash)) => {
+                        spend.datum = datums.get(&data_hash).map(to_json).transpose()?;
+                        spend.datum_hash = Some(data_hash);
+                    },
+                    _ => (),
+                }
+            }
+            interactions.push(spend);
+        }
+    }
+    for (index, policy_id) in body.mint.iter().flat_map(|mint| mint.0.keys()).enumerate() {
+        interactions.push(interaction("mint", RedeemerTagKind::Mint, index, policy_id.to_hex(), policy_id.clone())?);
+    }
+    for (index, cert) in body.certs.iter().flat_map(|certs| certs.0.iter()).enumerate() {
+        let credential = match &cert.0 {
+            CertificateEnum::StakeDeregistration(cert) => &cert.stake_credential,
+            CertificateEnum::StakeDelegation(cert) => &cert.stake_credential,
+            _ => continue,
+        };
+        if let Some(script_hash) = credential.to_scripthash() {
+            interactions.push(interaction("certificate", RedeemerTagKind::Cert, index, index.to_string(), script_hash)?);
+        }
+    }
+    let mut reward_addresses = body.withdrawals
+        .iter()
+        .flat_map(|withdrawals| withdrawals.0.keys())
+        .collect::<Vec<_>>();
+    // reward addresses are all the same length, so their canonical order is the order of their bytes
+    reward_addresses.sort_by_key(|reward_address| reward_address.to_address().to_bytes());
+    for (index, reward_address) in reward_addresses.into_iter().enumerate() {
+        if let Some(script_hash) = reward_address.payment_cred().to_scripthash() {
+            interactions.push(interaction("withdrawal", RedeemerTagKind::Reward, index, address_to_string(&reward_address.to_address()), script_hash)?);
+        }
+    }
+    Ok(interactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::common::utxo::TransactionUnspentOutput;
+    use crate::ledger::common::value::{to_bignum, BigInt, Int};
+
+    fn key_address(x: u8) -> Address {
+        BaseAddress::new(
+            0,
+            &StakeCredential::from_keyhash(&Ed25519KeyHash::from([x; Ed25519KeyHash::BYTE_COUNT])),
+            &StakeCredential::from_keyhash(&Ed25519KeyHash::from([x + 100; Ed25519KeyHash::BYTE_COUNT])),
+        ).to_address()
+    }
+
+    #[test]
+    fn payment_with_mint_and_delegation() {
+        let (sender, receiver) = (key_address(1), key_address(2));
+        let script = PlutusV1Script::new(vec![0x4e, 0x4d, 0x01, 0x00, 0x00, 0x33, 0x22, 0x22, 0x20, 0x05, 0x12, 0x00, 0x12, 0x00, 0x11]);
+        let script_address = EnterpriseAddress::new(0, &StakeCredential::from_scripthash(&script.hash())).to_address();
+        let datum = PlutusData::new_integer(&BigInt::from_str("42").unwrap());
+        let redeemer_data = PlutusData::new_bytes(vec![0xca, 0xfe]);
+
+        let tx_hash = TransactionHash::from([0; TransactionHash::BYTE_COUNT]);
+        let input = TransactionInput::new(&tx_hash, &to_bignum(0));
+        let script_input = TransactionInput::new(&tx_hash, &to_bignum(1));
+        let mut utxos = TransactionUnspentOutputs::new();
+        utxos.add(&TransactionUnspentOutput::new(&input, &TransactionOutput::new(&sender, &Value::new(&to_bignum(10_000_000)))));
+        let mut script_output = TransactionOutput::new(&script_address, &Value::new(&to_bignum(5_000_000)));
+        script_output.set_datum(&Datum::new_data_hash(&hash_plutus_data(&datum)));
+        utxos.add(&TransactionUnspentOutput::new(&script_input, &script_output));
+
+        let policy_id = PolicyID::from([7; PolicyID::BYTE_COUNT]);
+        let name = AssetName::new(b"Token".to_vec()).unwrap();
+        let mut mint = Mint::new();
+        mint.insert(&policy_id, &MintAssets::new_from_entry(&name, Int::new_i32(3)));
+        let mut minted = MultiAsset::new();
+        minted.set_asset(&policy_id, &name, &to_bignum(3));
+        let mut received = Value::new(&to_bignum(6_000_000));
+        received.set_multiasset(&minted);
+
+        let sender_stake = sender.staking_cred().unwrap();
+        let pool = Ed25519KeyHash::from([9; Ed25519KeyHash::BYTE_COUNT]);
+        let mut certs = Certificates::new();
+        certs.add(&Certificate::new_stake_registration(&StakeRegistration::new(&sender_stake)));
+        certs.add(&Certificate::new_stake_delegation(&StakeDelegation::new(&sender_stake, &pool)));
+
+        let mut inputs = TransactionInputs::new();
+        inputs.add(&input);
+        inputs.add(&script_input);
+        let mut outputs = TransactionOutputs::new();
+        outputs.add(&TransactionOutput::new(&receiver, &received));
+        outputs.add(&TransactionOutput::new(&sender, &Value::new(&to_bignum(6_800_000))));
+        let mut body = TransactionBody::new(&inputs, &outputs, &to_bignum(200_000), Some(to_bignum(1000)));
+        body.set_mint(&mint);
+        body.set_certs(&certs);
+
+        let mut witness_set = TransactionWitnessSet::new();
+        let mut scripts = PlutusV1Scripts::new();
+        scripts.add(&script);
+        witness_set.set_plutus_v1_scripts(&scripts);
+        let mut datums = PlutusList::new();
+        datums.add(&datum);
+        witness_set.set_plutus_data(&datums);
+        let mut redeemers = Redeemers::new();
+        redeemers.add(&Redeemer::new(&RedeemerTag::new_spend(), &to_bignum(1), &redeemer_data, &ExUnits::new(&to_bignum(100), &to_bignum(200))));
+        witness_set.set_redeemers(&redeemers);
+        let tx = Transaction::new(&body, &witness_set, None);
+
+        let summary = TransactionSummary::new(&tx, &utxos, &to_bignum(500_000_000), &to_bignum(2_000_000), None).unwrap();
+        let sender_change = summary.address_change(&sender);
+        assert_eq!(sender_change.coin(), Int::new_negative(&to_bignum(3_200_000)));
+        assert!(summary.address_change(&script_address).negative_part().unwrap() == Value::new(&to_bignum(5_000_000)));
+        assert_eq!(summary.address_change(&receiver).positive_part().unwrap(), received);
+        assert_eq!(summary.payment_credential_change(&receiver.payment_cred().unwrap()), summary.address_change(&receiver));
+        assert_eq!(summary.stake_credential_change(&sender_stake), sender_change);
+        assert!(summary.address_change(&key_address(3)).is_zero());
+
+        let json: serde_json::Value = serde_json::from_str(&summary.to_json().unwrap()).unwrap();
+        assert_eq!(json["deposit"], "2000000");
+        assert_eq!(json["minted"][0]["display_name"], "Token");
+        assert_eq!(json["minted"][0]["amount"], "3");
+        assert_eq!(json["certificates"][0]["type"], "stake_registration");
+        assert_eq!(json["certificates"][1]["pool"], pool.to_bech32(Some(Bech32Prefix::Pool)).unwrap());
+        let spend = &json["scripts"][0];
+        assert_eq!(spend["purpose"], "spend");
+        assert_eq!(spend["language"], "plutus_v1");
+        assert_eq!(spend["source"], "witness");
+        assert_eq!(spend["datum"], serde_json::json!({"int": 42}));
+        assert_eq!(spend["redeemer"]["data"], serde_json::json!({"bytes": "cafe"}));
+        // the minting policy is neither provided nor redeemed
+        assert_eq!(json["scripts"][1]["purpose"], "mint");
+        assert!(json["scripts"][1]["language"].is_null());
+
+        assert!(TransactionSummary::new(&tx, &TransactionUnspentOutputs::new(), &to_bignum(0), &to_bignum(0), None).is_err());
+    }
+
+    #[test]
+    fn withdrawal_redeemers_follow_sorted_reward_addresses() {
+        let sender = key_address(1);
+        let input = TransactionInput::new(&TransactionHash::from([0; TransactionHash::BYTE_COUNT]), &to_bignum(0));
+        let mut utxos = TransactionUnspentOutputs::new();
+        utxos.add(&TransactionUnspentOutput::new(&input, &TransactionOutput::new(&sender, &Value::new(&to_bignum(10_000_000)))));
+
+        let reward_address = |x: u8| RewardAddress::new(0, &StakeCredential::from_scripthash(&ScriptHash::from([x; ScriptHash::BYTE_COUNT])));
+        // inserted in descending order, while redeemers index the sorted reward addresses
+        let mut withdrawals = Withdrawals::new();
+        withdrawals.insert(&reward_address(2), &to_bignum(2_000_000));
+        withdrawals.insert(&reward_address(1), &to_bignum(1_000_000));
+
+        let mut inputs = TransactionInputs::new();
+        inputs.add(&input);
+        let mut outputs = TransactionOutputs::new();
+        outputs.add(&TransactionOutput::new(&sender, &Value::new(&to_bignum(12_800_000))));
+        let mut body = TransactionBody::new(&inputs, &outputs, &to_bignum(200_000), None);
+        body.set_withdrawals(&withdrawals);
+
+        let mut redeemers = Redeemers::new();
+        for index in 0..2 {
+            redeemers.add(&Redeemer::new(&RedeemerTag::new_reward(), &to_bignum(index), &PlutusData::new_bytes(vec![index as u8]), &ExUnits::new(&to_bignum(100), &to_bignum(200))));
+        }
+        let mut witness_set = TransactionWitnessSet::new();
+        witness_set.set_redeemers(&redeemers);
+        let tx = Transaction::new(&body, &witness_set, None);
+
+        let summary = TransactionSummary::new(&tx, &utxos, &to_bignum(500_000_000), &to_bignum(2_000_000), None).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&summary.to_json().unwrap()).unwrap();
+        for (index, x) in [1, 2].iter().enumerate() {
+            let withdrawal = &json["scripts"][index];
+            assert_eq!(withdrawal["purpose"], "withdrawal");
+            assert_eq!(withdrawal["target"], address_to_string(&reward_address(*x).to_address()));
+            assert_eq!(withdrawal["redeemer"]["data"], serde_json::json!({"bytes": hex::encode([index as u8])}));
+        }
+    }
+
+    #[derive(Debug)]
+    struct ChainState {
+        pool: Ed25519KeyHash,
+        stake: StakeCredential,
+        stake_deposit: Coin,
+    }
+
+    impl DepositState for ChainState {
+        fn is_pool_registered(&self, pool: &Ed25519KeyHash) -> bool {
+            *pool == self.pool
+        }
+
+        fn stake_deposit(&self, credential: &StakeCredential) -> Option<Coin> {
+            Some(self.stake_deposit).filter(|_| *credential == self.stake)
+        }
+    }
+
+    #[test]
+    fn deposits_with_chain_state() {
+        let sender = key_address(1);
+        let input = TransactionInput::new(&TransactionHash::from([0; TransactionHash::BYTE_COUNT]), &to_bignum(0));
+        let mut utxos = TransactionUnspentOutputs::new();
+        utxos.add(&TransactionUnspentOutput::new(&input, &TransactionOutput::new(&sender, &Value::new(&to_bignum(10_000_000)))));
+
+        let state = ChainState {
+            pool: Ed25519KeyHash::from([9; Ed25519KeyHash::BYTE_COUNT]),
+            stake: sender.staking_cred().unwrap(),
+            // paid when key_deposit was lower
+            stake_deposit: to_bignum(1_000_000),
+        };
+        let mut certs = Certificates::new();
+        certs.add(&Certificate::new_pool_registration(&PoolRegistration::new(&PoolParams::new(
+            &state.pool,
+            &VRFKeyHash::from([0; VRFKeyHash::BYTE_COUNT]),
+            &to_bignum(0),
+            &to_bignum(340_000_000),
+            &UnitInterval::new(&to_bignum(1), &to_bignum(100)),
+            &RewardAddress::new(0, &state.stake),
+            &Ed25519KeyHashes::new(),
+            &Relays::new(),
+            None,
+        ))));
+        certs.add(&Certificate::new_stake_deregistration(&StakeDeregistration::new(&state.stake)));
+
+        let mut inputs = TransactionInputs::new();
+        inputs.add(&input);
+        let mut outputs = TransactionOutputs::new();
+        outputs.add(&TransactionOutput::new(&sender, &Value::new(&to_bignum(10_800_000))));
+        let mut body = TransactionBody::new(&inputs, &outputs, &to_bignum(200_000), None);
+        body.set_certs(&certs);
+        let tx = Transaction::new(&body, &TransactionWitnessSet::new(), None);
+
+        let json = |deposit_state: Option<&dyn DepositState>| {
+            let summary = TransactionSummary::new(&tx, &utxos, &to_bignum(500_000_000), &to_bignum(2_000_000), deposit_state).unwrap();
+            serde_json::from_str::<serde_json::Value>(&summary.to_json().unwrap()).unwrap()
+        };
+        // without the chain state the pool is assumed to be new and the refund to be the current key_deposit
+        let stateless = json(None);
+        assert_eq!(stateless["deposit"], "500000000");
+        assert_eq!(stateless["certificates"][0]["deposit"], "500000000");
+        assert_eq!(stateless["certificates"][1]["refund"], "2000000");
+
+        let summary = json(Some(&state));
+        assert_eq!(summary["deposit"], "0");
+        assert_eq!(summary["certificates"][0]["deposit"], "0");
+        assert_eq!(summary["certificates"][1]["refund"], "1000000");
+        assert_eq!(summary["implicit_input"]["coin"], "1000000");
+    }
+}
@@ -1,13 +1,14 @@
 use std::io::Write;
 use std::io::{Seek, BufRead};
 use cbor_event::Special as CBORSpecial;
-use crate::error::JsError;
 
 use cbor_event::{self, de::Deserializer, se::{Serialize, Serializer}};
 
-use crate::{to_from_bytes, TransactionInput, TransactionOutput, error::{DeserializeFailure, DeserializeError}};
+use crate::{to_from_bytes, TransactionBody, TransactionInput, TransactionOutput, error::{DeserializeFailure, DeserializeError}};
 
 use super::binary::*;
+use super::hash::hash_transaction;
+use super::value::to_bignum;
 
 
 
@@ -83,6 +84,8 @@ impl Deserialize for TransactionUnspentOutput {
 #[derive(Clone, Debug, Default)]
 pub struct TransactionUnspentOutputs(pub(crate) Vec<TransactionUnspentOutput>);
 
+vec_iter!(TransactionUnspentOutputs, TransactionUnspentOutput);
+
 
 impl TransactionUnspentOutputs {
     pub fn new() -> Self {
@@ -106,3 +109,19 @@ impl TransactionUnspentOutputs {
         self.0.push(elem.clone());
     }
 }
+
+/// The outputs a transaction creates once it is on chain, keyed by the hash of its body and the output index
+/// If `is_valid` is false (failed phase-2 validation) only the collateral return is created,
+/// at the index right after the regular outputs
+pub fn get_produced_utxos(body: &TransactionBody, is_valid: bool) -> TransactionUnspentOutputs {
+    let tx_hash = hash_transaction(body);
+    let utxo = |index: usize, output: &TransactionOutput| TransactionUnspentOutput::new(
+        &TransactionInput::new(&tx_hash, &to_bignum(index as u64)),
+        output,
+    );
+    if is_valid {
+        TransactionUnspentOutputs(body.outputs.0.iter().enumerate().map(|(i, output)| utxo(i, output)).collect())
+    } else {
+        TransactionUnspentOutputs(body.collateral_return.iter().map(|output| utxo(body.outputs.0.len(), output)).collect())
+    }
+}
@@ -3,9 +3,10 @@ use std::io::{BufRead, Seek, Write};
 use cbor_event::Special as CBORSpecial;
 use cbor_event::Type as CBORType;
 
-use crate::{to_from_bytes, error::{JsError, DeserializeError, DeserializeFailure}, to_from_json, MultiAsset, Assets};
+use std::collections::BTreeMap;
+
+use crate::{to_from_bytes, error::{Error, DeserializeError, DeserializeFailure}, to_from_json, MultiAsset, Assets, AssetName, Mint, PolicyID, PolicyIDs, TransactionOutput};
 use cbor_event::{self, de::Deserializer, se::{Serialize, Serializer}};
-use wasm_bindgen::JsValue;
 use super::binary::*;
 
 use schemars::JsonSchema;
@@ -19,10 +20,10 @@ pub struct BigNum(u64);
 to_from_bytes!(BigNum);
 
 impl std::str::FromStr for BigNum {
-    type Err = JsError;
-    fn from_str(string: &str) -> Result<BigNum, JsError> {
+    type Err = Error;
+    fn from_str(string: &str) -> Result<BigNum, Error> {
         string.parse::<u64>()
-            .map_err(|e| JsError::from_str(&format! {"{:?}", e}))
+            .map_err(|e| Error::from_str(&format! {"{:?}", e}))
             .map(BigNum)
     }
 }
@@ -31,7 +32,7 @@ impl std::str::FromStr for BigNum {
 impl BigNum {
     // Create a BigNum from a standard rust string representation
     #[allow(clippy::should_implement_trait)]
-    pub fn from_str(string: &str) -> Result<BigNum, JsError> {
+    pub fn from_str(string: &str) -> Result<BigNum, Error> {
         // have to redefine so it's visible in WASM
         std::str::FromStr::from_str(string)
     }
@@ -49,24 +50,24 @@ impl BigNum {
         self.0 == 0
     }
 
-    pub fn checked_mul(&self, other: &BigNum) -> Result<BigNum, JsError> {
+    pub fn checked_mul(&self, other: &BigNum) -> Result<BigNum, Error> {
         match self.0.checked_mul(other.0) {
             Some(value) => Ok(BigNum(value)),
-            None => Err(JsError::from_str("overflow")),
+            None => Err(Error::from_str("overflow")),
         }
     }
 
-    pub fn checked_add(&self, other: &BigNum) -> Result<BigNum, JsError> {
+    pub fn checked_add(&self, other: &BigNum) -> Result<BigNum, Error> {
         match self.0.checked_add(other.0) {
             Some(value) => Ok(BigNum(value)),
-            None => Err(JsError::from_str("overflow")),
+            None => Err(Error::from_str("overflow")),
         }
     }
 
-    pub fn checked_sub(&self, other: &BigNum) -> Result<BigNum, JsError> {
+    pub fn checked_sub(&self, other: &BigNum) -> Result<BigNum, Error> {
         match self.0.checked_sub(other.0) {
             Some(value) => Ok(BigNum(value)),
-            None => Err(JsError::from_str("underflow")),
+            None => Err(Error::from_str("underflow")),
         }
     }
 
@@ -78,16 +79,23 @@ impl BigNum {
         }
     }
 
-    pub fn checked_div(&self, other: &BigNum) -> Result<BigNum, JsError> {
+    pub fn checked_div(&self, other: &BigNum) -> Result<BigNum, Error> {
         match self.0.checked_div(other.0) {
             Some(value) => Ok(BigNum(value)),
-            None => Err(JsError::from_str("underflow")),
+            None => Err(Error::from_str("underflow")),
         }
     }
 
-    pub fn checked_div_ceil(&self, other: &BigNum) -> Result<BigNum, JsError> {
+    pub fn checked_rem(&self, other: &BigNum) -> Result<BigNum, Error> {
+        match self.0.checked_rem(other.0) {
+            Some(value) => Ok(BigNum(value)),
+            None => Err(Error::from_str("underflow")),
+        }
+    }
+
+    pub fn checked_div_ceil(&self, other: &BigNum) -> Result<BigNum, Error> {
         if other.0 <= 0 {
-            return Err(JsError::from_str("underflow"));
+            return Err(Error::from_str("underflow"));
         }
         match self
             .0
@@ -96,7 +104,7 @@ impl BigNum {
             .checked_div(other.0)
         {
             Some(value) => Ok(BigNum(value)),
-            None => Err(JsError::from_str("underflow")),
+            None => Err(Error::from_str("underflow")),
         }
     }
 
@@ -224,7 +232,7 @@ impl Value {
         self.multiasset = Some(multiasset.clone());
     }
 
-    pub fn checked_add(&self, rhs: &Value) -> Result<Value, JsError> {
+    pub fn checked_add(&self, rhs: &Value) -> Result<Value, Error> {
         use std::collections::btree_map::Entry;
         let coin = self.coin.checked_add(&rhs.coin)?;
 
@@ -270,7 +278,7 @@ impl Value {
         })
     }
 
-    pub fn checked_sub(&self, rhs_value: &Value) -> Result<Value, JsError> {
+    pub fn checked_sub(&self, rhs_value: &Value) -> Result<Value, Error> {
         let coin = self.coin.checked_sub(&rhs_value.coin)?;
         let multiasset = match(&self.multiasset, &rhs_value.multiasset) {
             (Some(lhs_ma), Some(rhs_ma)) => {
@@ -313,6 +321,73 @@ impl Value {
             Some(std::cmp::Ordering::Greater) => Some(1),
         }
     }
+
+    /// multiplies the ADA and every asset amount by {scalar}
+    pub fn checked_mul(&self, scalar: &BigNum) -> Result<Value, Error> {
+        self.map_amounts(|amount| amount.checked_mul(scalar))
+    }
+
+    /// divides the ADA and every asset amount by {divisor}, rounding down
+    /// entries that become zero are removed
+    pub fn checked_div(&self, divisor: &BigNum) -> Result<Value, Error> {
+        self.map_amounts(|amount| amount.checked_div(divisor))
+    }
+
+    /// what is left of each amount after dividing by {divisor} (see checked_div)
+    /// quotient * divisor + remainder always gives back the original value
+    pub fn checked_rem(&self, divisor: &BigNum) -> Result<Value, Error> {
+        self.map_amounts(|amount| amount.checked_rem(divisor))
+    }
+
+    /// removes assets with a zero amount, policies without assets and an empty multiasset
+    /// does not modify this object, instead the result is returned
+    pub fn normalize(&self) -> Value {
+        Value {
+            coin: self.coin,
+            multiasset: self.multiasset
+                .as_ref()
+                .map(MultiAsset::normalize)
+                .filter(|multiasset| multiasset.len() > 0),
+        }
+    }
+
+    /// keeps the ADA but only the assets under the given policies
+    pub fn filter_policies(&self, policy_ids: &PolicyIDs) -> Value {
+        Value {
+            coin: self.coin,
+            multiasset: self.multiasset
+                .as_ref()
+                .map(|multiasset| multiasset.filter_policies(policy_ids))
+                .filter(|multiasset| multiasset.len() > 0),
+        }
+    }
+
+    /// total of {values} with the same overflow semantics as checked_add
+    pub fn checked_sum<'a, I: IntoIterator<Item = &'a Value>>(values: I) -> Result<Value, Error> {
+        values.into_iter().try_fold(Value::zero(), |total, value| total.checked_add(value))
+    }
+
+    /// total value held by {outputs}
+    pub fn sum_outputs<'a, I: IntoIterator<Item = &'a TransactionOutput>>(outputs: I) -> Result<Value, Error> {
+        Self::checked_sum(outputs.into_iter().map(|output| &output.amount))
+    }
+
+    fn map_amounts<F: Fn(&BigNum) -> Result<BigNum, Error>>(&self, f: F) -> Result<Value, Error> {
+        let coin = f(&self.coin)?;
+        let multiasset = match &self.multiasset {
+            Some(multiasset) => {
+                let mut result = MultiAsset::new();
+                for (policy_id, assets) in multiasset.0.iter() {
+                    for (asset_name, amount) in assets.0.iter() {
+                        result.set_asset(policy_id, asset_name, &f(amount)?);
+                    }
+                }
+                Some(result)
+            },
+            None => None,
+        };
+        Ok(Value { coin, multiasset }.normalize())
+    }
 }
 
 impl PartialOrd for Value {
@@ -344,6 +419,189 @@ impl PartialOrd for Value {
     }
 }
 
+/// Signed counterpart of Value: ADA and asset amounts can be negative
+/// e.g. the difference between two portfolios or the effect of a transaction on an address
+/// Amounts are stored as i128 so adding or subtracting any two Values can't overflow,
+/// but converting back to Value fails if an amount doesn't fit in a BigNum
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ValueDelta {
+    coin: i128,
+    // never contains zero amounts or policies without assets
+    assets: BTreeMap<PolicyID, BTreeMap<AssetName, i128>>,
+}
+
+impl ValueDelta {
+    pub fn new(coin: &Int) -> Self {
+        Self {
+            coin: coin.0,
+            assets: BTreeMap::new(),
+        }
+    }
+
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn from_value(value: &Value) -> Self {
+        let mut delta = Self::new(&Int::new(&value.coin));
+        for (policy_id, assets) in value.multiasset.iter().flat_map(|multiasset| multiasset.0.iter()) {
+            for (asset_name, amount) in assets.0.iter() {
+                delta.add_asset(policy_id, asset_name, amount.0 as i128);
+            }
+        }
+        delta
+    }
+
+    /// minted assets are positive and burned ones negative
+    pub fn from_mint(mint: &Mint) -> Self {
+        let mut delta = Self::zero();
+        for (policy_id, assets) in mint.0.iter() {
+            for (asset_name, amount) in assets.0.iter() {
+                delta.add_asset(policy_id, asset_name, amount.0);
+            }
+        }
+        delta
+    }
+
+    /// {to} - {from}
+    pub fn between(from: &Value, to: &Value) -> Result<Self, Error> {
+        Self::from_value(to).checked_sub(&Self::from_value(from))
+    }
+
+    pub fn coin(&self) -> Int {
+        Int(self.coin)
+    }
+
+    pub fn set_coin(&mut self, coin: &Int) {
+        self.coin = coin.0;
+    }
+
+    /// returns 0 if the asset isn't present
+    pub fn get_asset(&self, policy_id: &PolicyID, asset_name: &AssetName) -> Int {
+        Int(self.assets.get(policy_id).and_then(|assets| assets.get(asset_name)).copied().unwrap_or(0))
+    }
+
+    pub fn set_asset(&mut self, policy_id: &PolicyID, asset_name: &AssetName, amount: &Int) {
+        let assets = self.assets.entry(policy_id.clone()).or_default();
+        assets.insert(asset_name.clone(), amount.0);
+        self.remove_zero(policy_id, asset_name);
+    }
+
+    /// policy IDs of all assets whose amount changes
+    pub fn policy_ids(&self) -> PolicyIDs {
+        crate::ScriptHashes(self.assets.keys().cloned().collect())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coin == 0 && self.assets.is_empty()
+    }
+
+    pub fn checked_add(&self, rhs: &ValueDelta) -> Result<ValueDelta, Error> {
+        self.combine(rhs, i128::checked_add)
+    }
+
+    pub fn checked_sub(&self, rhs: &ValueDelta) -> Result<ValueDelta, Error> {
+        self.combine(rhs, i128::checked_sub)
+    }
+
+    pub fn negate(&self) -> ValueDelta {
+        ValueDelta {
+            coin: -self.coin,
+            assets: self.assets
+                .iter()
+                .map(|(policy_id, assets)| (policy_id.clone(), assets.iter().map(|(name, amount)| (name.clone(), -amount)).collect()))
+                .collect(),
+        }
+    }
+
+    /// only the positive amounts e.g. what an address received
+    pub fn positive_part(&self) -> Result<Value, Error> {
+        self.to_value_filtered(|amount| amount.max(0))
+    }
+
+    /// the absolute value of the negative amounts e.g. what an address sent
+    pub fn negative_part(&self) -> Result<Value, Error> {
+        self.to_value_filtered(|amount| (-amount).max(0))
+    }
+
+    /// fails if any amount is negative
+    pub fn to_value(&self) -> Result<Value, Error> {
+        if self.coin < 0 || self.assets.values().flat_map(|assets| assets.values()).any(|amount| *amount < 0) {
+            return Err(Error::from_str("ValueDelta has negative amounts and can't be converted to a Value"));
+        }
+        self.positive_part()
+    }
+
+    fn add_asset(&mut self, policy_id: &PolicyID, asset_name: &AssetName, amount: i128) {
+        // the amounts come from a u64 or an i128 that is never above u64::MAX in magnitude so this can't overflow
+        *self.assets.entry(policy_id.clone()).or_default().entry(asset_name.clone()).or_default() += amount;
+        self.remove_zero(policy_id, asset_name);
+    }
+
+    fn remove_zero(&mut self, policy_id: &PolicyID, asset_name: &AssetName) {
+        if let Some(assets) = self.assets.get_mut(policy_id) {
+            if assets.get(asset_name) == Some(&0) {
+                assets.remove(asset_name);
+            }
+            if assets.is_empty() {
+                self.assets.remove(policy_id);
+            }
+        }
+    }
+
+    fn combine(&self, rhs: &ValueDelta, op: fn(i128, i128) -> Option<i128>) -> Result<ValueDelta, Error> {
+        let overflow = || Error::from_str("overflow");
+        let mut result = ValueDelta::new(&Int(op(self.coin, rhs.coin).ok_or_else(overflow)?));
+        result.assets = self.assets.clone();
+        for (policy_id, assets) in rhs.assets.iter() {
+            for (asset_name, amount) in assets.iter() {
+                let current = result.get_asset(policy_id, asset_name).0;
+                let assets = result.assets.entry(policy_id.clone()).or_default();
+                assets.insert(asset_name.clone(), op(current, *amount).ok_or_else(overflow)?);
+                result.remove_zero(policy_id, asset_name);
+            }
+        }
+        Ok(result)
+    }
+
+    fn to_value_filtered<F: Fn(i128) -> i128>(&self, f: F) -> Result<Value, Error> {
+        use std::convert::TryFrom;
+        let to_bignum = |amount: i128| u64::try_from(f(amount))
+            .map(BigNum)
+            .map_err(|_| Error::from_str("overflow"));
+        let mut multiasset = MultiAsset::new();
+        for (policy_id, assets) in self.assets.iter() {
+            for (asset_name, amount) in assets.iter() {
+                let amount = to_bignum(*amount)?;
+                if !amount.is_zero() {
+                    multiasset.set_asset(policy_id, asset_name, &amount);
+                }
+            }
+        }
+        let mut value = Value::new(&to_bignum(self.coin)?);
+        if multiasset.len() > 0 {
+            value.multiasset = Some(multiasset);
+        }
+        Ok(value)
+    }
+}
+
+// same layout as Value's JSON with signed amounts as strings
+impl serde::Serialize for ValueDelta {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        use serde::ser::SerializeStruct;
+        let multiasset = self.assets
+            .iter()
+            .map(|(policy_id, assets)| (policy_id.to_hex(), assets.iter().map(|(name, amount)| (hex::encode(name.name()), amount.to_string())).collect::<BTreeMap<_, _>>()))
+            .collect::<BTreeMap<_, _>>();
+        let mut value = serializer.serialize_struct("ValueDelta", 2)?;
+        value.serialize_field("coin", &self.coin.to_string())?;
+        value.serialize_field("multiasset", &multiasset)?;
+        value.end()
+    }
+}
+
 impl cbor_event::se::Serialize for Value {
     fn serialize<'se, W: Write>(&self, serializer: &'se mut Serializer<W>) -> cbor_event::Result<&'se mut Serializer<W>> {
         match &self.multiasset {
@@ -393,12 +651,12 @@ impl Deserialize for Value {
 }
 
 impl std::str::FromStr for Int {
-    type Err = JsError;
-    fn from_str(string: &str) -> Result<Int, JsError> {
+    type Err = Error;
+    fn from_str(string: &str) -> Result<Int, Error> {
         let x = string.parse::<i128>()
-            .map_err(|e| JsError::from_str(&format! {"{:?}", e}))?;
+            .map_err(|e| Error::from_str(&format! {"{:?}", e}))?;
         if x.abs() > u64::MAX as i128 {
-            return Err(JsError::from_str(&format!("{} out of bounds. Value (without sign) must fit within 4 bytes limit of {}", x, u64::MAX)));
+            return Err(Error::from_str(&format!("{} out of bounds. Value (without sign) must fit within 4 bytes limit of {}", x, u64::MAX)));
         }
         Ok(Self(x))
     }
@@ -476,11 +734,11 @@ impl Int {
     }
 
     /// Returns the underlying value converted to i32 if possible (within limits)
-    /// JsError in case of out of boundary overflow
-    pub fn as_i32_or_fail(&self) -> Result<i32, JsError> {
+    /// Error in case of out of boundary overflow
+    pub fn as_i32_or_fail(&self) -> Result<i32, Error> {
         use std::convert::TryFrom;
         i32::try_from(self.0)
-            .map_err(|e| JsError::from_str(&format!("{}", e)))
+            .map_err(|e| Error::from_str(&format!("{}", e)))
     }
 
     /// Returns string representation of the underlying i128 value directly.
@@ -491,7 +749,7 @@ impl Int {
 
     // Create an Int from a standard rust string representation
     #[allow(clippy::should_implement_trait)]
-    pub fn from_str(string: &str) -> Result<Int, JsError> {
+    pub fn from_str(string: &str) -> Result<Int, Error> {
         // have to redefine so it's visible in WASM
         std::str::FromStr::from_str(string)
     }
@@ -592,10 +850,10 @@ impl JsonSchema for BigInt {
 }
 
 impl std::str::FromStr for BigInt {
-    type Err = JsError;
-    fn from_str(string: &str) -> Result<BigInt, JsError> {
+    type Err = Error;
+    fn from_str(string: &str) -> Result<BigInt, Error> {
         num_bigint::BigInt::from_str(string)
-            .map_err(|e| JsError::from_str(&format! {"{:?}", e}))
+            .map_err(|e| Error::from_str(&format! {"{:?}", e}))
             .map(BigInt)
     }
 }
@@ -629,7 +887,7 @@ impl BigInt {
     }
 
     #[allow(clippy::should_implement_trait)]
-    pub fn from_str(string: &str) -> Result<BigInt, JsError> {
+    pub fn from_str(string: &str) -> Result<BigInt, Error> {
         // have to redefine so it's visible in WASM
         std::str::FromStr::from_str(string)
     }
@@ -718,3 +976,80 @@ impl<T> std::convert::From<T> for BigInt where T: std::convert::Into<num_bigint:
         Self(x.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Address, MintAssets, ScriptHashes};
+
+    #[test]
+    fn value_scalar_arithmetic() {
+        let policy1 = PolicyID::from([0; PolicyID::BYTE_COUNT]);
+        let policy2 = PolicyID::from([1; PolicyID::BYTE_COUNT]);
+        let name = AssetName::new(vec![1]).unwrap();
+        let mut multiasset = MultiAsset::new();
+        multiasset.set_asset(&policy1, &name, &to_bignum(7));
+        multiasset.set_asset(&policy2, &name, &to_bignum(2));
+        let value = Value { coin: to_bignum(10), multiasset: Some(multiasset) };
+
+        let tripled = value.checked_mul(&to_bignum(3)).unwrap();
+        assert_eq!(tripled.coin, to_bignum(30));
+        assert_eq!(tripled.multiasset.as_ref().unwrap().get_asset(&policy1, &name), to_bignum(21));
+        assert!(value.checked_mul(&to_bignum(u64::MAX)).is_err());
+
+        // policy2's 2 tokens don't survive integer division by 3
+        let quotient = value.checked_div(&to_bignum(3)).unwrap();
+        let remainder = value.checked_rem(&to_bignum(3)).unwrap();
+        assert_eq!(quotient.multiasset.as_ref().unwrap().len(), 1);
+        assert_eq!(quotient.checked_mul(&to_bignum(3)).unwrap().checked_add(&remainder).unwrap(), value);
+        assert!(value.checked_div(&to_bignum(0)).is_err());
+
+        let filtered = value.filter_policies(&ScriptHashes(vec![policy2.clone()]));
+        assert_eq!(filtered.coin, to_bignum(10));
+        assert_eq!(filtered.multiasset.unwrap().keys(), ScriptHashes(vec![policy2.clone()]));
+
+        let mut with_zero = MultiAsset::new();
+        with_zero.set_asset(&policy1, &name, &to_bignum(0));
+        let normalized = Value { coin: to_bignum(1), multiasset: Some(with_zero) }.normalize();
+        assert_eq!(normalized.multiasset, None);
+
+        let outputs = vec![
+            TransactionOutput::new(&Address::from_bech32("addr1vyy6nhfyks7wdu3dudslys37v252w2nwhv0fw2nfawemmnqs6l44z").unwrap(), &value),
+            TransactionOutput::new(&Address::from_bech32("addr1vyy6nhfyks7wdu3dudslys37v252w2nwhv0fw2nfawemmnqs6l44z").unwrap(), &Value::new(&to_bignum(5))),
+        ];
+        assert_eq!(Value::sum_outputs(&outputs).unwrap().coin, to_bignum(15));
+    }
+
+    #[test]
+    fn value_delta() {
+        let policy = PolicyID::from([0; PolicyID::BYTE_COUNT]);
+        let (name1, name2) = (AssetName::new(vec![1]).unwrap(), AssetName::new(vec![2]).unwrap());
+        let mut before_assets = MultiAsset::new();
+        before_assets.set_asset(&policy, &name1, &to_bignum(5));
+        let before = Value { coin: to_bignum(100), multiasset: Some(before_assets) };
+        let mut after_assets = MultiAsset::new();
+        after_assets.set_asset(&policy, &name1, &to_bignum(5));
+        after_assets.set_asset(&policy, &name2, &to_bignum(3));
+        let after = Value { coin: to_bignum(40), multiasset: Some(after_assets) };
+
+        let delta = ValueDelta::between(&before, &after).unwrap();
+        assert_eq!(delta.coin(), Int::new_negative(&to_bignum(60)));
+        // unchanged assets are dropped
+        assert_eq!(delta.get_asset(&policy, &name1), Int::new_i32(0));
+        assert_eq!(delta.get_asset(&policy, &name2), Int::new_i32(3));
+        assert_eq!(delta.negative_part().unwrap(), Value::new(&to_bignum(60)));
+        assert_eq!(delta.positive_part().unwrap().multiasset.unwrap().get_asset(&policy, &name2), to_bignum(3));
+        assert!(delta.to_value().is_err());
+        assert!(delta.checked_add(&delta.negate()).unwrap().is_zero());
+        assert_eq!(ValueDelta::from_value(&before).checked_add(&delta).unwrap().to_value().unwrap(), after);
+
+        let mut mint = Mint::new();
+        let mut mint_assets = MintAssets::new();
+        mint_assets.insert(&name1, Int::new_i32(-2));
+        mint_assets.insert(&name2, Int::new_i32(4));
+        mint.insert(&policy, &mint_assets);
+        assert_eq!(mint.as_positive_value().multiasset.unwrap().get_asset(&policy, &name2), to_bignum(4));
+        assert_eq!(mint.as_negative_value().multiasset.unwrap().get_asset(&policy, &name1), to_bignum(2));
+        assert_eq!(ValueDelta::from_mint(&mint).get_asset(&policy, &name1), Int::new_i32(-2));
+    }
+}
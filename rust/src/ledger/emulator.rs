@@ -0,0 +1,755 @@
+use std::collections::BTreeMap;
+
+use crate::*;
+use crate::builders::certificate_builder::cert_required_wits;
+use crate::builders::input_builder::input_required_wits;
+use crate::builders::tx_builder::TransactionBuilderConfig;
+use crate::builders::withdrawal_builder::withdrawal_required_wits;
+use crate::builders::witness_builder::RequiredWitnessSet;
+use crate::crypto::blake2b256;
+use crate::error::Error;
+use crate::ledger::alonzo::fees::min_fee;
+use crate::ledger::babbage::min_ada::min_ada_required;
+use crate::ledger::common::deposit::{DepositCalculator, DepositState};
+use crate::ledger::common::hash::hash_transaction;
+use crate::ledger::common::native_script_eval::evaluate_native_script;
+use crate::ledger::common::utxo::{TransactionUnspentOutput, TransactionUnspentOutputs, get_produced_utxos};
+use crate::ledger::common::value::{BigNum, Coin, Value, to_bignum};
+
+#[derive(Clone, Debug)]
+struct StakeAccount {
+    delegation: Option<Ed25519KeyHash>,
+    rewards: Coin,
+    // refunded on deregistration
+    deposit: Coin,
+}
+
+#[derive(Clone, Debug)]
+struct PoolState {
+    params: PoolParams,
+    retiring: Option<Epoch>,
+}
+
+// accounts and pools are checked and updated together since certificates in a tx can depend on each other
+#[derive(Clone, Debug, Default)]
+struct StakeState {
+    accounts: BTreeMap<StakeCredential, StakeAccount>,
+    pools: BTreeMap<Ed25519KeyHash, PoolState>,
+}
+
+impl StakeState {
+    fn account_mut(&mut self, credential: &StakeCredential) -> Result<&mut StakeAccount, Error> {
+        self.accounts
+            .get_mut(credential)
+            .ok_or_else(|| Error::from_str(&format!("stake credential {} is not registered", credential_to_str(credential))))
+    }
+
+    // follows the ledger's DELEGS rule: withdrawals first, then certificates in order
+    fn apply(&mut self, body: &TransactionBody, epoch: Epoch, key_deposit: &Coin) -> Result<(), Error> {
+        if let Some(withdrawals) = &body.withdrawals {
+            for (reward_address, amount) in withdrawals.0.iter() {
+                let account = self.account_mut(&reward_address.payment_cred())?;
+                if account.rewards != *amount {
+                    return Err(Error::from_str(&format!(
+                        "withdrawals must take the whole reward balance: withdrew {} but balance is {}",
+                        amount.to_str(),
+                        account.rewards.to_str(),
+                    )));
+                }
+                account.rewards = Coin::zero();
+            }
+        }
+        for cert in body.certs.iter().flat_map(|certs| certs.0.iter()) {
+            match &cert.0 {
+                CertificateEnum::StakeRegistration(cert) => {
+                    if self.accounts.contains_key(&cert.stake_credential) {
+                        return Err(Error::from_str(&format!("stake credential {} is already registered", credential_to_str(&cert.stake_credential))));
+                    }
+                    self.accounts.insert(cert.stake_credential.clone(), StakeAccount { delegation: None, rewards: Coin::zero(), deposit: *key_deposit });
+                },
+                CertificateEnum::StakeDeregistration(cert) => {
+                    if !self.account_mut(&cert.stake_credential)?.rewards.is_zero() {
+                        return Err(Error::from_str("stake credential can't be deregistered before its rewards are withdrawn"));
+                    }
+                    self.accounts.remove(&cert.stake_credential);
+                },
+                CertificateEnum::StakeDelegation(cert) => {
+                    if !self.pools.contains_key(&cert.pool_keyhash) {
+                        return Err(Error::from_str(&format!("pool {} is not registered", cert.pool_keyhash.to_hex())));
+                    }
+                    self.account_mut(&cert.stake_credential)?.delegation = Some(cert.pool_keyhash.clone());
+                },
+                CertificateEnum::PoolRegistration(cert) => {
+                    // re-registering updates the parameters and cancels any pending retirement
+                    self.pools.insert(cert.pool_params.operator.clone(), PoolState {
+                        params: cert.pool_params.clone(),
+                        retiring: None,
+                    });
+                },
+                CertificateEnum::PoolRetirement(cert) => {
+                    let pool = self.pools
+                        .get_mut(&cert.pool_keyhash)
+                        .ok_or_else(|| Error::from_str(&format!("pool {} is not registered", cert.pool_keyhash.to_hex())))?;
+                    if cert.epoch <= epoch {
+                        return Err(Error::from_str(&format!("pool retirement epoch {} must be after the current epoch {}", cert.epoch, epoch)));
+                    }
+                    pool.retiring = Some(cert.epoch);
+                },
+                CertificateEnum::GenesisKeyDelegation(_) |
+                CertificateEnum::MoveInstantaneousRewardsCert(_) => {
+                    return Err(Error::from_str("genesis key delegation and MIR certificates are not supported by the emulator"));
+                },
+            }
+        }
+        Ok(())
+    }
+
+    // POOLREAP: retired pools' deposits go back to their reward account and their delegations are dropped
+    fn retire_pools(&mut self, epoch: Epoch, pool_deposit: &Coin) -> Result<(), Error> {
+        let retiring = self.pools
+            .iter()
+            .filter(|(_, pool)| pool.retiring == Some(epoch))
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        for id in retiring {
+            if let Some(pool) = self.pools.remove(&id) {
+                if let Some(account) = self.accounts.get_mut(&pool.params.reward_account.payment_cred()) {
+                    account.rewards = account.rewards.checked_add(pool_deposit)?;
+                }
+            }
+            for account in self.accounts.values_mut() {
+                if account.delegation.as_ref() == Some(&id) {
+                    account.delegation = None;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn credential_to_str(credential: &StakeCredential) -> String {
+    match (credential.to_keyhash(), credential.to_scripthash()) {
+        (Some(keyhash), _) => format!("key {}", keyhash.to_hex()),
+        (_, Some(script_hash)) => format!("script {}", script_hash.to_hex()),
+        _ => String::from("unknown"),
+    }
+}
+
+/// In-memory ledger to run transactions against without a node e.g. in integration tests
+///
+/// Holds a UTxO set, stake accounts, stake pools and a slot clock.
+/// Submitted transactions go through the phase-1 checks of the ledger (validity interval, fee, balance,
+/// min ADA, sizes, witnesses, native scripts, certificates and withdrawals) and are applied immediately.
+/// Plutus scripts are NOT executed: transactions are assumed to pass phase-2 unless `is_valid` is false,
+/// in which case only the collateral is consumed
+#[derive(Clone, Debug)]
+pub struct Emulator {
+    config: TransactionBuilderConfig,
+    slots_per_epoch: u64,
+    slot: u64,
+    utxos: BTreeMap<TransactionInput, TransactionOutput>,
+    stake: StakeState,
+    // used to give every genesis output a unique input
+    genesis_outputs: u64,
+}
+
+impl Emulator {
+    /// The protocol parameters are taken from the same config the `TransactionBuilder` uses
+    pub fn new(config: &TransactionBuilderConfig, slots_per_epoch: u64) -> Result<Emulator, Error> {
+        if slots_per_epoch == 0 {
+            return Err(Error::from_str("slots_per_epoch must be positive"));
+        }
+        Ok(Self {
+            config: config.clone(),
+            slots_per_epoch,
+            slot: 0,
+            utxos: BTreeMap::new(),
+            stake: StakeState::default(),
+            genesis_outputs: 0,
+        })
+    }
+
+    /// Creates an output out of thin air, as if it were in the genesis block
+    pub fn fund(&mut self, address: &Address, amount: &Value) -> TransactionUnspentOutput {
+        let genesis_hash = TransactionHash::from(blake2b256(&self.genesis_outputs.to_be_bytes()));
+        self.genesis_outputs += 1;
+        let input = TransactionInput::new(&genesis_hash, &BigNum::zero());
+        let output = TransactionOutput::new(address, amount);
+        self.utxos.insert(input.clone(), output.clone());
+        TransactionUnspentOutput::new(&input, &output)
+    }
+
+    pub fn slot(&self) -> Slot {
+        to_bignum(self.slot)
+    }
+
+    pub fn epoch(&self) -> Epoch {
+        (self.slot / self.slots_per_epoch) as Epoch
+    }
+
+    pub fn advance_slots(&mut self, slots: u64) -> Result<(), Error> {
+        let start_epoch = self.epoch();
+        self.slot = self.slot
+            .checked_add(slots)
+            .ok_or_else(|| Error::from_str("slot overflow"))?;
+        for epoch in (start_epoch + 1)..=self.epoch() {
+            self.stake.retire_pools(epoch, &self.config.pool_deposit)?;
+        }
+        Ok(())
+    }
+
+    /// Moves the clock to the first slot of the epoch `epochs` after the current one,
+    /// going through every epoch boundary in between
+    pub fn advance_epochs(&mut self, epochs: u32) -> Result<(), Error> {
+        let target = (self.epoch() as u64)
+            .checked_add(epochs as u64)
+            .and_then(|epoch| epoch.checked_mul(self.slots_per_epoch))
+            .ok_or_else(|| Error::from_str("slot overflow"))?;
+        self.advance_slots(target.saturating_sub(self.slot))
+    }
+
+    pub fn utxos(&self) -> TransactionUnspentOutputs {
+        TransactionUnspentOutputs(
+            self.utxos
+                .iter()
+                .map(|(input, output)| TransactionUnspentOutput::new(input, output))
+                .collect()
+        )
+    }
+
+    pub fn utxos_at(&self, address: &Address) -> TransactionUnspentOutputs {
+        TransactionUnspentOutputs(
+            self.utxos
+                .iter()
+                .filter(|(_, output)| output.address == *address)
+                .map(|(input, output)| TransactionUnspentOutput::new(input, output))
+                .collect()
+        )
+    }
+
+    pub fn utxo(&self, input: &TransactionInput) -> Option<TransactionOutput> {
+        self.utxos.get(input).cloned()
+    }
+
+    pub fn is_registered(&self, credential: &StakeCredential) -> bool {
+        self.stake.accounts.contains_key(credential)
+    }
+
+    pub fn delegation(&self, credential: &StakeCredential) -> Option<Ed25519KeyHash> {
+        self.stake.accounts.get(credential).and_then(|account| account.delegation.clone())
+    }
+
+    /// None if the credential is not registered
+    pub fn reward_balance(&self, credential: &StakeCredential) -> Option<Coin> {
+        self.stake.accounts.get(credential).map(|account| account.rewards)
+    }
+
+    /// Simulates a reward payout to a registered stake credential
+    pub fn add_rewards(&mut self, credential: &StakeCredential, amount: &Coin) -> Result<(), Error> {
+        let account = self.stake.account_mut(credential)?;
+        account.rewards = account.rewards.checked_add(amount)?;
+        Ok(())
+    }
+
+    pub fn is_pool_registered(&self, pool: &Ed25519KeyHash) -> bool {
+        self.stake.pools.contains_key(pool)
+    }
+
+    /// Runs the phase-1 checks against the current ledger state without applying the transaction
+    pub fn validate(&self, tx: &Transaction) -> Result<(), Error> {
+        let body = &tx.body;
+
+        if let Some(start) = body.validity_start_interval {
+            if self.slot < u64::from(start) {
+                return Err(Error::from_str(&format!("transaction is not valid before slot {} (current slot {})", start.to_str(), self.slot)));
+            }
+        }
+        if let Some(ttl) = body.ttl {
+            if self.slot >= u64::from(ttl) {
+                return Err(Error::from_str(&format!("transaction expired at slot {} (current slot {})", ttl.to_str(), self.slot)));
+            }
+        }
+
+        let tx_size = tx.to_bytes().len();
+        if tx_size > self.config.max_tx_size as usize {
+            return Err(Error::from_str(&format!("transaction size {} exceeds the maximum of {}", tx_size, self.config.max_tx_size)));
+        }
+
+        if body.inputs.0.is_empty() {
+            return Err(Error::from_str("transaction has no inputs"));
+        }
+        let inputs = self.resolve(&body.inputs, "input")?;
+        let reference_inputs = match &body.reference_inputs {
+            Some(reference_inputs) => self.resolve(reference_inputs, "reference input")?,
+            None => Vec::new(),
+        };
+
+        let min_fee = min_fee(tx, &self.config.fee_algo, &self.config.ex_unit_prices)?;
+        if body.fee < min_fee {
+            return Err(Error::from_str(&format!("fee {} is below the minimum fee {}", body.fee.to_str(), min_fee.to_str())));
+        }
+
+        for (i, output) in body.outputs.0.iter().enumerate() {
+            self.check_output(output).map_err(|e| Error::from_str(&format!("output {}: {}", i, e.as_string().unwrap_or_default())))?;
+        }
+
+        self.check_balance(body, &inputs)?;
+        self.stake.clone().apply(body, self.epoch(), &self.config.key_deposit)?;
+        self.check_witnesses(tx, &inputs, &reference_inputs)?;
+        if matches!(&tx.witness_set.redeemers, Some(redeemers) if !redeemers.0.is_empty()) {
+            self.check_collateral(body)?;
+        }
+        Ok(())
+    }
+
+    /// Validates and applies the transaction, returning its hash
+    pub fn submit(&mut self, tx: &Transaction) -> Result<TransactionHash, Error> {
+        self.validate(tx)?;
+        let body = &tx.body;
+        if tx.is_valid {
+            for input in body.inputs.0.iter() {
+                self.utxos.remove(input);
+            }
+            self.stake.apply(body, self.epoch(), &self.config.key_deposit)?;
+        } else {
+            // failed phase-2 validation: the collateral pays for the transaction instead
+            for input in body.collateral.iter().flat_map(|collateral| collateral.0.iter()) {
+                self.utxos.remove(input);
+            }
+        }
+        for utxo in get_produced_utxos(body, tx.is_valid).0 {
+            self.utxos.insert(utxo.input, utxo.output);
+        }
+        Ok(hash_transaction(body))
+    }
+
+    fn resolve(&self, inputs: &TransactionInputs, kind: &str) -> Result<Vec<TransactionUnspentOutput>, Error> {
+        let mut resolved = Vec::new();
+        for input in inputs.0.iter() {
+            if resolved.iter().any(|utxo: &TransactionUnspentOutput| utxo.input == *input) {
+                return Err(Error::from_str(&format!("{} {}#{} is used more than once", kind, input.transaction_id.to_hex(), input.index.to_str())));
+            }
+            let output = self.utxos
+                .get(input)
+                .ok_or_else(|| Error::from_str(&format!("{} {}#{} is not in the UTxO set", kind, input.transaction_id.to_hex(), input.index.to_str())))?;
+            resolved.push(TransactionUnspentOutput::new(input, output));
+        }
+        Ok(resolved)
+    }
+
+    fn check_output(&self, output: &TransactionOutput) -> Result<(), Error> {
+        let min_ada = min_ada_required(output, &self.config.coins_per_utxo_byte)?;
+        if output.amount.coin < min_ada {
+            return Err(Error::from_str(&format!("holds {} lovelace but requires at least {}", output.amount.coin.to_str(), min_ada.to_str())));
+        }
+        let value_size = output.amount.to_bytes().len();
+        if value_size > self.config.max_value_size as usize {
+            return Err(Error::from_str(&format!("value size {} exceeds the maximum of {}", value_size, self.config.max_value_size)));
+        }
+        Ok(())
+    }
+
+    fn check_balance(&self, body: &TransactionBody, inputs: &[TransactionUnspentOutput]) -> Result<(), Error> {
+        let deposits = DepositCalculator::new(&self.config.pool_deposit, &self.config.key_deposit).with_state(self);
+        let mut consumed = deposits.implicit_input(&body.withdrawals, &body.certs)?;
+        for utxo in inputs {
+            consumed = consumed.checked_add(&utxo.output.amount)?;
+        }
+        let mut produced = Value::new(&body.fee.checked_add(&deposits.deposit(&body.certs)?)?);
+        for output in body.outputs.0.iter() {
+            produced = produced.checked_add(&output.amount)?;
+        }
+        if let Some(mint) = &body.mint {
+            consumed = consumed.checked_add(&mint.as_positive_value())?;
+            produced = produced.checked_add(&mint.as_negative_value())?;
+        }
+        if consumed.compare(&produced) != Some(0) {
+            return Err(Error::from_str(&format!(
+                "value not preserved: consumed {} lovelace, produced {} lovelace{}",
+                consumed.coin.to_str(),
+                produced.coin.to_str(),
+                if consumed.coin == produced.coin { " (assets differ)" } else { "" },
+            )));
+        }
+        Ok(())
+    }
+
+    fn check_witnesses(&self, tx: &Transaction, inputs: &[TransactionUnspentOutput], reference_inputs: &[TransactionUnspentOutput]) -> Result<(), Error> {
+        let body = &tx.body;
+        let witness_set = &tx.witness_set;
+        let body_hash = hash_transaction(body);
+
+        let mut required = RequiredWitnessSet::default();
+        for utxo in inputs {
+            input_required_wits(&utxo.output, &mut required);
+        }
+        for (reward_address, _) in body.withdrawals.iter().flat_map(|withdrawals| withdrawals.0.iter()) {
+            withdrawal_required_wits(reward_address, &mut required);
+        }
+        for cert in body.certs.iter().flat_map(|certs| certs.0.iter()) {
+            cert_required_wits(cert, &mut required);
+        }
+        for policy_id in body.mint.iter().flat_map(|mint| mint.0.keys()) {
+            required.add_script_hash(policy_id);
+        }
+        for signer in body.required_signers.iter().flat_map(|signers| signers.0.iter()) {
+            required.add_vkey_key_hash(signer);
+        }
+
+        let mut signers = RequiredSignersSet::new();
+        for vkey in witness_set.vkeys.iter().flat_map(|vkeys| vkeys.0.iter()) {
+            let public_key = vkey.vkey().public_key();
+            if !public_key.verify(body_hash.to_bytes().as_ref(), &vkey.signature()) {
+                return Err(Error::from_str(&format!("invalid signature from {}", public_key.hash().to_hex())));
+            }
+            signers.insert(public_key.hash());
+        }
+        let mut bootstrap_addresses = Vec::new();
+        for bootstrap in witness_set.bootstraps.iter().flat_map(|bootstraps| bootstraps.0.iter()) {
+            if !bootstrap.vkey().public_key().verify(body_hash.to_bytes().as_ref(), &bootstrap.signature()) {
+                return Err(Error::from_str("invalid bootstrap witness signature"));
+            }
+            bootstrap_addresses.push(bootstrap.to_address()?);
+        }
+
+        for keyhash in required.vkeys.iter() {
+            if !signers.contains(keyhash) {
+                return Err(Error::from_str(&format!("missing signature from {}", keyhash.to_hex())));
+            }
+        }
+        for byron in required.bootstraps.iter() {
+            if !bootstrap_addresses.contains(&byron.address_content()) {
+                return Err(Error::from_str(&format!("missing bootstrap witness for {}", byron.to_base58())));
+            }
+        }
+
+        // scripts can be in the witness set or referenced by a spent or reference input
+        let mut scripts = BTreeMap::new();
+        for script in witness_set.native_scripts.iter().flat_map(|scripts| scripts.0.iter()) {
+            scripts.insert(script.hash(), Script::new_native(script));
+        }
+        for script in witness_set.plutus_v1_scripts.iter().flat_map(|scripts| scripts.0.iter()) {
+            scripts.insert(script.hash(), Script::new_plutus_v1(script));
+        }
+        for script in witness_set.plutus_v2_scripts.iter().flat_map(|scripts| scripts.0.iter()) {
+            scripts.insert(script.hash(), Script::new_plutus_v2(script));
+        }
+        for script in witness_set.plutus_v3_scripts.iter().flat_map(|scripts| scripts.0.iter()) {
+            scripts.insert(script.hash(), Script::new_plutus_v3(script));
+        }
+        for utxo in inputs.iter().chain(reference_inputs.iter()) {
+            if let Some(script_ref) = &utxo.output.script_ref {
+                scripts.insert(script_ref.script().hash(), script_ref.script());
+            }
+        }
+        let has_redeemers = matches!(&witness_set.redeemers, Some(redeemers) if !redeemers.0.is_empty());
+        let available_signers = Ed25519KeyHashes(signers.into_iter().collect());
+        for script_hash in required.scripts.iter() {
+            let script = scripts
+                .get(script_hash)
+                .ok_or_else(|| Error::from_str(&format!("missing script {}", script_hash.to_hex())))?;
+            match script.as_native() {
+                Some(native_script) => {
+                    let evaluation = evaluate_native_script(&native_script, &available_signers, body.validity_start_interval, body.ttl);
+                    if let Some(failure) = evaluation.failure() {
+                        return Err(Error::from_str(&format!("native script {} failed: {}", script_hash.to_hex(), failure)));
+                    }
+                },
+                None if !has_redeemers => {
+                    return Err(Error::from_str(&format!("plutus script {} has no redeemer", script_hash.to_hex())));
+                },
+                None => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn check_collateral(&self, body: &TransactionBody) -> Result<(), Error> {
+        let collateral = match &body.collateral {
+            Some(collateral) if !collateral.0.is_empty() => self.resolve(collateral, "collateral input")?,
+            _ => return Err(Error::from_str("transactions running plutus scripts need collateral")),
+        };
+        if collateral.len() > self.config.max_collateral_inputs as usize {
+            return Err(Error::from_str(&format!("{} collateral inputs exceed the maximum of {}", collateral.len(), self.config.max_collateral_inputs)));
+        }
+        let mut balance = Value::zero();
+        for utxo in collateral.iter() {
+            if utxo.output.address.payment_cred().and_then(|cred| cred.to_keyhash()).is_none() {
+                return Err(Error::from_str("collateral inputs must be locked by a key"));
+            }
+            balance = balance.checked_add(&utxo.output.amount)?;
+        }
+        if let Some(collateral_return) = &body.collateral_return {
+            balance = balance.checked_sub(&collateral_return.amount)?;
+        }
+        if matches!(&balance.multiasset, Some(assets) if assets.len() > 0) {
+            return Err(Error::from_str("collateral can't contain tokens that are not returned"));
+        }
+        let required = body.fee
+            .checked_mul(&to_bignum(self.config.collateral_percentage as u64))?
+            .checked_div_ceil(&to_bignum(100))?;
+        if balance.coin < required {
+            return Err(Error::from_str(&format!("collateral of {} is below the required {}", balance.coin.to_str(), required.to_str())));
+        }
+        if let Some(total_collateral) = body.total_collateral {
+            if total_collateral != balance.coin {
+                return Err(Error::from_str(&format!("total collateral {} does not match the collateral balance {}", total_collateral.to_str(), balance.coin.to_str())));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DepositState for Emulator {
+    fn is_pool_registered(&self, pool: &Ed25519KeyHash) -> bool {
+        self.stake.pools.contains_key(pool)
+    }
+
+    fn stake_deposit(&self, credential: &StakeCredential) -> Option<Coin> {
+        self.stake.accounts.get(credential).map(|account| account.deposit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::certificate_builder::SingleCertificateBuilder;
+    use crate::builders::input_builder::SingleInputBuilder;
+    use crate::builders::output_builder::TransactionOutputBuilder;
+    use crate::builders::tx_builder::{TransactionBuilder, TransactionBuilderConfigBuilder};
+    use crate::builders::withdrawal_builder::SingleWithdrawalBuilder;
+    use crate::ledger::alonzo::fees::LinearFee;
+    use crate::ledger::shelley::witness::make_vkey_witness;
+
+    const SLOTS_PER_EPOCH: u64 = 100;
+    const KEY_DEPOSIT: u64 = 2_000_000;
+    const POOL_DEPOSIT: u64 = 500_000_000;
+
+    fn config() -> TransactionBuilderConfig {
+        TransactionBuilderConfigBuilder::new()
+            .fee_algo(&LinearFee::new(&to_bignum(44), &to_bignum(155381)))
+            .pool_deposit(&to_bignum(POOL_DEPOSIT))
+            .key_deposit(&to_bignum(KEY_DEPOSIT))
+            .max_value_size(5000)
+            .max_tx_size(16384)
+            .coins_per_utxo_byte(&to_bignum(4310))
+            .ex_unit_prices(&ExUnitPrices::new(
+                &SubCoin::new(&to_bignum(577), &to_bignum(10000)),
+                &SubCoin::new(&to_bignum(721), &to_bignum(10000000)),
+            ))
+            .collateral_percentage(150)
+            .max_collateral_inputs(3)
+            .build()
+            .unwrap()
+    }
+
+    struct Wallet {
+        payment: PrivateKey,
+        stake: PrivateKey,
+    }
+
+    impl Wallet {
+        fn new(x: u8) -> Self {
+            Self {
+                payment: PrivateKey::from_normal_bytes(&[x; 32]).unwrap(),
+                stake: PrivateKey::from_normal_bytes(&[x + 100; 32]).unwrap(),
+            }
+        }
+
+        fn stake_cred(&self) -> StakeCredential {
+            StakeCredential::from_keyhash(&self.stake.to_public().hash())
+        }
+
+        fn address(&self) -> Address {
+            BaseAddress::new(0, &StakeCredential::from_keyhash(&self.payment.to_public().hash()), &self.stake_cred()).to_address()
+        }
+
+        fn reward_address(&self) -> RewardAddress {
+            RewardAddress::new(0, &self.stake_cred())
+        }
+
+        // spends all of the wallet's outputs sending `amount` to `to` (if any) and the change back to the wallet
+        fn build_tx(
+            &self,
+            emulator: &Emulator,
+            to: Option<(&Address, u64)>,
+            edit: impl FnOnce(&mut TransactionBuilder),
+        ) -> Transaction {
+            let mut builder = TransactionBuilder::new(&config());
+            let utxos = emulator.utxos_at(&self.address());
+            for i in 0..utxos.len() {
+                let utxo = utxos.get(i);
+                builder.add_input(&SingleInputBuilder::new(&utxo.input(), &utxo.output()).payment_key().unwrap());
+            }
+            if let Some((address, amount)) = to {
+                builder.add_output(&TransactionOutputBuilder::new()
+                    .with_address(address)
+                    .next().unwrap()
+                    .with_coin(&to_bignum(amount))
+                    .build().unwrap()
+                ).unwrap();
+            }
+            edit(&mut builder);
+            builder.add_change_if_needed(&self.address()).unwrap();
+            let mut signed = builder.build().unwrap().build().unwrap();
+            let body_hash = hash_transaction(&signed.body());
+            signed.add_vkey(&make_vkey_witness(&body_hash, &self.payment));
+            if signed.body().certs().is_some() || signed.body().withdrawals().is_some() {
+                signed.add_vkey(&make_vkey_witness(&body_hash, &self.stake));
+            }
+            signed.build_unchecked()
+        }
+    }
+
+    #[test]
+    fn chained_payments() {
+        let mut emulator = Emulator::new(&config(), SLOTS_PER_EPOCH).unwrap();
+        let (alice, bob) = (Wallet::new(1), Wallet::new(2));
+        emulator.fund(&alice.address(), &Value::new(&to_bignum(100_000_000)));
+
+        let tx = alice.build_tx(&emulator, Some((&bob.address(), 30_000_000)), |_| ());
+        let tx_hash = emulator.submit(&tx).unwrap();
+        assert!(emulator.submit(&tx).is_err(), "inputs were already spent");
+        assert_eq!(emulator.utxo(&TransactionInput::new(&tx_hash, &BigNum::zero())).unwrap().amount().coin(), to_bignum(30_000_000));
+
+        // bob spends the output created by the previous transaction
+        let tx = bob.build_tx(&emulator, Some((&alice.address(), 10_000_000)), |_| ());
+        emulator.submit(&tx).unwrap();
+        assert_eq!(emulator.utxos_at(&bob.address()).len(), 1);
+        assert_eq!(emulator.utxos_at(&alice.address()).len(), 2);
+    }
+
+    #[test]
+    fn phase_one_failures() {
+        let mut emulator = Emulator::new(&config(), SLOTS_PER_EPOCH).unwrap();
+        let (alice, bob) = (Wallet::new(1), Wallet::new(2));
+        emulator.fund(&alice.address(), &Value::new(&to_bignum(100_000_000)));
+
+        let tx = alice.build_tx(&emulator, Some((&bob.address(), 30_000_000)), |builder| builder.set_ttl(&to_bignum(50)));
+        emulator.advance_slots(50).unwrap();
+        assert!(emulator.validate(&tx).unwrap_err().as_string().unwrap().contains("expired"));
+
+        let tx = alice.build_tx(&emulator, Some((&bob.address(), 30_000_000)), |_| ());
+        let unsigned = Transaction::new(&tx.body(), &TransactionWitnessSet::new(), None);
+        assert!(emulator.validate(&unsigned).unwrap_err().as_string().unwrap().contains("missing signature"));
+
+        let mut body = tx.body();
+        body.fee = body.fee.checked_add(&to_bignum(1)).unwrap();
+        let unbalanced = Transaction::new(&body, &tx.witness_set(), None);
+        assert!(emulator.validate(&unbalanced).unwrap_err().as_string().unwrap().contains("value not preserved"));
+    }
+
+    #[test]
+    fn clock_overflow() {
+        let mut emulator = Emulator::new(&config(), u64::MAX / 2).unwrap();
+        assert!(emulator.advance_epochs(3).is_err());
+        assert_eq!(emulator.slot(), BigNum::zero());
+        emulator.advance_epochs(1).unwrap();
+        assert_eq!(emulator.slot(), to_bignum(u64::MAX / 2));
+        assert!(emulator.advance_slots(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn stake_lifecycle_across_epochs() {
+        let mut emulator = Emulator::new(&config(), SLOTS_PER_EPOCH).unwrap();
+        let (alice, operator) = (Wallet::new(1), Wallet::new(3));
+        emulator.fund(&alice.address(), &Value::new(&to_bignum(100_000_000)));
+        emulator.fund(&operator.address(), &Value::new(&to_bignum(1_000_000_000)));
+
+        let pool_id = operator.stake.to_public().hash();
+        let pool_params = PoolParams::new(
+            &pool_id,
+            &VRFKeyHash::from([0u8; VRFKeyHash::BYTE_COUNT]),
+            &to_bignum(0),
+            &to_bignum(340_000_000),
+            &UnitInterval::new(&to_bignum(1), &to_bignum(100)),
+            &operator.reward_address(),
+            &Ed25519KeyHashes::new(),
+            &Relays::new(),
+            None,
+        );
+        let tx = operator.build_tx(&emulator, None, |builder| {
+            builder.add_cert(&SingleCertificateBuilder::new(&Certificate::new_stake_registration(&StakeRegistration::new(&operator.stake_cred()))).skip_witness());
+            builder.add_cert(&SingleCertificateBuilder::new(&Certificate::new_pool_registration(&PoolRegistration::new(&pool_params))).payment_key().unwrap());
+        });
+        emulator.submit(&tx).unwrap();
+        assert!(emulator.is_pool_registered(&pool_id));
+
+        let tx = alice.build_tx(&emulator, None, |builder| {
+            builder.add_cert(&SingleCertificateBuilder::new(&Certificate::new_stake_registration(&StakeRegistration::new(&alice.stake_cred()))).skip_witness());
+            builder.add_cert(&SingleCertificateBuilder::new(&Certificate::new_stake_delegation(&StakeDelegation::new(&alice.stake_cred(), &pool_id))).payment_key().unwrap());
+        });
+        emulator.submit(&tx).unwrap();
+        assert_eq!(emulator.delegation(&alice.stake_cred()), Some(pool_id.clone()));
+        assert_eq!(emulator.reward_balance(&alice.stake_cred()), Some(Coin::zero()));
+
+        emulator.add_rewards(&alice.stake_cred(), &to_bignum(5_000_000)).unwrap();
+        let partial = alice.build_tx(&emulator, None, |builder| {
+            builder.add_withdrawal(&SingleWithdrawalBuilder::new(&alice.reward_address(), &to_bignum(1_000_000)).payment_key().unwrap());
+        });
+        assert!(emulator.submit(&partial).is_err(), "withdrawals must empty the reward account");
+        let tx = alice.build_tx(&emulator, None, |builder| {
+            builder.add_withdrawal(&SingleWithdrawalBuilder::new(&alice.reward_address(), &to_bignum(5_000_000)).payment_key().unwrap());
+        });
+        emulator.submit(&tx).unwrap();
+        assert_eq!(emulator.reward_balance(&alice.stake_cred()), Some(Coin::zero()));
+
+        // retire the pool two epochs from now and time travel past it
+        let tx = operator.build_tx(&emulator, None, |builder| {
+            builder.add_cert(&SingleCertificateBuilder::new(&Certificate::new_pool_retirement(&PoolRetirement::new(&pool_id, 2))).payment_key().unwrap());
+        });
+        emulator.submit(&tx).unwrap();
+        emulator.advance_epochs(1).unwrap();
+        assert!(emulator.is_pool_registered(&pool_id));
+        emulator.advance_epochs(1).unwrap();
+        assert_eq!(emulator.epoch(), 2);
+        assert!(!emulator.is_pool_registered(&pool_id));
+        assert_eq!(emulator.delegation(&alice.stake_cred()), None);
+        assert_eq!(emulator.reward_balance(&operator.stake_cred()), Some(to_bignum(POOL_DEPOSIT)));
+
+        let tx = alice.build_tx(&emulator, None, |builder| {
+            builder.add_cert(&SingleCertificateBuilder::new(&Certificate::new_stake_deregistration(&StakeDeregistration::new(&alice.stake_cred()))).payment_key().unwrap());
+        });
+        emulator.submit(&tx).unwrap();
+        assert!(!emulator.is_registered(&alice.stake_cred()));
+    }
+
+    #[test]
+    fn pool_update_with_deposit_state() {
+        let mut emulator = Emulator::new(&config(), SLOTS_PER_EPOCH).unwrap();
+        let operator = Wallet::new(3);
+        emulator.fund(&operator.address(), &Value::new(&to_bignum(2_000_000_000)));
+
+        let pool_id = operator.stake.to_public().hash();
+        let pool_params = |cost: u64| PoolParams::new(
+            &pool_id,
+            &VRFKeyHash::from([0u8; VRFKeyHash::BYTE_COUNT]),
+            &to_bignum(0),
+            &to_bignum(cost),
+            &UnitInterval::new(&to_bignum(1), &to_bignum(100)),
+            &operator.reward_address(),
+            &Ed25519KeyHashes::new(),
+            &Relays::new(),
+            None,
+        );
+        let tx = operator.build_tx(&emulator, None, |builder| {
+            builder.add_cert(&SingleCertificateBuilder::new(&Certificate::new_pool_registration(&PoolRegistration::new(&pool_params(340_000_000)))).payment_key().unwrap());
+        });
+        emulator.submit(&tx).unwrap();
+
+        // updating the registered pool doesn't pay another deposit, which the builder only knows from the chain state
+        let update = |builder: &mut TransactionBuilder| {
+            builder.add_cert(&SingleCertificateBuilder::new(&Certificate::new_pool_registration(&PoolRegistration::new(&pool_params(400_000_000)))).payment_key().unwrap());
+        };
+        let tx = operator.build_tx(&emulator, None, update);
+        assert!(emulator.validate(&tx).unwrap_err().as_string().unwrap().contains("value not preserved"));
+        let state = std::sync::Arc::new(emulator.clone());
+        let tx = operator.build_tx(&emulator, None, |builder| {
+            builder.set_deposit_state(state);
+            update(builder);
+            assert_eq!(builder.get_deposit().unwrap(), Coin::zero());
+        });
+        emulator.submit(&tx).unwrap();
+        assert!(emulator.is_pool_registered(&pool_id));
+    }
+}
@@ -0,0 +1,602 @@
+//! Transactions of the Shelley-based eras up to Babbage.
+//!
+//! Transaction and TransactionBody decode the union of what all eras allow, so the era a transaction
+//! was made for can't be told from them. EraTransaction checks a transaction only uses what its era
+//! allows and re-encodes it the way that era does: pre-Alonzo transactions have no is_valid flag.
+
+use std::fmt;
+use std::io::{BufRead, Seek};
+
+use cbor_event::{self, de::Deserializer, se::Serializer, Special as CBORSpecial, Type as CBORType};
+use schemars::JsonSchema;
+
+use crate::*;
+use crate::error::{DeserializeError, DeserializeFailure, Error, Key};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub enum Era {
+    Shelley,
+    /// adds validity intervals and timelock scripts
+    Allegra,
+    /// adds multi-assets and minting
+    Mary,
+    /// adds Plutus scripts, collateral and the is_valid flag
+    Alonzo,
+    /// adds inline datums, reference scripts and inputs and collateral return
+    Babbage,
+}
+
+impl Era {
+    pub const ALL: [Era; 5] = [Era::Shelley, Era::Allegra, Era::Mary, Era::Alonzo, Era::Babbage];
+}
+
+impl fmt::Display for Era {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+fn unknown_key(location: &str, key: u64) -> DeserializeError {
+    DeserializeError::new(location, DeserializeFailure::UnknownKey(Key::Uint(key)))
+}
+
+fn unexpected_type(location: &str, expected: CBORType, found: CBORType) -> DeserializeError {
+    DeserializeError::new(location, DeserializeFailure::CBOR(cbor_event::Error::Expected(expected, found)))
+}
+
+fn has_timelock(script: &NativeScript) -> bool {
+    match &script.0 {
+        NativeScriptEnum::ScriptPubkey(_) => false,
+        NativeScriptEnum::ScriptAll(script) => script.native_scripts.0.iter().any(has_timelock),
+        NativeScriptEnum::ScriptAny(script) => script.native_scripts.0.iter().any(has_timelock),
+        NativeScriptEnum::ScriptNOfK(script) => script.native_scripts.0.iter().any(has_timelock),
+        NativeScriptEnum::TimelockStart(_) | NativeScriptEnum::TimelockExpiry(_) => true,
+    }
+}
+
+fn check_native_scripts(era: Era, scripts: &NativeScripts, location: &str) -> Result<(), DeserializeError> {
+    // Shelley's multisig scripts are timelock scripts without the timelocks
+    match era == Era::Shelley && scripts.0.iter().any(has_timelock) {
+        true => Err(DeserializeError::new(location, DeserializeFailure::NoVariantMatched)),
+        false => Ok(()),
+    }
+}
+
+fn check_output(era: Era, output: &TransactionOutput, location: &str) -> Result<(), DeserializeError> {
+    if era < Era::Mary && output.amount.multiasset.is_some() {
+        return Err(unexpected_type(&format!("{}.amount", location), CBORType::UnsignedInteger, CBORType::Array));
+    }
+    if era < Era::Babbage && (output.prefer_babbage_format || output.script_ref.is_some() || matches!(output.datum_option, Some(DatumEnum::InlineDatum(_)))) {
+        return Err(unexpected_type(location, CBORType::Array, CBORType::Map));
+    }
+    if era < Era::Alonzo && output.datum_option.is_some() {
+        return Err(DeserializeError::new(location, DeserializeFailure::DefiniteLenMismatch(3, Some(2))));
+    }
+    // Plutus V3 came with Conway
+    if matches!(&output.script_ref, Some(script_ref) if script_ref.script().kind() == ScriptKind::PlutusScriptV3) {
+        return Err(DeserializeError::new(format!("{}.script_ref", location), DeserializeFailure::NoVariantMatched));
+    }
+    Ok(())
+}
+
+fn check_body(era: Era, body: &TransactionBody) -> Result<(), DeserializeError> {
+    if era == Era::Shelley && body.ttl.is_none() {
+        return Err(DeserializeError::new("body", DeserializeFailure::MandatoryFieldMissing(Key::Uint(3))));
+    }
+    let fields = [
+        (body.validity_start_interval.is_some(), 8, Era::Allegra),
+        (body.mint.is_some(), 9, Era::Mary),
+        (body.script_data_hash.is_some(), 11, Era::Alonzo),
+        (body.collateral.is_some(), 13, Era::Alonzo),
+        (body.required_signers.is_some(), 14, Era::Alonzo),
+        (body.network_id.is_some(), 15, Era::Alonzo),
+        (body.collateral_return.is_some(), 16, Era::Babbage),
+        (body.total_collateral.is_some(), 17, Era::Babbage),
+        (body.reference_inputs.is_some(), 18, Era::Babbage),
+    ];
+    if let Some((_, key, _)) = fields.iter().find(|(present, _, since)| *present && era < *since) {
+        return Err(unknown_key("body", *key));
+    }
+    for (index, output) in body.outputs.0.iter().enumerate() {
+        check_output(era, output, &format!("body.outputs.{}", index))?;
+    }
+    if let Some(collateral_return) = &body.collateral_return {
+        check_output(era, collateral_return, "body.collateral_return")?;
+    }
+    for (index, cert) in body.certs.iter().flat_map(|certs| certs.0.iter()).enumerate() {
+        if let CertificateEnum::MoveInstantaneousRewardsCert(cert) = &cert.0 {
+            // transfers between the reserves and the treasury came with Alonzo
+            if era < Era::Alonzo && matches!(cert.move_instantaneous_reward.variant, MIREnum::ToOtherPot(_)) {
+                return Err(DeserializeError::new(format!("body.certs.{}", index), DeserializeFailure::NoVariantMatched));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_witness_set(era: Era, witness_set: &TransactionWitnessSet) -> Result<(), DeserializeError> {
+    let fields = [
+        (witness_set.plutus_v1_scripts.is_some(), 3, Era::Alonzo),
+        (witness_set.plutus_data.is_some(), 4, Era::Alonzo),
+        (witness_set.redeemers.is_some(), 5, Era::Alonzo),
+        (witness_set.plutus_v2_scripts.is_some(), 6, Era::Babbage),
+    ];
+    if let Some((_, key, _)) = fields.iter().find(|(present, _, since)| *present && era < *since) {
+        return Err(unknown_key("witness_set", *key));
+    }
+    if witness_set.plutus_v3_scripts.is_some() {
+        return Err(unknown_key("witness_set", 7));
+    }
+    // as are the voting and proposing redeemers
+    for (index, redeemer) in witness_set.redeemers.iter().flat_map(|redeemers| redeemers.0.iter()).enumerate() {
+        if matches!(redeemer.tag().kind(), RedeemerTagKind::Voting | RedeemerTagKind::Proposing) {
+            return Err(DeserializeError::new(format!("witness_set.redeemers.{}.tag", index), DeserializeFailure::NoVariantMatched));
+        }
+    }
+    match &witness_set.native_scripts {
+        Some(scripts) => check_native_scripts(era, scripts, "witness_set.native_scripts"),
+        None => Ok(()),
+    }
+}
+
+fn check_auxiliary_data(era: Era, auxiliary_data: &AuxiliaryData) -> Result<(), DeserializeError> {
+    // Shelley only has metadata, Allegra and Mary metadata alone or along with native scripts.
+    // Anything else is encoded in the Alonzo format
+    if era < Era::Alonzo {
+        let expected = if era == Era::Shelley { CBORType::Map } else { CBORType::Array };
+        if auxiliary_data.prefer_alonzo_format {
+            return Err(unexpected_type("auxiliary_data", expected, CBORType::Tag));
+        }
+        if era == Era::Shelley && auxiliary_data.native_scripts.is_some() {
+            return Err(unexpected_type("auxiliary_data", CBORType::Map, CBORType::Array));
+        }
+        if auxiliary_data.metadata.is_none() {
+            return Err(DeserializeError::new("auxiliary_data", DeserializeFailure::MandatoryFieldMissing(Key::Uint(0))));
+        }
+    }
+    let fields = [
+        (auxiliary_data.plutus_v1_scripts.is_some(), 2, Era::Alonzo),
+        (auxiliary_data.plutus_v2_scripts.is_some(), 3, Era::Babbage),
+    ];
+    if let Some((_, key, _)) = fields.iter().find(|(present, _, since)| *present && era < *since) {
+        return Err(unknown_key("auxiliary_data", *key));
+    }
+    if auxiliary_data.plutus_v3_scripts.is_some() {
+        return Err(unknown_key("auxiliary_data", 4));
+    }
+    match &auxiliary_data.native_scripts {
+        Some(scripts) => check_native_scripts(era, scripts, "auxiliary_data.native_scripts"),
+        None => Ok(()),
+    }
+}
+
+fn check_transaction(era: Era, transaction: &Transaction) -> Result<(), DeserializeError> {
+    if era < Era::Alonzo && !transaction.is_valid {
+        return Err(DeserializeError::new("is_valid", DeserializeFailure::ExpectedNull));
+    }
+    check_body(era, &transaction.body)?;
+    check_witness_set(era, &transaction.witness_set)?;
+    match &transaction.auxiliary_data {
+        Some(auxiliary_data) => check_auxiliary_data(era, auxiliary_data),
+        None => Ok(()),
+    }
+}
+
+/// [body, witness_set, auxiliary_data / null], where Transaction would also take a bool for is_valid
+fn deserialize_pre_alonzo<R: BufRead + Seek>(raw: &mut Deserializer<R>) -> Result<Transaction, DeserializeError> {
+    let body = TransactionBody::deserialize(raw).map_err(|e| e.annotate("body"))?;
+    let witness_set = TransactionWitnessSet::deserialize(raw).map_err(|e| e.annotate("witness_set"))?;
+    let auxiliary_data = (|| -> Result<_, DeserializeError> {
+        match raw.cbor_type()? {
+            CBORType::Special => match raw.special()? {
+                CBORSpecial::Null => Ok(None),
+                _ => Err(DeserializeFailure::ExpectedNull.into()),
+            },
+            _ => Ok(Some(AuxiliaryData::deserialize(raw)?)),
+        }
+    })().map_err(|e| e.annotate("auxiliary_data"))?;
+    Ok(Transaction::new(&body, &witness_set, auxiliary_data))
+}
+
+/// A transaction along with the era it's encoded for
+#[derive(Clone)]
+pub struct EraTransaction {
+    era: Era,
+    transaction: Transaction,
+}
+
+impl EraTransaction {
+    /// Fails if the transaction uses anything its era doesn't allow
+    pub fn new(era: Era, transaction: &Transaction) -> Result<EraTransaction, Error> {
+        check_transaction(era, transaction).map_err(|e| Error::from_str(&format!("not a {} transaction: {}", era, e)))?;
+        Ok(Self {
+            era,
+            transaction: transaction.clone(),
+        })
+    }
+
+    /// Decodes a transaction with the rules of {era}
+    pub fn from_bytes(era: Era, data: Vec<u8>) -> Result<EraTransaction, DeserializeError> {
+        let mut raw = Deserializer::from(std::io::Cursor::new(data));
+        Self::deserialize(era, &mut raw)
+    }
+
+    /// Decodes a transaction of any era, tagging it with the earliest era it can belong to.
+    /// e.g. a transaction without validity start nor multi-assets is tagged Shelley even if it was
+    /// submitted in Mary, as nothing in it tells the two apart
+    pub fn from_bytes_earliest_era(data: Vec<u8>) -> Result<EraTransaction, DeserializeError> {
+        let mut error = None;
+        for era in Era::ALL.iter() {
+            match Self::from_bytes(*era, data.clone()) {
+                Ok(transaction) => return Ok(transaction),
+                Err(e) => error = Some(e),
+            }
+        }
+        Err(error.unwrap())
+    }
+
+    fn deserialize<R: BufRead + Seek>(era: Era, raw: &mut Deserializer<R>) -> Result<EraTransaction, DeserializeError> {
+        (|| -> Result<_, DeserializeError> {
+            let len = raw.array()?;
+            let expected_len = if era < Era::Alonzo { 3 } else { 4 };
+            if let cbor_event::Len::Len(n) = len {
+                if n != expected_len {
+                    return Err(DeserializeFailure::DefiniteLenMismatch(n, Some(expected_len)).into());
+                }
+            }
+            let transaction = match era < Era::Alonzo {
+                true => deserialize_pre_alonzo(raw)?,
+                false => Transaction::deserialize_as_embedded_group(raw, len)?,
+            };
+            if len == cbor_event::Len::Indefinite && raw.special()? != CBORSpecial::Break {
+                return Err(DeserializeFailure::EndingBreakMissing.into());
+            }
+            check_transaction(era, &transaction)?;
+            Ok(Self { era, transaction })
+        })().map_err(|e| e.annotate(format!("{}Transaction", era)))
+    }
+
+    /// The encoding of the era: pre-Alonzo transactions are [body, witness_set, auxiliary_data / null]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        if self.era >= Era::Alonzo {
+            return self.transaction.to_bytes();
+        }
+        let mut serializer = Serializer::new_vec();
+        (|| -> cbor_event::Result<()> {
+            serializer.write_array(cbor_event::Len::Len(3))?;
+            self.transaction.body.serialize(&mut serializer)?;
+            self.transaction.witness_set.serialize(&mut serializer)?;
+            match &self.transaction.auxiliary_data {
+                Some(auxiliary_data) => auxiliary_data.serialize(&mut serializer)?,
+                None => serializer.write_special(CBORSpecial::Null)?,
+            };
+            Ok(())
+        })().expect("serializing to a vec can't fail");
+        serializer.finalize()
+    }
+
+    pub fn era(&self) -> Era {
+        self.era
+    }
+
+    pub fn transaction(&self) -> Transaction {
+        self.transaction.clone()
+    }
+
+    pub fn body(&self) -> MultiEraTransactionBody {
+        let body = self.transaction.body.clone();
+        match self.era {
+            Era::Shelley => MultiEraTransactionBody::Shelley(ShelleyTransactionBody(body)),
+            Era::Allegra => MultiEraTransactionBody::Allegra(AllegraTransactionBody(body)),
+            Era::Mary => MultiEraTransactionBody::Mary(MaryTransactionBody(body)),
+            Era::Alonzo => MultiEraTransactionBody::Alonzo(AlonzoTransactionBody(body)),
+            Era::Babbage => MultiEraTransactionBody::Babbage(BabbageTransactionBody(body)),
+        }
+    }
+}
+
+/// A view of a body checked to belong to an era, with only the fields of that era
+macro_rules! era_transaction_body {
+    ($name:ident, $doc:literal, [$($field:ident: $ty:ty),*]) => {
+        #[doc = $doc]
+        #[derive(Clone)]
+        pub struct $name(TransactionBody);
+
+        impl $name {
+            pub fn inputs(&self) -> TransactionInputs {
+                self.0.inputs.clone()
+            }
+
+            pub fn outputs(&self) -> TransactionOutputs {
+                self.0.outputs.clone()
+            }
+
+            pub fn fee(&self) -> Coin {
+                self.0.fee
+            }
+
+            pub fn certs(&self) -> Option<Certificates> {
+                self.0.certs.clone()
+            }
+
+            pub fn withdrawals(&self) -> Option<Withdrawals> {
+                self.0.withdrawals.clone()
+            }
+
+            pub fn update(&self) -> Option<Update> {
+                self.0.update.clone()
+            }
+
+            pub fn auxiliary_data_hash(&self) -> Option<AuxiliaryDataHash> {
+                self.0.auxiliary_data_hash.clone()
+            }
+
+            $(
+                pub fn $field(&self) -> Option<$ty> {
+                    self.0.$field.clone()
+                }
+            )*
+
+            /// The body as the union of all eras
+            pub fn body(&self) -> TransactionBody {
+                self.0.clone()
+            }
+        }
+    };
+}
+
+era_transaction_body!(ShelleyTransactionBody, "Shelley bodies always have a ttl", []);
+
+impl ShelleyTransactionBody {
+    pub fn ttl(&self) -> Slot {
+        self.0.ttl.expect("Shelley bodies are checked to have a ttl")
+    }
+}
+
+era_transaction_body!(AllegraTransactionBody, "Allegra made the ttl optional and added the validity start", [
+    ttl: Slot,
+    validity_start_interval: Slot
+]);
+
+era_transaction_body!(MaryTransactionBody, "Mary added minting, without script data as it only has native scripts", [
+    ttl: Slot,
+    validity_start_interval: Slot,
+    mint: Mint
+]);
+
+era_transaction_body!(AlonzoTransactionBody, "Alonzo added script data, collateral, required signers and the network ID", [
+    ttl: Slot,
+    validity_start_interval: Slot,
+    mint: Mint,
+    script_data_hash: ScriptDataHash,
+    collateral: TransactionInputs,
+    required_signers: RequiredSigners,
+    network_id: NetworkId
+]);
+
+era_transaction_body!(BabbageTransactionBody, "Babbage added collateral return, total collateral and reference inputs", [
+    ttl: Slot,
+    validity_start_interval: Slot,
+    mint: Mint,
+    script_data_hash: ScriptDataHash,
+    collateral: TransactionInputs,
+    required_signers: RequiredSigners,
+    network_id: NetworkId,
+    collateral_return: TransactionOutput,
+    total_collateral: Coin,
+    reference_inputs: TransactionInputs
+]);
+
+#[derive(Clone)]
+pub enum MultiEraTransactionBody {
+    Shelley(ShelleyTransactionBody),
+    Allegra(AllegraTransactionBody),
+    Mary(MaryTransactionBody),
+    Alonzo(AlonzoTransactionBody),
+    Babbage(BabbageTransactionBody),
+}
+
+impl MultiEraTransactionBody {
+    pub fn era(&self) -> Era {
+        match self {
+            MultiEraTransactionBody::Shelley(_) => Era::Shelley,
+            MultiEraTransactionBody::Allegra(_) => Era::Allegra,
+            MultiEraTransactionBody::Mary(_) => Era::Mary,
+            MultiEraTransactionBody::Alonzo(_) => Era::Alonzo,
+            MultiEraTransactionBody::Babbage(_) => Era::Babbage,
+        }
+    }
+
+    pub fn body(&self) -> TransactionBody {
+        match self {
+            MultiEraTransactionBody::Shelley(body) => body.body(),
+            MultiEraTransactionBody::Allegra(body) => body.body(),
+            MultiEraTransactionBody::Mary(body) => body.body(),
+            MultiEraTransactionBody::Alonzo(body) => body.body(),
+            MultiEraTransactionBody::Babbage(body) => body.body(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::common::value::{to_bignum, BigInt, Int, Value};
+
+    fn shelley_body() -> TransactionBody {
+        let address = EnterpriseAddress::new(1, &StakeCredential::from_keyhash(&Ed25519KeyHash::from([1; Ed25519KeyHash::BYTE_COUNT]))).to_address();
+        let mut inputs = TransactionInputs::new();
+        inputs.add(&TransactionInput::new(&TransactionHash::from([0; TransactionHash::BYTE_COUNT]), &to_bignum(0)));
+        let mut outputs = TransactionOutputs::new();
+        outputs.add(&TransactionOutput::new(&address, &Value::new(&to_bignum(1_000_000))));
+        TransactionBody::new(&inputs, &outputs, &to_bignum(170_000), Some(to_bignum(1000)))
+    }
+
+    #[test]
+    fn eras_of_transactions() {
+        let mut body = shelley_body();
+        let shelley = EraTransaction::new(Era::Shelley, &Transaction::new(&body, &TransactionWitnessSet::new(), None)).unwrap();
+        let bytes = shelley.to_bytes();
+        assert_eq!(bytes[0], 0x83);
+        assert_eq!(EraTransaction::from_bytes(Era::Shelley, bytes.clone()).unwrap().to_bytes(), bytes);
+        assert_eq!(EraTransaction::from_bytes_earliest_era(bytes.clone()).unwrap().era(), Era::Shelley);
+        assert!(EraTransaction::from_bytes(Era::Alonzo, bytes.clone()).is_err());
+        // an is_valid flag in place of the auxiliary data, followed by a fourth item
+        let mut with_is_valid = bytes[..bytes.len() - 1].to_vec();
+        with_is_valid.extend_from_slice(&[0xf5, 0xf6]);
+        let error = EraTransaction::from_bytes(Era::Shelley, with_is_valid).err().unwrap().to_string();
+        assert!(error.contains("auxiliary_data"), "{}", error);
+        match shelley.body() {
+            MultiEraTransactionBody::Shelley(body) => assert_eq!(body.ttl(), to_bignum(1000)),
+            _ => panic!("Shelley transaction with a body of another era"),
+        }
+
+        body.set_validity_start_interval(&to_bignum(10));
+        let allegra = Transaction::new(&body, &TransactionWitnessSet::new(), None);
+        let error = EraTransaction::new(Era::Shelley, &allegra).err().unwrap().as_string().unwrap();
+        assert!(error.contains("unexpected key 8"), "{}", error);
+        let bytes = EraTransaction::new(Era::Mary, &allegra).unwrap().to_bytes();
+        assert_eq!(EraTransaction::from_bytes_earliest_era(bytes).unwrap().era(), Era::Allegra);
+
+        // timelocks aren't Shelley multisig scripts
+        let mut witness_set = TransactionWitnessSet::new();
+        let mut scripts = NativeScripts::new();
+        scripts.add(&NativeScript::new_timelock_start(&TimelockStart::new(&to_bignum(10))));
+        witness_set.set_native_scripts(&scripts);
+        let timelocked = Transaction::new(&shelley_body(), &witness_set, None);
+        assert!(EraTransaction::new(Era::Shelley, &timelocked).is_err());
+        assert!(EraTransaction::new(Era::Allegra, &timelocked).is_ok());
+
+        let mut multiasset = MultiAsset::new();
+        multiasset.set_asset(&PolicyID::from([2; PolicyID::BYTE_COUNT]), &AssetName::new(b"A".to_vec()).unwrap(), &to_bignum(1));
+        let mut output = body.outputs.get(0);
+        output.amount.set_multiasset(&multiasset);
+        body.outputs = TransactionOutputs::new();
+        body.outputs.add(&output);
+        let mary = Transaction::new(&body, &TransactionWitnessSet::new(), None);
+        assert!(EraTransaction::new(Era::Allegra, &mary).is_err());
+        let bytes = EraTransaction::new(Era::Babbage, &mary).unwrap().to_bytes();
+        assert_eq!(bytes[0], 0x84);
+        assert_eq!(EraTransaction::from_bytes_earliest_era(bytes.clone()).unwrap().era(), Era::Alonzo);
+        assert!(EraTransaction::from_bytes(Era::Mary, bytes).is_err());
+    }
+
+    #[test]
+    fn auxiliary_data_of_eras() {
+        let mut metadata = GeneralTransactionMetadata::new();
+        metadata.insert(&to_bignum(1), &TransactionMetadatum::new_int(&Int::new_i32(1)));
+        let mut native_scripts = NativeScripts::new();
+        native_scripts.add(&NativeScript::new_script_pubkey(&ScriptPubkey::new(&Ed25519KeyHash::from([1; Ed25519KeyHash::BYTE_COUNT]))));
+        let mut plutus_v1_scripts = PlutusV1Scripts::new();
+        plutus_v1_scripts.add(&PlutusV1Script::new(vec![0x01]));
+        let mut plutus_v2_scripts = PlutusV2Scripts::new();
+        plutus_v2_scripts.add(&PlutusV2Script::new(vec![0x01]));
+
+        let with = |has_metadata: bool, has_native: bool, has_plutus_v1: bool, has_plutus_v2: bool| {
+            let mut auxiliary_data = AuxiliaryData::new();
+            if has_metadata {
+                auxiliary_data.set_metadata(&metadata);
+            }
+            if has_native {
+                auxiliary_data.set_native_scripts(&native_scripts);
+            }
+            if has_plutus_v1 {
+                auxiliary_data.set_plutus_v1_scripts(&plutus_v1_scripts);
+            }
+            if has_plutus_v2 {
+                auxiliary_data.set_plutus_v2_scripts(&plutus_v2_scripts);
+            }
+            auxiliary_data
+        };
+        for (auxiliary_data, since) in [
+            (with(true, false, false, false), Era::Shelley),
+            (with(true, true, false, false), Era::Allegra),
+            (with(false, false, false, false), Era::Alonzo),
+            (with(false, true, false, false), Era::Alonzo),
+            (with(true, false, true, false), Era::Alonzo),
+            (with(true, false, false, true), Era::Babbage),
+        ] {
+            let transaction = Transaction::new(&shelley_body(), &TransactionWitnessSet::new(), Some(auxiliary_data.clone()));
+            for era in Era::ALL.iter() {
+                match EraTransaction::new(*era, &transaction) {
+                    Ok(era_transaction) => {
+                        assert!(*era >= since, "{} {:?}", era, auxiliary_data);
+                        let bytes = era_transaction.to_bytes();
+                        assert_eq!(EraTransaction::from_bytes(*era, bytes.clone()).unwrap().to_bytes(), bytes);
+                    },
+                    Err(_) => {
+                        assert!(*era < since, "{} {:?}", era, auxiliary_data);
+                        assert!(EraTransaction::from_bytes(*era, transaction.to_bytes()).is_err());
+                    },
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn babbage_output_format() {
+        let body = shelley_body();
+        let output = body.outputs.get(0);
+        // an output needing neither inline datum nor script ref, in the map format anyway
+        let mut serializer = Serializer::new_vec();
+        serializer.write_map(cbor_event::Len::Len(2)).unwrap();
+        serializer.write_unsigned_integer(0).unwrap();
+        output.address.serialize(&mut serializer).unwrap();
+        serializer.write_unsigned_integer(1).unwrap();
+        output.amount.serialize(&mut serializer).unwrap();
+        let map_bytes = serializer.finalize();
+        assert_ne!(output.to_bytes(), map_bytes);
+        let decoded = TransactionOutput::from_bytes(map_bytes.clone()).unwrap();
+        assert_eq!(decoded.to_bytes(), map_bytes);
+
+        let mut outputs = TransactionOutputs::new();
+        outputs.add(&decoded);
+        let babbage = Transaction::new(&TransactionBody::new(&body.inputs, &outputs, &body.fee, body.ttl), &TransactionWitnessSet::new(), None);
+        let bytes = babbage.to_bytes();
+        let transaction = EraTransaction::from_bytes_earliest_era(bytes.clone()).unwrap();
+        assert_eq!(transaction.era(), Era::Babbage);
+        assert_eq!(transaction.to_bytes(), bytes);
+        assert_eq!(transaction.body().body().outputs().get(0).to_bytes(), map_bytes);
+        assert!(EraTransaction::from_bytes(Era::Alonzo, bytes).is_err());
+    }
+
+    #[test]
+    fn conway_content() {
+        let body = shelley_body();
+        let mut output = body.outputs.get(0);
+        output.script_ref = Some(ScriptRef::new(&Script::new_plutus_v3(&PlutusV3Script::new(vec![0x01]))));
+        let mut outputs = TransactionOutputs::new();
+        outputs.add(&output);
+        let v3_script_ref = Transaction::new(&TransactionBody::new(&body.inputs, &outputs, &body.fee, body.ttl), &TransactionWitnessSet::new(), None);
+        let error = EraTransaction::new(Era::Babbage, &v3_script_ref).err().unwrap().as_string().unwrap();
+        assert!(error.contains("script_ref"), "{}", error);
+        assert!(EraTransaction::from_bytes_earliest_era(v3_script_ref.to_bytes()).is_err());
+        output.script_ref = Some(ScriptRef::new(&Script::new_plutus_v2(&PlutusV2Script::new(vec![0x01]))));
+        outputs = TransactionOutputs::new();
+        outputs.add(&output);
+        let v2_script_ref = Transaction::new(&TransactionBody::new(&body.inputs, &outputs, &body.fee, body.ttl), &TransactionWitnessSet::new(), None);
+        assert!(EraTransaction::new(Era::Babbage, &v2_script_ref).is_ok());
+
+        for (tag, allowed) in [
+            (RedeemerTag::new_reward(), true),
+            (RedeemerTag::new_voting(), false),
+            (RedeemerTag::new_proposing(), false),
+        ] {
+            let mut redeemers = Redeemers::new();
+            redeemers.add(&Redeemer::new(&tag, &to_bignum(0), &PlutusData::new_integer(&BigInt::from_str("0").unwrap()), &ExUnits::new(&to_bignum(1), &to_bignum(1))));
+            let mut witness_set = TransactionWitnessSet::new();
+            witness_set.set_redeemers(&redeemers);
+            let transaction = Transaction::new(&body, &witness_set, None);
+            assert_eq!(EraTransaction::new(Era::Babbage, &transaction).is_ok(), allowed, "{:?}", tag);
+            assert_eq!(EraTransaction::from_bytes(Era::Babbage, transaction.to_bytes()).is_ok(), allowed, "{:?}", tag);
+        }
+
+        let mut plutus_v3_scripts = PlutusV3Scripts::new();
+        plutus_v3_scripts.add(&PlutusV3Script::new(vec![0x01]));
+        let mut auxiliary_data = AuxiliaryData::new();
+        auxiliary_data.set_plutus_v3_scripts(&plutus_v3_scripts);
+        let v3_auxiliary_data = Transaction::new(&body, &TransactionWitnessSet::new(), Some(auxiliary_data));
+        assert!(EraTransaction::new(Era::Babbage, &v3_auxiliary_data).is_err());
+        assert!(EraTransaction::from_bytes_earliest_era(v3_auxiliary_data.to_bytes()).is_err());
+    }
+}
@@ -2,4 +2,6 @@ pub mod alonzo;
 pub mod common;
 pub mod babbage;
 pub mod byron;
-pub mod shelley;
\ No newline at end of file
+pub mod shelley;
+pub mod emulator;
+pub mod era;
\ No newline at end of file
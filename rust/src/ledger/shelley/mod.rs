@@ -1 +1,2 @@
-pub mod witness;
\ No newline at end of file
+pub mod rewards;
+pub mod witness;
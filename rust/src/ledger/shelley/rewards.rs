@@ -0,0 +1,378 @@
+//! Shelley reward calculation (section 11 "Rewards and the Epoch Boundary" of the Shelley ledger spec)
+//!
+//! All intermediate values are exact rationals, rounding down only where the spec does,
+//! so the results match the ledger to the lovelace.
+
+use std::collections::BTreeMap;
+
+use fraction::{BigInt, Ratio, ToPrimitive};
+
+use crate::{PoolParams, StakeCredential, UnitInterval};
+use crate::crypto::Ed25519KeyHash;
+use crate::error::Error;
+use crate::genesis::shelley::config::GenesisData;
+use crate::ledger::common::value::{from_bignum, to_bignum, Coin};
+
+type BigRational = Ratio<BigInt>;
+
+/// The protocol parameters and genesis values the reward formulas depend on
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RewardParams {
+    /// desired number of pools (k)
+    pub n_opt: u64,
+    /// a0
+    pub pool_pledge_influence: UnitInterval,
+    /// rho: fraction of the reserves paid out each epoch
+    pub expansion_rate: UnitInterval,
+    /// tau: fraction of the reward pot that goes to the treasury
+    pub treasury_growth_rate: UnitInterval,
+    /// decentralisation parameter. 0 from Babbage onwards
+    pub d: UnitInterval,
+    pub active_slots_coeff: UnitInterval,
+    /// in slots
+    pub epoch_length: u64,
+}
+
+impl From<&GenesisData> for RewardParams {
+    fn from(genesis: &GenesisData) -> Self {
+        Self {
+            n_opt: genesis.protocol_params.n_opt,
+            pool_pledge_influence: genesis.protocol_params.a0.clone(),
+            expansion_rate: genesis.protocol_params.rho.clone(),
+            treasury_growth_rate: genesis.protocol_params.tau.clone(),
+            d: genesis.protocol_params.decentralisation_param.clone(),
+            active_slots_coeff: genesis.active_slots_coeff.clone(),
+            epoch_length: genesis.epoch_length,
+        }
+    }
+}
+
+/// The stake distribution ("go" snapshot) rewards are computed from
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StakeSnapshot {
+    /// stake of each registered credential: its UTxO balance plus its reward balance
+    pub stake: BTreeMap<StakeCredential, Coin>,
+    /// credential -> pool id
+    pub delegations: BTreeMap<StakeCredential, Ed25519KeyHash>,
+    pub pools: BTreeMap<Ed25519KeyHash, PoolParams>,
+}
+
+impl StakeSnapshot {
+    fn delegators(&self, pool_id: &Ed25519KeyHash) -> impl Iterator<Item = (&StakeCredential, Coin)> {
+        let pool_id = pool_id.clone();
+        self.delegations
+            .iter()
+            .filter(move |(_, pool)| **pool == pool_id)
+            .map(move |(credential, _)| (credential, self.stake.get(credential).cloned().unwrap_or_else(Coin::zero)))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolRewards {
+    /// total stake delegated to the pool
+    pub stake: Coin,
+    /// stake of the owners delegated to the pool, which must cover the pledge
+    pub owner_stake: Coin,
+    pub blocks: u64,
+    /// maxPool: what the pool would get with a perfect performance
+    pub max_reward: Coin,
+    /// what the pool got, 0 if its pledge isn't met
+    pub reward: Coin,
+    /// cost plus margin plus the owners' share, paid to the reward account of the pool
+    pub operator_reward: Coin,
+    pub member_rewards: BTreeMap<StakeCredential, Coin>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EpochRewards {
+    /// monetary expansion taken from the reserves
+    pub delta_reserves: Coin,
+    /// the treasury's cut of the reward pot
+    pub delta_treasury: Coin,
+    /// reserves expansion plus fees minus the treasury's cut, shared between pools
+    pub reward_pot: Coin,
+    pub pools: BTreeMap<Ed25519KeyHash, PoolRewards>,
+    /// rewards summed per credential, with operator rewards going to the credential of the pool's reward account
+    pub rewards: BTreeMap<StakeCredential, Coin>,
+    /// part of the reward pot not given to any pool, which goes back to the reserves
+    pub undistributed: Coin,
+}
+
+fn rational(numerator: u64, denominator: u64) -> Result<BigRational, Error> {
+    if denominator == 0 {
+        return Err(Error::from_str("rational with a denominator of 0"));
+    }
+    Ok(BigRational::new(BigInt::from(numerator), BigInt::from(denominator)))
+}
+
+fn interval(interval: &UnitInterval) -> Result<BigRational, Error> {
+    rational(from_bignum(&interval.numerator()), from_bignum(&interval.denominator()))
+}
+
+fn coin(coin: &Coin) -> BigRational {
+    BigRational::from_integer(BigInt::from(from_bignum(coin)))
+}
+
+fn floor(value: &BigRational) -> Result<Coin, Error> {
+    value
+        .floor()
+        .to_integer()
+        .to_u64()
+        .map(to_bignum)
+        .ok_or_else(|| Error::from_str("reward doesn't fit in a u64"))
+}
+
+/// Fraction of the stake owned by `part`, 0 if there is no stake at all
+fn share(part: &Coin, total: &Coin) -> BigRational {
+    match from_bignum(total) {
+        0 => BigRational::from_integer(BigInt::from(0)),
+        total => BigRational::new(BigInt::from(from_bignum(part)), BigInt::from(total)),
+    }
+}
+
+/// maxPool: the rewards of a pool with a perfect performance
+/// `total_stake` is the circulation, i.e. max lovelace supply minus reserves
+pub fn max_pool_reward(params: &RewardParams, reward_pot: &Coin, pool_stake: &Coin, pledge: &Coin, total_stake: &Coin) -> Result<Coin, Error> {
+    let a0 = interval(&params.pool_pledge_influence)?;
+    let z0 = rational(1, params.n_opt)?;
+    let sigma = std::cmp::min(share(pool_stake, total_stake), z0.clone());
+    let pledge = std::cmp::min(share(pledge, total_stake), z0.clone());
+    let one = BigRational::from_integer(BigInt::from(1));
+    let factor1 = coin(reward_pot) / (one + &a0);
+    let factor2 = &sigma + &pledge * &a0 * (&sigma - &pledge * (&z0 - &sigma) / &z0) / &z0;
+    floor(&(factor1 * factor2))
+}
+
+/// Ratio of the pool's share of the blocks to its share of the active stake, 1 while d >= 0.8
+fn apparent_performance(d: &BigRational, pool_stake: &Coin, active_stake: &Coin, blocks: u64, total_blocks: u64) -> BigRational {
+    if *d >= BigRational::new(BigInt::from(4), BigInt::from(5)) {
+        return BigRational::from_integer(BigInt::from(1));
+    }
+    let sigma = share(pool_stake, active_stake);
+    if sigma == BigRational::from_integer(BigInt::from(0)) {
+        return sigma;
+    }
+    BigRational::new(BigInt::from(blocks), BigInt::from(std::cmp::max(1, total_blocks))) / sigma
+}
+
+/// The operator's part of the pool reward: the cost, then the margin and the owners' share of the rest
+pub fn leader_reward(pool: &PoolParams, pool_reward: &Coin, owner_stake: &Coin, pool_stake: &Coin) -> Result<Coin, Error> {
+    if *pool_reward <= pool.cost() {
+        return Ok(*pool_reward);
+    }
+    let margin = interval(&pool.margin())?;
+    let one = BigRational::from_integer(BigInt::from(1));
+    let profit = coin(pool_reward) - coin(&pool.cost());
+    let reward = floor(&(profit * (&margin + (one - &margin) * share(owner_stake, pool_stake))))?;
+    pool.cost().checked_add(&reward)
+}
+
+/// A delegator's part of the pool reward, in proportion to its stake once cost and margin are taken
+pub fn member_reward(pool: &PoolParams, pool_reward: &Coin, member_stake: &Coin, pool_stake: &Coin) -> Result<Coin, Error> {
+    if *pool_reward <= pool.cost() {
+        return Ok(Coin::zero());
+    }
+    let margin = interval(&pool.margin())?;
+    let one = BigRational::from_integer(BigInt::from(1));
+    let profit = coin(pool_reward) - coin(&pool.cost());
+    floor(&(profit * (one - margin) * share(member_stake, pool_stake)))
+}
+
+/// The rewards of an epoch given its stake snapshot and the blocks each pool made.
+/// `reserves` is the value at the start of the epoch and `fees` the fees it collected.
+///
+/// Credentials that deregistered since the snapshot lose their rewards in the ledger:
+/// callers tracking registrations should drop them from `rewards`
+pub fn epoch_rewards(
+    params: &RewardParams,
+    snapshot: &StakeSnapshot,
+    blocks: &BTreeMap<Ed25519KeyHash, u64>,
+    reserves: &Coin,
+    fees: &Coin,
+    max_lovelace_supply: &Coin,
+) -> Result<EpochRewards, Error> {
+    let d = interval(&params.d)?;
+    let one = BigRational::from_integer(BigInt::from(1));
+    let total_blocks: u64 = blocks.values().sum();
+
+    // eta: monetary expansion is reduced when fewer blocks than expected were made
+    let eta = if d >= BigRational::new(BigInt::from(4), BigInt::from(5)) {
+        one.clone()
+    } else {
+        let expected_blocks = floor(&((&one - &d) * coin(&to_bignum(params.epoch_length)) * interval(&params.active_slots_coeff)?))?;
+        std::cmp::min(one.clone(), share(&to_bignum(total_blocks), &expected_blocks))
+    };
+    let delta_reserves = floor(&(eta * interval(&params.expansion_rate)? * coin(reserves)))?;
+    let pot = delta_reserves.checked_add(fees)?;
+    let delta_treasury = floor(&(interval(&params.treasury_growth_rate)? * coin(&pot)))?;
+    let reward_pot = pot.checked_sub(&delta_treasury)?;
+
+    let total_stake = max_lovelace_supply.checked_sub(reserves)?;
+    let mut active_stake = Coin::zero();
+    for (credential, pool_id) in &snapshot.delegations {
+        if snapshot.pools.contains_key(pool_id) {
+            active_stake = active_stake.checked_add(&snapshot.stake.get(credential).cloned().unwrap_or_else(Coin::zero))?;
+        }
+    }
+
+    let mut pools = BTreeMap::new();
+    let mut rewards: BTreeMap<StakeCredential, Coin> = BTreeMap::new();
+    let mut distributed = Coin::zero();
+    for (pool_id, pool) in &snapshot.pools {
+        let owners = pool.pool_owners();
+        let is_owner = |credential: &StakeCredential| matches!(credential.to_keyhash(), Some(key_hash) if owners.0.contains(&key_hash));
+        let mut pool_stake = Coin::zero();
+        let mut owner_stake = Coin::zero();
+        for (credential, stake) in snapshot.delegators(pool_id) {
+            pool_stake = pool_stake.checked_add(&stake)?;
+            if is_owner(credential) {
+                owner_stake = owner_stake.checked_add(&stake)?;
+            }
+        }
+        let pool_blocks = blocks.get(pool_id).cloned().unwrap_or(0);
+        let max_reward = max_pool_reward(params, &reward_pot, &pool_stake, &pool.pledge(), &total_stake)?;
+        let reward = match owner_stake >= pool.pledge() {
+            true => floor(&(apparent_performance(&d, &pool_stake, &active_stake, pool_blocks, total_blocks) * coin(&max_reward)))?,
+            false => Coin::zero(),
+        };
+
+        let operator_reward = leader_reward(pool, &reward, &owner_stake, &pool_stake)?;
+        let mut member_rewards = BTreeMap::new();
+        for (credential, stake) in snapshot.delegators(pool_id) {
+            let member = member_reward(pool, &reward, &stake, &pool_stake)?;
+            if !is_owner(credential) && !member.is_zero() {
+                member_rewards.insert(credential.clone(), member);
+            }
+        }
+
+        for (credential, amount) in member_rewards.iter().chain(std::iter::once((&pool.reward_account().payment_cred(), &operator_reward))) {
+            if amount.is_zero() {
+                continue;
+            }
+            let total = rewards.entry(credential.clone()).or_insert_with(Coin::zero);
+            *total = total.checked_add(amount)?;
+            distributed = distributed.checked_add(amount)?;
+        }
+        pools.insert(pool_id.clone(), PoolRewards {
+            stake: pool_stake,
+            owner_stake,
+            blocks: pool_blocks,
+            max_reward,
+            reward,
+            operator_reward,
+            member_rewards,
+        });
+    }
+
+    Ok(EpochRewards {
+        delta_reserves,
+        delta_treasury,
+        undistributed: reward_pot.checked_sub(&distributed)?,
+        reward_pot,
+        pools,
+        rewards,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Ed25519KeyHashes, Relays, RewardAddress};
+    use crate::crypto::VRFKeyHash;
+
+    fn params() -> RewardParams {
+        // mainnet values
+        RewardParams {
+            n_opt: 500,
+            pool_pledge_influence: UnitInterval::new(&to_bignum(3), &to_bignum(10)),
+            expansion_rate: UnitInterval::new(&to_bignum(3), &to_bignum(1000)),
+            treasury_growth_rate: UnitInterval::new(&to_bignum(1), &to_bignum(5)),
+            d: UnitInterval::new(&to_bignum(0), &to_bignum(1)),
+            active_slots_coeff: UnitInterval::new(&to_bignum(1), &to_bignum(20)),
+            epoch_length: 432_000,
+        }
+    }
+
+    fn key_hash(x: u8) -> Ed25519KeyHash {
+        Ed25519KeyHash::from([x; Ed25519KeyHash::BYTE_COUNT])
+    }
+
+    fn pool(x: u8, pledge: u64, cost: u64, margin: (u64, u64)) -> PoolParams {
+        let mut owners = Ed25519KeyHashes::new();
+        owners.add(&key_hash(x));
+        PoolParams::new(
+            &key_hash(x),
+            &VRFKeyHash::from([x; VRFKeyHash::BYTE_COUNT]),
+            &to_bignum(pledge),
+            &to_bignum(cost),
+            &UnitInterval::new(&to_bignum(margin.0), &to_bignum(margin.1)),
+            &RewardAddress::new(1, &StakeCredential::from_keyhash(&key_hash(x))),
+            &owners,
+            &Relays::new(),
+            None,
+        )
+    }
+
+    #[test]
+    fn reward_formulas() {
+        let params = params();
+        let total = to_bignum(33_000_000_000_000_000);
+        // a saturated pool gets R / (1 + a0) * (z0 + p * a0)
+        let pot = to_bignum(26_000_000_000_000);
+        let saturated = max_pool_reward(&params, &pot, &to_bignum(70_000_000_000_000), &to_bignum(0), &total).unwrap();
+        assert_eq!(saturated, to_bignum(26_000_000_000_000 * 10 / 13 / 500));
+        let pledged = max_pool_reward(&params, &pot, &to_bignum(70_000_000_000_000), &to_bignum(66_000_000_000_000), &total).unwrap();
+        assert!(pledged > saturated);
+        // beyond saturation more stake doesn't help
+        assert_eq!(max_pool_reward(&params, &pot, &to_bignum(100_000_000_000_000), &to_bignum(0), &total).unwrap(), saturated);
+
+        let pool = pool(1, 0, 340_000_000, (1, 100));
+        assert_eq!(leader_reward(&pool, &to_bignum(300_000_000), &to_bignum(0), &to_bignum(1_000)).unwrap(), to_bignum(300_000_000));
+        assert_eq!(member_reward(&pool, &to_bignum(300_000_000), &to_bignum(500), &to_bignum(1_000)).unwrap(), Coin::zero());
+        // 1_000 ADA profit: 1% margin then half of the rest for a delegator with half the stake
+        assert_eq!(member_reward(&pool, &to_bignum(1_340_000_000), &to_bignum(500), &to_bignum(1_000)).unwrap(), to_bignum(495_000_000));
+        assert_eq!(leader_reward(&pool, &to_bignum(1_340_000_000), &to_bignum(0), &to_bignum(1_000)).unwrap(), to_bignum(350_000_000));
+    }
+
+    #[test]
+    fn rewards_of_an_epoch() {
+        let params = params();
+        let owner = StakeCredential::from_keyhash(&key_hash(1));
+        let delegator = StakeCredential::from_keyhash(&key_hash(10));
+        let unpledged_owner = StakeCredential::from_keyhash(&key_hash(2));
+
+        let mut snapshot = StakeSnapshot::default();
+        snapshot.pools.insert(key_hash(1), pool(1, 1_000_000_000_000, 340_000_000, (1, 100)));
+        // pledges more than its owner delegates so gets nothing
+        snapshot.pools.insert(key_hash(2), pool(2, 100_000_000_000_000, 340_000_000, (0, 1)));
+        for (credential, pool_id, stake) in [(&owner, 1, 1_000_000_000_000), (&delegator, 1, 59_000_000_000_000), (&unpledged_owner, 2, 60_000_000_000_000)] {
+            snapshot.stake.insert(credential.clone(), to_bignum(stake));
+            snapshot.delegations.insert(credential.clone(), key_hash(pool_id));
+        }
+        let blocks = vec![(key_hash(1), 10_000), (key_hash(2), 10_000)].into_iter().collect();
+        let max_supply = to_bignum(45_000_000_000_000_000);
+        let reserves = to_bignum(12_000_000_000_000_000);
+        let rewards = epoch_rewards(&params, &snapshot, &blocks, &reserves, &to_bignum(50_000_000_000), &max_supply).unwrap();
+
+        // 20000 of the 21600 expected blocks were made, which scales down monetary expansion
+        assert_eq!(rewards.delta_reserves, to_bignum(12_000_000_000_000_000 * 3 / 1000 * 20_000 / 21600));
+        let pot = from_bignum(&rewards.delta_reserves) + 50_000_000_000;
+        assert_eq!(rewards.delta_treasury, to_bignum(pot / 5));
+        assert_eq!(rewards.reward_pot, to_bignum(pot - pot / 5));
+
+        let pool_rewards = &rewards.pools[&key_hash(1)];
+        assert_eq!(pool_rewards.stake, to_bignum(60_000_000_000_000));
+        assert_eq!(pool_rewards.owner_stake, to_bignum(1_000_000_000_000));
+        // made half the blocks with half the active stake
+        assert_eq!(pool_rewards.reward, pool_rewards.max_reward);
+        assert_eq!(pool_rewards.member_rewards.keys().collect::<Vec<_>>(), vec![&delegator], "owners only get the operator reward");
+        assert_eq!(rewards.rewards[&owner], pool_rewards.operator_reward);
+        assert_eq!(rewards.rewards[&delegator], pool_rewards.member_rewards[&delegator]);
+        assert_eq!(rewards.pools[&key_hash(2)].reward, Coin::zero());
+        assert!(!rewards.rewards.contains_key(&unpledged_owner));
+
+        let distributed = from_bignum(&pool_rewards.operator_reward) + from_bignum(&pool_rewards.member_rewards[&delegator]);
+        assert!(distributed <= from_bignum(&pool_rewards.reward));
+        assert_eq!(from_bignum(&rewards.undistributed), from_bignum(&rewards.reward_pot) - distributed);
+    }
+}
@@ -16,7 +16,6 @@ extern crate hex;
 
 use std::convert::TryInto;
 use std::io::{BufRead, Seek, Write};
-use wasm_bindgen::JsValue;
 
 use crate::ledger::common::binary::*;
 use ledger::common::hash::{ScriptHashNamespace, hash_script};
@@ -35,6 +34,7 @@ use cbor_event::{
 
 pub mod traits;
 pub mod address;
+pub mod address_inspect;
 pub mod chain_core;
 pub mod chain_crypto;
 pub mod crypto;
@@ -43,10 +43,18 @@ pub mod genesis;
 pub mod impl_mockchain;
 pub mod metadata;
 pub mod plutus;
+pub mod uplc;
 pub mod serialization;
 pub mod builders;
 pub mod typed_bytes;
 pub mod emip3;
+pub mod cip5;
+pub mod cip14;
+pub mod cip25;
+pub mod cip68;
+pub mod cip36;
+pub mod metadata_schema;
+pub mod pool_metadata;
 pub mod ledger;
 pub mod byron;
 
@@ -163,6 +171,8 @@ to_from_bytes!(TransactionInputs);
 
 to_from_json!(TransactionInputs);
 
+vec_iter!(TransactionInputs, TransactionInput);
+
 
 impl TransactionInputs {
     pub fn new() -> Self {
@@ -190,6 +200,8 @@ to_from_bytes!(TransactionOutputs);
 
 to_from_json!(TransactionOutputs);
 
+vec_iter!(TransactionOutputs, TransactionOutput);
+
 
 impl TransactionOutputs {
     pub fn new() -> Self {
@@ -207,6 +219,11 @@ impl TransactionOutputs {
     pub fn add(&mut self, elem: &TransactionOutput) {
         self.0.push(elem.clone());
     }
+
+    /// total value held by all outputs
+    pub fn total_value(&self) -> Result<Value, Error> {
+        Value::sum_outputs(&self.0)
+    }
 }
 
 
@@ -217,6 +234,8 @@ to_from_bytes!(Certificates);
 
 to_from_json!(Certificates);
 
+vec_iter!(Certificates, Certificate);
+
 
 impl Certificates {
     pub fn new() -> Self {
@@ -513,6 +532,10 @@ pub struct TransactionOutput {
     pub (crate) amount: Value,
     datum_option: Option<DatumEnum>,
     script_ref: Option<ScriptRef>,
+    /// kept from deserialization so outputs re-encode as they were: only Babbage outputs with an inline
+    /// datum or a script ref need the map format, but any output can use it since Babbage
+    #[serde(skip)]
+    pub(crate) prefer_babbage_format: bool,
 }
 
 to_from_bytes!(TransactionOutput);
@@ -551,6 +574,7 @@ impl TransactionOutput {
             amount: amount.clone(),
             datum_option: None,
             script_ref: None,
+            prefer_babbage_format: false,
         }
     }
 }
@@ -638,6 +662,8 @@ to_from_bytes!(Ed25519KeyHashes);
 
 to_from_json!(Ed25519KeyHashes);
 
+vec_iter!(Ed25519KeyHashes, Ed25519KeyHash);
+
 
 impl Ed25519KeyHashes {
     pub fn new() -> Self {
@@ -665,6 +691,8 @@ to_from_bytes!(Relays);
 
 to_from_json!(Relays);
 
+vec_iter!(Relays, Relay);
+
 
 impl Relays {
     pub fn new() -> Self {
@@ -1165,8 +1193,8 @@ to_from_json!(Ipv4);
 
 
 impl Ipv4 {
-    pub fn new(data: Vec<u8>) -> Result<Ipv4, JsError> {
-        Self::new_impl(data).map_err(|e| JsError::from_str(&e.to_string()))
+    pub fn new(data: Vec<u8>) -> Result<Ipv4, Error> {
+        Self::new_impl(data).map_err(|e| Error::from_str(&e.to_string()))
     }
 
     pub(crate) fn new_impl(data: Vec<u8>) -> Result<Ipv4, DeserializeError> {
@@ -1195,8 +1223,8 @@ to_from_json!(Ipv6);
 
 
 impl Ipv6 {
-    pub fn new(data: Vec<u8>) -> Result<Ipv6, JsError> {
-        Self::new_impl(data).map_err(|e| JsError::from_str(&e.to_string()))
+    pub fn new(data: Vec<u8>) -> Result<Ipv6, Error> {
+        Self::new_impl(data).map_err(|e| Error::from_str(&e.to_string()))
     }
 
     pub(crate) fn new_impl(data: Vec<u8>) -> Result<Ipv6, DeserializeError> {
@@ -1225,8 +1253,8 @@ to_from_bytes!(URL);
 
 
 impl URL {
-    pub fn new(url: String) -> Result<URL, JsError> {
-        Self::new_impl(url).map_err(|e| JsError::from_str(&e.to_string()))
+    pub fn new(url: String) -> Result<URL, Error> {
+        Self::new_impl(url).map_err(|e| Error::from_str(&e.to_string()))
     }
 
     pub (crate) fn new_impl(url: String) -> Result<URL, DeserializeError> {
@@ -1256,8 +1284,8 @@ to_from_bytes!(DNSRecordAorAAAA);
 
 
 impl DNSRecordAorAAAA {
-    pub fn new(dns_name: String) -> Result<DNSRecordAorAAAA, JsError> {
-        Self::new_impl(dns_name).map_err(|e| JsError::from_str(&e.to_string()))
+    pub fn new(dns_name: String) -> Result<DNSRecordAorAAAA, Error> {
+        Self::new_impl(dns_name).map_err(|e| Error::from_str(&e.to_string()))
     }
 
     pub (crate) fn new_impl(dns_name: String) -> Result<DNSRecordAorAAAA, DeserializeError> {
@@ -1285,8 +1313,8 @@ to_from_bytes!(DNSRecordSRV);
 
 
 impl DNSRecordSRV {
-    pub fn new(dns_name: String) -> Result<DNSRecordSRV, JsError> {
-        Self::new_impl(dns_name).map_err(|e| JsError::from_str(&e.to_string()))
+    pub fn new(dns_name: String) -> Result<DNSRecordSRV, Error> {
+        Self::new_impl(dns_name).map_err(|e| Error::from_str(&e.to_string()))
     }
 
     pub (crate) fn new_impl(dns_name: String) -> Result<DNSRecordSRV, DeserializeError> {
@@ -1495,6 +1523,8 @@ to_from_bytes!(StakeCredentials);
 
 to_from_json!(StakeCredentials);
 
+vec_iter!(StakeCredentials, StakeCredential);
+
 
 impl StakeCredentials {
     pub fn new() -> Self {
@@ -1522,6 +1552,8 @@ to_from_bytes!(RewardAddresses);
 
 to_from_json!(RewardAddresses);
 
+vec_iter!(RewardAddresses, RewardAddress);
+
 
 impl RewardAddresses {
     pub fn new() -> Self {
@@ -1545,6 +1577,8 @@ impl RewardAddresses {
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub struct Withdrawals(linked_hash_map::LinkedHashMap<RewardAddress, Coin>);
 
+map_iter!(Withdrawals, linked_hash_map, RewardAddress, Coin);
+
 to_from_bytes!(Withdrawals);
 
 to_from_json!(Withdrawals);
@@ -1611,6 +1645,7 @@ pub struct TransactionWitnessSet {
     plutus_data: Option<PlutusList>,
     redeemers: Option<Redeemers>,
     plutus_v2_scripts: Option<PlutusV2Scripts>,
+    plutus_v3_scripts: Option<PlutusV3Scripts>,
 }
 
 to_from_bytes!(TransactionWitnessSet);
@@ -1675,6 +1710,14 @@ impl TransactionWitnessSet {
         self.plutus_v2_scripts.clone()
     }
 
+    pub fn set_plutus_v3_scripts(&mut self, plutus_v3_scripts: &PlutusV3Scripts) {
+        self.plutus_v3_scripts = Some(plutus_v3_scripts.clone())
+    }
+
+    pub fn plutus_v3_scripts(&self) -> Option<PlutusV3Scripts> {
+        self.plutus_v3_scripts.clone()
+    }
+
     pub fn new() -> Self {
         Self {
             vkeys: None,
@@ -1684,6 +1727,7 @@ impl TransactionWitnessSet {
             plutus_data: None,
             redeemers: None,
             plutus_v2_scripts: None,
+            plutus_v3_scripts: None,
         }
     }
 }
@@ -1959,6 +2003,8 @@ impl NativeScript {
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize, JsonSchema)]
 pub struct NativeScripts(Vec<NativeScript>);
 
+vec_iter!(NativeScripts, NativeScript);
+
 
 impl NativeScripts {
     pub fn new() -> Self {
@@ -1978,15 +2024,6 @@ impl NativeScripts {
     }
 }
 
-impl From<Vec<NativeScript>> for NativeScripts {
-    fn from(scripts: Vec<NativeScript>) -> Self {
-        scripts.iter().fold(NativeScripts::new(), |mut scripts, s| {
-            scripts.add(s);
-            scripts
-        })
-    }
-}
-
 impl NoneOrEmpty for NativeScripts {
     fn is_none_or_empty(&self) -> bool {
         self.0.is_empty()
@@ -2033,6 +2070,8 @@ to_from_bytes!(GenesisHashes);
 
 to_from_json!(GenesisHashes);
 
+vec_iter!(GenesisHashes, GenesisHash);
+
 
 impl GenesisHashes {
     pub fn new() -> Self {
@@ -2060,6 +2099,8 @@ to_from_bytes!(ScriptHashes);
 
 to_from_json!(ScriptHashes);
 
+vec_iter!(ScriptHashes, ScriptHash);
+
 
 impl ScriptHashes {
     pub fn new() -> Self {
@@ -2085,6 +2126,8 @@ pub struct ProposedProtocolParameterUpdates(
     linked_hash_map::LinkedHashMap<GenesisHash, ProtocolParamUpdate>,
 );
 
+map_iter!(ProposedProtocolParameterUpdates, linked_hash_map, GenesisHash, ProtocolParamUpdate);
+
 impl serde::Serialize for ProposedProtocolParameterUpdates {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where S: serde::Serializer {
@@ -2443,6 +2486,8 @@ to_from_bytes!(TransactionBodies);
 
 to_from_json!(TransactionBodies);
 
+vec_iter!(TransactionBodies, TransactionBody);
+
 
 impl TransactionBodies {
     pub fn new() -> Self {
@@ -2470,6 +2515,8 @@ to_from_bytes!(TransactionWitnessSets);
 
 to_from_json!(TransactionWitnessSets);
 
+vec_iter!(TransactionWitnessSets, TransactionWitnessSet);
+
 
 impl TransactionWitnessSets {
     pub fn new() -> Self {
@@ -2495,6 +2542,8 @@ pub struct TransactionIndexes(Vec<TransactionIndex>);
 
 to_from_bytes!(TransactionIndexes);
 
+vec_iter!(TransactionIndexes, TransactionIndex);
+
 
 impl TransactionIndexes {
     pub fn new() -> Self {
@@ -2518,6 +2567,8 @@ impl TransactionIndexes {
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub struct AuxiliaryDataSet(linked_hash_map::LinkedHashMap<TransactionIndex, AuxiliaryData>);
 
+map_iter!(AuxiliaryDataSet, linked_hash_map, TransactionIndex, AuxiliaryData);
+
 
 impl AuxiliaryDataSet {
     pub fn new() -> Self {
@@ -2810,8 +2861,8 @@ to_from_json!(AssetName);
 
 
 impl AssetName {
-    pub fn new(name: Vec<u8>) -> Result<AssetName, JsError> {
-        Self::new_impl(name).map_err(|e| JsError::from_str(&e.to_string()))
+    pub fn new(name: Vec<u8>) -> Result<AssetName, Error> {
+        Self::new_impl(name).map_err(|e| Error::from_str(&e.to_string()))
     }
 
     pub (crate) fn new_impl(name: Vec<u8>) -> Result<AssetName, DeserializeError> {
@@ -2829,6 +2880,46 @@ impl AssetName {
     pub fn name(&self) -> Vec<u8> {
         self.0.clone()
     }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.0)
+    }
+
+    pub fn from_hex(hex_str: &str) -> Result<AssetName, Error> {
+        let bytes = hex::decode(hex_str).map_err(|e| Error::from_str(&format!("hex decode failed: {}", e)))?;
+        AssetName::new(bytes)
+    }
+
+    /// The name as text, if it is valid UTF-8 without control characters
+    pub fn to_utf8(&self) -> Option<String> {
+        String::from_utf8(self.0.clone())
+            .ok()
+            .filter(|name| !name.chars().any(char::is_control))
+    }
+
+    /// The CIP-67 label the name starts with (e.g. 222 for CIP-68 NFTs), if any
+    pub fn cip67_label(&self) -> Option<u16> {
+        cip68::cip67_label(self)
+    }
+
+    /// The name without its CIP-67 label prefix if it has one
+    pub fn without_cip67_label(&self) -> AssetName {
+        match self.cip67_label() {
+            Some(_) => AssetName(self.0[4..].to_vec()),
+            None => self.clone(),
+        }
+    }
+
+    /// Human-readable name: the label in parentheses (if any) followed by the name as text,
+    /// or as hex if it isn't printable e.g. "(222) SpaceBud" or "0x00ff"
+    pub fn to_display_string(&self) -> String {
+        let name = self.without_cip67_label();
+        let name = name.to_utf8().unwrap_or_else(|| format!("0x{}", name.to_hex()));
+        match self.cip67_label() {
+            Some(label) => format!("({}) {}", label, name),
+            None => name,
+        }
+    }
 }
 
 impl serde::Serialize for AssetName {
@@ -2865,6 +2956,8 @@ to_from_bytes!(AssetNames);
 
 to_from_json!(AssetNames);
 
+vec_iter!(AssetNames, AssetName);
+
 
 impl AssetNames {
     pub fn new() -> Self {
@@ -2891,6 +2984,8 @@ pub type PolicyIDs = ScriptHashes;
 #[derive(Clone, Debug, Default, Eq, Ord, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize, JsonSchema)]
 pub struct Assets(pub (crate) std::collections::BTreeMap<AssetName, BigNum>);
 
+map_iter!(Assets, std::collections::btree_map, AssetName, BigNum);
+
 to_from_bytes!(Assets);
 
 to_from_json!(Assets);
@@ -2922,6 +3017,8 @@ impl Assets {
 #[derive(Clone, Debug, Eq, Ord, PartialEq, serde::Serialize, serde::Deserialize, JsonSchema)]
 pub struct MultiAsset(pub (crate) std::collections::BTreeMap<PolicyID, Assets>);
 
+map_iter!(MultiAsset, std::collections::btree_map, PolicyID, Assets);
+
 to_from_bytes!(MultiAsset);
 
 to_from_json!(MultiAsset);
@@ -2964,6 +3061,51 @@ impl MultiAsset {
         ScriptHashes(self.0.iter().map(|(k, _v)| k.clone()).collect::<Vec<PolicyID>>())
     }
 
+    /// Iterates over every asset and its amount, with fully qualified IDs
+    pub fn iter_assets(&self) -> impl Iterator<Item = (cip14::AssetId, BigNum)> + '_ {
+        self.0.iter().flat_map(|(policy_id, assets)| {
+            assets.0.iter().map(move |(asset_name, amount)| (cip14::AssetId::new(policy_id, asset_name), *amount))
+        })
+    }
+
+    /// IDs of all assets in this multiasset, sorted by policy ID then asset name
+    pub fn asset_ids(&self) -> cip14::AssetIds {
+        cip14::AssetIds(self.iter_assets().map(|(id, _)| id).collect())
+    }
+
+    /// Amount of the asset with this ID, or 0 if it is not present
+    pub fn get_by_id(&self, asset_id: &cip14::AssetId) -> BigNum {
+        self.get_asset(&asset_id.policy_id, &asset_id.asset_name)
+    }
+
+    /// Same as set_asset but with a fully qualified ID
+    pub fn set_by_id(&mut self, asset_id: &cip14::AssetId, value: &BigNum) -> Option<BigNum> {
+        self.set_asset(&asset_id.policy_id, &asset_id.asset_name, value)
+    }
+
+    /// removes assets with a zero amount and policies left without assets
+    /// does not modify this object, instead the result is returned
+    pub fn normalize(&self) -> MultiAsset {
+        MultiAsset(
+            self.0
+                .iter()
+                .map(|(policy_id, assets)| (policy_id.clone(), Assets(assets.0.iter().filter(|(_, amount)| !amount.is_zero()).map(|(name, amount)| (name.clone(), *amount)).collect())))
+                .filter(|(_, assets)| !assets.0.is_empty())
+                .collect()
+        )
+    }
+
+    /// only the assets under the given policies
+    pub fn filter_policies(&self, policy_ids: &PolicyIDs) -> MultiAsset {
+        MultiAsset(
+            self.0
+                .iter()
+                .filter(|(policy_id, _)| policy_ids.0.contains(policy_id))
+                .map(|(policy_id, assets)| (policy_id.clone(), assets.clone()))
+                .collect()
+        )
+    }
+
     /// removes an asset from the list if the result is 0 or less
     /// does not modify this object, instead the result is returned
     pub fn sub(&self, rhs_ma: &MultiAsset) -> MultiAsset {
@@ -3049,6 +3191,8 @@ impl PartialOrd for MultiAsset {
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize, JsonSchema)]
 pub struct MintAssets(std::collections::BTreeMap<AssetName, Int>);
 
+map_iter!(MintAssets, std::collections::btree_map, AssetName, Int);
+
 
 impl MintAssets {
     pub fn new() -> Self {
@@ -3082,6 +3226,8 @@ impl MintAssets {
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize, JsonSchema)]
 pub struct Mint(std::collections::BTreeMap<PolicyID, MintAssets>);
 
+map_iter!(Mint, std::collections::btree_map, PolicyID, MintAssets);
+
 to_from_bytes!(Mint);
 
 to_from_json!(Mint);
@@ -3144,6 +3290,16 @@ impl Mint {
     pub fn as_negative_multiasset(&self) -> MultiAsset {
         self.as_multiasset(false)
     }
+
+    /// The minted assets as a Value (no ADA) e.g. to add to the consumed side of a balance
+    pub fn as_positive_value(&self) -> Value {
+        Value::new_from_assets(&self.as_positive_multiasset().normalize())
+    }
+
+    /// The burned assets as a Value (no ADA) e.g. to add to the produced side of a balance
+    pub fn as_negative_value(&self) -> Value {
+        Value::new_from_assets(&self.as_negative_multiasset().normalize())
+    }
 }
 
 
@@ -3481,4 +3637,33 @@ mod tests {
         let test = StakeCredential::from_bytes(hex::decode("8200581cb861eeadde300385d88aaa98cad0f0ed1f95419bbb9971a0fb7c96fb").unwrap()).unwrap();
         assert_eq!(hex::encode(test.to_bytes()), "8200581cb861eeadde300385d88aaa98cad0f0ed1f95419bbb9971a0fb7c96fb");
     }
+
+    #[test]
+    fn collection_iterators() {
+        let inputs = (0..3u64)
+            .map(|index| TransactionInput::new(&TransactionHash::from([0; TransactionHash::BYTE_COUNT]), &to_bignum(index)))
+            .collect::<TransactionInputs>();
+        assert_eq!(inputs.len(), 3);
+        assert_eq!(inputs.iter().map(|input| u64::from(input.index())).collect::<Vec<_>>(), vec![0, 1, 2]);
+        for (index, input) in (&inputs).into_iter().enumerate() {
+            assert_eq!(input, &inputs.get(index));
+        }
+        let mut inputs = TransactionInputs::from(inputs.into_iter().rev().collect::<Vec<_>>());
+        inputs.extend(std::iter::once(TransactionInput::new(&TransactionHash::from([1; TransactionHash::BYTE_COUNT]), &to_bignum(0))));
+        assert_eq!(inputs.len(), 4);
+        assert_eq!(inputs.into_iter().next().unwrap().index(), to_bignum(2));
+
+        // maps keep their own order, the insertion order for withdrawals
+        let reward_address = |x: u8| RewardAddress::new(0, &StakeCredential::from_keyhash(&Ed25519KeyHash::from([x; Ed25519KeyHash::BYTE_COUNT])));
+        let mut withdrawals = vec![(reward_address(2), to_bignum(2)), (reward_address(1), to_bignum(1))]
+            .into_iter()
+            .collect::<Withdrawals>();
+        withdrawals.extend(std::iter::once((reward_address(3), to_bignum(3))));
+        assert_eq!(withdrawals.get(&reward_address(1)), Some(to_bignum(1)));
+        assert_eq!(withdrawals.iter().map(|(_, amount)| u64::from(*amount)).collect::<Vec<_>>(), vec![2, 1, 3]);
+        let mut assets = Assets::new();
+        assets.extend(vec![(AssetName::new(b"B".to_vec()).unwrap(), to_bignum(2)), (AssetName::new(b"A".to_vec()).unwrap(), to_bignum(1))]);
+        assert_eq!((&assets).into_iter().map(|(name, _)| name.name()).collect::<Vec<_>>(), vec![b"A".to_vec(), b"B".to_vec()]);
+        assert_eq!(assets.into_iter().map(|(_, amount)| amount).collect::<Vec<_>>(), vec![to_bignum(1), to_bignum(2)]);
+    }
 }
@@ -2,6 +2,7 @@ use crate::ledger::common::binary::*;
 
 use super::*;
 use linked_hash_map::LinkedHashMap;
+use std::io::SeekFrom;
 
 const MD_MAX_LEN: usize = 64;
 
@@ -11,6 +12,8 @@ pub struct MetadataMap(
     LinkedHashMap<TransactionMetadatum, TransactionMetadatum>,
 );
 
+map_iter!(MetadataMap, linked_hash_map, TransactionMetadatum, TransactionMetadatum);
+
 to_from_bytes!(MetadataMap);
 
 
@@ -36,7 +39,7 @@ impl MetadataMap {
         &mut self,
         key: &str,
         value: &TransactionMetadatum,
-    ) -> Result<Option<TransactionMetadatum>, JsError> {
+    ) -> Result<Option<TransactionMetadatum>, Error> {
         Ok(self.insert(&TransactionMetadatum::new_text(key.to_owned())?, value))
     }
 
@@ -49,17 +52,17 @@ impl MetadataMap {
         self.insert(&TransactionMetadatum::new_int(&Int::new_i32(key)), value)
     }
 
-    pub fn get(&self, key: &TransactionMetadatum) -> Result<TransactionMetadatum, JsError> {
-        self.0.get(key).map(|v| v.clone()).ok_or_else(|| JsError::from_str(&format!("key {:?} not found", key)))
+    pub fn get(&self, key: &TransactionMetadatum) -> Result<TransactionMetadatum, Error> {
+        self.0.get(key).map(|v| v.clone()).ok_or_else(|| Error::from_str(&format!("key {:?} not found", key)))
     }
 
     // convenience function for retrieving a string key
-    pub fn get_str(&self, key: &str) -> Result<TransactionMetadatum, JsError> {
+    pub fn get_str(&self, key: &str) -> Result<TransactionMetadatum, Error> {
         self.get(&TransactionMetadatum::new_text(key.to_owned())?)
     }
 
     // convenience function for retrieving 32-bit integer keys - for higher-precision integers use get() with an Int struct
-    pub fn get_i32(&self, key: i32) -> Result<TransactionMetadatum, JsError> {
+    pub fn get_i32(&self, key: i32) -> Result<TransactionMetadatum, Error> {
         self.get(&TransactionMetadatum::new_int(&Int::new_i32(key)))
     }
 
@@ -83,6 +86,8 @@ pub struct MetadataList(Vec<TransactionMetadatum>);
 
 to_from_bytes!(MetadataList);
 
+vec_iter!(MetadataList, TransactionMetadatum);
+
 
 impl MetadataList {
     pub fn new() -> Self {
@@ -122,53 +127,92 @@ enum TransactionMetadatumEnum {
 }
 
 
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-pub struct TransactionMetadatum(TransactionMetadatumEnum);
+#[derive(Clone, Debug)]
+pub struct TransactionMetadatum {
+    datum: TransactionMetadatumEnum,
+    // Metadata is hashed (and signed, e.g. by voting registrations) as it is encoded, which isn't
+    // always the way we would re-encode it. So this field stores the original bytes to re-use.
+    // Only the outermost decoded metadatum keeps them: the ones nested inside it are encoded as part of it.
+    // It is left out of comparisons so that decoded map keys can still be looked up.
+    original_bytes: Option<Vec<u8>>,
+}
+
+impl PartialEq for TransactionMetadatum {
+    fn eq(&self, other: &Self) -> bool {
+        self.datum == other.datum
+    }
+}
+
+impl Eq for TransactionMetadatum {}
+
+impl std::hash::Hash for TransactionMetadatum {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.datum.hash(state)
+    }
+}
+
+impl PartialOrd for TransactionMetadatum {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TransactionMetadatum {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.datum.cmp(&other.datum)
+    }
+}
 
 to_from_bytes!(TransactionMetadatum);
 
 
 impl TransactionMetadatum {
+    fn from_datum(datum: TransactionMetadatumEnum) -> Self {
+        Self {
+            datum,
+            original_bytes: None,
+        }
+    }
+
+    // elements of maps and lists don't keep their bytes, which would otherwise be copied once per nesting level
+    fn deserialize_nested<R: BufRead + Seek>(raw: &mut Deserializer<R>) -> Result<Self, DeserializeError> {
+        TransactionMetadatumEnum::deserialize(raw).map(Self::from_datum)
+    }
+
     pub fn new_map(
         map: &MetadataMap,
     ) -> Self {
-        Self(
-            TransactionMetadatumEnum::MetadataMap(
-                map.clone(),
-            ),
-        )
+        Self::from_datum(TransactionMetadatumEnum::MetadataMap(map.clone()))
     }
 
     pub fn new_list(
         list: &MetadataList,
     ) -> Self {
-        Self(TransactionMetadatumEnum::MetadataList(
-            list.clone(),
-        ))
+        Self::from_datum(TransactionMetadatumEnum::MetadataList(list.clone()))
     }
 
     pub fn new_int(int: &Int) -> Self {
-        Self(TransactionMetadatumEnum::Int(int.clone()))
+        Self::from_datum(TransactionMetadatumEnum::Int(int.clone()))
     }
 
-    pub fn new_bytes(bytes: Vec<u8>) -> Result<TransactionMetadatum, JsError> {
+    pub fn new_bytes(bytes: Vec<u8>) -> Result<TransactionMetadatum, Error> {
         if bytes.len() > MD_MAX_LEN {
-            Err(JsError::from_str(&format!("Max metadata bytes too long: {}, max = {}", bytes.len(), MD_MAX_LEN)))
+            Err(Error::from_str(&format!("Max metadata bytes too long: {}, max = {}", bytes.len(), MD_MAX_LEN)))
         } else {
-            Ok(Self(TransactionMetadatumEnum::Bytes(bytes)))
+            Ok(Self::from_datum(TransactionMetadatumEnum::Bytes(bytes)))
         }
     }
 
-    pub fn new_text(text: String) -> Result<TransactionMetadatum, JsError> {
+    pub fn new_text(text: String) -> Result<TransactionMetadatum, Error> {
         if text.len() > MD_MAX_LEN {
-            Err(JsError::from_str(&format!("Max metadata string too long: {}, max = {}", text.len(), MD_MAX_LEN)))
+            Err(Error::from_str(&format!("Max metadata string too long: {}, max = {}", text.len(), MD_MAX_LEN)))
         } else {
-            Ok(Self(TransactionMetadatumEnum::Text(text)))
+            Ok(Self::from_datum(TransactionMetadatumEnum::Text(text)))
         }
     }
 
     pub fn kind(&self) -> TransactionMetadatumKind {
-        match &self.0 {
+        match &self.datum {
             TransactionMetadatumEnum::MetadataMap(_) => {
                 TransactionMetadatumKind::MetadataMap
             }
@@ -183,40 +227,40 @@ impl TransactionMetadatum {
 
     pub fn as_map(
         &self,
-    ) -> Result<MetadataMap, JsError> {
-        match &self.0 {
+    ) -> Result<MetadataMap, Error> {
+        match &self.datum {
             TransactionMetadatumEnum::MetadataMap(x) => {
                 Ok(x.clone())
             }
-            _ => Err(JsError::from_str("not a map")),
+            _ => Err(Error::from_str("not a map")),
         }
     }
 
-    pub fn as_list(&self) -> Result<MetadataList, JsError> {
-        match &self.0 {
+    pub fn as_list(&self) -> Result<MetadataList, Error> {
+        match &self.datum {
             TransactionMetadatumEnum::MetadataList(x) => Ok(x.clone()),
-            _ => Err(JsError::from_str("not a list")),
+            _ => Err(Error::from_str("not a list")),
         }
     }
 
-    pub fn as_int(&self) -> Result<Int, JsError> {
-        match &self.0 {
+    pub fn as_int(&self) -> Result<Int, Error> {
+        match &self.datum {
             TransactionMetadatumEnum::Int(x) => Ok(x.clone()),
-            _ => Err(JsError::from_str("not an int")),
+            _ => Err(Error::from_str("not an int")),
         }
     }
 
-    pub fn as_bytes(&self) -> Result<Vec<u8>, JsError> {
-        match &self.0 {
+    pub fn as_bytes(&self) -> Result<Vec<u8>, Error> {
+        match &self.datum {
             TransactionMetadatumEnum::Bytes(x) => Ok(x.clone()),
-            _ => Err(JsError::from_str("not bytes")),
+            _ => Err(Error::from_str("not bytes")),
         }
     }
 
-    pub fn as_text(&self) -> Result<String, JsError> {
-        match &self.0 {
+    pub fn as_text(&self) -> Result<String, Error> {
+        match &self.datum {
             TransactionMetadatumEnum::Text(x) => Ok(x.clone()),
-            _ => Err(JsError::from_str("not text")),
+            _ => Err(Error::from_str("not text")),
         }
     }
 }
@@ -255,6 +299,8 @@ pub struct TransactionMetadatumLabels(Vec<TransactionMetadatumLabel>);
 
 to_from_bytes!(TransactionMetadatumLabels);
 
+vec_iter!(TransactionMetadatumLabels, TransactionMetadatumLabel);
+
 
 impl TransactionMetadatumLabels {
     pub fn new() -> Self {
@@ -278,6 +324,8 @@ impl TransactionMetadatumLabels {
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub struct GeneralTransactionMetadata(LinkedHashMap<TransactionMetadatumLabel, TransactionMetadatum>);
 
+map_iter!(GeneralTransactionMetadata, linked_hash_map, TransactionMetadatumLabel, TransactionMetadatum);
+
 to_from_bytes!(GeneralTransactionMetadata);
 
 to_from_json!(GeneralTransactionMetadata);
@@ -346,6 +394,7 @@ pub struct AuxiliaryData {
     pub(crate) native_scripts: Option<NativeScripts>,
     pub(crate) plutus_v1_scripts: Option<PlutusV1Scripts>,
     pub(crate) plutus_v2_scripts: Option<PlutusV2Scripts>,
+    pub(crate) plutus_v3_scripts: Option<PlutusV3Scripts>,
     #[serde(skip)]
     pub(crate) prefer_alonzo_format: bool,
 }
@@ -362,6 +411,7 @@ impl AuxiliaryData {
             native_scripts: None,
             plutus_v1_scripts: None,
             plutus_v2_scripts: None,
+            plutus_v3_scripts: None,
             prefer_alonzo_format: false,
         }
     }
@@ -397,6 +447,14 @@ impl AuxiliaryData {
     pub fn set_plutus_v2_scripts(&mut self, plutus_v2_scripts: &PlutusV2Scripts) {
         self.plutus_v2_scripts = Some(plutus_v2_scripts.clone())
     }
+
+    pub fn plutus_v3_scripts(&self) -> Option<PlutusV3Scripts> {
+        self.plutus_v3_scripts.clone()
+    }
+
+    pub fn set_plutus_v3_scripts(&mut self, plutus_v3_scripts: &PlutusV3Scripts) {
+        self.plutus_v3_scripts = Some(plutus_v3_scripts.clone())
+    }
 }
 
 // encodes arbitrary bytes into chunks of 64 bytes (the limit for bytes) as a list to be valid Metadata
@@ -412,7 +470,7 @@ pub fn encode_arbitrary_bytes_as_metadatum(bytes: &[u8]) -> TransactionMetadatum
 
 // decodes from chunks of bytes in a list to a byte vector if that is the metadata format, otherwise returns None
 
-pub fn decode_arbitrary_bytes_from_metadatum(metadata: &TransactionMetadatum) -> Result<Vec<u8>, JsError> {
+pub fn decode_arbitrary_bytes_from_metadatum(metadata: &TransactionMetadatum) -> Result<Vec<u8>, Error> {
     let mut bytes = Vec::new();
     for elem in metadata.as_list()?.0 {
         bytes.append(&mut elem.as_bytes()?);
@@ -484,23 +542,23 @@ fn bytes_to_hex_string(bytes: &[u8]) -> String {
 
 // Converts JSON to Metadata according to MetadataJsonSchema
 
-pub fn encode_json_str_to_metadatum(json: String, schema: MetadataJsonSchema) -> Result<TransactionMetadatum, JsError> {
-    let value = serde_json::from_str(&json).map_err(|e| JsError::from_str(&e.to_string()))?;
+pub fn encode_json_str_to_metadatum(json: String, schema: MetadataJsonSchema) -> Result<TransactionMetadatum, Error> {
+    let value = serde_json::from_str(&json).map_err(|e| Error::from_str(&e.to_string()))?;
     encode_json_value_to_metadatum(value, schema)
 }
 
-pub fn encode_json_value_to_metadatum(value: serde_json::Value, schema: MetadataJsonSchema) -> Result<TransactionMetadatum, JsError> {
+pub fn encode_json_value_to_metadatum(value: serde_json::Value, schema: MetadataJsonSchema) -> Result<TransactionMetadatum, Error> {
     use serde_json::Value;
-    fn encode_number(x: serde_json::Number) -> Result<TransactionMetadatum, JsError> {
+    fn encode_number(x: serde_json::Number) -> Result<TransactionMetadatum, Error> {
         if let Some(x) = x.as_u64() {
             Ok(TransactionMetadatum::new_int(&Int::new(&to_bignum(x))))
         } else if let Some(x) = x.as_i64() {
             Ok(TransactionMetadatum::new_int(&Int::new_negative(&to_bignum(-x as u64))))
         } else {
-            Err(JsError::from_str("floats not allowed in metadata"))
+            Err(Error::from_str("floats not allowed in metadata"))
         }
     }
-    fn encode_string(s: String, schema: MetadataJsonSchema) -> Result<TransactionMetadatum, JsError> {
+    fn encode_string(s: String, schema: MetadataJsonSchema) -> Result<TransactionMetadatum, Error> {
         if schema == MetadataJsonSchema::BasicConversions {
             match hex_string_to_bytes(&s) {
                 Some(bytes) => TransactionMetadatum::new_bytes(bytes),
@@ -510,7 +568,7 @@ pub fn encode_json_value_to_metadatum(value: serde_json::Value, schema: Metadata
             TransactionMetadatum::new_text(s)
         }
     }
-    fn encode_array(json_arr: Vec<Value>, schema: MetadataJsonSchema) -> Result<TransactionMetadatum, JsError> {
+    fn encode_array(json_arr: Vec<Value>, schema: MetadataJsonSchema) -> Result<TransactionMetadatum, Error> {
         let mut arr = MetadataList::new();
         for value in json_arr {
             arr.add(&encode_json_value_to_metadatum(value, schema)?);
@@ -520,8 +578,8 @@ pub fn encode_json_value_to_metadatum(value: serde_json::Value, schema: Metadata
     match schema {
         MetadataJsonSchema::NoConversions |
         MetadataJsonSchema::BasicConversions => match value {
-            Value::Null => Err(JsError::from_str("null not allowed in metadata")),
-            Value::Bool(_) => Err(JsError::from_str("bools not allowed in metadata")),
+            Value::Null => Err(Error::from_str("null not allowed in metadata")),
+            Value::Bool(_) => Err(Error::from_str("bools not allowed in metadata")),
             Value::Number(x) => encode_number(x),
             Value::String(s) => encode_string(s, schema),
             Value::Array(json_arr) => encode_array(json_arr, schema),
@@ -545,8 +603,8 @@ pub fn encode_json_value_to_metadatum(value: serde_json::Value, schema: Metadata
         MetadataJsonSchema::DetailedSchema => match value {
             Value::Object(obj) if obj.len() == 1 => {
                 let (k, v) = obj.into_iter().next().unwrap();
-                fn tag_mismatch() -> JsError {
-                    JsError::from_str("key does not match type")
+                fn tag_mismatch() -> Error {
+                    Error::from_str("key does not match type")
                 }
                 match k.as_str() {
                     "int" => match v {
@@ -556,13 +614,13 @@ pub fn encode_json_value_to_metadatum(value: serde_json::Value, schema: Metadata
                     "string" => encode_string(v.as_str().ok_or_else(tag_mismatch)?.to_owned(), schema),
                     "bytes" => match hex::decode(v.as_str().ok_or_else(tag_mismatch)?) {
                         Ok(bytes) => TransactionMetadatum::new_bytes(bytes),
-                        Err(_) => Err(JsError::from_str("invalid hex string in tagged byte-object")),
+                        Err(_) => Err(Error::from_str("invalid hex string in tagged byte-object")),
                     },
                     "list" => encode_array(v.as_array().ok_or_else(tag_mismatch)?.clone(), schema),
                     "map" => {
                         let mut map = MetadataMap::new();
-                        fn map_entry_err() -> JsError {
-                            JsError::from_str("entry format in detailed schema map object not correct. Needs to be of form {\"k\": \"key\", \"v\": value}")
+                        fn map_entry_err() -> Error {
+                            Error::from_str("entry format in detailed schema map object not correct. Needs to be of form {\"k\": \"key\", \"v\": value}")
                         }
                         for entry in v.as_array().ok_or_else(tag_mismatch)? {
                             let entry_obj = entry.as_object().ok_or_else(map_entry_err)?;
@@ -575,26 +633,26 @@ pub fn encode_json_value_to_metadatum(value: serde_json::Value, schema: Metadata
                         }
                         Ok(TransactionMetadatum::new_map(&map))
                     },
-                    invalid_key => Err(JsError::from_str(&format!("key '{}' in tagged object not valid", invalid_key))),
+                    invalid_key => Err(Error::from_str(&format!("key '{}' in tagged object not valid", invalid_key))),
                 }
             },
-            _ => Err(JsError::from_str("DetailedSchema requires types to be tagged objects")),
+            _ => Err(Error::from_str("DetailedSchema requires types to be tagged objects")),
         },
     }
 }
 
 // Converts Metadata to JSON according to MetadataJsonSchema
 
-pub fn decode_metadatum_to_json_str(metadatum: &TransactionMetadatum, schema: MetadataJsonSchema) -> Result<String, JsError> {
+pub fn decode_metadatum_to_json_str(metadatum: &TransactionMetadatum, schema: MetadataJsonSchema) -> Result<String, Error> {
     let value = decode_metadatum_to_json_value(metadatum, schema)?;
-    serde_json::to_string(&value).map_err(|e| JsError::from_str(&e.to_string()))
+    serde_json::to_string(&value).map_err(|e| Error::from_str(&e.to_string()))
 }
 
-pub fn decode_metadatum_to_json_value(metadatum: &TransactionMetadatum, schema: MetadataJsonSchema) -> Result<serde_json::Value, JsError> {
+pub fn decode_metadatum_to_json_value(metadatum: &TransactionMetadatum, schema: MetadataJsonSchema) -> Result<serde_json::Value, Error> {
     use serde_json::Value;
     use std::convert::TryFrom;
-    fn decode_key(key: &TransactionMetadatum, schema: MetadataJsonSchema) -> Result<String, JsError> {
-        match &key.0 {
+    fn decode_key(key: &TransactionMetadatum, schema: MetadataJsonSchema) -> Result<String, Error> {
+        match &key.datum {
             TransactionMetadatumEnum::Text(s) => Ok(s.clone()),
             TransactionMetadatumEnum::Bytes(b) if schema != MetadataJsonSchema::NoConversions => Ok(bytes_to_hex_string(b.as_ref())),
             TransactionMetadatumEnum::Int(i) if schema != MetadataJsonSchema::NoConversions => {
@@ -603,14 +661,14 @@ pub fn decode_metadatum_to_json_value(metadatum: &TransactionMetadatum, schema:
                 } else {
                     i64::try_from(i.0).map(|x| x.to_string())
                 };
-                int_str.map_err(|e| JsError::from_str(&e.to_string()))
+                int_str.map_err(|e| Error::from_str(&e.to_string()))
             },
             TransactionMetadatumEnum::MetadataList(list) if schema == MetadataJsonSchema::DetailedSchema => decode_metadatum_to_json_str(&TransactionMetadatum::new_list(&list), schema),
             TransactionMetadatumEnum::MetadataMap(map) if schema == MetadataJsonSchema::DetailedSchema => decode_metadatum_to_json_str(&TransactionMetadatum::new_map(&map), schema),
-            _ => Err(JsError::from_str(&format!("key type {:?} not allowed in JSON under specified schema", key.0))),
+            _ => Err(Error::from_str(&format!("key type {:?} not allowed in JSON under specified schema", key.datum))),
         }
     }
-    let (type_key, value) = match &metadatum.0 {
+    let (type_key, value) = match &metadatum.datum {
         TransactionMetadatumEnum::MetadataMap(map) => match schema {
             MetadataJsonSchema::NoConversions |
             MetadataJsonSchema::BasicConversions => {
@@ -634,20 +692,20 @@ pub fn decode_metadatum_to_json_value(metadatum: &TransactionMetadatum, schema:
                 kv_obj.insert(String::from("k"), Value::from(k));
                 kv_obj.insert(String::from("v"), v);
                 Ok(Value::from(kv_obj))
-            }).collect::<Result<Vec<_>, JsError>>()?))
+            }).collect::<Result<Vec<_>, Error>>()?))
         },
         TransactionMetadatumEnum::MetadataList(arr) => {
             ("list", Value::from(arr.0.iter().map(|e| {
                 decode_metadatum_to_json_value(e, schema)
-            }).collect::<Result<Vec<_>, JsError>>()?))
+            }).collect::<Result<Vec<_>, Error>>()?))
         },
         TransactionMetadatumEnum::Int(x) => ("int", if x.0 >= 0 {
-            Value::from(u64::try_from(x.0).map_err(|e| JsError::from_str(&e.to_string()))?)
+            Value::from(u64::try_from(x.0).map_err(|e| Error::from_str(&e.to_string()))?)
         } else {
-            Value::from(i64::try_from(x.0).map_err(|e| JsError::from_str(&e.to_string()))?)
+            Value::from(i64::try_from(x.0).map_err(|e| Error::from_str(&e.to_string()))?)
         }),
         TransactionMetadatumEnum::Bytes(bytes) => ("bytes", match schema {
-            MetadataJsonSchema::NoConversions => Err(JsError::from_str("bytes not allowed in JSON in specified schema")),
+            MetadataJsonSchema::NoConversions => Err(Error::from_str("bytes not allowed in JSON in specified schema")),
             // 0x prefix
             MetadataJsonSchema::BasicConversions => Ok(Value::from(bytes_to_hex_string(bytes.as_ref()))),
             // no prefix
@@ -688,8 +746,8 @@ impl Deserialize for MetadataMap {
                     assert_eq!(raw.special()?, CBORSpecial::Break);
                     break;
                 }
-                let key = TransactionMetadatum::deserialize(raw)?;
-                let value = TransactionMetadatum::deserialize(raw)?;
+                let key = TransactionMetadatum::deserialize_nested(raw)?;
+                let value = TransactionMetadatum::deserialize_nested(raw)?;
                 entries.push((key.clone(), value));
             }
             Ok(())
@@ -724,7 +782,7 @@ impl Deserialize for MetadataList {
                     assert_eq!(raw.special()?, CBORSpecial::Break);
                     break;
                 }
-                arr.push(TransactionMetadatum::deserialize(raw)?);
+                arr.push(TransactionMetadatum::deserialize_nested(raw)?);
             }
             Ok(())
         })().map_err(|e| e.annotate("MetadataList"))?;
@@ -759,8 +817,8 @@ impl Deserialize for TransactionMetadatumEnum {
         match raw.cbor_type()? {
             CBORType::Array => MetadataList::deserialize(raw).map(TransactionMetadatumEnum::MetadataList),
             CBORType::Map => MetadataMap::deserialize(raw).map(TransactionMetadatumEnum::MetadataMap),
-            CBORType::Bytes => TransactionMetadatum::new_bytes(raw.bytes()?).map(|m| m.0).map_err(|e| DeserializeFailure::Metadata(e).into()),
-            CBORType::Text => TransactionMetadatum::new_text(raw.text()?).map(|m| m.0).map_err(|e| DeserializeFailure::Metadata(e).into()),
+            CBORType::Bytes => TransactionMetadatum::new_bytes(raw.bytes()?).map(|m| m.datum).map_err(|e| DeserializeFailure::Metadata(e).into()),
+            CBORType::Text => TransactionMetadatum::new_text(raw.text()?).map(|m| m.datum).map_err(|e| DeserializeFailure::Metadata(e).into()),
             CBORType::UnsignedInteger |
             CBORType::NegativeInteger => Int::deserialize(raw).map(TransactionMetadatumEnum::Int),
             _ => Err(DeserializeError::new("TransactionMetadatumEnum", DeserializeFailure::NoVariantMatched.into()))
@@ -770,13 +828,28 @@ impl Deserialize for TransactionMetadatumEnum {
 
 impl cbor_event::se::Serialize for TransactionMetadatum {
     fn serialize<'se, W: Write>(&self, serializer: &'se mut Serializer<W>) -> cbor_event::Result<&'se mut Serializer<W>> {
-        self.0.serialize(serializer)
+        match &self.original_bytes {
+            Some(bytes) => serializer.write_raw_bytes(bytes),
+            None => self.datum.serialize(serializer),
+        }
     }
 }
 
 impl Deserialize for TransactionMetadatum {
     fn deserialize<R: BufRead + Seek>(raw: &mut Deserializer<R>) -> Result<Self, DeserializeError> {
-        Ok(Self(TransactionMetadatumEnum::deserialize(raw)?))
+        // these unwraps are fine since we're seeking the current position
+        let before = raw.as_mut_ref().stream_position().unwrap();
+        let datum = TransactionMetadatumEnum::deserialize(raw)?;
+        let after = raw.as_mut_ref().stream_position().unwrap();
+        let bytes_read = (after - before) as usize;
+        raw.as_mut_ref().seek(SeekFrom::Start(before)).unwrap();
+        // these unwraps are fine since we read the above already
+        let original_bytes = raw.as_mut_ref().fill_buf().unwrap()[..bytes_read].to_vec();
+        raw.as_mut_ref().consume(bytes_read);
+        Ok(Self {
+            datum,
+            original_bytes: Some(original_bytes),
+        })
     }
 }
 
@@ -846,7 +919,7 @@ impl cbor_event::se::Serialize for AuxiliaryData {
         // we still serialize using the shelley-mary era format as it is still supported
         // and it takes up less space on-chain so this should be better for scaling.
         // Plus the code was already written for shelley-mary anyway
-        if !self.prefer_alonzo_format && self.metadata.is_some() && self.plutus_v1_scripts.is_none() && self.plutus_v2_scripts.is_none()  {
+        if !self.prefer_alonzo_format && self.metadata.is_some() && self.plutus_v1_scripts.is_none() && self.plutus_v2_scripts.is_none() && self.plutus_v3_scripts.is_none() {
             match &self.native_scripts() {
                 Some(native_scripts) => {
                     serializer.write_array(cbor_event::Len::Len(2))?;
@@ -862,7 +935,8 @@ impl cbor_event::se::Serialize for AuxiliaryData {
                 if self.metadata.is_some() { 1 } else { 0 } +
                 if self.native_scripts.is_some() { 1 } else { 0 } +
                 if self.plutus_v1_scripts.is_some() { 1 } else { 0 } +
-                if self.plutus_v2_scripts.is_some() { 1 } else { 0 }))?;
+                if self.plutus_v2_scripts.is_some() { 1 } else { 0 } +
+                if self.plutus_v3_scripts.is_some() { 1 } else { 0 }))?;
             if let Some(metadata) = &self.metadata {
                 serializer.write_unsigned_integer(0)?;
                 metadata.serialize(serializer)?;
@@ -875,10 +949,14 @@ impl cbor_event::se::Serialize for AuxiliaryData {
                 serializer.write_unsigned_integer(2)?;
                 plutus_v1_scripts.serialize(serializer)?;
             }
-	    if let Some(plutus_v2_scripts) = &self.plutus_v2_scripts {
+            if let Some(plutus_v2_scripts) = &self.plutus_v2_scripts {
                 serializer.write_unsigned_integer(3)?;
                 plutus_v2_scripts.serialize(serializer)?;
             }
+            if let Some(plutus_v3_scripts) = &self.plutus_v3_scripts {
+                serializer.write_unsigned_integer(4)?;
+                plutus_v3_scripts.serialize(serializer)?;
+            }
             Ok(serializer)
         }
     }
@@ -888,7 +966,7 @@ impl Deserialize for AuxiliaryData {
     fn deserialize<R: BufRead + Seek>(raw: &mut Deserializer<R>) -> Result<Self, DeserializeError> {
         (|| -> Result<_, DeserializeError> {
             match raw.cbor_type()? {
-                // alonzo+ format (babbage adds optional v2 script field)
+                // alonzo+ format (babbage adds optional v2 script field, conway v3)
                 CBORType::Tag => {
                     let tag = raw.tag()?;
                     if tag != 259 {
@@ -900,6 +978,7 @@ impl Deserialize for AuxiliaryData {
                     let mut native_scripts = None;
                     let mut plutus_v1_scripts = None;
                     let mut plutus_v2_scripts = None;
+                    let mut plutus_v3_scripts = None;
                     let mut read = 0;
                     while match len { cbor_event::Len::Len(n) => read < n as usize, cbor_event::Len::Indefinite => true, } {
                         match raw.cbor_type()? {
@@ -940,6 +1019,15 @@ impl Deserialize for AuxiliaryData {
                                         Ok(PlutusV2Scripts::deserialize(raw)?)
                                     })().map_err(|e| e.annotate("plutus_v2_scripts"))?);
                                 },
+                                4 =>  {
+                                    if plutus_v3_scripts.is_some() {
+                                        return Err(DeserializeFailure::DuplicateKey(Key::Uint(4)).into());
+                                    }
+                                    plutus_v3_scripts = Some((|| -> Result<_, DeserializeError> {
+                                        read_len.read_elems(1)?;
+                                        PlutusV3Scripts::deserialize(raw)
+                                    })().map_err(|e| e.annotate("plutus_v3_scripts"))?);
+                                },
                                 unknown_key => return Err(DeserializeFailure::UnknownKey(Key::Uint(unknown_key)).into()),
                             },
                             CBORType::Text => match raw.text()?.as_str() {
@@ -962,6 +1050,7 @@ impl Deserialize for AuxiliaryData {
                         native_scripts,
                         plutus_v1_scripts,
                         plutus_v2_scripts,
+                        plutus_v3_scripts,
                         prefer_alonzo_format: true,
                     })
                 },
@@ -988,6 +1077,7 @@ impl Deserialize for AuxiliaryData {
                         native_scripts: Some(native_scripts),
                         plutus_v1_scripts: None,
                         plutus_v2_scripts: None,
+                        plutus_v3_scripts: None,
                         prefer_alonzo_format: false,
                     })
                 },
@@ -997,6 +1087,7 @@ impl Deserialize for AuxiliaryData {
                     native_scripts: None,
                     plutus_v1_scripts: None,
                     plutus_v2_scripts: None,
+                    plutus_v3_scripts: None,
                     prefer_alonzo_format: false,
                 }),
                 _ => return Err(DeserializeFailure::NoVariantMatched)?
@@ -1176,6 +1267,39 @@ mod tests {
         assert_eq!(aux_pre_alonzo.to_bytes(), bytes_pre_alonzo);
     }
 
+    #[test]
+    fn plutus_v3_auxiliary_data() {
+        let mut plutus_v3_scripts = PlutusV3Scripts::new();
+        plutus_v3_scripts.add(&PlutusV3Script::new(vec![0x01]));
+        let mut aux_data = AuxiliaryData::new();
+        aux_data.set_plutus_v3_scripts(&plutus_v3_scripts);
+        let bytes = aux_data.to_bytes();
+        assert_eq!(bytes, hex::decode("d90103a104814101").unwrap());
+        let deser = AuxiliaryData::from_bytes(bytes.clone()).unwrap();
+        assert_eq!(deser.plutus_v3_scripts(), Some(plutus_v3_scripts));
+        assert_eq!(deser.to_bytes(), bytes);
+
+        // along with metadata it still needs the alonzo format
+        let mut gmd = GeneralTransactionMetadata::new();
+        gmd.insert(&to_bignum(100), &TransactionMetadatum::new_int(&Int::new_i32(1)));
+        aux_data.set_metadata(&gmd);
+        let deser = AuxiliaryData::from_bytes(aux_data.to_bytes()).unwrap();
+        assert_eq!(deser.metadata(), Some(gmd));
+        assert!(deser.plutus_v3_scripts().is_some());
+    }
+
+    #[test]
+    fn original_bytes_of_outermost_metadatum() {
+        // {1: [2 as a 64-bit integer, [_ 3]]}
+        let bytes = hex::decode("a101821b00000000000000029f03ff").unwrap();
+        let metadatum = TransactionMetadatum::from_bytes(bytes.clone()).unwrap();
+        assert_eq!(metadatum.to_bytes(), bytes);
+        let list = metadatum.as_map().unwrap().get_i32(1).unwrap();
+        assert!(list.original_bytes.is_none());
+        assert!(list.as_list().unwrap().0.iter().all(|element| element.original_bytes.is_none()));
+        assert_eq!(list.to_bytes(), hex::decode("82028103").unwrap());
+    }
+
     #[test]
     fn metadatum_map_duplicate_keys() {
         let bytes = hex::decode("a105a4781b232323232323232323232323232323232323232323232323232323827840232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323237840232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323236e232323232323232323232323232382a36f2323232323232323232323232323236a323030302d30312d303166232323232323784023232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323712323232323232323232323232323232323784023232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323a36f2323232323232323232323232323236a323030302d30312d303166232323232323784023232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323712323232323232323232323232323232323784023232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323232323752323232323232323232323232323232323232323236a323030302d30312d3031752323232323232323232323232323232323232323236a323030302d30312d3031").unwrap();
@@ -0,0 +1,849 @@
+use std::collections::{BTreeMap, BTreeSet};
+use super::*;
+
+// Generation of typed encoders: one Rust type per type rule (and per anonymous map, array or type
+// choice that can't be expressed as a Vec) with a `to_metadatum` that checks everything the types
+// don't already guarantee (ranges, sizes, occurrences and duplicate keys) while encoding.
+// Group choices (//) and literals that repeat are not supported
+
+// names used by the generated code itself
+const RESERVED_TYPES: [&str; 16] = [
+    "Self", "Error", "BigNum", "Int", "MetadataList", "MetadataMap", "TransactionMetadatum", "Option",
+    "Vec", "String", "Result", "Box", "Some", "None", "Ok", "Err",
+];
+// the generated `to_metadatum` functions declare these after binding the fields
+const RESERVED_FIELDS: [&str; 11] = [
+    "map", "list", "encode_uint", "encode_int", "encode_text", "encode_bytes", "encode_list", "encode_map",
+    "insert_entry", "check_count", "check_size",
+];
+const RUST_KEYWORDS: [&str; 51] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "macro", "override", "priv", "try", "typeof", "unsized",
+    "virtual", "yield", "union",
+];
+
+// (name, helpers it calls, source)
+const HELPERS: [(&str, &[&str], &str); 9] = [
+    ("check_count", &[], r#"fn check_count(len: usize, min: usize, max: usize, what: &str) -> Result<(), Error> {
+    if len < min || len > max {
+        return Err(Error::from_str(&format!("{}: expected {}..{} entries, found {}", what, min, max, len)));
+    }
+    Ok(())
+}
+"#),
+    ("check_size", &[], r#"fn check_size(len: usize, min: usize, max: usize, what: &str) -> Result<(), Error> {
+    if len < min || len > max {
+        return Err(Error::from_str(&format!("{}: expected a length in {}..{}, found {}", what, min, max, len)));
+    }
+    Ok(())
+}
+"#),
+    ("insert_entry", &[], r#"fn insert_entry(map: &mut MetadataMap, key: TransactionMetadatum, value: TransactionMetadatum, what: &str) -> Result<(), Error> {
+    match map.insert(&key, &value) {
+        Some(_) => Err(Error::from_str(&format!("{}: duplicate key", what))),
+        None => Ok(()),
+    }
+}
+"#),
+    ("encode_uint", &[], r#"fn encode_uint(x: &u64, min: u64, max: u64, what: &str) -> Result<TransactionMetadatum, Error> {
+    if *x < min || *x > max {
+        return Err(Error::from_str(&format!("{}: expected {}..{}, found {}", what, min, max, x)));
+    }
+    Ok(TransactionMetadatum::new_int(&Int::new(&BigNum::from(*x))))
+}
+"#),
+    ("encode_int", &[], r#"fn encode_int(x: &i64, min: i64, max: i64, what: &str) -> Result<TransactionMetadatum, Error> {
+    if *x < min || *x > max {
+        return Err(Error::from_str(&format!("{}: expected {}..{}, found {}", what, min, max, x)));
+    }
+    let abs = BigNum::from(x.unsigned_abs());
+    Ok(TransactionMetadatum::new_int(&if *x < 0 { Int::new_negative(&abs) } else { Int::new(&abs) }))
+}
+"#),
+    ("encode_text", &["check_size"], r#"fn encode_text(x: &str, min: usize, max: usize, what: &str) -> Result<TransactionMetadatum, Error> {
+    check_size(x.len(), min, max, what)?;
+    TransactionMetadatum::new_text(x.to_owned())
+}
+"#),
+    ("encode_bytes", &["check_size"], r#"fn encode_bytes(x: &[u8], min: usize, max: usize, what: &str) -> Result<TransactionMetadatum, Error> {
+    check_size(x.len(), min, max, what)?;
+    TransactionMetadatum::new_bytes(x.to_vec())
+}
+"#),
+    ("encode_list", &["check_count"], r#"fn encode_list<T>(
+    items: &[T],
+    min: usize,
+    max: usize,
+    what: &str,
+    encode: impl Fn(&T) -> Result<TransactionMetadatum, Error>,
+) -> Result<TransactionMetadatum, Error> {
+    check_count(items.len(), min, max, what)?;
+    let mut list = MetadataList::new();
+    for item in items {
+        list.add(&encode(item)?);
+    }
+    Ok(TransactionMetadatum::new_list(&list))
+}
+"#),
+    ("encode_map", &["check_count", "insert_entry"], r#"fn encode_map<K, V>(
+    entries: &[(K, V)],
+    min: usize,
+    max: usize,
+    what: &str,
+    encode_key: impl Fn(&K) -> Result<TransactionMetadatum, Error>,
+    encode_value: impl Fn(&V) -> Result<TransactionMetadatum, Error>,
+) -> Result<TransactionMetadatum, Error> {
+    check_count(entries.len(), min, max, what)?;
+    let mut map = MetadataMap::new();
+    for (key, value) in entries {
+        insert_entry(&mut map, encode_key(key)?, encode_value(value)?, what)?;
+    }
+    Ok(TransactionMetadatum::new_map(&map))
+}
+"#),
+];
+
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut prev = None;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+        } else {
+            // camelCase boundary
+            if c.is_ascii_uppercase() && matches!(prev, Some(p) if char::is_ascii_lowercase(&p) || char::is_ascii_digit(&p)) && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push(c.to_ascii_lowercase());
+        }
+        prev = Some(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+// `prefix` is used when the name doesn't start with a letter
+fn camel_case(name: &str, prefix: &str) -> String {
+    let camel = words(name)
+        .iter()
+        .map(|word| word[..1].to_ascii_uppercase() + &word[1..])
+        .collect::<String>();
+    match camel.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => camel,
+        _ => format!("{}{}", prefix, camel),
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let snake = words(name).join("_");
+    match snake.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => (),
+        Some(_) => return format!("field_{}", snake),
+        None => return String::from("field"),
+    }
+    if RUST_KEYWORDS.contains(&snake.as_str()) || RESERVED_FIELDS.contains(&snake.as_str()) {
+        format!("{}_", snake)
+    } else {
+        snake
+    }
+}
+
+fn unique(name: String, taken: &mut BTreeSet<String>, separator: &str) -> String {
+    let mut unique = name.clone();
+    let mut n = 2;
+    while taken.contains(&unique) {
+        unique = format!("{}{}{}", name, separator, n);
+        n += 1;
+    }
+    taken.insert(unique.clone());
+    unique
+}
+
+fn uint_bound(x: u64) -> String {
+    if x == u64::MAX { String::from("u64::MAX") } else { x.to_string() }
+}
+
+fn int_bound(x: i64) -> String {
+    match x {
+        i64::MIN => String::from("i64::MIN"),
+        i64::MAX => String::from("i64::MAX"),
+        _ => x.to_string(),
+    }
+}
+
+fn len_bound(x: usize) -> String {
+    if x == usize::MAX { String::from("usize::MAX") } else { x.to_string() }
+}
+
+fn indent(lines: &[String], spaces: usize) -> String {
+    lines.iter().map(|line| format!("{}{}\n", " ".repeat(spaces), line)).collect()
+}
+
+// expression creating the literal and whether it evaluates to a Result
+fn literal(ty: &SchemaType, what: &str) -> Result<(String, bool), String> {
+    match ty {
+        SchemaType::IntValue(x) => {
+            let (constructor, abs) = if *x < 0 { ("new_negative", -x) } else { ("new", *x) };
+            let abs = u64::try_from(abs).map_err(|_| format!("{}: {} does not fit in metadata", what, x))?;
+            Ok((format!("TransactionMetadatum::new_int(&Int::{}(&BigNum::from({}u64)))", constructor, abs), false))
+        },
+        SchemaType::TextValue(text) => Ok((format!("TransactionMetadatum::new_text(String::from({:?}))", text), true)),
+        SchemaType::BytesValue(bytes) => {
+            let bytes = match bytes.is_empty() {
+                true => String::from("Vec::new()"),
+                false => format!("vec![{}]", bytes.iter().map(|b| format!("0x{:02x}", b)).collect::<Vec<_>>().join(", ")),
+            };
+            Ok((format!("TransactionMetadatum::new_bytes({})", bytes), true))
+        },
+        _ => Err(format!("{}: expected a literal", what)),
+    }
+}
+
+// the literal as a TransactionMetadatum (inside a function returning a Result)
+fn literal_value(ty: &SchemaType, what: &str) -> Result<String, String> {
+    let (expr, fallible) = literal(ty, what)?;
+    Ok(if fallible { format!("{}?", expr) } else { expr })
+}
+
+// the literal as a Result<TransactionMetadatum, Error>
+fn literal_result(ty: &SchemaType, what: &str) -> Result<String, String> {
+    let (expr, fallible) = literal(ty, what)?;
+    Ok(if fallible { expr } else { format!("Ok({})", expr) })
+}
+
+fn literal_name(ty: &SchemaType) -> String {
+    match ty {
+        SchemaType::IntValue(x) if *x < 0 => format!("key_neg_{}", -x),
+        SchemaType::IntValue(x) => format!("key_{}", x),
+        SchemaType::TextValue(text) => text.clone(),
+        SchemaType::BytesValue(bytes) => format!("key_{}", hex::encode(bytes)),
+        _ => String::new(),
+    }
+}
+
+fn flatten_choices(ty: &SchemaType) -> Vec<&SchemaType> {
+    match ty {
+        SchemaType::Choice(choices) => choices.iter().flat_map(flatten_choices).collect(),
+        _ => vec![ty],
+    }
+}
+
+// Rust type of a value and how to encode it
+struct Encoding {
+    rust_type: String,
+    // expression around a reference to the value
+    before: String,
+    after: String,
+    // whether the expression evaluates to a Result<TransactionMetadatum, Error> or a TransactionMetadatum
+    fallible: bool,
+    // generated types the value contains without a Vec in between
+    contains: Vec<String>,
+}
+
+impl Encoding {
+    fn new(rust_type: &str, before: &str, after: String) -> Self {
+        Self {
+            rust_type: rust_type.to_owned(),
+            before: before.to_owned(),
+            after,
+            fallible: true,
+            contains: Vec::new(),
+        }
+    }
+
+    // Result<TransactionMetadatum, Error>
+    fn encode(&self, value: &str) -> String {
+        match self.fallible {
+            true => format!("{}{}{}", self.before, value, self.after),
+            false => format!("Ok({}{}{})", self.before, value, self.after),
+        }
+    }
+
+    // TransactionMetadatum (inside a function returning a Result)
+    fn value(&self, value: &str) -> String {
+        match self.fallible {
+            true => format!("{}{}{}?", self.before, value, self.after),
+            false => format!("{}{}{}", self.before, value, self.after),
+        }
+    }
+}
+
+struct Field {
+    name: String,
+    rust_type: String,
+}
+
+fn render(name: &str, declaration: String, destructure: Option<String>, body: Vec<String>) -> String {
+    let mut lines = destructure.into_iter().collect::<Vec<_>>();
+    lines.extend(body);
+    format!(
+        "#[derive(Clone, Debug, PartialEq, Eq)]\n{}\n\nimpl {} {{\n    pub fn to_metadatum(&self) -> Result<TransactionMetadatum, Error> {{\n{}    }}\n}}\n",
+        declaration,
+        name,
+        indent(&lines, 8),
+    )
+}
+
+fn render_struct(name: &str, fields: &[Field], tuple: bool, body: Vec<String>) -> String {
+    if fields.is_empty() {
+        return render(name, format!("pub struct {};", name), None, body);
+    }
+    let names = fields.iter().map(|field| field.name.clone()).collect::<Vec<_>>().join(", ");
+    let (declaration, destructure) = if tuple {
+        let types = fields.iter().map(|field| format!("pub {}", field.rust_type)).collect::<Vec<_>>().join(", ");
+        (format!("pub struct {}({});", name, types), format!("let {}({}) = self;", name, names))
+    } else {
+        let members = fields.iter().map(|field| format!("    pub {}: {},\n", field.name, field.rust_type)).collect::<String>();
+        (format!("pub struct {} {{\n{}}}", name, members), format!("let {} {{ {} }} = self;", name, names))
+    };
+    render(name, declaration, Some(destructure), body)
+}
+
+struct Generator<'a> {
+    schema: &'a MetadataSchema,
+    type_names: BTreeSet<String>,
+    rule_types: BTreeMap<&'a str, String>,
+    contains: BTreeMap<String, Vec<String>>,
+    helpers: BTreeSet<&'static str>,
+    items: Vec<String>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(schema: &'a MetadataSchema) -> Self {
+        Self {
+            schema,
+            type_names: RESERVED_TYPES.iter().map(|name| name.to_string()).collect(),
+            rule_types: BTreeMap::new(),
+            contains: BTreeMap::new(),
+            helpers: BTreeSet::new(),
+            items: Vec::new(),
+        }
+    }
+
+    fn generate(mut self) -> Result<String, String> {
+        for (name, rule) in self.schema.rules.iter() {
+            if let Rule::Type(_) = rule {
+                let rust_name = unique(camel_case(name, "Rule"), &mut self.type_names, "");
+                self.rule_types.insert(name, rust_name);
+            }
+        }
+        for (name, rule) in self.schema.rules.iter() {
+            let ty = match rule {
+                Rule::Type(ty) => ty,
+                Rule::Group(_) => continue,
+            };
+            let rust_name = self.rule_types[name.as_str()].clone();
+            match ty {
+                SchemaType::Ref(other) => {
+                    let target = self.rule_types[other.as_str()].clone();
+                    self.items.push(format!("/// `{}`\npub type {} = {};\n", name, rust_name, target));
+                    self.contains.insert(rust_name, vec![target]);
+                },
+                _ => self.define(rust_name, ty, name)?,
+            }
+        }
+        self.check_recursion()?;
+        Ok(self.source())
+    }
+
+    fn source(&self) -> String {
+        let mut helpers = BTreeSet::new();
+        let mut pending = self.helpers.iter().cloned().collect::<Vec<_>>();
+        while let Some(name) = pending.pop() {
+            if helpers.insert(name) {
+                let (_, calls, _) = HELPERS.iter().find(|(helper, _, _)| *helper == name).unwrap();
+                pending.extend(calls.iter());
+            }
+        }
+        let mut sections = HELPERS
+            .iter()
+            .filter(|(name, _, _)| helpers.contains(name))
+            .map(|(_, _, source)| source.to_string())
+            .collect::<Vec<_>>();
+        sections.extend(self.items.iter().cloned());
+        let code = sections.join("\n");
+        let used = |names: &[&'static str]| names
+            .iter()
+            .filter(|name| code.contains(&format!("{}::", name)))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut imports = vec![(String::from("error"), vec!["Error"])];
+        let value = used(&["BigNum", "Int"]);
+        if !value.is_empty() {
+            imports.push((String::from("ledger::common::value"), value));
+        }
+        let mut metadata = used(&["MetadataList", "MetadataMap"]);
+        metadata.push("TransactionMetadatum");
+        imports.push((String::from("metadata"), metadata));
+        let imports = imports
+            .into_iter()
+            .map(|(module, names)| match names.as_slice() {
+                [name] => format!("use cardano_multiplatform_lib::{}::{};\n", module, name),
+                _ => format!("use cardano_multiplatform_lib::{}::{{{}}};\n", module, names.join(", ")),
+            })
+            .collect::<String>();
+        format!("// Generated by MetadataSchema::generate_rust, do not edit\n\n{}\n{}", imports, code)
+    }
+
+    fn use_helper(&mut self, name: &'static str) {
+        self.helpers.insert(name);
+    }
+
+    // fails when the rules would give types of infinite size
+    fn check_recursion(&self) -> Result<(), String> {
+        for start in self.contains.keys() {
+            let mut visited = BTreeSet::new();
+            let mut pending = self.contains[start].iter().collect::<Vec<_>>();
+            while let Some(name) = pending.pop() {
+                if name == start {
+                    return Err(format!(
+                        "{} contains itself without a repeated (* or +) array or map entry in between",
+                        start,
+                    ));
+                }
+                if visited.insert(name) {
+                    pending.extend(self.contains.get(name).into_iter().flatten());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // `{ * key => value }`
+    fn homogeneous_map(&self, group: &'a Group, what: &str) -> Result<Option<MapEntry<'a>>, String> {
+        let entries = match group.0.as_slice() {
+            [entries] => entries,
+            _ => return Err(format!("{}: group choices (//) are not supported", what)),
+        };
+        let mut entries = self.schema.flatten_map_entries(entries, false, 0).map_err(|e| format!("{}: {}", what, e))?;
+        match entries.as_slice() {
+            [entry] if !entry.key.is_literal() => Ok(entries.pop()),
+            _ => Ok(None),
+        }
+    }
+
+    // `[ * value ]`
+    fn homogeneous_array(&self, group: &'a Group) -> Option<(Occurrence, &'a SchemaType)> {
+        match group.0.as_slice() {
+            [entries] => match entries.as_slice() {
+                [entry @ GroupEntry::Member { occurrence, value, .. }]
+                    if occurrence.max > 1 && !value.is_literal() && self.schema.entry_group(entry).is_none() => Some((*occurrence, value)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn encoding(&mut self, ty: &'a SchemaType, type_name: &str, what: &str) -> Result<Encoding, String> {
+        let what_literal = format!("{:?}", what);
+        let uint = |min: u64, max: u64| Encoding::new("u64", "encode_uint(", format!(", {}, {}, {})", min, uint_bound(max), what_literal));
+        let int = |min: i64, max: i64| Encoding::new("i64", "encode_int(", format!(", {}, {}, {})", int_bound(min), int_bound(max), what_literal));
+        let sized = |inner: &SchemaType, min: usize, max: usize| match inner {
+            SchemaType::Text => Some(Encoding::new("String", "encode_text(", format!(", {}, {}, {})", min, len_bound(max), what_literal))),
+            SchemaType::Bytes => Some(Encoding::new("Vec<u8>", "encode_bytes(", format!(", {}, {}, {})", min, len_bound(max), what_literal))),
+            _ => None,
+        };
+        let encoding = match ty {
+            SchemaType::Any => Encoding {
+                fallible: false,
+                ..Encoding::new("TransactionMetadatum", "", String::from(".clone()"))
+            },
+            SchemaType::Uint => uint(0, u64::MAX),
+            SchemaType::Nint => int(i64::MIN, -1),
+            SchemaType::Int => int(i64::MIN, i64::MAX),
+            // values of the range that don't fit in the Rust type can't be encoded
+            SchemaType::Range(min, max) if *min >= 0 => {
+                let min = u64::try_from(*min).map_err(|_| format!("{}: range {}..{} does not fit in a u64", what, min, max))?;
+                uint(min, u64::try_from(*max).unwrap_or(u64::MAX))
+            },
+            SchemaType::Range(min, max) => {
+                if *max < i128::from(i64::MIN) {
+                    return Err(format!("{}: range {}..{} does not fit in an i64", what, min, max));
+                }
+                int(i64::try_from(*min).unwrap_or(i64::MIN), i64::try_from(*max).unwrap_or(i64::MAX))
+            },
+            SchemaType::Text => sized(ty, 0, usize::MAX).unwrap(),
+            SchemaType::Bytes => sized(ty, 0, usize::MAX).unwrap(),
+            SchemaType::Sized { inner, min, max } => sized(inner, *min, *max)
+                .ok_or_else(|| format!("{}: .size is only supported on text, bytes and uint", what))?,
+            SchemaType::IntValue(_) | SchemaType::TextValue(_) | SchemaType::BytesValue(_) => {
+                return Err(format!("{}: literals are only supported as map keys, array elements that occur at most once and type choices", what));
+            },
+            SchemaType::Ref(name) => {
+                let rust_type = self.rule_types[name.as_str()].clone();
+                let mut encoding = Encoding::new(&rust_type, "", String::from(".to_metadatum()"));
+                encoding.contains.push(rust_type);
+                encoding
+            },
+            SchemaType::Map(group) => match self.homogeneous_map(group, what)? {
+                Some(entry) => {
+                    let key = self.encoding(entry.key, &format!("{}Key", type_name), &format!("{}.key", what))?;
+                    let value = self.encoding(entry.value, &format!("{}Value", type_name), &format!("{}.value", what))?;
+                    Encoding::new(
+                        &format!("Vec<({}, {})>", key.rust_type, value.rust_type),
+                        "encode_map(",
+                        format!(
+                            ", {}, {}, {}, |key| {}, |value| {})",
+                            entry.occurrence.min,
+                            len_bound(entry.occurrence.max),
+                            what_literal,
+                            key.encode("key"),
+                            value.encode("value"),
+                        ),
+                    )
+                },
+                None => self.named(ty, type_name, what)?,
+            },
+            SchemaType::Array(group) => match self.homogeneous_array(group) {
+                Some((occurrence, value)) => {
+                    let item = self.encoding(value, &format!("{}Item", type_name), what)?;
+                    Encoding::new(
+                        &format!("Vec<{}>", item.rust_type),
+                        "encode_list(",
+                        format!(", {}, {}, {}, |item| {})", occurrence.min, len_bound(occurrence.max), what_literal, item.encode("item")),
+                    )
+                },
+                None => self.named(ty, type_name, what)?,
+            },
+            SchemaType::Choice(_) => self.named(ty, type_name, what)?,
+        };
+        if let Some((helper, _, _)) = HELPERS.iter().find(|(helper, _, _)| encoding.before.strip_suffix('(') == Some(helper)) {
+            self.use_helper(helper);
+        }
+        Ok(encoding)
+    }
+
+    // generates a type for an anonymous map, array or type choice
+    fn named(&mut self, ty: &'a SchemaType, type_name: &str, what: &str) -> Result<Encoding, String> {
+        let rust_type = unique(type_name.to_owned(), &mut self.type_names, "");
+        self.define(rust_type.clone(), ty, what)?;
+        let mut encoding = Encoding::new(&rust_type, "", String::from(".to_metadatum()"));
+        encoding.contains.push(rust_type);
+        Ok(encoding)
+    }
+
+    fn define(&mut self, name: String, ty: &'a SchemaType, what: &str) -> Result<(), String> {
+        // before the types generated for its fields
+        let index = self.items.len();
+        self.items.push(String::new());
+        let (item, contains) = match ty {
+            SchemaType::Choice(_) => self.define_enum(&name, ty, what)?,
+            SchemaType::Map(group) if self.homogeneous_map(group, what)?.is_none() => self.define_map(&name, group, what)?,
+            SchemaType::Array(group) if self.homogeneous_array(group).is_none() => self.define_array(&name, group, what)?,
+            _ if ty.is_literal() => (render_struct(&name, &[], false, vec![literal_result(ty, what)?]), Vec::new()),
+            _ => {
+                let encoding = self.encoding(ty, &name, what)?;
+                let field = Field { name: String::from("value"), rust_type: encoding.rust_type.clone() };
+                (render_struct(&name, &[field], true, vec![encoding.encode("value")]), encoding.contains)
+            },
+        };
+        self.contains.insert(name, contains);
+        self.items[index] = format!("/// `{}`\n{}", what, item);
+        Ok(())
+    }
+
+    fn define_enum(&mut self, name: &str, ty: &'a SchemaType, what: &str) -> Result<(String, Vec<String>), String> {
+        let mut variant_names = BTreeSet::new();
+        let mut variants = Vec::new();
+        let mut arms = Vec::new();
+        let mut contains = Vec::new();
+        for choice in flatten_choices(ty) {
+            let variant = match choice {
+                SchemaType::Any => String::from("Any"),
+                SchemaType::Uint => String::from("Uint"),
+                SchemaType::Nint => String::from("Nint"),
+                SchemaType::Int => String::from("Int"),
+                SchemaType::Range(..) => String::from("Range"),
+                SchemaType::Text => String::from("Text"),
+                SchemaType::Sized { inner, .. } if **inner == SchemaType::Text => String::from("Text"),
+                SchemaType::Bytes | SchemaType::Sized { .. } => String::from("Bytes"),
+                SchemaType::IntValue(x) if *x < 0 => format!("Minus{}", -x),
+                SchemaType::IntValue(x) => format!("Value{}", x),
+                SchemaType::TextValue(text) => camel_case(text, "Text"),
+                SchemaType::BytesValue(bytes) => format!("Bytes{}", hex::encode(bytes)),
+                SchemaType::Ref(rule) => self.rule_types[rule.as_str()].clone(),
+                SchemaType::Map(_) => String::from("Map"),
+                SchemaType::Array(_) => String::from("Array"),
+                SchemaType::Choice(_) => String::from("Choice"),
+            };
+            let variant = unique(variant, &mut variant_names, "");
+            if choice.is_literal() {
+                variants.push(format!("    {},\n", variant));
+                arms.push(format!("    {}::{} => {},", name, variant, literal_result(choice, what)?));
+            } else {
+                let encoding = self.encoding(choice, &format!("{}{}", name, variant), what)?;
+                variants.push(format!("    {}({}),\n", variant, encoding.rust_type));
+                arms.push(format!("    {}::{}(value) => {},", name, variant, encoding.encode("value")));
+                contains.extend(encoding.contains);
+            }
+        }
+        let declaration = format!("pub enum {} {{\n{}}}", name, variants.concat());
+        let mut body = vec![String::from("match self {")];
+        body.extend(arms);
+        body.push(String::from("}"));
+        Ok((render(name, declaration, None, body), contains))
+    }
+
+    fn define_map(&mut self, name: &str, group: &'a Group, what: &str) -> Result<(String, Vec<String>), String> {
+        // group choices were already rejected by homogeneous_map()
+        let entries = self.schema.flatten_map_entries(&group.0[0], false, 0).map_err(|e| format!("{}: {}", what, e))?;
+        let mut field_names = BTreeSet::new();
+        let mut fields = Vec::new();
+        let mut body = Vec::new();
+        let mut contains = Vec::new();
+        for entry in entries {
+            let required = entry.occurrence.min > 0;
+            if entry.key.is_literal() {
+                let key_name = literal_name(entry.key);
+                let field_name = unique(snake_case(&key_name), &mut field_names, "_");
+                let field_what = format!("{}.{}", what, key_name);
+                let key = literal_value(entry.key, &field_what)?;
+                let insert = |value: String| format!("insert_entry(&mut map, {}, {}, {:?})?;", key, value, field_what);
+                if entry.value.is_literal() {
+                    let value = literal_value(entry.value, &field_what)?;
+                    if required {
+                        body.push(insert(value));
+                    } else {
+                        body.push(format!("if *{} {{", field_name));
+                        body.push(format!("    {}", insert(value)));
+                        body.push(String::from("}"));
+                        fields.push(Field { name: field_name, rust_type: String::from("bool") });
+                    }
+                    continue;
+                }
+                let encoding = self.encoding(entry.value, &format!("{}{}", name, camel_case(&key_name, "Key")), &field_what)?;
+                let value = encoding.value(&field_name);
+                if required {
+                    body.push(insert(value));
+                    fields.push(Field { name: field_name, rust_type: encoding.rust_type });
+                } else {
+                    body.push(format!("if let Some({}) = {} {{", field_name, field_name));
+                    body.push(format!("    {}", insert(value)));
+                    body.push(String::from("}"));
+                    fields.push(Field { name: field_name, rust_type: format!("Option<{}>", encoding.rust_type) });
+                }
+                contains.extend(encoding.contains);
+            } else {
+                let field_name = unique(String::from("entries"), &mut field_names, "_");
+                let key = self.encoding(entry.key, &format!("{}Key", name), &format!("{}.key", what))?;
+                let value = self.encoding(entry.value, &format!("{}Value", name), &format!("{}.value", what))?;
+                if entry.occurrence.min > 0 || entry.occurrence.max < usize::MAX {
+                    self.use_helper("check_count");
+                    body.push(format!(
+                        "check_count({}.len(), {}, {}, {:?})?;",
+                        field_name,
+                        entry.occurrence.min,
+                        len_bound(entry.occurrence.max),
+                        what,
+                    ));
+                }
+                body.push(format!("for (key, value) in {} {{", field_name));
+                body.push(format!("    insert_entry(&mut map, {}, {}, {:?})?;", key.value("key"), value.value("value"), what));
+                body.push(String::from("}"));
+                fields.push(Field { name: field_name, rust_type: format!("Vec<({}, {})>", key.rust_type, value.rust_type) });
+            }
+        }
+        if body.is_empty() {
+            body.push(String::from("Ok(TransactionMetadatum::new_map(&MetadataMap::new()))"));
+        } else {
+            self.use_helper("insert_entry");
+            body.insert(0, String::from("let mut map = MetadataMap::new();"));
+            body.push(String::from("Ok(TransactionMetadatum::new_map(&map))"));
+        }
+        Ok((render_struct(name, &fields, false, body), contains))
+    }
+
+    // members of the array with all groups inlined
+    fn array_members(&self, entries: &'a [GroupEntry], what: &str, depth: usize, members: &mut Vec<(Occurrence, Option<&'a SchemaType>, &'a SchemaType)>) -> Result<(), String> {
+        if depth > MAX_GROUP_DEPTH {
+            return Err(format!("{}: groups nested too deep", what));
+        }
+        for entry in entries {
+            if let Some(group) = self.schema.entry_group(entry) {
+                match group.0.as_slice() {
+                    [entries] if entry.occurrence() == ONCE => self.array_members(entries, what, depth + 1, members)?,
+                    [_] => return Err(format!("{}: groups inside arrays can only occur once", what)),
+                    _ => return Err(format!("{}: group choices (//) are not supported", what)),
+                }
+                continue;
+            }
+            if let GroupEntry::Member { occurrence, key, value } = entry {
+                members.push((*occurrence, key.as_ref(), value));
+            }
+        }
+        Ok(())
+    }
+
+    fn define_array(&mut self, name: &str, group: &'a Group, what: &str) -> Result<(String, Vec<String>), String> {
+        let entries = match group.0.as_slice() {
+            [entries] => entries,
+            _ => return Err(format!("{}: group choices (//) are not supported", what)),
+        };
+        let mut members = Vec::new();
+        self.array_members(entries, what, 0, &mut members)?;
+        let mut field_names = BTreeSet::new();
+        let mut fields = Vec::new();
+        let mut named = false;
+        let mut body = Vec::new();
+        let mut contains = Vec::new();
+        for (i, (occurrence, key, value)) in members.into_iter().enumerate() {
+            let member_name = match (key, value) {
+                (Some(SchemaType::TextValue(key)), _) => Some(key.clone()),
+                (_, SchemaType::Ref(rule)) => Some(rule.clone()),
+                _ => None,
+            };
+            named |= member_name.is_some();
+            let field_name = unique(member_name.as_deref().map(snake_case).unwrap_or_else(|| format!("field_{}", i)), &mut field_names, "_");
+            let field_what = format!("{}[{}]", what, i);
+            if value.is_literal() {
+                let value = literal_value(value, &field_what)?;
+                match (occurrence.min, occurrence.max) {
+                    (1, 1) => body.push(format!("list.add(&{});", value)),
+                    (0, 1) => {
+                        body.push(format!("if *{} {{", field_name));
+                        body.push(format!("    list.add(&{});", value));
+                        body.push(String::from("}"));
+                        fields.push(Field { name: field_name, rust_type: String::from("bool") });
+                    },
+                    _ => return Err(format!("{}: literals can only occur once", field_what)),
+                }
+                continue;
+            }
+            let type_name = format!("{}{}", name, member_name.as_deref().map(|name| camel_case(name, "Field")).unwrap_or_else(|| format!("Field{}", i)));
+            let encoding = self.encoding(value, &type_name, &field_what)?;
+            match (occurrence.min, occurrence.max) {
+                (1, 1) => {
+                    body.push(format!("list.add(&{});", encoding.value(&field_name)));
+                    fields.push(Field { name: field_name, rust_type: encoding.rust_type });
+                    contains.extend(encoding.contains);
+                },
+                (0, 1) => {
+                    body.push(format!("if let Some({}) = {} {{", field_name, field_name));
+                    body.push(format!("    list.add(&{});", encoding.value(&field_name)));
+                    body.push(String::from("}"));
+                    fields.push(Field { name: field_name, rust_type: format!("Option<{}>", encoding.rust_type) });
+                    contains.extend(encoding.contains);
+                },
+                (min, max) => {
+                    if min > 0 || max < usize::MAX {
+                        self.use_helper("check_count");
+                        body.push(format!("check_count({}.len(), {}, {}, {:?})?;", field_name, min, len_bound(max), field_what));
+                    }
+                    body.push(format!("for item in {} {{", field_name));
+                    body.push(format!("    list.add(&{});", encoding.value("item")));
+                    body.push(String::from("}"));
+                    fields.push(Field { name: field_name, rust_type: format!("Vec<{}>", encoding.rust_type) });
+                },
+            }
+        }
+        if body.is_empty() {
+            body.push(String::from("Ok(TransactionMetadatum::new_list(&MetadataList::new()))"));
+        } else {
+            body.insert(0, String::from("let mut list = MetadataList::new();"));
+            body.push(String::from("Ok(TransactionMetadatum::new_list(&list))"));
+        }
+        Ok((render_struct(name, &fields, !named, body), contains))
+    }
+}
+
+impl MetadataSchema {
+    /// Generates Rust source with a type for each type rule of the schema. Their `to_metadatum` fails
+    /// instead of encoding values the schema doesn't allow (sizes, ranges, occurrences and duplicate keys),
+    /// so a dApp can be sure the metadata it attaches matches its published schema
+    ///
+    /// The generated code refers to this crate as `cardano_multiplatform_lib`.
+    /// Integers are `u64` where the schema doesn't allow negative values and `i64` otherwise
+    pub fn generate_rust(&self) -> Result<String, Error> {
+        Generator::new(self).generate().map_err(|e| Error::from_str(&e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+        metadata = { * policy_id => { * asset_name => details } }
+        policy_id = text .size (56..56)
+        asset_name = text
+        details = {
+            name: text,
+            image: text / [* text],
+            ? description: text,
+            ? files: [* file_entry],
+            ? votes: votes,
+            ? 1: bytes .size (0..64),
+            * text => any,
+        }
+        file = ( src: text, mediaType: text )
+        file_entry = { file }
+        votes = [ 1*3 vote, ? comment ]
+        vote = [ proposal: uint, choice: 0..2 / "abstain" ]
+        comment = text .size (1..16)
+    "#;
+
+    // output of generate_rust for SCHEMA, compiled with the tests
+    #[allow(dead_code)]
+    mod generated {
+        use crate as cardano_multiplatform_lib;
+        include!("test_generated.rs");
+    }
+
+    #[test]
+    fn generated_source() {
+        let source = MetadataSchema::from_cddl(SCHEMA).unwrap().generate_rust().unwrap();
+        assert_eq!(source, include_str!("test_generated.rs"));
+    }
+
+    #[test]
+    fn generated_encoders() {
+        use generated::*;
+        let schema = MetadataSchema::from_cddl(SCHEMA).unwrap();
+        let vote = |proposal: u64, choice: VoteChoice| Vote { proposal, choice };
+        let details = Details {
+            name: String::from("NFT 0"),
+            image: DetailsImage::Array(vec![String::from("ipfs://"), String::from("Qm")]),
+            description: None,
+            files: Some(vec![FileEntry { src: String::from("ipfs://Qm"), media_type: String::from("image/png") }]),
+            votes: Some(Votes { vote: vec![vote(1, VoteChoice::Abstain), vote(2, VoteChoice::Range(2))], comment: None }),
+            key_1: Some(vec![1, 2, 3]),
+            entries: vec![(String::from("edition"), TransactionMetadatum::new_int(&Int::new_i32(3)))],
+        };
+        let nft = |details: Details| Metadata(vec![(PolicyId("ab".repeat(28)), vec![(AssetName(String::from("nft0")), details)])]);
+        let metadatum = nft(details.clone()).to_metadatum().unwrap();
+        assert!(schema.validate(&metadatum).is_empty());
+        let encoded = metadatum.as_map().unwrap().get_str(&"ab".repeat(28)).unwrap().as_map().unwrap().get_str("nft0").unwrap().as_map().unwrap();
+        assert_eq!(encoded.get_i32(1).unwrap().as_bytes().unwrap(), vec![1, 2, 3]);
+        assert_eq!(encoded.get_str("edition").unwrap().as_int().unwrap().as_i32_or_fail().unwrap(), 3);
+
+        // whatever the types can't enforce is checked while encoding
+        let err = Metadata(vec![(PolicyId(String::from("abcd")), Vec::new())]).to_metadatum().unwrap_err();
+        assert_eq!(err.as_string().unwrap(), "policy_id: expected a length in 56..56, found 4");
+        let invalid = Details { votes: Some(Votes { vote: vec![vote(1, VoteChoice::Range(3))], comment: None }), ..details.clone() };
+        assert_eq!(nft(invalid).to_metadatum().unwrap_err().as_string().unwrap(), "vote[1]: expected 0..2, found 3");
+        let invalid = Details { votes: Some(Votes { vote: Vec::new(), comment: Some(Comment(String::from("yes"))) }), ..details.clone() };
+        assert_eq!(nft(invalid).to_metadatum().unwrap_err().as_string().unwrap(), "votes[0]: expected 1..3 entries, found 0");
+        let mut invalid = details;
+        invalid.entries.push((String::from("name"), TransactionMetadatum::new_int(&Int::new_i32(0))));
+        assert_eq!(nft(invalid).to_metadatum().unwrap_err().as_string().unwrap(), "details: duplicate key");
+    }
+
+    #[test]
+    fn generate_errors() {
+        let generate = |cddl: &str| MetadataSchema::from_cddl(cddl).unwrap().generate_rust();
+        assert!(generate("a = { x: int // y: text }").is_err());
+        assert!(generate("a = [* pair]\npair = (x: int, y: int)").is_err());
+        assert!(generate("a = [* \"a\"]").is_err());
+        // would be a type of infinite size
+        assert!(generate("tree = int / [tree, tree]").is_err());
+        assert!(generate("tree = int / [* tree] / { * text => tree }").is_ok());
+        assert!(generate("a = { ? next: b }\nb = [a]").is_err());
+    }
+}
@@ -0,0 +1,1316 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use linked_hash_map::LinkedHashMap;
+use serde_json::Value;
+use super::*;
+use super::error::Error;
+
+mod codegen;
+
+// Validation of metadata against a CDDL schema.
+// Only the subset of CDDL that maps onto transaction metadata is supported (the same subset
+// tools/metadata-cddl-checker accepts): uint/nint/int/text/bytes, literals, ranges, .size,
+// maps, arrays, type choices, group choices, occurrences and named groups.
+// Floats, bools, null, tags, sockets and other control operators are rejected when parsing.
+// Typed encoders (Rust types generated from the schema) are in codegen.rs.
+
+const MAX_LITERAL_LEN: usize = 64;
+// guards against groups that (directly or indirectly) contain themselves
+const MAX_GROUP_DEPTH: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SchemaType {
+    Any,
+    Uint,
+    Nint,
+    Int,
+    Text,
+    Bytes,
+    IntValue(i128),
+    TextValue(String),
+    BytesValue(Vec<u8>),
+    // inclusive on both ends
+    Range(i128, i128),
+    // byte length bounds of a text/bytes
+    Sized {
+        inner: Box<SchemaType>,
+        min: usize,
+        max: usize,
+    },
+    Ref(String),
+    Map(Group),
+    Array(Group),
+    Choice(Vec<SchemaType>),
+}
+
+impl SchemaType {
+    fn is_literal(&self) -> bool {
+        matches!(self, SchemaType::IntValue(_) | SchemaType::TextValue(_) | SchemaType::BytesValue(_))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Occurrence {
+    min: usize,
+    // usize::MAX when unbounded
+    max: usize,
+}
+
+const ONCE: Occurrence = Occurrence { min: 1, max: 1 };
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum GroupEntry {
+    // key is ignored inside arrays
+    Member {
+        occurrence: Occurrence,
+        key: Option<SchemaType>,
+        value: SchemaType,
+    },
+    Group {
+        occurrence: Occurrence,
+        group: Group,
+    },
+}
+
+impl GroupEntry {
+    fn occurrence(&self) -> Occurrence {
+        match self {
+            GroupEntry::Member { occurrence, .. } |
+            GroupEntry::Group { occurrence, .. } => *occurrence,
+        }
+    }
+}
+
+// group choices (//), each one a sequence of entries
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Group(Vec<Vec<GroupEntry>>);
+
+impl Group {
+    // `name = (type)` is a parenthesized type rather than a group
+    fn as_plain_type(&self) -> Option<&SchemaType> {
+        match self.0.as_slice() {
+            [entries] => match entries.as_slice() {
+                [GroupEntry::Member { occurrence: ONCE, key: None, value }] => Some(value),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Rule {
+    Type(SchemaType),
+    Group(Group),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Int(i128),
+    Text(String),
+    Bytes(Vec<u8>),
+    // control operator e.g. .size
+    Control(String),
+    Punct(&'static str),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(f, "{}", ident),
+            Token::Int(x) => write!(f, "{}", x),
+            Token::Text(s) => write!(f, "\"{}\"", s),
+            Token::Bytes(bytes) => write!(f, "h'{}'", hex::encode(bytes)),
+            Token::Control(op) => write!(f, ".{}", op),
+            Token::Punct(p) => write!(f, "'{}'", p),
+        }
+    }
+}
+
+// longest first so that e.g. "//" is not read as two "/"
+const PUNCTUATION: [&str; 22] = [
+    "...", "//=", "=>", "//", "/=", "..", "=", "{", "}", "[", "]", "(", ")", ",", ":", "/", "?", "*", "+", "~", "#", "&",
+];
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '@' || c == '_' || c == '$'
+}
+
+fn is_ident_char(c: char) -> bool {
+    is_ident_start(c) || c.is_ascii_digit() || c == '-'
+}
+
+fn tokenize(cddl: &str) -> Result<Vec<Token>, String> {
+    let chars = cddl.chars().collect::<Vec<char>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == ';' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '.' && matches!(next, Some(n) if n.is_ascii_alphabetic()) {
+            let start = i + 1;
+            i = start;
+            while i < chars.len() && is_ident_char(chars[i]) {
+                i += 1;
+            }
+            tokens.push(Token::Control(chars[start..i].iter().collect()));
+        } else if c.is_ascii_digit() || (c == '-' && matches!(next, Some(n) if n.is_ascii_digit())) {
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric()) {
+                i += 1;
+            }
+            if i + 1 < chars.len() && chars[i] == '.' && chars[i + 1].is_ascii_digit() {
+                return Err(String::from("floats are not allowed in metadata"));
+            }
+            let literal = chars[start..i].iter().collect::<String>();
+            let (negative, digits) = match literal.strip_prefix('-') {
+                Some(digits) => (true, digits),
+                None => (false, literal.as_str()),
+            };
+            let parsed = match digits.strip_prefix("0x") {
+                Some(hex_digits) => i128::from_str_radix(hex_digits, 16),
+                None => digits.parse::<i128>(),
+            };
+            let x = parsed.map_err(|_| format!("invalid integer: {}", literal))?;
+            tokens.push(Token::Int(if negative { -x } else { x }));
+        } else if c == '"' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err(String::from("unterminated text string")),
+                    Some('"') => break,
+                    Some('\\') => {
+                        text.push(*chars.get(i + 1).ok_or("unterminated text string")?);
+                        i += 2;
+                    },
+                    Some(c) => {
+                        text.push(*c);
+                        i += 1;
+                    },
+                }
+            }
+            i += 1;
+            tokens.push(Token::Text(text));
+        } else if c == '\'' || (c == 'h' && next == Some('\'')) {
+            let is_hex = c == 'h';
+            let start = if is_hex { i + 2 } else { i + 1 };
+            let end = (start..chars.len())
+                .find(|j| chars[*j] == '\'')
+                .ok_or("unterminated byte string")?;
+            let contents = chars[start..end].iter().collect::<String>();
+            let bytes = if is_hex {
+                let digits = contents.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+                hex::decode(&digits).map_err(|_| format!("invalid hex byte string: h'{}'", contents))?
+            } else {
+                contents.into_bytes()
+            };
+            i = end + 1;
+            tokens.push(Token::Bytes(bytes));
+        } else if is_ident_start(c) {
+            let start = i;
+            while i < chars.len() && is_ident_char(chars[i]) {
+                i += 1;
+            }
+            if chars.get(i) == Some(&'\'') {
+                return Err(format!("unsupported byte string prefix: {}", chars[start..i].iter().collect::<String>()));
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest = chars[i..std::cmp::min(i + 3, chars.len())].iter().collect::<String>();
+            let punct = PUNCTUATION
+                .iter()
+                .find(|p| rest.starts_with(*p))
+                .ok_or_else(|| format!("unexpected character '{}'", c))?;
+            i += punct.len();
+            tokens.push(Token::Punct(punct));
+        }
+    }
+    Ok(tokens)
+}
+
+fn literal_text(text: String) -> Result<SchemaType, String> {
+    if text.len() > MAX_LITERAL_LEN {
+        return Err(format!("literal len too big: {}, limit is {}", text.len(), MAX_LITERAL_LEN));
+    }
+    Ok(SchemaType::TextValue(text))
+}
+
+fn literal_bytes(bytes: Vec<u8>) -> Result<SchemaType, String> {
+    if bytes.len() > MAX_LITERAL_LEN {
+        return Err(format!("literal len too big: {}, limit is {}", bytes.len(), MAX_LITERAL_LEN));
+    }
+    Ok(SchemaType::BytesValue(bytes))
+}
+
+fn prelude_type(name: String) -> Result<SchemaType, String> {
+    Ok(match name.as_str() {
+        "any" => SchemaType::Any,
+        "uint" => SchemaType::Uint,
+        "nint" => SchemaType::Nint,
+        "int" => SchemaType::Int,
+        "text" | "tstr" => SchemaType::Text,
+        "bytes" | "bstr" => SchemaType::Bytes,
+        // non-standard types used by cddl-codegen
+        "u32" => SchemaType::Range(0, u32::MAX.into()),
+        "u64" => SchemaType::Range(0, u64::MAX.into()),
+        "i32" => SchemaType::Range(i32::MIN.into(), i32::MAX.into()),
+        "i64" => SchemaType::Range(i64::MIN.into(), i64::MAX.into()),
+        "bool" | "float" | "float16" | "float32" | "float64" | "float16-32" | "float32-64" | "tdate" |
+        "time" | "number" | "biguint" | "bignint" | "bigint" | "integer" | "unsigned" | "decfrac" |
+        "bigfloat" | "eb64url" | "eb64legacy" | "eb16" | "encoded-cbor" | "uri" | "b64url" | "b64legacy" |
+        "regexp" | "mime-message" | "cbor-any" | "null" | "nil" | "undefined" | "true" | "false" => {
+            return Err(format!("invalid standard prelude type: {}", name))
+        },
+        _ => SchemaType::Ref(name),
+    })
+}
+
+fn apply_size(ty: SchemaType, size: SchemaType) -> Result<SchemaType, String> {
+    let (min, max) = match size {
+        SchemaType::IntValue(n) if n >= 0 => (n, n),
+        SchemaType::Range(min, max) if min >= 0 => (min, max),
+        _ => return Err(String::from(".size expects a length or a range of lengths")),
+    };
+    match ty {
+        SchemaType::Text | SchemaType::Bytes => {
+            let to_bound = |n: i128| usize::try_from(n).map_err(|_| format!("invalid .size bound: {}", n));
+            Ok(SchemaType::Sized {
+                inner: Box::new(ty),
+                min: to_bound(min)?,
+                max: to_bound(max)?,
+            })
+        },
+        // size in bytes of the unsigned integer
+        SchemaType::Uint if min == max && max <= 8 => Ok(SchemaType::Range(0, (1i128 << (8 * max)) - 1)),
+        _ => Err(String::from(".size is only supported on text, bytes and uint")),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn is_punct(&self, offset: usize, punct: &str) -> bool {
+        matches!(self.tokens.get(self.pos + offset), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self.peek().cloned().ok_or("unexpected end of input")?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        let found = self.is_punct(0, punct);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            match self.peek() {
+                Some(token) => Err(format!("expected '{}', found {}", punct, token)),
+                None => Err(format!("expected '{}', found end of input", punct)),
+            }
+        }
+    }
+
+    fn parse_rules(&mut self) -> Result<LinkedHashMap<String, Rule>, String> {
+        let mut rules = LinkedHashMap::new();
+        while self.peek().is_some() {
+            let name = match self.next()? {
+                Token::Ident(name) => name,
+                other => return Err(format!("expected a rule name, found {}", other)),
+            };
+            if self.is_punct(0, "/=") || self.is_punct(0, "//=") {
+                return Err(format!("rule {}: choice extensions (/= and //=) are not supported", name));
+            }
+            let rule = self.expect("=")
+                .and_then(|_| self.parse_rule_body())
+                .map_err(|e| format!("rule {}: {}", name, e))?;
+            if rules.insert(name.clone(), rule).is_some() {
+                return Err(format!("rule {} is defined more than once", name));
+            }
+        }
+        if rules.is_empty() {
+            return Err(String::from("no rules found"));
+        }
+        Ok(rules)
+    }
+
+    fn parse_rule_body(&mut self) -> Result<Rule, String> {
+        if self.is_punct(0, "(") {
+            let start = self.pos;
+            self.pos += 1;
+            match self.parse_group(")") {
+                Ok(group) if group.as_plain_type().is_none() => return Ok(Rule::Group(group)),
+                // parenthesized type, possibly followed by more type choices
+                _ => self.pos = start,
+            }
+        }
+        Ok(Rule::Type(self.parse_type()?))
+    }
+
+    fn parse_group(&mut self, closing: &str) -> Result<Group, String> {
+        let mut choices = vec![Vec::new()];
+        while !self.eat(closing) {
+            if self.eat("//") {
+                choices.push(Vec::new());
+                continue;
+            }
+            let entry = self.parse_group_entry()?;
+            choices.last_mut().unwrap().push(entry);
+            self.eat(",");
+        }
+        Ok(Group(choices))
+    }
+
+    fn parse_group_entry(&mut self) -> Result<GroupEntry, String> {
+        let occurrence = self.parse_occurrence()?;
+        if self.eat("(") {
+            let group = self.parse_group(")")?;
+            return Ok(GroupEntry::Group { occurrence, group });
+        }
+        if self.is_punct(1, ":") {
+            let key = match self.peek().cloned() {
+                Some(Token::Ident(name)) => Some(literal_text(name)?),
+                Some(Token::Text(text)) => Some(literal_text(text)?),
+                Some(Token::Bytes(bytes)) => Some(literal_bytes(bytes)?),
+                Some(Token::Int(x)) => Some(SchemaType::IntValue(x)),
+                _ => None,
+            };
+            if let Some(key) = key {
+                self.pos += 2;
+                let value = self.parse_type()?;
+                return Ok(GroupEntry::Member { occurrence, key: Some(key), value });
+            }
+        }
+        let first = self.parse_type1()?;
+        if self.eat("=>") {
+            let value = self.parse_type()?;
+            return Ok(GroupEntry::Member { occurrence, key: Some(first), value });
+        }
+        let value = self.parse_type_choices(first)?;
+        Ok(GroupEntry::Member { occurrence, key: None, value })
+    }
+
+    fn parse_occurrence(&mut self) -> Result<Occurrence, String> {
+        if self.eat("?") {
+            return Ok(Occurrence { min: 0, max: 1 });
+        }
+        if self.eat("+") {
+            return Ok(Occurrence { min: 1, max: usize::MAX });
+        }
+        let min = match self.peek() {
+            Some(Token::Int(n)) if self.is_punct(1, "*") => {
+                let n = *n;
+                self.pos += 1;
+                Some(n)
+            },
+            _ => None,
+        };
+        if min.is_none() && !self.is_punct(0, "*") {
+            return Ok(ONCE);
+        }
+        self.expect("*")?;
+        // in `* 1: uint` the integer is a key and not an upper bound
+        let max = match self.peek() {
+            Some(Token::Int(n)) if !self.is_punct(1, ":") && !self.is_punct(1, "=>") => {
+                let n = *n;
+                self.pos += 1;
+                Some(n)
+            },
+            _ => None,
+        };
+        let to_bound = |n: i128| usize::try_from(n).map_err(|_| format!("invalid occurrence bound: {}", n));
+        let occurrence = Occurrence {
+            min: min.map(to_bound).transpose()?.unwrap_or(0),
+            max: max.map(to_bound).transpose()?.unwrap_or(usize::MAX),
+        };
+        if occurrence.min > occurrence.max {
+            return Err(format!("invalid occurrence {}*{}", occurrence.min, occurrence.max));
+        }
+        Ok(occurrence)
+    }
+
+    fn parse_type(&mut self) -> Result<SchemaType, String> {
+        let first = self.parse_type1()?;
+        self.parse_type_choices(first)
+    }
+
+    fn parse_type_choices(&mut self, first: SchemaType) -> Result<SchemaType, String> {
+        let mut choices = vec![first];
+        while self.eat("/") {
+            choices.push(self.parse_type1()?);
+        }
+        Ok(match choices.len() {
+            1 => choices.remove(0),
+            _ => SchemaType::Choice(choices),
+        })
+    }
+
+    fn parse_type1(&mut self) -> Result<SchemaType, String> {
+        let ty = self.parse_type2()?;
+        let inclusive = self.eat("..");
+        let ty = if inclusive || self.eat("...") {
+            let bound = |ty: SchemaType| match ty {
+                SchemaType::IntValue(x) => Ok(x),
+                other => Err(format!("range bounds must be integers, found {:?}", other)),
+            };
+            let min = bound(ty)?;
+            let max = bound(self.parse_type2()?)?;
+            let max = if inclusive { max } else { max - 1 };
+            if min > max {
+                return Err(format!("empty range {}..{}", min, max));
+            }
+            SchemaType::Range(min, max)
+        } else {
+            ty
+        };
+        match self.peek() {
+            Some(Token::Control(op)) if op == "size" => {
+                self.pos += 1;
+                let size = self.parse_type2()?;
+                apply_size(ty, size)
+            },
+            Some(Token::Control(op)) => Err(format!("control operator .{} is not supported", op)),
+            _ => Ok(ty),
+        }
+    }
+
+    fn parse_type2(&mut self) -> Result<SchemaType, String> {
+        match self.next()? {
+            Token::Int(x) => Ok(SchemaType::IntValue(x)),
+            Token::Text(text) => literal_text(text),
+            Token::Bytes(bytes) => literal_bytes(bytes),
+            Token::Ident(name) => prelude_type(name),
+            Token::Punct("{") => Ok(SchemaType::Map(self.parse_group("}")?)),
+            Token::Punct("[") => Ok(SchemaType::Array(self.parse_group("]")?)),
+            Token::Punct("(") => {
+                let ty = self.parse_type()?;
+                self.expect(")")?;
+                Ok(ty)
+            },
+            Token::Punct("#") => Err(String::from("tagged data is not allowed in metadata")),
+            other => Err(format!("unexpected {}", other)),
+        }
+    }
+}
+
+/// A problem found while validating metadata against a `MetadataSchema`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetadataSchemaError {
+    path: String,
+    message: String,
+}
+
+impl MetadataSchemaError {
+    fn new<P: Into<String>, M: Into<String>>(path: P, message: M) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Location of the offending entry. `$` is the validated value, `.key` a map entry and `[i]` an array element
+    /// e.g. `$.files[0].src`. Integer keys are written as `.1` and byte keys as `.0x<hex>`
+    pub fn path(&self) -> String {
+        self.path.clone()
+    }
+
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+impl std::fmt::Display for MetadataSchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MetadataSchemaErrors(Vec<MetadataSchemaError>);
+
+vec_iter!(MetadataSchemaErrors, MetadataSchemaError);
+
+impl MetadataSchemaErrors {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> MetadataSchemaError {
+        self.0[index].clone()
+    }
+}
+
+impl std::fmt::Display for MetadataSchemaErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors = self.0.iter().map(|e| e.to_string()).collect::<Vec<String>>();
+        write!(f, "{}", errors.join("; "))
+    }
+}
+
+type Positions<T> = BTreeMap<usize, Vec<T>>;
+// checks or encodes the array element at an index as the given type
+type ItemMatcher<'a, T> = dyn Fn(&SchemaType, usize) -> Result<T, Vec<MetadataSchemaError>> + 'a;
+
+// entry of a map group after inlining all nested/named groups
+struct MapEntry<'a> {
+    occurrence: Occurrence,
+    key: &'a SchemaType,
+    value: &'a SchemaType,
+}
+
+fn describe_value(metadatum: &TransactionMetadatum) -> String {
+    match metadatum.kind() {
+        TransactionMetadatumKind::MetadataMap => String::from("map"),
+        TransactionMetadatumKind::MetadataList => String::from("array"),
+        TransactionMetadatumKind::Int => format!("int {}", metadatum.as_int().map(|x| x.0).unwrap_or_default()),
+        TransactionMetadatumKind::Bytes => format!("bytes h'{}'", hex::encode(metadatum.as_bytes().unwrap_or_default())),
+        TransactionMetadatumKind::Text => format!("text \"{}\"", metadatum.as_text().unwrap_or_default()),
+    }
+}
+
+fn key_path(path: &str, key: &TransactionMetadatum) -> String {
+    match key.kind() {
+        TransactionMetadatumKind::Int => format!("{}.{}", path, key.as_int().map(|x| x.0).unwrap_or_default()),
+        TransactionMetadatumKind::Bytes => format!("{}.0x{}", path, hex::encode(key.as_bytes().unwrap_or_default())),
+        TransactionMetadatumKind::Text => format!("{}.{}", path, key.as_text().unwrap_or_default()),
+        _ => format!("{}.<{}>", path, describe_value(key)),
+    }
+}
+
+fn json_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn error_depth(errors: &[MetadataSchemaError]) -> usize {
+    errors.iter().map(|e| e.path.len()).max().unwrap_or(0)
+}
+
+// picks the errors of the choice that got the furthest, unless they all failed on the value itself
+fn closest_failure(failures: Vec<Vec<MetadataSchemaError>>, path: &str) -> Option<Vec<MetadataSchemaError>> {
+    failures
+        .into_iter()
+        .rev()
+        .filter(|errors| errors.iter().any(|e| e.path != path))
+        .max_by_key(|errors| error_depth(errors))
+}
+
+/// Schema for transaction metadata parsed from a CDDL definition, e.g. the one a dApp publishes for its label
+///
+/// The first rule is the root type used by `validate` and `encode_json`
+#[derive(Clone, Debug)]
+pub struct MetadataSchema {
+    rules: LinkedHashMap<String, Rule>,
+}
+
+impl MetadataSchema {
+    pub fn from_cddl(cddl: &str) -> Result<MetadataSchema, Error> {
+        let tokens = tokenize(cddl).map_err(|e| Error::from_str(&e))?;
+        let rules = Parser { tokens, pos: 0 }.parse_rules().map_err(|e| Error::from_str(&e))?;
+        let schema = Self { rules };
+        for (name, rule) in schema.rules.iter() {
+            let checked = match rule {
+                Rule::Type(ty) => schema.check_references(ty),
+                Rule::Group(group) => schema.check_group_references(group),
+            };
+            checked.map_err(|e| Error::from_str(&format!("rule {}: {}", name, e)))?;
+        }
+        for (name, rule) in schema.rules.iter() {
+            if let Rule::Type(ty) = rule {
+                let mut referenced = Vec::new();
+                schema.unguarded_references(ty, &mut referenced);
+                if referenced.contains(&name.as_str()) {
+                    return Err(Error::from_str(&format!("rule {}: refers to itself without a map or array in between", name)));
+                }
+            }
+        }
+        Ok(schema)
+    }
+
+    pub fn root_rule(&self) -> String {
+        self.rules.keys().next().cloned().unwrap_or_default()
+    }
+
+    pub fn rule_names(&self) -> Vec<String> {
+        self.rules.keys().cloned().collect()
+    }
+
+    /// Validates `metadatum` against the root rule. No errors means it matches the schema
+    pub fn validate(&self, metadatum: &TransactionMetadatum) -> MetadataSchemaErrors {
+        MetadataSchemaErrors(self.check(&SchemaType::Ref(self.root_rule()), metadatum, "$"))
+    }
+
+    pub fn validate_rule(&self, rule: &str, metadatum: &TransactionMetadatum) -> Result<MetadataSchemaErrors, Error> {
+        let ty = self.type_rule(rule)?;
+        Ok(MetadataSchemaErrors(self.check(&ty, metadatum, "$")))
+    }
+
+    /// Converts `json` to metadata with `encode_json_str_to_metadatum` and validates the result against the root rule
+    pub fn validate_json(&self, json: &str, schema: MetadataJsonSchema) -> Result<MetadataSchemaErrors, Error> {
+        let metadatum = encode_json_str_to_metadatum(json.to_owned(), schema)?;
+        Ok(self.validate(&metadatum))
+    }
+
+    /// Encodes JSON for the root rule, with the schema deciding how each JSON value is encoded
+    /// (e.g. a JSON string becomes bytes where the schema expects `bytes`, and object keys become integers
+    /// where it expects integer keys). Fails unless the result matches the schema
+    ///
+    /// Bytes are given as hex strings with or without a 0x prefix
+    pub fn encode_json(&self, json: &str) -> Result<TransactionMetadatum, Error> {
+        self.encode_json_rule(&self.root_rule(), json)
+    }
+
+    pub fn encode_json_rule(&self, rule: &str, json: &str) -> Result<TransactionMetadatum, Error> {
+        let ty = self.type_rule(rule)?;
+        let value = serde_json::from_str(json).map_err(|e| Error::from_str(&e.to_string()))?;
+        self.encode(&ty, &value, "$")
+            .map_err(|errors| Error::from_str(&MetadataSchemaErrors(errors).to_string()))
+    }
+
+    fn type_rule(&self, rule: &str) -> Result<SchemaType, Error> {
+        match self.rules.get(rule) {
+            Some(Rule::Type(_)) => Ok(SchemaType::Ref(rule.to_owned())),
+            Some(Rule::Group(_)) => Err(Error::from_str(&format!("{} is a group and not a type", rule))),
+            None => Err(Error::from_str(&format!("no rule named {}", rule))),
+        }
+    }
+
+    fn group_rule(&self, name: &str) -> Option<&Group> {
+        match self.rules.get(name) {
+            Some(Rule::Group(group)) => Some(group),
+            _ => None,
+        }
+    }
+
+    // nested group or reference to a named group
+    fn entry_group<'a>(&'a self, entry: &'a GroupEntry) -> Option<&'a Group> {
+        match entry {
+            GroupEntry::Group { group, .. } => Some(group),
+            GroupEntry::Member { key: None, value: SchemaType::Ref(name), .. } => self.group_rule(name),
+            GroupEntry::Member { .. } => None,
+        }
+    }
+
+    fn check_references(&self, ty: &SchemaType) -> Result<(), String> {
+        match ty {
+            SchemaType::Ref(name) => match self.rules.get(name) {
+                Some(Rule::Type(_)) => Ok(()),
+                Some(Rule::Group(_)) => Err(format!("group {} used as a type", name)),
+                None => Err(format!("undefined rule {}", name)),
+            },
+            SchemaType::Sized { inner, .. } => self.check_references(inner),
+            SchemaType::Map(group) | SchemaType::Array(group) => self.check_group_references(group),
+            SchemaType::Choice(choices) => choices.iter().try_for_each(|ty| self.check_references(ty)),
+            _ => Ok(()),
+        }
+    }
+
+    // rules `ty` refers to without a map or array in between. check and encode would never get
+    // any further into the metadata when following them back to the same rule
+    fn unguarded_references<'a>(&'a self, ty: &'a SchemaType, referenced: &mut Vec<&'a str>) {
+        match ty {
+            SchemaType::Ref(name) if !referenced.contains(&name.as_str()) => {
+                referenced.push(name);
+                if let Some(Rule::Type(ty)) = self.rules.get(name) {
+                    self.unguarded_references(ty, referenced);
+                }
+            },
+            SchemaType::Sized { inner, .. } => self.unguarded_references(inner, referenced),
+            SchemaType::Choice(choices) => choices.iter().for_each(|ty| self.unguarded_references(ty, referenced)),
+            _ => (),
+        }
+    }
+
+    fn check_group_references(&self, group: &Group) -> Result<(), String> {
+        for entry in group.0.iter().flatten() {
+            match entry {
+                GroupEntry::Group { group, .. } => self.check_group_references(group)?,
+                GroupEntry::Member { key: None, value: SchemaType::Ref(name), .. } if self.group_rule(name).is_some() => (),
+                GroupEntry::Member { key, value, .. } => {
+                    if let Some(key) = key {
+                        self.check_references(key)?;
+                    }
+                    self.check_references(value)?;
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn describe(&self, ty: &SchemaType) -> String {
+        match ty {
+            SchemaType::Any => String::from("any"),
+            SchemaType::Uint => String::from("uint"),
+            SchemaType::Nint => String::from("nint"),
+            SchemaType::Int => String::from("int"),
+            SchemaType::Text => String::from("text"),
+            SchemaType::Bytes => String::from("bytes"),
+            SchemaType::IntValue(x) => x.to_string(),
+            SchemaType::TextValue(text) => format!("\"{}\"", text),
+            SchemaType::BytesValue(bytes) => format!("h'{}'", hex::encode(bytes)),
+            SchemaType::Range(min, max) => format!("{}..{}", min, max),
+            SchemaType::Sized { inner, min, max } => format!("{} .size ({}..{})", self.describe(inner), min, max),
+            SchemaType::Ref(name) => name.clone(),
+            SchemaType::Map(_) => String::from("map"),
+            SchemaType::Array(_) => String::from("array"),
+            SchemaType::Choice(choices) => choices.iter().map(|ty| self.describe(ty)).collect::<Vec<String>>().join(" / "),
+        }
+    }
+
+    fn mismatch(&self, ty: &SchemaType, metadatum: &TransactionMetadatum, path: &str) -> Vec<MetadataSchemaError> {
+        vec![MetadataSchemaError::new(path, format!("expected {}, found {}", self.describe(ty), describe_value(metadatum)))]
+    }
+
+    fn check(&self, ty: &SchemaType, metadatum: &TransactionMetadatum, path: &str) -> Vec<MetadataSchemaError> {
+        let int = metadatum.as_int().ok().map(|x| x.0);
+        let matches = match ty {
+            SchemaType::Any => true,
+            SchemaType::Uint => matches!(int, Some(x) if x >= 0),
+            SchemaType::Nint => matches!(int, Some(x) if x < 0),
+            SchemaType::Int => int.is_some(),
+            SchemaType::Text => metadatum.kind() == TransactionMetadatumKind::Text,
+            SchemaType::Bytes => metadatum.kind() == TransactionMetadatumKind::Bytes,
+            SchemaType::IntValue(expected) => int == Some(*expected),
+            SchemaType::TextValue(expected) => metadatum.as_text().ok().as_ref() == Some(expected),
+            SchemaType::BytesValue(expected) => metadatum.as_bytes().ok().as_ref() == Some(expected),
+            SchemaType::Range(min, max) => matches!(int, Some(x) if *min <= x && x <= *max),
+            SchemaType::Sized { inner, min, max } => {
+                let errors = self.check(inner, metadatum, path);
+                if !errors.is_empty() {
+                    return errors;
+                }
+                let len = match metadatum.kind() {
+                    TransactionMetadatumKind::Text => metadatum.as_text().map(|text| text.len()).unwrap_or_default(),
+                    _ => metadatum.as_bytes().map(|bytes| bytes.len()).unwrap_or_default(),
+                };
+                if len < *min || len > *max {
+                    return vec![MetadataSchemaError::new(path, format!("expected a length in {}..{}, found {}", min, max, len))];
+                }
+                true
+            },
+            SchemaType::Ref(name) => return match self.rules.get(name) {
+                Some(Rule::Type(ty)) => self.check(ty, metadatum, path),
+                _ => vec![MetadataSchemaError::new(path, format!("{} is not a type", name))],
+            },
+            SchemaType::Map(group) => return match metadatum.as_map() {
+                Ok(map) => self.check_map(group, &map, path),
+                Err(_) => self.mismatch(ty, metadatum, path),
+            },
+            SchemaType::Array(group) => return match metadatum.as_list() {
+                Ok(list) => {
+                    let items = (0..list.len()).map(|i| list.get(i)).collect::<Vec<_>>();
+                    let check_item = |ty: &SchemaType, i: usize| {
+                        let errors = self.check(ty, &items[i], &format!("{}[{}]", path, i));
+                        if errors.is_empty() { Ok(()) } else { Err(errors) }
+                    };
+                    self.match_sequence(group, items.len(), &check_item, path).err().unwrap_or_default()
+                },
+                Err(_) => self.mismatch(ty, metadatum, path),
+            },
+            SchemaType::Choice(choices) => {
+                let mut failures = Vec::new();
+                for choice in choices {
+                    let errors = self.check(choice, metadatum, path);
+                    if errors.is_empty() {
+                        return errors;
+                    }
+                    failures.push(errors);
+                }
+                return closest_failure(failures, path).unwrap_or_else(|| self.mismatch(ty, metadatum, path));
+            },
+        };
+        if matches {
+            Vec::new()
+        } else {
+            self.mismatch(ty, metadatum, path)
+        }
+    }
+
+    fn flatten_map_entries<'a>(&'a self, entries: &'a [GroupEntry], optional: bool, depth: usize) -> Result<Vec<MapEntry<'a>>, String> {
+        if depth > MAX_GROUP_DEPTH {
+            return Err(String::from("groups nested too deep"));
+        }
+        let mut flattened = Vec::new();
+        for entry in entries {
+            let mut occurrence = entry.occurrence();
+            if optional {
+                occurrence.min = 0;
+            }
+            if let Some(group) = self.entry_group(entry) {
+                match group.0.as_slice() {
+                    // entries of an optional group are all optional
+                    [entries] => flattened.extend(self.flatten_map_entries(entries, occurrence.min == 0, depth + 1)?),
+                    _ => return Err(String::from("group choices nested inside maps are not supported")),
+                }
+                continue;
+            }
+            match entry {
+                GroupEntry::Member { key: Some(key), value, .. } => flattened.push(MapEntry { occurrence, key, value }),
+                GroupEntry::Member { value, .. } => return Err(format!("map entry {} has no key", self.describe(value))),
+                GroupEntry::Group { .. } => (),
+            }
+        }
+        Ok(flattened)
+    }
+
+    fn check_map(&self, group: &Group, map: &MetadataMap, path: &str) -> Vec<MetadataSchemaError> {
+        let mut failures = Vec::new();
+        for entries in group.0.iter() {
+            let errors = match self.flatten_map_entries(entries, false, 0) {
+                Ok(entries) => self.check_map_entries(&entries, map, path),
+                Err(e) => vec![MetadataSchemaError::new(path, e)],
+            };
+            if errors.is_empty() {
+                return errors;
+            }
+            failures.push(errors);
+        }
+        failures.into_iter().min_by_key(|errors| errors.len()).unwrap_or_default()
+    }
+
+    fn check_map_entries(&self, entries: &[MapEntry], map: &MetadataMap, path: &str) -> Vec<MetadataSchemaError> {
+        let keys = map.keys();
+        let pairs = (0..keys.len())
+            .filter_map(|i| {
+                let key = keys.get(i);
+                map.get(&key).ok().map(|value| (key, value))
+            })
+            .collect::<Vec<_>>();
+        let mut consumed = vec![false; pairs.len()];
+        let mut errors = Vec::new();
+        // literal keys take precedence over `type => type` entries regardless of their order
+        for entry in entries.iter().filter(|entry| entry.key.is_literal()) {
+            match pairs.iter().position(|(key, _)| self.check(entry.key, key, path).is_empty()) {
+                Some(i) => {
+                    consumed[i] = true;
+                    let (key, value) = &pairs[i];
+                    errors.extend(self.check(entry.value, value, &key_path(path, key)));
+                },
+                None if entry.occurrence.min > 0 => {
+                    errors.push(MetadataSchemaError::new(path, format!("missing key {}", self.describe(entry.key))));
+                },
+                None => (),
+            }
+        }
+        for entry in entries.iter().filter(|entry| !entry.key.is_literal()) {
+            let mut count = 0;
+            for (i, (key, value)) in pairs.iter().enumerate() {
+                if consumed[i] || count == entry.occurrence.max || !self.check(entry.key, key, path).is_empty() {
+                    continue;
+                }
+                consumed[i] = true;
+                count += 1;
+                errors.extend(self.check(entry.value, value, &key_path(path, key)));
+            }
+            if count < entry.occurrence.min {
+                errors.push(MetadataSchemaError::new(
+                    path,
+                    format!("expected at least {} entries with {} keys, found {}", entry.occurrence.min, self.describe(entry.key), count),
+                ));
+            }
+        }
+        for (i, (key, _)) in pairs.iter().enumerate() {
+            if !consumed[i] {
+                errors.push(MetadataSchemaError::new(key_path(path, key), "unexpected key"));
+            }
+        }
+        errors
+    }
+
+    // matches the `len` elements of an array against `group`, returning the result of `item` for each element
+    fn match_sequence<T: Clone>(
+        &self,
+        group: &Group,
+        len: usize,
+        item: &ItemMatcher<T>,
+        path: &str,
+    ) -> Result<Vec<T>, Vec<MetadataSchemaError>> {
+        let mut start = Positions::new();
+        start.insert(0, Vec::new());
+        // errors of the furthest element that could not be matched
+        let mut failure = None;
+        let mut ends = self
+            .match_group(group, start, len, item, &mut failure, 0)
+            .map_err(|e| vec![MetadataSchemaError::new(path, e)])?;
+        if let Some(items) = ends.remove(&len) {
+            return Ok(items);
+        }
+        let furthest = ends.keys().next_back().copied();
+        match (failure, furthest) {
+            (Some((i, errors)), Some(furthest)) if i >= furthest => Err(errors),
+            (Some((_, errors)), None) => Err(errors),
+            (_, Some(furthest)) => Err(vec![MetadataSchemaError::new(format!("{}[{}]", path, furthest), "unexpected element")]),
+            (None, None) => Err(vec![MetadataSchemaError::new(path, format!("expected more than {} elements", len))]),
+        }
+    }
+
+    fn match_group<T: Clone>(
+        &self,
+        group: &Group,
+        start: Positions<T>,
+        len: usize,
+        item: &ItemMatcher<T>,
+        failure: &mut Option<(usize, Vec<MetadataSchemaError>)>,
+        depth: usize,
+    ) -> Result<Positions<T>, String> {
+        if depth > MAX_GROUP_DEPTH {
+            return Err(String::from("groups nested too deep"));
+        }
+        let mut ends = Positions::new();
+        for entries in group.0.iter() {
+            let mut positions = start.clone();
+            for entry in entries {
+                if positions.is_empty() {
+                    break;
+                }
+                positions = self.match_entry(entry, positions, len, item, failure, depth)?;
+            }
+            for (position, items) in positions {
+                ends.entry(position).or_insert(items);
+            }
+        }
+        Ok(ends)
+    }
+
+    fn match_entry<T: Clone>(
+        &self,
+        entry: &GroupEntry,
+        positions: Positions<T>,
+        len: usize,
+        item: &ItemMatcher<T>,
+        failure: &mut Option<(usize, Vec<MetadataSchemaError>)>,
+        depth: usize,
+    ) -> Result<Positions<T>, String> {
+        let occurrence = entry.occurrence();
+        let mut ends = if occurrence.min == 0 { positions.clone() } else { Positions::new() };
+        let mut current = positions;
+        let mut count = 0;
+        // repetitions that consume nothing can't get any further than len + 1 iterations
+        while !current.is_empty() && count < occurrence.max && count <= len {
+            current = match self.entry_group(entry) {
+                Some(group) => self.match_group(group, current, len, item, failure, depth + 1)?,
+                None => {
+                    let value = match entry {
+                        GroupEntry::Member { value, .. } => value,
+                        GroupEntry::Group { .. } => return Ok(ends),
+                    };
+                    let mut next = Positions::new();
+                    for (position, mut items) in current.into_iter().filter(|(position, _)| *position < len) {
+                        match item(value, position) {
+                            Ok(matched) => {
+                                items.push(matched);
+                                next.entry(position + 1).or_insert(items);
+                            },
+                            // on ties prefer the entry that got deeper into the element, then the one furthest along in the group
+                            Err(errors) => {
+                                let is_closer = match failure {
+                                    Some((furthest, furthest_errors)) => position > *furthest
+                                        || (position == *furthest && error_depth(&errors) >= error_depth(furthest_errors)),
+                                    None => true,
+                                };
+                                if is_closer {
+                                    *failure = Some((position, errors));
+                                }
+                            },
+                        }
+                    }
+                    next
+                },
+            };
+            count += 1;
+            if count >= occurrence.min {
+                for (position, items) in current.iter() {
+                    ends.entry(*position).or_insert_with(|| items.clone());
+                }
+            }
+        }
+        Ok(ends)
+    }
+
+    fn encode(&self, ty: &SchemaType, value: &Value, path: &str) -> Result<TransactionMetadatum, Vec<MetadataSchemaError>> {
+        let error = |message: String| vec![MetadataSchemaError::new(path, message)];
+        let from_js = |e: Error| error(e.as_string().unwrap_or_default());
+        let metadatum = match (ty, value) {
+            (SchemaType::Any, _) => encode_json_value_to_metadatum(value.clone(), MetadataJsonSchema::BasicConversions).map_err(from_js)?,
+            (SchemaType::Uint | SchemaType::Nint | SchemaType::Int | SchemaType::IntValue(_) | SchemaType::Range(..), Value::Number(x)) => {
+                let x = x.as_i64().map(i128::from)
+                    .or_else(|| x.as_u64().map(i128::from))
+                    .ok_or_else(|| error(String::from("floats are not allowed in metadata")))?;
+                TransactionMetadatum::new_int(&Int(x))
+            },
+            (SchemaType::Text | SchemaType::TextValue(_), Value::String(text)) => TransactionMetadatum::new_text(text.clone()).map_err(from_js)?,
+            (SchemaType::Bytes | SchemaType::BytesValue(_), Value::String(text)) => {
+                let bytes = hex::decode(text.strip_prefix("0x").unwrap_or(text))
+                    .map_err(|_| error(format!("expected a hex string, found \"{}\"", text)))?;
+                TransactionMetadatum::new_bytes(bytes).map_err(from_js)?
+            },
+            (SchemaType::Sized { inner, .. }, _) => self.encode(inner, value, path)?,
+            (SchemaType::Ref(name), _) => return match self.rules.get(name) {
+                Some(Rule::Type(ty)) => self.encode(ty, value, path),
+                _ => Err(error(format!("{} is not a type", name))),
+            },
+            (SchemaType::Choice(choices), _) => {
+                let mut failures = Vec::new();
+                for choice in choices {
+                    match self.encode(choice, value, path) {
+                        Ok(metadatum) => return Ok(metadatum),
+                        Err(errors) => failures.push(errors),
+                    }
+                }
+                return Err(closest_failure(failures, path)
+                    .unwrap_or_else(|| error(format!("expected {}, found JSON {}", self.describe(ty), json_kind(value)))));
+            },
+            (SchemaType::Array(group), Value::Array(values)) => {
+                let encode_item = |ty: &SchemaType, i: usize| self.encode(ty, &values[i], &format!("{}[{}]", path, i));
+                let mut list = MetadataList::new();
+                for metadatum in self.match_sequence(group, values.len(), &encode_item, path)? {
+                    list.add(&metadatum);
+                }
+                return Ok(TransactionMetadatum::new_list(&list));
+            },
+            (SchemaType::Map(group), Value::Object(object)) => {
+                let mut failures = Vec::new();
+                for entries in group.0.iter() {
+                    let encoded = self.flatten_map_entries(entries, false, 0)
+                        .map_err(error)
+                        .and_then(|entries| self.encode_map_entries(&entries, object, path));
+                    match encoded {
+                        Ok(metadatum) => return Ok(metadatum),
+                        Err(errors) => failures.push(errors),
+                    }
+                }
+                return Err(failures.into_iter().min_by_key(|errors| errors.len()).unwrap_or_default());
+            },
+            _ => return Err(error(format!("expected {}, found JSON {}", self.describe(ty), json_kind(value)))),
+        };
+        // literal values, ranges and sizes are checked on the encoded value
+        let errors = self.check(ty, &metadatum, path);
+        if errors.is_empty() {
+            Ok(metadatum)
+        } else {
+            Err(errors)
+        }
+    }
+
+    // JSON object keys are always strings so this tries every metadata type the key could be
+    fn encode_key(&self, ty: &SchemaType, key: &str) -> Option<TransactionMetadatum> {
+        let int = key.parse::<i128>().ok().map(|x| TransactionMetadatum::new_int(&Int(x)));
+        let text = TransactionMetadatum::new_text(key.to_owned()).ok();
+        let bytes = hex::decode(key.strip_prefix("0x").unwrap_or(key))
+            .ok()
+            .and_then(|bytes| TransactionMetadatum::new_bytes(bytes).ok());
+        vec![int, text, bytes]
+            .into_iter()
+            .flatten()
+            .find(|candidate| self.check(ty, candidate, "").is_empty())
+    }
+
+    fn encode_map_entries(&self, entries: &[MapEntry], object: &serde_json::Map<String, Value>, path: &str) -> Result<TransactionMetadatum, Vec<MetadataSchemaError>> {
+        let mut map = MetadataMap::new();
+        let mut errors = Vec::new();
+        let literal_entries = entries.iter().filter(|entry| entry.key.is_literal());
+        let typed_entries = entries.iter().filter(|entry| !entry.key.is_literal());
+        for (json_key, json_value) in object {
+            let entry_path = format!("{}.{}", path, json_key);
+            let mut encoded = None;
+            for entry in literal_entries.clone().chain(typed_entries.clone()) {
+                if let Some(key) = self.encode_key(entry.key, json_key) {
+                    match self.encode(entry.value, json_value, &entry_path) {
+                        Ok(value) => {
+                            encoded = Some(Ok((key, value)));
+                            break;
+                        },
+                        // keep the error of the first matching key in case no other entry accepts the value
+                        Err(e) => if encoded.is_none() {
+                            encoded = Some(Err(e));
+                        },
+                    }
+                }
+            }
+            match encoded {
+                Some(Ok((key, value))) => {
+                    map.insert(&key, &value);
+                },
+                Some(Err(e)) => errors.extend(e),
+                None => errors.push(MetadataSchemaError::new(entry_path, "unexpected key")),
+            }
+        }
+        if errors.is_empty() {
+            // enforces required keys and occurrences
+            errors = self.check_map_entries(entries, &map, path);
+        }
+        if errors.is_empty() {
+            Ok(TransactionMetadatum::new_map(&map))
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NFT_SCHEMA: &str = r#"
+        ; simplified CIP-25
+        metadata = { * policy_id => { * asset_name => details } }
+        policy_id = text .size (56..56)
+        asset_name = text
+        details = {
+            name: text,
+            image: text / [* text],
+            ? description: text,
+            ? files: [* file_entry],
+            * text => any,
+        }
+        file = ( src: text, mediaType: text )
+        file_entry = { file }
+    "#;
+
+    fn policy() -> String {
+        "ab".repeat(28)
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(MetadataSchema::from_cddl("foo = float").is_err());
+        assert!(MetadataSchema::from_cddl("foo = { a: bar }").is_err());
+        assert!(MetadataSchema::from_cddl("foo = #6.24(bytes)").is_err());
+        assert!(MetadataSchema::from_cddl("foo = text .regexp \"a+\"").is_err());
+        assert!(MetadataSchema::from_cddl(&format!("foo = \"{}\"", "a".repeat(65))).is_err());
+        // .size bounds that do not fit in a usize are rejected rather than truncated
+        assert!(MetadataSchema::from_cddl("foo = bytes .size (0..18446744073709551616)").is_err());
+        assert!(MetadataSchema::from_cddl(&format!("foo = text .size {}", i128::MAX)).is_err());
+        // references that never get into the metadata
+        assert!(MetadataSchema::from_cddl("a = b\nb = a").is_err());
+        assert!(MetadataSchema::from_cddl("a = a / int").is_err());
+        // recursion through arrays and maps is fine
+        let schema = MetadataSchema::from_cddl("tree = int / [* tree] / { * text => tree }").unwrap();
+        assert!(schema.validate(&TransactionMetadatum::from_bytes(hex::decode("82018102").unwrap()).unwrap()).is_empty());
+        let schema = MetadataSchema::from_cddl(NFT_SCHEMA).unwrap();
+        assert_eq!(schema.root_rule(), "metadata");
+        assert_eq!(schema.rule_names().len(), 6);
+    }
+
+    #[test]
+    fn validate_with_paths() {
+        let schema = MetadataSchema::from_cddl(NFT_SCHEMA).unwrap();
+        let valid = format!(
+            r#"{{"{}": {{"nft0": {{"name": "NFT 0", "image": ["ipfs://", "Qm"], "edition": 3}}}}}}"#,
+            policy(),
+        );
+        assert!(schema.validate_json(&valid, MetadataJsonSchema::NoConversions).unwrap().is_empty());
+
+        let invalid = format!(
+            r#"{{"{}": {{"nft0": {{"image": ["ipfs://", 1], "description": 7}}}}}}"#,
+            policy(),
+        );
+        let errors = schema.validate_json(&invalid, MetadataJsonSchema::NoConversions).unwrap();
+        let found = (0..errors.len()).map(|i| errors.get(i).to_string()).collect::<Vec<_>>();
+        let nft_path = format!("$.{}.nft0", policy());
+        assert_eq!(found, vec![
+            format!("{}: missing key \"name\"", nft_path),
+            format!("{}.image[1]: expected text, found int 1", nft_path),
+            format!("{}.description: expected text, found int 7", nft_path),
+        ]);
+
+        let errors = schema.validate_json(r#"{"abcd": {}}"#, MetadataJsonSchema::NoConversions).unwrap();
+        assert_eq!(errors.get(0).path(), "$.abcd");
+    }
+
+    #[test]
+    fn arrays_and_groups() {
+        let schema = MetadataSchema::from_cddl(r#"
+            votes = [ 1*3 vote, ? comment ]
+            vote = [ proposal: uint, choice: 0..2 ]
+            comment = text .size (1..16)
+        "#).unwrap();
+        let int = |x: i32| TransactionMetadatum::new_int(&Int::new_i32(x));
+        let vote = |proposal: i32, choice: i32| {
+            let mut list = MetadataList::new();
+            list.add(&int(proposal));
+            list.add(&int(choice));
+            TransactionMetadatum::new_list(&list)
+        };
+        let votes = |items: Vec<TransactionMetadatum>| {
+            let mut list = MetadataList::new();
+            items.iter().for_each(|item| list.add(item));
+            TransactionMetadatum::new_list(&list)
+        };
+        let comment = TransactionMetadatum::new_text(String::from("yes")).unwrap();
+        assert!(schema.validate(&votes(vec![vote(1, 0), vote(2, 2), comment.clone()])).is_empty());
+        assert!(schema.validate(&votes(vec![vote(1, 0)])).is_empty());
+
+        let errors = schema.validate(&votes(vec![vote(1, 0), vote(2, 3), comment.clone()]));
+        assert_eq!(errors.get(0).to_string(), "$[1][1]: expected 0..2, found int 3");
+        let errors = schema.validate(&votes(vec![comment.clone()]));
+        assert_eq!(errors.get(0).path(), "$[0]");
+        let errors = schema.validate(&votes(vec![vote(1, 0), comment.clone(), comment]));
+        assert_eq!(errors.get(0).to_string(), "$[2]: unexpected element");
+        let errors = schema.validate(&votes(vec![vote(1, 0), TransactionMetadatum::new_text(String::new()).unwrap()]));
+        assert_eq!(errors.get(0).to_string(), "$[1]: expected a length in 1..16, found 0");
+    }
+
+    #[test]
+    fn json_encoder() {
+        let schema = MetadataSchema::from_cddl(r#"
+            registration = {
+                1: [* delegation],
+                2: bytes .size 32,
+                ? 5: uint,
+            }
+            delegation = [bytes .size 32, uint]
+        "#).unwrap();
+        let key = "11".repeat(32);
+        let json = format!(r#"{{"1": [["{}", 1]], "2": "0x{}"}}"#, key, key);
+        let metadatum = schema.encode_json(&json).unwrap();
+        let map = metadatum.as_map().unwrap();
+        assert_eq!(map.get_i32(2).unwrap().as_bytes().unwrap(), vec![0x11; 32]);
+        let delegation = map.get_i32(1).unwrap().as_list().unwrap().get(0).as_list().unwrap();
+        assert_eq!(delegation.get(0).as_bytes().unwrap(), vec![0x11; 32]);
+        assert!(schema.validate(&metadatum).is_empty());
+
+        // the generic JSON conversion has no way of knowing the delegation key is bytes
+        assert!(!schema.validate_json(&json, MetadataJsonSchema::BasicConversions).unwrap().is_empty());
+
+        let err = schema.encode_json(&format!(r#"{{"1": [], "2": "{}", "3": 0}}"#, key)).unwrap_err();
+        assert_eq!(err.as_string().unwrap(), "$.3: unexpected key");
+        let err = schema.encode_json(r#"{"1": [], "2": "abcd"}"#).unwrap_err();
+        assert_eq!(err.as_string().unwrap(), "$.2: expected a length in 32..32, found 2");
+        let err = schema.encode_json(&format!(r#"{{"2": "{}"}}"#, key)).unwrap_err();
+        assert_eq!(err.as_string().unwrap(), "$: missing key 1");
+    }
+}
@@ -0,0 +1,243 @@
+// Generated by MetadataSchema::generate_rust, do not edit
+
+use cardano_multiplatform_lib::error::Error;
+use cardano_multiplatform_lib::ledger::common::value::{BigNum, Int};
+use cardano_multiplatform_lib::metadata::{MetadataList, MetadataMap, TransactionMetadatum};
+
+fn check_count(len: usize, min: usize, max: usize, what: &str) -> Result<(), Error> {
+    if len < min || len > max {
+        return Err(Error::from_str(&format!("{}: expected {}..{} entries, found {}", what, min, max, len)));
+    }
+    Ok(())
+}
+
+fn check_size(len: usize, min: usize, max: usize, what: &str) -> Result<(), Error> {
+    if len < min || len > max {
+        return Err(Error::from_str(&format!("{}: expected a length in {}..{}, found {}", what, min, max, len)));
+    }
+    Ok(())
+}
+
+fn insert_entry(map: &mut MetadataMap, key: TransactionMetadatum, value: TransactionMetadatum, what: &str) -> Result<(), Error> {
+    match map.insert(&key, &value) {
+        Some(_) => Err(Error::from_str(&format!("{}: duplicate key", what))),
+        None => Ok(()),
+    }
+}
+
+fn encode_uint(x: &u64, min: u64, max: u64, what: &str) -> Result<TransactionMetadatum, Error> {
+    if *x < min || *x > max {
+        return Err(Error::from_str(&format!("{}: expected {}..{}, found {}", what, min, max, x)));
+    }
+    Ok(TransactionMetadatum::new_int(&Int::new(&BigNum::from(*x))))
+}
+
+fn encode_text(x: &str, min: usize, max: usize, what: &str) -> Result<TransactionMetadatum, Error> {
+    check_size(x.len(), min, max, what)?;
+    TransactionMetadatum::new_text(x.to_owned())
+}
+
+fn encode_bytes(x: &[u8], min: usize, max: usize, what: &str) -> Result<TransactionMetadatum, Error> {
+    check_size(x.len(), min, max, what)?;
+    TransactionMetadatum::new_bytes(x.to_vec())
+}
+
+fn encode_list<T>(
+    items: &[T],
+    min: usize,
+    max: usize,
+    what: &str,
+    encode: impl Fn(&T) -> Result<TransactionMetadatum, Error>,
+) -> Result<TransactionMetadatum, Error> {
+    check_count(items.len(), min, max, what)?;
+    let mut list = MetadataList::new();
+    for item in items {
+        list.add(&encode(item)?);
+    }
+    Ok(TransactionMetadatum::new_list(&list))
+}
+
+fn encode_map<K, V>(
+    entries: &[(K, V)],
+    min: usize,
+    max: usize,
+    what: &str,
+    encode_key: impl Fn(&K) -> Result<TransactionMetadatum, Error>,
+    encode_value: impl Fn(&V) -> Result<TransactionMetadatum, Error>,
+) -> Result<TransactionMetadatum, Error> {
+    check_count(entries.len(), min, max, what)?;
+    let mut map = MetadataMap::new();
+    for (key, value) in entries {
+        insert_entry(&mut map, encode_key(key)?, encode_value(value)?, what)?;
+    }
+    Ok(TransactionMetadatum::new_map(&map))
+}
+
+/// `metadata`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Metadata(pub Vec<(PolicyId, Vec<(AssetName, Details)>)>);
+
+impl Metadata {
+    pub fn to_metadatum(&self) -> Result<TransactionMetadatum, Error> {
+        let Metadata(value) = self;
+        encode_map(value, 0, usize::MAX, "metadata", |key| key.to_metadatum(), |value| encode_map(value, 0, usize::MAX, "metadata.value", |key| key.to_metadatum(), |value| value.to_metadatum()))
+    }
+}
+
+/// `policy_id`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PolicyId(pub String);
+
+impl PolicyId {
+    pub fn to_metadatum(&self) -> Result<TransactionMetadatum, Error> {
+        let PolicyId(value) = self;
+        encode_text(value, 56, 56, "policy_id")
+    }
+}
+
+/// `asset_name`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetName(pub String);
+
+impl AssetName {
+    pub fn to_metadatum(&self) -> Result<TransactionMetadatum, Error> {
+        let AssetName(value) = self;
+        encode_text(value, 0, usize::MAX, "asset_name")
+    }
+}
+
+/// `details`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Details {
+    pub name: String,
+    pub image: DetailsImage,
+    pub description: Option<String>,
+    pub files: Option<Vec<FileEntry>>,
+    pub votes: Option<Votes>,
+    pub key_1: Option<Vec<u8>>,
+    pub entries: Vec<(String, TransactionMetadatum)>,
+}
+
+impl Details {
+    pub fn to_metadatum(&self) -> Result<TransactionMetadatum, Error> {
+        let Details { name, image, description, files, votes, key_1, entries } = self;
+        let mut map = MetadataMap::new();
+        insert_entry(&mut map, TransactionMetadatum::new_text(String::from("name"))?, encode_text(name, 0, usize::MAX, "details.name")?, "details.name")?;
+        insert_entry(&mut map, TransactionMetadatum::new_text(String::from("image"))?, image.to_metadatum()?, "details.image")?;
+        if let Some(description) = description {
+            insert_entry(&mut map, TransactionMetadatum::new_text(String::from("description"))?, encode_text(description, 0, usize::MAX, "details.description")?, "details.description")?;
+        }
+        if let Some(files) = files {
+            insert_entry(&mut map, TransactionMetadatum::new_text(String::from("files"))?, encode_list(files, 0, usize::MAX, "details.files", |item| item.to_metadatum())?, "details.files")?;
+        }
+        if let Some(votes) = votes {
+            insert_entry(&mut map, TransactionMetadatum::new_text(String::from("votes"))?, votes.to_metadatum()?, "details.votes")?;
+        }
+        if let Some(key_1) = key_1 {
+            insert_entry(&mut map, TransactionMetadatum::new_int(&Int::new(&BigNum::from(1u64))), encode_bytes(key_1, 0, 64, "details.key_1")?, "details.key_1")?;
+        }
+        for (key, value) in entries {
+            insert_entry(&mut map, encode_text(key, 0, usize::MAX, "details.key")?, value.clone(), "details")?;
+        }
+        Ok(TransactionMetadatum::new_map(&map))
+    }
+}
+
+/// `details.image`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DetailsImage {
+    Text(String),
+    Array(Vec<String>),
+}
+
+impl DetailsImage {
+    pub fn to_metadatum(&self) -> Result<TransactionMetadatum, Error> {
+        match self {
+            DetailsImage::Text(value) => encode_text(value, 0, usize::MAX, "details.image"),
+            DetailsImage::Array(value) => encode_list(value, 0, usize::MAX, "details.image", |item| encode_text(item, 0, usize::MAX, "details.image")),
+        }
+    }
+}
+
+/// `file_entry`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileEntry {
+    pub src: String,
+    pub media_type: String,
+}
+
+impl FileEntry {
+    pub fn to_metadatum(&self) -> Result<TransactionMetadatum, Error> {
+        let FileEntry { src, media_type } = self;
+        let mut map = MetadataMap::new();
+        insert_entry(&mut map, TransactionMetadatum::new_text(String::from("src"))?, encode_text(src, 0, usize::MAX, "file_entry.src")?, "file_entry.src")?;
+        insert_entry(&mut map, TransactionMetadatum::new_text(String::from("mediaType"))?, encode_text(media_type, 0, usize::MAX, "file_entry.mediaType")?, "file_entry.mediaType")?;
+        Ok(TransactionMetadatum::new_map(&map))
+    }
+}
+
+/// `votes`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Votes {
+    pub vote: Vec<Vote>,
+    pub comment: Option<Comment>,
+}
+
+impl Votes {
+    pub fn to_metadatum(&self) -> Result<TransactionMetadatum, Error> {
+        let Votes { vote, comment } = self;
+        let mut list = MetadataList::new();
+        check_count(vote.len(), 1, 3, "votes[0]")?;
+        for item in vote {
+            list.add(&item.to_metadatum()?);
+        }
+        if let Some(comment) = comment {
+            list.add(&comment.to_metadatum()?);
+        }
+        Ok(TransactionMetadatum::new_list(&list))
+    }
+}
+
+/// `vote`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vote {
+    pub proposal: u64,
+    pub choice: VoteChoice,
+}
+
+impl Vote {
+    pub fn to_metadatum(&self) -> Result<TransactionMetadatum, Error> {
+        let Vote { proposal, choice } = self;
+        let mut list = MetadataList::new();
+        list.add(&encode_uint(proposal, 0, u64::MAX, "vote[0]")?);
+        list.add(&choice.to_metadatum()?);
+        Ok(TransactionMetadatum::new_list(&list))
+    }
+}
+
+/// `vote[1]`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VoteChoice {
+    Range(u64),
+    Abstain,
+}
+
+impl VoteChoice {
+    pub fn to_metadatum(&self) -> Result<TransactionMetadatum, Error> {
+        match self {
+            VoteChoice::Range(value) => encode_uint(value, 0, 2, "vote[1]"),
+            VoteChoice::Abstain => TransactionMetadatum::new_text(String::from("abstain")),
+        }
+    }
+}
+
+/// `comment`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment(pub String);
+
+impl Comment {
+    pub fn to_metadatum(&self) -> Result<TransactionMetadatum, Error> {
+        let Comment(value) = self;
+        encode_text(value, 1, 16, "comment")
+    }
+}
@@ -34,6 +34,15 @@ impl PlutusV1Script {
         Self(bytes)
     }
 
+    /**
+     * Applies `params` in order to a parameterized script e.g. a blueprint validator
+     * compiled with an owner pubkey hash or a one-shot UTxO ref as parameters.
+     * Each parameter is applied as a Data constant.
+     */
+    pub fn apply_params(&self, params: &PlutusList) -> Result<PlutusV1Script, Error> {
+        Ok(Self(crate::uplc::apply_params(&self.0, params)?))
+    }
+
     /**
      * The raw bytes of this compiled Plutus script.
      * If you need "cborBytes" for cardano-cli use PlutusV1Script::to_bytes() instead.
@@ -74,6 +83,8 @@ to_from_bytes!(PlutusV1Scripts);
 
 to_from_json!(PlutusV1Scripts);
 
+vec_iter!(PlutusV1Scripts, PlutusV1Script);
+
 
 impl PlutusV1Scripts {
     pub fn new() -> Self {
@@ -116,6 +127,15 @@ impl PlutusV2Script {
         Self(bytes)
     }
 
+    /**
+     * Applies `params` in order to a parameterized script e.g. a blueprint validator
+     * compiled with an owner pubkey hash or a one-shot UTxO ref as parameters.
+     * Each parameter is applied as a Data constant.
+     */
+    pub fn apply_params(&self, params: &PlutusList) -> Result<PlutusV2Script, Error> {
+        Ok(Self(crate::uplc::apply_params(&self.0, params)?))
+    }
+
     /**
      * The raw bytes of this compiled Plutus script.
      * If you need "cborBytes" for cardano-cli use PlutusV2Script::to_bytes() instead.
@@ -156,6 +176,8 @@ to_from_bytes!(PlutusV2Scripts);
 
 to_from_json!(PlutusV2Scripts);
 
+vec_iter!(PlutusV2Scripts, PlutusV2Script);
+
 
 impl PlutusV2Scripts {
     pub fn new() -> Self {
@@ -176,6 +198,99 @@ impl PlutusV2Scripts {
 }
 
 
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct PlutusV3Script(Vec<u8>);
+
+to_from_bytes!(PlutusV3Script);
+
+to_from_json!(PlutusV3Script);
+
+
+impl PlutusV3Script {
+    pub fn hash(&self) -> ScriptHash {
+        hash_script(ScriptHashNamespace::PlutusV3, self.bytes())
+    }
+
+    /**
+     * Creates a new Plutus script from the RAW bytes of the compiled script.
+     * This does NOT include any CBOR encoding around these bytes (e.g. from "cborBytes" in cardano-cli)
+     * If you creating this from those you should use PlutusV3Script::from_bytes() instead.
+     */
+    pub fn new(bytes: Vec<u8>) -> PlutusV3Script {
+        Self(bytes)
+    }
+
+    /**
+     * Applies `params` in order to a parameterized script e.g. a blueprint validator
+     * compiled with an owner pubkey hash or a one-shot UTxO ref as parameters.
+     * Each parameter is applied as a Data constant.
+     */
+    pub fn apply_params(&self, params: &PlutusList) -> Result<PlutusV3Script, Error> {
+        Ok(Self(crate::uplc::apply_params(&self.0, params)?))
+    }
+
+    /**
+     * The raw bytes of this compiled Plutus script.
+     * If you need "cborBytes" for cardano-cli use PlutusV3Script::to_bytes() instead.
+     */
+    pub fn bytes(&self) -> Vec<u8> {
+        self.0.clone()
+    }
+}
+
+impl serde::Serialize for PlutusV3Script {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        serializer.serialize_str(&hex::encode(&self.0))
+    }
+}
+
+impl <'de> serde::de::Deserialize<'de> for PlutusV3Script {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where
+    D: serde::de::Deserializer<'de> {
+        let s = <String as serde::de::Deserialize>::deserialize(deserializer)?;
+        hex::decode(&s)
+            .map(|bytes| PlutusV3Script::new(bytes))
+            .map_err(|_err| serde::de::Error::invalid_value(serde::de::Unexpected::Str(&s), &"PlutusV3Script as hex string e.g. F8AB28C2 (without CBOR bytes tag)"))
+    }
+}
+
+impl JsonSchema for PlutusV3Script {
+    fn schema_name() -> String { String::from("PlutusV3Script") }
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema { String::json_schema(gen) }
+    fn is_referenceable() -> bool { String::is_referenceable() }
+}
+
+
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct PlutusV3Scripts(pub (crate) Vec<PlutusV3Script>);
+
+to_from_bytes!(PlutusV3Scripts);
+
+to_from_json!(PlutusV3Scripts);
+
+vec_iter!(PlutusV3Scripts, PlutusV3Script);
+
+
+impl PlutusV3Scripts {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn get(&self, index: usize) -> PlutusV3Script {
+        self.0[index].clone()
+    }
+
+    pub fn add(&mut self, elem: &PlutusV3Script) {
+        self.0.push(elem.clone());
+    }
+}
+
+
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub struct ConstrPlutusData {
     alternative: BigNum,
@@ -236,11 +351,13 @@ impl ConstrPlutusData {
 
 const PLUTUS_V1_COST_MODEL_OP_COUNT: usize = 166;
 const PLUTUS_V2_COST_MODEL_OP_COUNT: usize = 175;
+const PLUTUS_V3_COST_MODEL_OP_COUNT: usize = 251;
 
 fn cost_model_op_count(lang: LanguageKind) -> usize {
     match lang {
         LanguageKind::PlutusV1 => PLUTUS_V1_COST_MODEL_OP_COUNT,
         LanguageKind::PlutusV2 => PLUTUS_V2_COST_MODEL_OP_COUNT,
+        LanguageKind::PlutusV3 => PLUTUS_V3_COST_MODEL_OP_COUNT,
     }
 }
 
@@ -268,18 +385,18 @@ impl CostModel {
         }
     }
 
-    pub fn set(&mut self, operation: usize, cost: &Int) -> Result<Int, JsError> {
+    pub fn set(&mut self, operation: usize, cost: &Int) -> Result<Int, Error> {
         if operation >= self.op_costs.len() {
-            return Err(JsError::from_str(&format!("CostModel operation {} out of bounds. Max is {}", operation, self.op_costs.len())));
+            return Err(Error::from_str(&format!("CostModel operation {} out of bounds. Max is {}", operation, self.op_costs.len())));
         }
         let old = self.op_costs[operation].clone();
         self.op_costs[operation] = cost.clone();
         Ok(old)
     }
 
-    pub fn get(&self, operation: usize) -> Result<Int, JsError> {
+    pub fn get(&self, operation: usize) -> Result<Int, Error> {
         if operation >= self.op_costs.len() {
-            return Err(JsError::from_str(&format!("CostModel operation {} out of bounds. Max is {}", operation, self.op_costs.len())));
+            return Err(Error::from_str(&format!("CostModel operation {} out of bounds. Max is {}", operation, self.op_costs.len())));
         }
         Ok(self.op_costs[operation].clone())
     }
@@ -301,6 +418,8 @@ impl CostModel {
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize, JsonSchema)]
 pub struct Costmdls(std::collections::BTreeMap<Language, CostModel>);
 
+map_iter!(Costmdls, std::collections::btree_map, Language, CostModel);
+
 to_from_bytes!(Costmdls);
 to_from_json!(Costmdls);
 
@@ -355,9 +474,9 @@ impl Costmdls {
                     cost_model_serializer.write_special(cbor_event::Special::Break).unwrap();
                     serializer.write_bytes(cost_model_serializer.finalize()).unwrap();
                 },
-                LanguageKind::PlutusV2 => {
-                    // For PlutusV2 (language id 1), the language view is the following:
-                    //    * the value of costmdls map at key 1 is encoded as an definite length list.
+                LanguageKind::PlutusV2 | LanguageKind::PlutusV3 => {
+                    // For PlutusV2 (language id 1) and PlutusV3 (language id 2), the language view is the following:
+                    //    * the value of costmdls map at the language id is encoded as an definite length list.
                     key.serialize(&mut serializer).unwrap();
                     let cost_model = self.0.get(&key).unwrap();
                     serializer.write_array(cbor_event::Len::Len(cost_model.op_costs.len() as u64)).unwrap();
@@ -376,6 +495,7 @@ impl Costmdls {
 pub enum PlutusScriptEnum {
     PlutusV1(PlutusV1Script),
     PlutusV2(PlutusV2Script),
+    PlutusV3(PlutusV3Script),
 }
 
 impl PlutusScriptEnum {
@@ -385,10 +505,14 @@ impl PlutusScriptEnum {
     pub fn from_v2(script: &PlutusV2Script) -> PlutusScriptEnum {
         PlutusScriptEnum::PlutusV2(script.clone())
     }
+    pub fn from_v3(script: &PlutusV3Script) -> PlutusScriptEnum {
+        PlutusScriptEnum::PlutusV3(script.clone())
+    }
     pub fn hash(&self) -> ScriptHash {
         match &self {
             PlutusScriptEnum::PlutusV1(script) => script.hash(),
-            PlutusScriptEnum::PlutusV2(script) => script.hash()
+            PlutusScriptEnum::PlutusV2(script) => script.hash(),
+            PlutusScriptEnum::PlutusV3(script) => script.hash(),
         }
     }
 }
@@ -406,6 +530,9 @@ impl PlutusScript {
     pub fn from_v2(script: &PlutusV2Script) -> PlutusScript {
         PlutusScript(PlutusScriptEnum::PlutusV2(script.clone()))
     }
+    pub fn from_v3(script: &PlutusV3Script) -> PlutusScript {
+        PlutusScript(PlutusScriptEnum::PlutusV3(script.clone()))
+    }
     pub fn hash(&self) -> ScriptHash {
         self.0.hash()
     }
@@ -466,7 +593,7 @@ impl ExUnits {
         }
     }
 
-    pub fn checked_add(&self, other: &ExUnits) -> Result<ExUnits, JsError> {
+    pub fn checked_add(&self, other: &ExUnits) -> Result<ExUnits, Error> {
         let mem = self.mem.checked_add(&other.mem())?;
         let step = self.steps.checked_add(&other.steps())?;
         Ok(ExUnits::new(&mem, &step))
@@ -483,6 +610,7 @@ impl ExUnits {
 pub enum LanguageKind {
     PlutusV1,
     PlutusV2,
+    PlutusV3,
 }
 
 
@@ -501,6 +629,10 @@ impl Language {
         Self(LanguageKind::PlutusV2)
     }
 
+    pub fn new_plutus_v3() -> Self {
+        Self(LanguageKind::PlutusV3)
+    }
+
     pub fn kind(&self) -> LanguageKind {
         self.0
     }
@@ -510,6 +642,8 @@ impl Language {
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize, JsonSchema)]
 pub struct Languages(pub(crate) Vec<Language>);
 
+vec_iter!(Languages, Language);
+
 
 impl Languages {
     pub fn new() -> Self {
@@ -533,6 +667,8 @@ impl Languages {
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub struct PlutusMap(std::collections::BTreeMap<PlutusData, PlutusData>);
 
+map_iter!(PlutusMap, std::collections::btree_map, PlutusData, PlutusData);
+
 to_from_bytes!(PlutusMap);
 
 
@@ -781,6 +917,8 @@ pub enum RedeemerTagKind {
     Mint,
     Cert,
     Reward,
+    Voting,
+    Proposing,
 }
 
 
@@ -807,6 +945,14 @@ impl RedeemerTag {
         Self(RedeemerTagKind::Reward)
     }
 
+    pub fn new_voting() -> Self {
+        Self(RedeemerTagKind::Voting)
+    }
+
+    pub fn new_proposing() -> Self {
+        Self(RedeemerTagKind::Proposing)
+    }
+
     pub fn kind(&self) -> RedeemerTagKind {
         self.0
     }
@@ -818,6 +964,8 @@ pub struct Redeemers(pub (crate) Vec<Redeemer>);
 
 to_from_bytes!(Redeemers);
 
+vec_iter!(Redeemers, Redeemer);
+
 
 impl Redeemers {
     pub fn new() -> Self {
@@ -836,7 +984,7 @@ impl Redeemers {
         self.0.push(elem.clone());
     }
 
-    pub fn get_total_ex_units(&self) -> Result<ExUnits, JsError> {
+    pub fn get_total_ex_units(&self) -> Result<ExUnits, Error> {
         let mut sum = ExUnits::new(&BigNum::zero(), &BigNum::zero());
         for redeemer in &self.0 {
             sum = sum.checked_add(&redeemer.ex_units())?;
@@ -849,6 +997,8 @@ impl Redeemers {
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub struct Strings(Vec<String>);
 
+vec_iter!(Strings, String);
+
 
 impl Strings {
     pub fn new() -> Self {
@@ -873,6 +1023,7 @@ pub enum ScriptEnum {
     Native(NativeScript),
     PlutusV1(PlutusV1Script),
     PlutusV2(PlutusV2Script),
+    PlutusV3(PlutusV3Script),
 }
 
 impl From<&ScriptEnum> for Option<Language> {
@@ -881,6 +1032,7 @@ impl From<&ScriptEnum> for Option<Language> {
             ScriptEnum::Native(_) => None,
             ScriptEnum::PlutusV1(_) => Some(Language::new_plutus_v1()),
             ScriptEnum::PlutusV2(_) => Some(Language::new_plutus_v2()),
+            ScriptEnum::PlutusV3(_) => Some(Language::new_plutus_v3()),
         }
     }
 }
@@ -893,6 +1045,7 @@ pub enum ScriptKind {
     NativeScript,
     PlutusScriptV1,
     PlutusScriptV2,
+    PlutusScriptV3,
 }
 
 
@@ -917,11 +1070,16 @@ impl Script {
         Self(ScriptEnum::PlutusV2(plutus_script.clone()))
     }
 
+    pub fn new_plutus_v3(plutus_script: &PlutusV3Script) -> Self {
+        Self(ScriptEnum::PlutusV3(plutus_script.clone()))
+    }
+
     pub fn kind(&self) -> ScriptKind {
         match &self.0 {
             ScriptEnum::Native(_) => ScriptKind::NativeScript,
             ScriptEnum::PlutusV1(_) => ScriptKind::PlutusScriptV1,
             ScriptEnum::PlutusV2(_) => ScriptKind::PlutusScriptV2,
+            ScriptEnum::PlutusV3(_) => ScriptKind::PlutusScriptV3,
         }
     }
 
@@ -946,11 +1104,19 @@ impl Script {
         }
     }
 
+    pub fn as_plutus_v3(&self) -> Option<PlutusV3Script> {
+        match &self.0 {
+            ScriptEnum::PlutusV3(plutus_script) => Some(plutus_script.clone()),
+            _ => None,
+        }
+    }
+
     pub fn hash(&self) -> ScriptHash {
         match &self.0 {
             ScriptEnum::Native(native_script) => native_script.hash(),
             ScriptEnum::PlutusV1(plutus_script) => plutus_script.hash(),
             ScriptEnum::PlutusV2(plutus_script) => plutus_script.hash(),
+            ScriptEnum::PlutusV3(plutus_script) => plutus_script.hash(),
         }
     }
 }
@@ -960,17 +1126,21 @@ impl cbor_event::se::Serialize for Script {
         serializer.write_array(cbor_event::Len::Len(2))?;
         match &self.0 {
             ScriptEnum::Native(ns) => {
-                serializer.write_tag(0u64)?;
+                serializer.write_unsigned_integer(0u64)?;
                 ns.serialize(serializer)
             },
             ScriptEnum::PlutusV1(ps1) => {
-                serializer.write_tag(1u64)?;
+                serializer.write_unsigned_integer(1u64)?;
                 ps1.serialize(serializer)
             },
             ScriptEnum::PlutusV2(ps2) => {
-                serializer.write_tag(2u64)?;
+                serializer.write_unsigned_integer(2u64)?;
                 ps2.serialize(serializer)
             },
+            ScriptEnum::PlutusV3(ps3) => {
+                serializer.write_unsigned_integer(3u64)?;
+                ps3.serialize(serializer)
+            },
         }
     }
 }
@@ -985,6 +1155,7 @@ impl Deserialize for Script {
                 0 => NativeScript::deserialize(raw).map(ScriptEnum::Native),
                 1 => PlutusV1Script::deserialize(raw).map(ScriptEnum::PlutusV1),
                 2 => PlutusV2Script::deserialize(raw).map(ScriptEnum::PlutusV2),
+                3 => PlutusV3Script::deserialize(raw).map(ScriptEnum::PlutusV3),
                 _ => Err(DeserializeFailure::NoVariantMatched.into()),
             };
             match len {
@@ -1100,29 +1271,29 @@ pub enum PlutusDatumSchema {
 }
 
 
-pub fn encode_json_str_to_plutus_datum(json: &str, schema: PlutusDatumSchema) -> Result<PlutusData, JsError> {
-    let value = serde_json::from_str(json).map_err(|e| JsError::from_str(&e.to_string()))?;
+pub fn encode_json_str_to_plutus_datum(json: &str, schema: PlutusDatumSchema) -> Result<PlutusData, Error> {
+    let value = serde_json::from_str(json).map_err(|e| Error::from_str(&e.to_string()))?;
     encode_json_value_to_plutus_datum(value, schema)
 }
 
-pub fn encode_json_value_to_plutus_datum(value: serde_json::Value, schema: PlutusDatumSchema) -> Result<PlutusData, JsError> {
+pub fn encode_json_value_to_plutus_datum(value: serde_json::Value, schema: PlutusDatumSchema) -> Result<PlutusData, Error> {
     use serde_json::Value;
-    fn encode_number(x: serde_json::Number) -> Result<PlutusData, JsError> {
+    fn encode_number(x: serde_json::Number) -> Result<PlutusData, Error> {
         if let Some(x) = x.as_u64() {
             Ok(PlutusData::new_integer(&BigInt::from(x)))
         } else if let Some(x) = x.as_i64() {
             Ok(PlutusData::new_integer(&BigInt::from(x)))
         } else {
-            Err(JsError::from_str("floats not allowed in plutus datums"))
+            Err(Error::from_str("floats not allowed in plutus datums"))
         }
     }
-    fn encode_string(s: &str, schema: PlutusDatumSchema, is_key: bool) -> Result<PlutusData, JsError> {
+    fn encode_string(s: &str, schema: PlutusDatumSchema, is_key: bool) -> Result<PlutusData, Error> {
         if schema == PlutusDatumSchema::BasicConversions {
             if s.starts_with("0x") {
                 // this must be a valid hex bytestring after
                 hex::decode(&s[2..])
                     .map(|bytes| PlutusData::new_bytes(bytes))
-                    .map_err(|err| JsError::from_str(&format!("Error decoding {}: {}", s, err)))
+                    .map_err(|err| Error::from_str(&format!("Error decoding {}: {}", s, err)))
             } else if is_key {
                 // try as an integer
                 BigInt::from_str(s)
@@ -1135,15 +1306,15 @@ pub fn encode_json_value_to_plutus_datum(value: serde_json::Value, schema: Plutu
             }
         } else {
             if s.starts_with("0x") {
-                Err(JsError::from_str("Hex byte strings in detailed schema should NOT start with 0x and should just contain the hex characters"))
+                Err(Error::from_str("Hex byte strings in detailed schema should NOT start with 0x and should just contain the hex characters"))
             } else {
                 hex::decode(s)
                     .map(|bytes| PlutusData::new_bytes(bytes))
-                    .map_err(|e| JsError::from_str(&e.to_string()))
+                    .map_err(|e| Error::from_str(&e.to_string()))
             }
         }
     }
-    fn encode_array(json_arr: Vec<Value>, schema: PlutusDatumSchema) -> Result<PlutusData, JsError> {
+    fn encode_array(json_arr: Vec<Value>, schema: PlutusDatumSchema) -> Result<PlutusData, Error> {
         let mut arr = PlutusList::new();
         for value in json_arr {
             arr.add(&encode_json_value_to_plutus_datum(value, schema)?);
@@ -1152,8 +1323,8 @@ pub fn encode_json_value_to_plutus_datum(value: serde_json::Value, schema: Plutu
     }
     match schema {
         PlutusDatumSchema::BasicConversions => match value {
-            Value::Null => Err(JsError::from_str("null not allowed in plutus datums")),
-            Value::Bool(_) => Err(JsError::from_str("bools not allowed in plutus datums")),
+            Value::Null => Err(Error::from_str("null not allowed in plutus datums")),
+            Value::Bool(_) => Err(Error::from_str("bools not allowed in plutus datums")),
             Value::Number(x) => encode_number(x),
             // no strings in plutus so it's all bytes (as hex or utf8 printable)
             Value::String(s) => encode_string(&s, schema, false),
@@ -1173,8 +1344,8 @@ pub fn encode_json_value_to_plutus_datum(value: serde_json::Value, schema: Plutu
                 if obj.len() == 1 {
                     // all variants except tagged constructors
                     let (k, v) = obj.into_iter().next().unwrap();
-                    fn tag_mismatch() -> JsError {
-                        JsError::from_str("key does not match type")
+                    fn tag_mismatch() -> Error {
+                        Error::from_str("key does not match type")
                     }
                     match k.as_str() {
                         "int" => match v {
@@ -1185,8 +1356,8 @@ pub fn encode_json_value_to_plutus_datum(value: serde_json::Value, schema: Plutu
                         "list" => encode_array(v.as_array().ok_or_else(tag_mismatch)?.clone(), schema),
                         "map" => {
                             let mut map = PlutusMap::new();
-                            fn map_entry_err() -> JsError {
-                                JsError::from_str("entry format in detailed schema map object not correct. Needs to be of form {\"k\": {\"key_type\": key}, \"v\": {\"value_type\", value}}")
+                            fn map_entry_err() -> Error {
+                                Error::from_str("entry format in detailed schema map object not correct. Needs to be of form {\"k\": {\"key_type\": key}, \"v\": {\"value_type\", value}}")
                             }
                             for entry in v.as_array().ok_or_else(tag_mismatch)? {
                                 let entry_obj = entry.as_object().ok_or_else(map_entry_err)?;
@@ -1201,21 +1372,21 @@ pub fn encode_json_value_to_plutus_datum(value: serde_json::Value, schema: Plutu
                             }
                             Ok(PlutusData::new_map(&map))
                         },
-                        invalid_key => Err(JsError::from_str(&format!("key '{}' in tagged object not valid", invalid_key))),
+                        invalid_key => Err(Error::from_str(&format!("key '{}' in tagged object not valid", invalid_key))),
                     }
                 } else {
                     // constructor with tagged variant
                     if obj.len() != 2 {
-                        return Err(JsError::from_str("detailed schemas must either have only one of the following keys: \"int\", \"bytes\", \"list\" or \"map\", or both of these 2 keys: \"constructor\" + \"fields\""));
+                        return Err(Error::from_str("detailed schemas must either have only one of the following keys: \"int\", \"bytes\", \"list\" or \"map\", or both of these 2 keys: \"constructor\" + \"fields\""));
                     }
                     let variant: BigNum = obj
                         .get("constructor")
                         .and_then(|v| Some(to_bignum(v.as_u64()?)))
-                        .ok_or_else(|| JsError::from_str("tagged constructors must contain an unsigned integer called \"constructor\""))?;
+                        .ok_or_else(|| Error::from_str("tagged constructors must contain an unsigned integer called \"constructor\""))?;
                     let fields_json = obj
                         .get("fields")
                         .and_then(|f| f.as_array())
-                        .ok_or_else(|| JsError::from_str("tagged constructors must contian a list called \"fields\""))?;
+                        .ok_or_else(|| Error::from_str("tagged constructors must contian a list called \"fields\""))?;
                     let mut fields = PlutusList::new();
                     for field_json in fields_json {
                         let field = encode_json_value_to_plutus_datum(field_json.clone(), schema)?;
@@ -1224,18 +1395,18 @@ pub fn encode_json_value_to_plutus_datum(value: serde_json::Value, schema: Plutu
                     Ok(PlutusData::new_constr_plutus_data(&ConstrPlutusData::new(&variant, &fields)))
                 }
             },
-            _ => Err(JsError::from_str(&format!("DetailedSchema requires ALL JSON to be tagged objects, found: {}", value))),
+            _ => Err(Error::from_str(&format!("DetailedSchema requires ALL JSON to be tagged objects, found: {}", value))),
         },
     }
 }
 
 
-pub fn decode_plutus_datum_to_json_str(datum: &PlutusData, schema: PlutusDatumSchema) -> Result<String, JsError> {
+pub fn decode_plutus_datum_to_json_str(datum: &PlutusData, schema: PlutusDatumSchema) -> Result<String, Error> {
     let value = decode_plutus_datum_to_json_value(datum, schema)?;
-    serde_json::to_string(&value).map_err(|e| JsError::from_str(&e.to_string()))
+    serde_json::to_string(&value).map_err(|e| Error::from_str(&e.to_string()))
 }
 
-pub fn decode_plutus_datum_to_json_value(datum: &PlutusData, schema: PlutusDatumSchema) -> Result<serde_json::Value, JsError> {
+pub fn decode_plutus_datum_to_json_value(datum: &PlutusData, schema: PlutusDatumSchema) -> Result<serde_json::Value, Error> {
     use serde_json::Value;
     use std::convert::TryFrom;
     let (type_tag, json_value) = match &datum.datum {
@@ -1258,15 +1429,15 @@ pub fn decode_plutus_datum_to_json_value(datum: &PlutusData, schema: PlutusDatum
         PlutusDataEnum::Map(map) => match schema {
             PlutusDatumSchema::BasicConversions => (None, Value::from(map.0.iter().map(|(key, value)| {
                 let json_key: String = match &key.datum {
-                    PlutusDataEnum::ConstrPlutusData(_) => Err(JsError::from_str("plutus data constructors are not allowed as keys in this schema. Use DetailedSchema.")),
-                    PlutusDataEnum::Map(_) => Err(JsError::from_str("plutus maps are not allowed as keys in this schema. Use DetailedSchema.")),
-                    PlutusDataEnum::List(_) => Err(JsError::from_str("plutus lists are not allowed as keys in this schema. Use DetailedSchema.")),
+                    PlutusDataEnum::ConstrPlutusData(_) => Err(Error::from_str("plutus data constructors are not allowed as keys in this schema. Use DetailedSchema.")),
+                    PlutusDataEnum::Map(_) => Err(Error::from_str("plutus maps are not allowed as keys in this schema. Use DetailedSchema.")),
+                    PlutusDataEnum::List(_) => Err(Error::from_str("plutus lists are not allowed as keys in this schema. Use DetailedSchema.")),
                     PlutusDataEnum::Integer(x) => Ok(x.to_str()),
                     PlutusDataEnum::Bytes(bytes) => String::from_utf8(bytes.clone()).or_else(|_err| Ok(format!("0x{}", hex::encode(bytes))))
                 }?;
                 let json_value = decode_plutus_datum_to_json_value(value, schema)?;
                 Ok((json_key, Value::from(json_value)))
-            }).collect::<Result<serde_json::map::Map<String, Value>, JsError>>()?)),
+            }).collect::<Result<serde_json::map::Map<String, Value>, Error>>()?)),
             PlutusDatumSchema::DetailedSchema => (Some("map"), Value::from(map.0.iter().map(|(key, value)| {
                 let k = decode_plutus_datum_to_json_value(key, schema)?;
                 let v = decode_plutus_datum_to_json_value(value, schema)?;
@@ -1274,7 +1445,7 @@ pub fn decode_plutus_datum_to_json_value(datum: &PlutusData, schema: PlutusDatum
                 kv_obj.insert(String::from("k"), k);
                 kv_obj.insert(String::from("v"), v);
                 Ok(Value::from(kv_obj))
-            }).collect::<Result<Vec<_>, JsError>>()?)),
+            }).collect::<Result<Vec<_>, Error>>()?)),
         },
         PlutusDataEnum::List(list) => {
             let mut elems = Vec::new();
@@ -1289,7 +1460,7 @@ pub fn decode_plutus_datum_to_json_value(datum: &PlutusData, schema: PlutusDatum
                 .as_int()
                 .as_ref()
                 .map(|int| if int.0 >= 0 { Value::from(int.0 as u64) } else { Value::from(int.0 as i64) })
-                .ok_or_else(|| JsError::from_str(&format!("Integer {} too big for our JSON support", bigint.to_str())))?
+                .ok_or_else(|| Error::from_str(&format!("Integer {} too big for our JSON support", bigint.to_str())))?
         ),
         PlutusDataEnum::Bytes(bytes) => (Some("bytes"), Value::from(match schema {
             PlutusDatumSchema::BasicConversions => {
@@ -1402,6 +1573,46 @@ impl Deserialize for PlutusV2Scripts {
     }
 }
 
+impl cbor_event::se::Serialize for PlutusV3Script {
+    fn serialize<'se, W: Write>(&self, serializer: &'se mut Serializer<W>) -> cbor_event::Result<&'se mut Serializer<W>> {
+        serializer.write_bytes(&self.0)
+    }
+}
+
+impl Deserialize for PlutusV3Script {
+    fn deserialize<R: BufRead + Seek>(raw: &mut Deserializer<R>) -> Result<Self, DeserializeError> {
+        Ok(Self(raw.bytes()?))
+    }
+}
+
+impl cbor_event::se::Serialize for PlutusV3Scripts {
+    fn serialize<'se, W: Write>(&self, serializer: &'se mut Serializer<W>) -> cbor_event::Result<&'se mut Serializer<W>> {
+        serializer.write_array(cbor_event::Len::Len(self.0.len() as u64))?;
+        for element in &self.0 {
+            element.serialize(serializer)?;
+        }
+        Ok(serializer)
+    }
+}
+
+impl Deserialize for PlutusV3Scripts {
+    fn deserialize<R: BufRead + Seek>(raw: &mut Deserializer<R>) -> Result<Self, DeserializeError> {
+        let mut arr = Vec::new();
+        (|| -> Result<_, DeserializeError> {
+            let len = raw.array()?;
+            while match len { cbor_event::Len::Len(n) => arr.len() < n as usize, cbor_event::Len::Indefinite => true, } {
+                if raw.cbor_type()? == CBORType::Special {
+                    assert_eq!(raw.special()?, CBORSpecial::Break);
+                    break;
+                }
+                arr.push(PlutusV3Script::deserialize(raw)?);
+            }
+            Ok(())
+        })().map_err(|e| e.annotate("PlutusV3Scripts"))?;
+        Ok(Self(arr))
+    }
+}
+
 impl cbor_event::se::Serialize for ConstrPlutusData {
     fn serialize<'se, W: Write>(&self, serializer: &'se mut Serializer<W>) -> cbor_event::Result<&'se mut Serializer<W>> {
         if let Some(compact_tag) = Self::alternative_to_compact_cbor_tag(from_bignum(&self.alternative)) {
@@ -1470,21 +1681,31 @@ impl cbor_event::se::Serialize for CostModel {
     }
 }
 
+impl CostModel {
+    fn deserialize_op_costs<R: BufRead + Seek>(raw: &mut Deserializer<R>) -> Result<Vec<Int>, DeserializeError> {
+        let mut op_costs = Vec::new();
+        let len = raw.array()?;
+        while match len { cbor_event::Len::Len(n) => op_costs.len() < n as usize, cbor_event::Len::Indefinite => true, } {
+            if raw.cbor_type()? == CBORType::Special {
+                assert_eq!(raw.special()?, CBORSpecial::Break);
+                break;
+            }
+            op_costs.push(Int::deserialize(raw)?);
+        }
+        Ok(op_costs)
+    }
+}
+
 impl Deserialize for CostModel {
     fn deserialize<R: BufRead + Seek>(raw: &mut Deserializer<R>) -> Result<Self, DeserializeError> {
         (|| -> Result<_, DeserializeError> {
-            let mut op_costs = Vec::new();
-            let len = raw.array()?;
-            while match len { cbor_event::Len::Len(n) => op_costs.len() < n as usize, cbor_event::Len::Indefinite => true, } {
-                if raw.cbor_type()? == CBORType::Special {
-                    assert_eq!(raw.special()?, CBORSpecial::Break);
-                    break;
-                }
-                op_costs.push(Int::deserialize(raw)?);
-            }
+            let op_costs = Self::deserialize_op_costs(raw)?;
+            // without a Costmdls key the language can only be guessed from the operation count,
+            // which is why Costmdls doesn't rely on it: counts change between protocol versions
             let language = match op_costs.len() {
                 PLUTUS_V1_COST_MODEL_OP_COUNT => Ok(Language::new_plutus_v1()),
                 PLUTUS_V2_COST_MODEL_OP_COUNT => Ok(Language::new_plutus_v2()),
+                PLUTUS_V3_COST_MODEL_OP_COUNT => Ok(Language::new_plutus_v3()),
                 _ => Err(DeserializeFailure::NoVariantMatched),
             }?;
             Ok(CostModel {
@@ -1517,7 +1738,10 @@ impl Deserialize for Costmdls {
                     break;
                 }
                 let key = Language::deserialize(raw)?;
-                let value = CostModel::deserialize(raw)?;
+                let value = CostModel {
+                    language: key,
+                    op_costs: CostModel::deserialize_op_costs(raw).map_err(|e| e.annotate("CostModel"))?,
+                };
                 if table.insert(key.clone(), value).is_some() {
                     return Err(DeserializeFailure::DuplicateKey(Key::Str(String::from("some complicated/unsupported type"))).into());
                 }
@@ -1609,6 +1833,9 @@ impl cbor_event::se::Serialize for Language {
             LanguageKind::PlutusV2 => {
                 serializer.write_unsigned_integer(1u64)
             },
+            LanguageKind::PlutusV3 => {
+                serializer.write_unsigned_integer(2u64)
+            },
         }
     }
 }
@@ -1618,6 +1845,8 @@ impl Deserialize for Language {
         (|| -> Result<_, DeserializeError> {
             match raw.unsigned_integer()? {
                 0 => Ok(Language::new_plutus_v1()),
+                1 => Ok(Language::new_plutus_v2()),
+                2 => Ok(Language::new_plutus_v3()),
                 _ => Err(DeserializeError::new("Language", DeserializeFailure::NoVariantMatched.into())),
             }
         })().map_err(|e| e.annotate("Language"))
@@ -1881,6 +2110,12 @@ impl cbor_event::se::Serialize for RedeemerTagKind {
             RedeemerTagKind::Reward => {
                 serializer.write_unsigned_integer(3u64)
             },
+            RedeemerTagKind::Voting => {
+                serializer.write_unsigned_integer(4u64)
+            },
+            RedeemerTagKind::Proposing => {
+                serializer.write_unsigned_integer(5u64)
+            },
         }
     }
 }
@@ -1893,6 +2128,8 @@ impl Deserialize for RedeemerTagKind {
                 Ok(1) => Ok(RedeemerTagKind::Mint),
                 Ok(2) => Ok(RedeemerTagKind::Cert),
                 Ok(3) => Ok(RedeemerTagKind::Reward),
+                Ok(4) => Ok(RedeemerTagKind::Voting),
+                Ok(5) => Ok(RedeemerTagKind::Proposing),
                 Ok(_) | Err(_) => Err(DeserializeFailure::NoVariantMatched.into()),
             }
         })().map_err(|e| e.annotate("RedeemerTagEnum"))
@@ -1986,6 +2223,11 @@ mod tests {
 
         assert_ne!(hex::encode(full_cbor.bytes()), script);
 
+        // re-encoding the decoded UPLC program gives back the compiled script
+        let program = crate::uplc::Program::from_script_bytes(&full_cbor.bytes()).unwrap();
+        assert_eq!(program.to_script_bytes(), full_cbor.bytes());
+        assert_eq!(program.to_string().parse::<crate::uplc::Program>().unwrap(), program);
+
         assert_eq!(
             address.to_address().to_bech32(None).unwrap(),
             "addr_test1wz8jmzsx9uh2pgcxj7za36jeln7sprheumhkd3srnytfacg6cgclw"
@@ -2175,4 +2417,73 @@ mod tests {
         let data_back = serde_json::from_str(&json_str).unwrap();
         assert_eq!(data, data_back);
     }
+
+    #[test]
+    fn plutus_v3_script() {
+        let bytes = hex::decode("4e4d01000033222220051200120011").unwrap();
+        let v2 = PlutusV2Script::new(bytes.clone());
+        let v3 = PlutusV3Script::new(bytes.clone());
+        let mut namespaced = vec![3u8];
+        namespaced.extend(&bytes);
+        assert_eq!(v3.hash(), ScriptHash::from(crate::crypto::blake2b224(&namespaced)));
+        assert_ne!(v3.hash(), v2.hash());
+        assert_eq!(PlutusScript::from_v3(&v3).hash(), v3.hash());
+
+        // script refs use tag 3
+        let script = Script::new_plutus_v3(&v3);
+        assert_eq!(script.kind(), ScriptKind::PlutusScriptV3);
+        assert_eq!(&hex::encode(script.to_bytes())[..4], "8203");
+        assert_eq!(Script::from_bytes(script.to_bytes()).unwrap().as_plutus_v3(), Some(v3.clone()));
+
+        // witness set key 7
+        let mut scripts = PlutusV3Scripts::new();
+        scripts.add(&v3);
+        let mut witness_set = TransactionWitnessSet::new();
+        witness_set.set_plutus_v3_scripts(&scripts);
+        assert_eq!(&hex::encode(witness_set.to_bytes())[..6], "a10781");
+        assert_eq!(TransactionWitnessSet::from_bytes(witness_set.to_bytes()).unwrap().plutus_v3_scripts(), Some(scripts));
+
+        for (tag, encoded) in [(RedeemerTag::new_voting(), "04"), (RedeemerTag::new_proposing(), "05")] {
+            assert_eq!(hex::encode(tag.to_bytes()), encoded);
+            assert_eq!(RedeemerTag::from_bytes(tag.to_bytes()).unwrap(), tag);
+        }
+    }
+
+    #[test]
+    fn plutus_v3_language_view() {
+        let language = Language::new_plutus_v3();
+        assert_eq!(Language::from_bytes(language.to_bytes()).unwrap(), language);
+        let mut cms = Costmdls::new();
+        cms.insert(&CostModel::empty_model(&language));
+        // same as PlutusV2: the language id then a definite length list of 251 costs
+        assert_eq!(hex::encode(cms.language_views_encoding()), format!("a10298fb{}", "00".repeat(251)));
+        let cost_model = CostModel::from_bytes(cms.get(&language).unwrap().to_bytes()).unwrap();
+        assert_eq!(cost_model.language(), language);
+    }
+
+    #[test]
+    fn cost_model_language_from_key() {
+        // the later 297 operation V3 model and 185 operation V2 model
+        let mut cms = Costmdls::new();
+        for (language, op_count) in [(Language::new_plutus_v2(), 185), (Language::new_plutus_v3(), 297)] {
+            cms.insert(&CostModel::new(&language, &vec![Int::new_i32(1); op_count]));
+        }
+        let decoded = Costmdls::from_bytes(cms.to_bytes()).unwrap();
+        assert_eq!(decoded, cms);
+        assert_eq!(decoded.get(&Language::new_plutus_v3()).unwrap().language(), Language::new_plutus_v3());
+        assert_eq!(decoded.get(&Language::new_plutus_v2()).unwrap().get(184).unwrap(), Int::new_i32(1));
+    }
+
+    #[test]
+    fn apply_params_to_script() {
+        // (program 1.0.0 (lam x x))
+        let script = PlutusV2Script::new(hex::decode("46010000200101").unwrap());
+        let mut params = PlutusList::new();
+        params.add(&PlutusData::new_integer(&BigInt::from_str("42").unwrap()));
+        let applied = script.apply_params(&params).unwrap();
+        assert_eq!(hex::encode(applied.bytes()), "4c010000320014c102182a0001");
+        assert_ne!(applied.hash(), script.hash());
+        assert_eq!(script.apply_params(&PlutusList::new()).unwrap(), script);
+        assert!(PlutusV2Script::new(vec![0x01]).apply_params(&params).is_err());
+    }
 }
@@ -0,0 +1,406 @@
+//! Off-chain stake pool metadata, hosted at the URL of the PoolMetadata of a pool registration
+//! https://developers.cardano.org/docs/operate-a-stake-pool/register-stake-pool-metadata
+//!
+//! The extended metadata it can link to comes in the CIP-6 and adapools.org formats, see ExtendedPoolMetadata
+
+use super::*;
+use super::error::Error;
+use super::ledger::common::hash::hash_pool_metadata;
+
+/// Metadata larger than this is ignored by the tools fetching it (db-sync, SMASH)
+pub const POOL_METADATA_MAX_BYTES: usize = 512;
+const NAME_MAX_LEN: usize = 50;
+const DESCRIPTION_MAX_LEN: usize = 255;
+
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PoolMetadataJson {
+    name: String,
+    description: String,
+    ticker: String,
+    homepage: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    extended: Option<String>,
+}
+
+impl PoolMetadataJson {
+    pub fn new(name: String, description: String, ticker: String, homepage: String) -> Result<PoolMetadataJson, Error> {
+        let metadata = Self {
+            name,
+            description,
+            ticker,
+            homepage,
+            extended: None,
+        };
+        metadata.validate()?;
+        Ok(metadata)
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    /// 3 to 5 characters among A-Z and 0-9
+    pub fn ticker(&self) -> String {
+        self.ticker.clone()
+    }
+
+    pub fn homepage(&self) -> String {
+        self.homepage.clone()
+    }
+
+    /// URL of the extended metadata
+    pub fn extended(&self) -> Option<String> {
+        self.extended.clone()
+    }
+
+    pub fn set_extended(&mut self, extended: &URL) {
+        self.extended = Some(extended.url());
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        let check_len = |field: &str, value: &str, max: usize| match value.chars().count() {
+            len if len > max => Err(Error::from_str(&format!("pool metadata {} is {} characters long, the maximum is {}", field, len, max))),
+            _ => Ok(()),
+        };
+        check_len("name", &self.name, NAME_MAX_LEN)?;
+        check_len("description", &self.description, DESCRIPTION_MAX_LEN)?;
+        URL::new(self.homepage.clone()).map_err(|e| Error::from_str(&format!("invalid pool metadata homepage: {}", e.as_string().unwrap_or_default())))?;
+        if let Some(extended) = &self.extended {
+            URL::new(extended.clone()).map_err(|e| Error::from_str(&format!("invalid pool metadata extended URL: {}", e.as_string().unwrap_or_default())))?;
+        }
+        if !(3..=5).contains(&self.ticker.len()) || !self.ticker.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
+            return Err(Error::from_str(&format!("pool ticker {} must be 3 to 5 characters among A-Z and 0-9", self.ticker)));
+        }
+        Ok(())
+    }
+
+    /// Parses and validates the metadata as hosted
+    pub fn from_json_bytes(bytes: &[u8]) -> Result<PoolMetadataJson, Error> {
+        if bytes.len() > POOL_METADATA_MAX_BYTES {
+            return Err(Error::from_str(&format!("pool metadata is {} bytes, the maximum is {}", bytes.len(), POOL_METADATA_MAX_BYTES)));
+        }
+        let metadata: PoolMetadataJson = serde_json::from_slice(bytes).map_err(|e| Error::from_str(&format!("invalid pool metadata JSON: {}", e)))?;
+        metadata.validate()?;
+        Ok(metadata)
+    }
+
+    /// The bytes to host. Keep them as they are: the hash is computed over them
+    pub fn to_json_bytes(&self) -> Result<Vec<u8>, Error> {
+        let bytes = serde_json::to_vec_pretty(self).map_err(|e| Error::from_str(&e.to_string()))?;
+        if bytes.len() > POOL_METADATA_MAX_BYTES {
+            return Err(Error::from_str(&format!("pool metadata is {} bytes, the maximum is {}", bytes.len(), POOL_METADATA_MAX_BYTES)));
+        }
+        Ok(bytes)
+    }
+
+    /// Parses the bytes fetched from the URL of `pool_metadata` after checking they match its hash
+    pub fn from_hosted(pool_metadata: &PoolMetadata, bytes: &[u8]) -> Result<PoolMetadataJson, Error> {
+        let hash = hash_pool_metadata(bytes);
+        if hash != pool_metadata.pool_metadata_hash() {
+            return Err(Error::from_str(&format!(
+                "pool metadata at {} has hash {} but the registration expects {}",
+                pool_metadata.url().url(),
+                hash.to_hex(),
+                pool_metadata.pool_metadata_hash().to_hex(),
+            )));
+        }
+        Self::from_json_bytes(bytes)
+    }
+}
+
+/// Extended metadata as specified by CIP-6
+/// https://cips.cardano.org/cips/cip6/
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CIP6ExtendedMetadata {
+    /// must increase with every change so caches can tell the latest version
+    pub serial: u64,
+    pub pool: CIP6Pool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CIP6Pool {
+    /// hex encoded pool id
+    pub id: String,
+    /// ISO 3166-1 alpha-3 country code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<CIP6PoolStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact: Option<CIP6Contact>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_assets: Option<CIP6MediaAssets>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub itn: Option<CIP6Itn>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CIP6PoolStatus {
+    Active,
+    Retired,
+    Offline,
+    Experimental,
+    Private,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CIP6Contact {
+    /// which of the other fields to use first e.g. "email"
+    pub primary: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facebook: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github: Option<String>,
+    /// RSS feed URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feed: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telegram: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub twitter: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CIP6MediaAssets {
+    pub icon_png_64x64: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo_png: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo_svg: Option<String>,
+    /// #RRGGBB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_fg: Option<String>,
+    /// #RRGGBB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_bg: Option<String>,
+}
+
+/// Proof of ownership of an Incentivized Testnet pool
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CIP6Itn {
+    /// ed25519_pk1... bech32 public key of the ITN pool owner
+    pub owner: String,
+    /// signature of the pool id by the owner key
+    pub witness: String,
+}
+
+impl CIP6ExtendedMetadata {
+    fn validate(&self) -> Result<(), Error> {
+        Ed25519KeyHash::from_hex(&self.pool.id).map_err(|_| Error::from_str(&format!("CIP-6 pool id {} is not a hex encoded pool id", self.pool.id)))?;
+        if let Some(country) = &self.pool.country {
+            if country.len() != 3 || !country.chars().all(|c| c.is_ascii_uppercase()) {
+                return Err(Error::from_str(&format!("CIP-6 country {} is not an ISO 3166-1 alpha-3 code", country)));
+            }
+        }
+        if let Some(media_assets) = &self.pool.media_assets {
+            for color in media_assets.color_fg.iter().chain(media_assets.color_bg.iter()) {
+                let valid = color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+                if !valid {
+                    return Err(Error::from_str(&format!("CIP-6 color {} is not #RRGGBB", color)));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Extended metadata in the format of adapools.org (now cexplorer.io)
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AdapoolsExtendedMetadata {
+    #[serde(default)]
+    pub info: AdapoolsInfo,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub telegram_admin_handle: Vec<String>,
+    /// hex encoded ids of the other pools of the operator
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub my_pool_ids: Vec<String>,
+    /// hex encoded ids of the pools to delegate to once this one is saturated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub when_satured_then_recommend: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AdapoolsInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_png_icon_64x64: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_png_logo: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub social: Option<AdapoolsSocial>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub company: Option<AdapoolsCompany>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub about: Option<AdapoolsAbout>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rss: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AdapoolsSocial {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub twitter_handle: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telegram_handle: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facebook_handle: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub youtube_handle: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub twitch_handle: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discord_handle: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_handle: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AdapoolsCompany {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub addr: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub company_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vat_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AdapoolsAbout {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub me: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub company: Option<String>,
+}
+
+impl AdapoolsExtendedMetadata {
+    fn validate(&self) -> Result<(), Error> {
+        for pool_id in self.my_pool_ids.iter().chain(self.when_satured_then_recommend.iter()) {
+            Ed25519KeyHash::from_hex(pool_id).map_err(|_| Error::from_str(&format!("adapools pool id {} is not a hex encoded pool id", pool_id)))?;
+        }
+        Ok(())
+    }
+}
+
+/// The document at the `extended` URL of the pool metadata
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExtendedPoolMetadata {
+    CIP6(CIP6ExtendedMetadata),
+    Adapools(AdapoolsExtendedMetadata),
+}
+
+impl ExtendedPoolMetadata {
+    /// The format is told apart by the top-level fields: serial and pool for CIP-6, info for adapools
+    pub fn from_json_bytes(bytes: &[u8]) -> Result<ExtendedPoolMetadata, Error> {
+        let json: serde_json::Value = serde_json::from_slice(bytes).map_err(|e| Error::from_str(&format!("invalid extended pool metadata JSON: {}", e)))?;
+        let invalid = |format: &str, e: serde_json::Error| Error::from_str(&format!("invalid {} extended pool metadata: {}", format, e));
+        let metadata = if json.get("serial").is_some() && json.get("pool").is_some() {
+            ExtendedPoolMetadata::CIP6(serde_json::from_value(json).map_err(|e| invalid("CIP-6", e))?)
+        } else if json.get("info").is_some() {
+            ExtendedPoolMetadata::Adapools(serde_json::from_value(json).map_err(|e| invalid("adapools", e))?)
+        } else {
+            return Err(Error::from_str("extended pool metadata is neither CIP-6 (serial and pool) nor adapools (info)"));
+        };
+        metadata.validate()?;
+        Ok(metadata)
+    }
+
+    pub fn to_json_bytes(&self) -> Result<Vec<u8>, Error> {
+        self.validate()?;
+        match self {
+            ExtendedPoolMetadata::CIP6(metadata) => serde_json::to_vec_pretty(metadata),
+            ExtendedPoolMetadata::Adapools(metadata) => serde_json::to_vec_pretty(metadata),
+        }.map_err(|e| Error::from_str(&e.to_string()))
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        match self {
+            ExtendedPoolMetadata::CIP6(metadata) => metadata.validate(),
+            ExtendedPoolMetadata::Adapools(metadata) => metadata.validate(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_metadata_json() {
+        // hand-written metadata formatted the way pools host it, whitespace included
+        let hosted = b"{\n  \"name\": \"Test Pool\",\n  \"description\": \"A pool for tests\",\n  \"ticker\": \"TEST1\",\n  \"homepage\": \"https://example.com\",\n  \"extended\": \"https://example.com/extended.json\"\n}";
+        let metadata = PoolMetadataJson::from_json_bytes(hosted).unwrap();
+        assert_eq!(metadata.ticker(), "TEST1");
+        assert_eq!(metadata.extended(), Some(String::from("https://example.com/extended.json")));
+
+        let pool_metadata = PoolMetadata::new(&URL::new(String::from("https://example.com/pool.json")).unwrap(), &hash_pool_metadata(hosted));
+        assert_eq!(PoolMetadataJson::from_hosted(&pool_metadata, hosted).unwrap(), metadata);
+        // the same JSON formatted differently has another hash
+        assert!(PoolMetadataJson::from_hosted(&pool_metadata, &serde_json::to_vec(&metadata).unwrap()).is_err());
+
+        assert!(PoolMetadataJson::new(String::from("Test"), String::new(), String::from("test"), String::from("https://example.com")).is_err());
+        assert!(PoolMetadataJson::new(String::from("Test"), String::new(), String::from("TOOLONG"), String::from("https://example.com")).is_err());
+        assert!(PoolMetadataJson::new("x".repeat(51), String::new(), String::from("TEST"), String::from("https://example.com")).is_err());
+        assert!(PoolMetadataJson::new(String::from("Test"), "x".repeat(256), String::from("TEST"), String::from("https://example.com")).is_err());
+        let mut long = PoolMetadataJson::new("x".repeat(50), "x".repeat(255), String::from("TEST"), format!("https://{}", "x".repeat(56))).unwrap();
+        assert!(long.to_json_bytes().is_ok());
+        long.set_extended(&URL::new(format!("https://{}", "x".repeat(56))).unwrap());
+        assert!(long.to_json_bytes().is_err(), "over 512 bytes");
+    }
+
+    #[test]
+    fn extended_pool_metadata() {
+        let cip6 = br##"{
+            "serial": 2,
+            "pool": {
+                "id": "0f292fcaa02b8b2f9b3c8f9fd8e0bb21abedb692a6d5058df3ef2735",
+                "country": "JPN",
+                "status": "active",
+                "contact": { "primary": "email", "email": "pool@example.com" },
+                "media_assets": { "icon_png_64x64": "https://example.com/icon.png", "color_fg": "#ff0000" }
+            }
+        }"##;
+        match ExtendedPoolMetadata::from_json_bytes(cip6).unwrap() {
+            ExtendedPoolMetadata::CIP6(metadata) => {
+                assert_eq!(metadata.serial, 2);
+                assert_eq!(metadata.pool.status, Some(CIP6PoolStatus::Active));
+                assert_eq!(metadata.pool.contact.unwrap().email, Some(String::from("pool@example.com")));
+            },
+            other => panic!("expected CIP-6 metadata, found {:?}", other),
+        }
+        let invalid_color = String::from_utf8(cip6.to_vec()).unwrap().replace("#ff0000", "red");
+        assert!(ExtendedPoolMetadata::from_json_bytes(invalid_color.as_bytes()).is_err());
+
+        let adapools = br#"{
+            "info": {
+                "url_png_icon_64x64": "https://example.com/icon.png",
+                "location": "Tokyo, Japan",
+                "social": { "twitter_handle": "pool" },
+                "about": { "me": "operator" }
+            },
+            "my_pool_ids": ["0f292fcaa02b8b2f9b3c8f9fd8e0bb21abedb692a6d5058df3ef2735"]
+        }"#;
+        let metadata = ExtendedPoolMetadata::from_json_bytes(adapools).unwrap();
+        match &metadata {
+            ExtendedPoolMetadata::Adapools(adapools) => assert_eq!(adapools.info.social.as_ref().unwrap().twitter_handle, Some(String::from("pool"))),
+            other => panic!("expected adapools metadata, found {:?}", other),
+        }
+        assert_eq!(ExtendedPoolMetadata::from_json_bytes(&metadata.to_json_bytes().unwrap()).unwrap(), metadata);
+        assert!(ExtendedPoolMetadata::from_json_bytes(b"{\"name\": \"pool\"}").is_err());
+    }
+}
@@ -576,7 +576,7 @@ impl cbor_event::se::Serialize for TransactionOutput {
             Some(DatumEnum::InlineDatum(_)) => true,
             None => false,
         };
-        if !has_data && self.script_ref.is_none() {
+        if !has_data && self.script_ref.is_none() && !self.prefer_babbage_format {
             // Alonzo format
             serializer.write_array(cbor_event::Len::Len(if self.datum_option.is_some() { 3 } else { 2 }))?;
             self.address.serialize(serializer)?;
@@ -670,6 +670,7 @@ impl Deserialize for TransactionOutput {
                         amount,
                         datum_option: data_hash.map(DatumEnum::DatumHash),
                         script_ref: None,
+                        prefer_babbage_format: false,
                     })
                 },
                 // Vasil format
@@ -777,6 +778,7 @@ impl Deserialize for TransactionOutput {
                         amount,
                         datum_option,
                         script_ref,
+                        prefer_babbage_format: true,
                     })
                 },
                 _ => Err(DeserializeFailure::NoVariantMatched.into()),
@@ -1971,7 +1973,7 @@ impl Deserialize for Withdrawals {
 
 impl cbor_event::se::Serialize for TransactionWitnessSet {
     fn serialize<'se, W: Write>(&self, serializer: &'se mut Serializer<W>) -> cbor_event::Result<&'se mut Serializer<W>> {
-        serializer.write_map(cbor_event::Len::Len(match &self.vkeys { Some(_) => 1, None => 0 } + match &self.native_scripts { Some(_) => 1, None => 0 } + match &self.bootstraps { Some(_) => 1, None => 0 } + match &self.plutus_v1_scripts { Some(_) => 1, None => 0 } + match &self.plutus_data { Some(_) => 1, None => 0 } + match &self.redeemers { Some(_) => 1, None => 0 } + match &self.plutus_v2_scripts { Some(_) => 1, None => 0 }))?;
+        serializer.write_map(cbor_event::Len::Len(match &self.vkeys { Some(_) => 1, None => 0 } + match &self.native_scripts { Some(_) => 1, None => 0 } + match &self.bootstraps { Some(_) => 1, None => 0 } + match &self.plutus_v1_scripts { Some(_) => 1, None => 0 } + match &self.plutus_data { Some(_) => 1, None => 0 } + match &self.redeemers { Some(_) => 1, None => 0 } + match &self.plutus_v2_scripts { Some(_) => 1, None => 0 } + match &self.plutus_v3_scripts { Some(_) => 1, None => 0 }))?;
         if let Some(field) = &self.vkeys {
             serializer.write_unsigned_integer(0)?;
             field.serialize(serializer)?;
@@ -2000,6 +2002,10 @@ impl cbor_event::se::Serialize for TransactionWitnessSet {
             serializer.write_unsigned_integer(6)?;
             field.serialize(serializer)?;
         }
+        if let Some(field) = &self.plutus_v3_scripts {
+            serializer.write_unsigned_integer(7)?;
+            field.serialize(serializer)?;
+        }
         Ok(serializer)
     }
 }
@@ -2016,6 +2022,7 @@ impl Deserialize for TransactionWitnessSet {
             let mut plutus_data = None;
             let mut redeemers = None;
             let mut plutus_v2_scripts = None;
+            let mut plutus_v3_scripts = None;
             let mut read = 0;
             while match len { cbor_event::Len::Len(n) => read < n as usize, cbor_event::Len::Indefinite => true, } {
                 match raw.cbor_type()? {
@@ -2083,6 +2090,15 @@ impl Deserialize for TransactionWitnessSet {
                                 Ok(PlutusV2Scripts::deserialize(raw)?)
                             })().map_err(|e| e.annotate("plutus_v2_scripts"))?);
                         },
+                        7 =>  {
+                            if plutus_v3_scripts.is_some() {
+                                return Err(DeserializeFailure::DuplicateKey(Key::Uint(7)).into());
+                            }
+                            plutus_v3_scripts = Some((|| -> Result<_, DeserializeError> {
+                                read_len.read_elems(1)?;
+                                Ok(PlutusV3Scripts::deserialize(raw)?)
+                            })().map_err(|e| e.annotate("plutus_v3_scripts"))?);
+                        },
                         unknown_key => return Err(DeserializeFailure::UnknownKey(Key::Uint(unknown_key)).into()),
                     },
                     CBORType::Text => match raw.text()?.as_str() {
@@ -2107,7 +2123,8 @@ impl Deserialize for TransactionWitnessSet {
                 plutus_v1_scripts,
                 plutus_data,
                 redeemers,
-                plutus_v2_scripts
+                plutus_v2_scripts,
+                plutus_v3_scripts,
             })
         })().map_err(|e| e.annotate("TransactionWitnessSet"))
     }
@@ -3798,6 +3815,7 @@ mod tests {
             amount: val.clone(),
             datum_option: None,
             script_ref: None,
+            prefer_babbage_format: false,
         };
         let mut txo_dh = txo.clone();
         txo_dh.set_datum(&Datum::new_data_hash(&DataHash::from([47u8; DataHash::BYTE_COUNT])));
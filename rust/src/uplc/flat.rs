@@ -0,0 +1,419 @@
+use std::convert::TryFrom;
+
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+
+use crate::error::Error;
+use crate::plutus::PlutusData;
+
+use super::*;
+
+// Flat encoding of UPLC programs as used on-chain (DeBruijn indices, binders aren't encoded)
+// see the "Flat serialisation" appendix of the Plutus Core specification
+
+const TERM_TAG_WIDTH: usize = 4;
+const TYPE_TAG_WIDTH: usize = 4;
+const BUILTIN_TAG_WIDTH: usize = 7;
+
+const TYPE_TAG_INTEGER: u8 = 0;
+const TYPE_TAG_BYTESTRING: u8 = 1;
+const TYPE_TAG_STRING: u8 = 2;
+const TYPE_TAG_UNIT: u8 = 3;
+const TYPE_TAG_BOOL: u8 = 4;
+const TYPE_TAG_PROTO_LIST: u8 = 5;
+const TYPE_TAG_PROTO_PAIR: u8 = 6;
+const TYPE_TAG_APPLY: u8 = 7;
+const TYPE_TAG_DATA: u8 = 8;
+
+/// Nesting of terms, which are decoded recursively
+const MAX_TERM_DEPTH: usize = 1024;
+
+pub(crate) struct Decoder<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+    used_bits: usize,
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(buffer: &'a [u8]) -> Self {
+        Self {
+            buffer,
+            pos: 0,
+            used_bits: 0,
+            depth: 0,
+        }
+    }
+
+    fn bit(&mut self) -> Result<bool, Error> {
+        let byte = self.buffer.get(self.pos).ok_or_else(|| Error::from_str("flat: unexpected end of input"))?;
+        let bit = (byte >> (7 - self.used_bits)) & 1 == 1;
+        self.used_bits += 1;
+        if self.used_bits == 8 {
+            self.used_bits = 0;
+            self.pos += 1;
+        }
+        Ok(bit)
+    }
+
+    fn bits(&mut self, width: usize) -> Result<u8, Error> {
+        let mut out = 0u8;
+        for _ in 0..width {
+            out = (out << 1) | self.bit()? as u8;
+        }
+        Ok(out)
+    }
+
+    /// zeros followed by a one, up to the next byte boundary
+    fn filler(&mut self) -> Result<(), Error> {
+        while !self.bit()? {}
+        Ok(())
+    }
+
+    /// little-endian groups of 7 bits, each preceded by a continuation bit
+    fn big_word(&mut self) -> Result<BigInt, Error> {
+        let mut out = BigInt::from(0u8);
+        let mut shift = 0;
+        loop {
+            let group = self.bits(8)?;
+            out += BigInt::from(group & 0x7f) << shift;
+            shift += 7;
+            if group & 0x80 == 0 {
+                return Ok(out);
+            }
+        }
+    }
+
+    fn word(&mut self) -> Result<u64, Error> {
+        let word = self.big_word()?;
+        u64::try_from(&word).map_err(|_| Error::from_str(&format!("flat: natural {} too large", word)))
+    }
+
+    fn integer(&mut self) -> Result<BigInt, Error> {
+        // zigzag encoding: 0, -1, 1, -2 ... map to 0, 1, 2, 3 ...
+        let zigzag = self.big_word()?;
+        let (half, rem) = zigzag.div_rem(&BigInt::from(2u8));
+        Ok(match rem.sign() {
+            Sign::NoSign => half,
+            _ => -half - 1,
+        })
+    }
+
+    fn bytes(&mut self) -> Result<Vec<u8>, Error> {
+        self.filler()?;
+        let mut out = Vec::new();
+        loop {
+            let chunk_len = self.bits(8)? as usize;
+            if chunk_len == 0 {
+                return Ok(out);
+            }
+            let chunk = self.buffer
+                .get(self.pos..self.pos + chunk_len)
+                .ok_or_else(|| Error::from_str("flat: unexpected end of input"))?;
+            out.extend_from_slice(chunk);
+            self.pos += chunk_len;
+        }
+    }
+
+    fn list<T>(&mut self, mut elem: impl FnMut(&mut Self) -> Result<T, Error>) -> Result<Vec<T>, Error> {
+        let mut out = Vec::new();
+        while self.bit()? {
+            out.push(elem(self)?);
+        }
+        Ok(out)
+    }
+
+    pub fn program(&mut self) -> Result<Program, Error> {
+        let version = (self.word()?, self.word()?, self.word()?);
+        let term = self.term()?;
+        self.filler()?;
+        if self.pos != self.buffer.len() {
+            return Err(Error::from_str(&format!("flat: {} trailing bytes after program", self.buffer.len() - self.pos)));
+        }
+        Ok(Program { version, term })
+    }
+
+    fn term(&mut self) -> Result<Term, Error> {
+        if self.depth == MAX_TERM_DEPTH {
+            return Err(Error::from_str("flat: terms nested too deep"));
+        }
+        self.depth += 1;
+        let term = self.term_body();
+        self.depth -= 1;
+        term
+    }
+
+    // one frame per nesting level: leaves and error formatting are in helpers so it stays small
+    fn term_body(&mut self) -> Result<Term, Error> {
+        match self.bits(TERM_TAG_WIDTH)? {
+            0 => self.word().map(Term::Var),
+            1 => self.term().map(|term| Term::Delay(Box::new(term))),
+            2 => self.term().map(|term| Term::Lambda(Box::new(term))),
+            3 => {
+                let function = self.term()?;
+                self.term().map(|arg| Term::Apply(Box::new(function), Box::new(arg)))
+            },
+            4 => self.typed_constant().map(Term::Constant),
+            5 => self.term().map(|term| Term::Force(Box::new(term))),
+            6 => Ok(Term::Error),
+            7 => self.builtin().map(Term::Builtin),
+            8 => {
+                let tag = self.word()?;
+                self.list(Self::term).map(|fields| Term::Constr(tag, fields))
+            },
+            9 => {
+                let scrutinee = self.term()?;
+                self.list(Self::term).map(|branches| Term::Case(Box::new(scrutinee), branches))
+            },
+            tag => Err(unknown_tag("term", tag)),
+        }
+    }
+
+    fn builtin(&mut self) -> Result<u8, Error> {
+        let builtin = self.bits(BUILTIN_TAG_WIDTH)?;
+        if builtin_name(builtin).is_none() {
+            return Err(unknown_tag("builtin", builtin));
+        }
+        Ok(builtin)
+    }
+
+    fn typed_constant(&mut self) -> Result<Constant, Error> {
+        let tags = self.list(|d| d.bits(TYPE_TAG_WIDTH))?;
+        let mut tags = tags.into_iter();
+        let typ = decode_type(&mut tags)?;
+        if tags.next().is_some() {
+            return Err(Error::from_str("flat: trailing constant type tags"));
+        }
+        self.constant(&typ)
+    }
+
+    fn constant(&mut self, typ: &Type) -> Result<Constant, Error> {
+        Ok(match typ {
+            Type::Integer => Constant::Integer(self.integer()?),
+            Type::ByteString => Constant::ByteString(self.bytes()?),
+            Type::String => Constant::String(
+                String::from_utf8(self.bytes()?).map_err(|e| Error::from_str(&format!("flat: {}", e)))?,
+            ),
+            Type::Unit => Constant::Unit,
+            Type::Bool => Constant::Bool(self.bit()?),
+            Type::List(elem) => Constant::List((**elem).clone(), self.list(|d| d.constant(elem))?),
+            Type::Pair(fst, snd) => Constant::Pair(
+                Box::new(self.constant(fst)?),
+                Box::new(self.constant(snd)?),
+            ),
+            Type::Data => Constant::Data(
+                PlutusData::from_bytes(self.bytes()?).map_err(|e| Error::from_str(&format!("flat: {}", e)))?,
+            ),
+        })
+    }
+}
+
+fn unknown_tag(kind: &str, tag: u8) -> Error {
+    Error::from_str(&format!("flat: unknown {} tag {}", kind, tag))
+}
+
+fn decode_type(tags: &mut impl Iterator<Item = u8>) -> Result<Type, Error> {
+    let tag = tags.next().ok_or_else(|| Error::from_str("flat: missing constant type tag"))?;
+    Ok(match tag {
+        TYPE_TAG_INTEGER => Type::Integer,
+        TYPE_TAG_BYTESTRING => Type::ByteString,
+        TYPE_TAG_STRING => Type::String,
+        TYPE_TAG_UNIT => Type::Unit,
+        TYPE_TAG_BOOL => Type::Bool,
+        TYPE_TAG_DATA => Type::Data,
+        TYPE_TAG_APPLY => match tags.next() {
+            Some(TYPE_TAG_PROTO_LIST) => Type::List(Box::new(decode_type(tags)?)),
+            Some(TYPE_TAG_APPLY) => match tags.next() {
+                Some(TYPE_TAG_PROTO_PAIR) => Type::Pair(Box::new(decode_type(tags)?), Box::new(decode_type(tags)?)),
+                _ => return Err(Error::from_str("flat: invalid constant type application")),
+            },
+            _ => return Err(Error::from_str("flat: invalid constant type application")),
+        },
+        tag => return Err(Error::from_str(&format!("flat: unsupported constant type tag {}", tag))),
+    })
+}
+
+fn encode_type(typ: &Type, tags: &mut Vec<u8>) {
+    match typ {
+        Type::Integer => tags.push(TYPE_TAG_INTEGER),
+        Type::ByteString => tags.push(TYPE_TAG_BYTESTRING),
+        Type::String => tags.push(TYPE_TAG_STRING),
+        Type::Unit => tags.push(TYPE_TAG_UNIT),
+        Type::Bool => tags.push(TYPE_TAG_BOOL),
+        Type::Data => tags.push(TYPE_TAG_DATA),
+        Type::List(elem) => {
+            tags.extend([TYPE_TAG_APPLY, TYPE_TAG_PROTO_LIST]);
+            encode_type(elem, tags);
+        },
+        Type::Pair(fst, snd) => {
+            tags.extend([TYPE_TAG_APPLY, TYPE_TAG_APPLY, TYPE_TAG_PROTO_PAIR]);
+            encode_type(fst, tags);
+            encode_type(snd, tags);
+        },
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Encoder {
+    buffer: Vec<u8>,
+    current: u8,
+    used_bits: usize,
+    pub sizes: SizeBreakdown,
+}
+
+impl Encoder {
+    fn bit_len(&self) -> usize {
+        self.buffer.len() * 8 + self.used_bits
+    }
+
+    fn bit(&mut self, bit: bool) {
+        if bit {
+            self.current |= 1 << (7 - self.used_bits);
+        }
+        self.used_bits += 1;
+        if self.used_bits == 8 {
+            self.buffer.push(self.current);
+            self.current = 0;
+            self.used_bits = 0;
+        }
+    }
+
+    fn bits(&mut self, width: usize, value: u8) {
+        for i in (0..width).rev() {
+            self.bit((value >> i) & 1 == 1);
+        }
+    }
+
+    fn filler(&mut self) {
+        while self.used_bits != 7 {
+            self.bit(false);
+        }
+        self.bit(true);
+    }
+
+    fn big_word(&mut self, word: &BigInt) {
+        let mut rest = word.clone();
+        loop {
+            let group = rest.to_u32_digits().1.first().map_or(0, |low| low & 0x7f) as u8;
+            rest >>= 7;
+            let more = rest.sign() == Sign::Plus;
+            self.bits(8, if more { group | 0x80 } else { group });
+            if !more {
+                return;
+            }
+        }
+    }
+
+    fn word(&mut self, word: u64) {
+        self.big_word(&BigInt::from(word));
+    }
+
+    fn integer(&mut self, integer: &BigInt) {
+        let zigzag = match integer.sign() {
+            Sign::Minus => -(integer * 2u8) - 1u8,
+            _ => integer * 2u8,
+        };
+        self.big_word(&zigzag);
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.filler();
+        for chunk in bytes.chunks(255) {
+            self.buffer.push(chunk.len() as u8);
+            self.buffer.extend_from_slice(chunk);
+        }
+        self.buffer.push(0);
+    }
+
+    fn list<T>(&mut self, elems: &[T], mut elem: impl FnMut(&mut Self, &T)) {
+        for e in elems {
+            self.bit(true);
+            elem(self, e);
+        }
+        self.bit(false);
+    }
+
+    pub fn program(mut self, program: &Program) -> Vec<u8> {
+        self.write_program(program);
+        self.buffer
+    }
+
+    pub fn write_program(&mut self, program: &Program) {
+        self.word(program.version.0);
+        self.word(program.version.1);
+        self.word(program.version.2);
+        self.term(&program.term);
+        self.filler();
+        self.sizes.flat_bytes = self.buffer.len();
+    }
+
+    fn term(&mut self, term: &Term) {
+        self.sizes.terms += 1;
+        let start = self.bit_len();
+        match term {
+            Term::Var(index) => {
+                self.bits(TERM_TAG_WIDTH, 0);
+                self.word(*index);
+            },
+            Term::Delay(body) => {
+                self.bits(TERM_TAG_WIDTH, 1);
+                self.term(body);
+            },
+            Term::Lambda(body) => {
+                self.bits(TERM_TAG_WIDTH, 2);
+                self.term(body);
+            },
+            Term::Apply(function, argument) => {
+                self.bits(TERM_TAG_WIDTH, 3);
+                self.term(function);
+                self.term(argument);
+            },
+            Term::Constant(constant) => {
+                self.bits(TERM_TAG_WIDTH, 4);
+                let mut tags = Vec::new();
+                encode_type(&constant.typ(), &mut tags);
+                self.list(&tags, |e, tag| e.bits(TYPE_TAG_WIDTH, *tag));
+                self.constant(constant);
+                self.sizes.constants += 1;
+                self.sizes.constant_bits += self.bit_len() - start;
+            },
+            Term::Force(body) => {
+                self.bits(TERM_TAG_WIDTH, 5);
+                self.term(body);
+            },
+            Term::Error => self.bits(TERM_TAG_WIDTH, 6),
+            Term::Builtin(builtin) => {
+                self.bits(TERM_TAG_WIDTH, 7);
+                self.bits(BUILTIN_TAG_WIDTH, *builtin);
+                self.sizes.builtins += 1;
+                self.sizes.builtin_bits += self.bit_len() - start;
+            },
+            Term::Constr(tag, fields) => {
+                self.bits(TERM_TAG_WIDTH, 8);
+                self.word(*tag);
+                self.list(fields, |e, field| e.term(field));
+            },
+            Term::Case(scrutinee, branches) => {
+                self.bits(TERM_TAG_WIDTH, 9);
+                self.term(scrutinee);
+                self.list(branches, |e, branch| e.term(branch));
+            },
+        }
+    }
+
+    fn constant(&mut self, constant: &Constant) {
+        match constant {
+            Constant::Integer(integer) => self.integer(integer),
+            Constant::ByteString(bytes) => self.bytes(bytes),
+            Constant::String(string) => self.bytes(string.as_bytes()),
+            Constant::Unit => {},
+            Constant::Bool(b) => self.bit(*b),
+            Constant::List(_typ, elems) => self.list(elems, |e, elem| e.constant(elem)),
+            Constant::Pair(fst, snd) => {
+                self.constant(fst);
+                self.constant(snd);
+            },
+            Constant::Data(data) => self.bytes(&data.to_bytes()),
+        }
+    }
+}
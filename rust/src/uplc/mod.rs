@@ -0,0 +1,282 @@
+//! Untyped Plutus Core (UPLC) programs as found inside Plutus scripts
+//!
+//! Only what's needed to work with compiled scripts is supported: decoding/encoding the flat format,
+//! the textual `(program 1.0.0 ...)` syntax and applying parameters. Nothing here evaluates scripts.
+
+use cbor_event::{de::Deserializer, se::Serializer};
+use num_bigint::BigInt;
+
+use crate::error::Error;
+use crate::plutus::{PlutusData, PlutusList};
+
+pub(crate) mod flat;
+mod parse;
+mod pretty;
+
+/// Builtin functions, indexed by their flat tag
+const BUILTINS: [&str; 87] = [
+    "addInteger", "subtractInteger", "multiplyInteger", "divideInteger", "quotientInteger",
+    "remainderInteger", "modInteger", "equalsInteger", "lessThanInteger", "lessThanEqualsInteger",
+    "appendByteString", "consByteString", "sliceByteString", "lengthOfByteString", "indexByteString",
+    "equalsByteString", "lessThanByteString", "lessThanEqualsByteString", "sha2_256", "sha3_256",
+    "blake2b_256", "verifyEd25519Signature", "appendString", "equalsString", "encodeUtf8",
+    "decodeUtf8", "ifThenElse", "chooseUnit", "trace", "fstPair",
+    "sndPair", "chooseList", "mkCons", "headList", "tailList",
+    "nullList", "chooseData", "constrData", "mapData", "listData",
+    "iData", "bData", "unConstrData", "unMapData", "unListData",
+    "unIData", "unBData", "equalsData", "mkPairData", "mkNilData",
+    "mkNilPairData", "serialiseData", "verifyEcdsaSecp256k1Signature", "verifySchnorrSecp256k1Signature", "bls12_381_G1_add",
+    "bls12_381_G1_neg", "bls12_381_G1_scalarMul", "bls12_381_G1_equal", "bls12_381_G1_compress", "bls12_381_G1_uncompress",
+    "bls12_381_G1_hashToGroup", "bls12_381_G2_add", "bls12_381_G2_neg", "bls12_381_G2_scalarMul", "bls12_381_G2_equal",
+    "bls12_381_G2_compress", "bls12_381_G2_uncompress", "bls12_381_G2_hashToGroup", "bls12_381_millerLoop", "bls12_381_mulMlResult",
+    "bls12_381_finalVerify", "keccak_256", "blake2b_224", "integerToByteString", "byteStringToInteger",
+    "andByteString", "orByteString", "xorByteString", "complementByteString", "readBit",
+    "writeBits", "replicateByte", "shiftByteString", "rotateByteString", "countSetBits",
+    "findFirstSetBit", "ripemd_160",
+];
+
+/// e.g. addInteger for 0
+pub fn builtin_name(tag: u8) -> Option<&'static str> {
+    BUILTINS.get(tag as usize).copied()
+}
+
+pub fn builtin_tag(name: &str) -> Option<u8> {
+    BUILTINS.iter().position(|builtin| *builtin == name).map(|tag| tag as u8)
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Type {
+    Integer,
+    ByteString,
+    String,
+    Unit,
+    Bool,
+    List(Box<Type>),
+    Pair(Box<Type>, Box<Type>),
+    Data,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Constant {
+    Integer(BigInt),
+    ByteString(Vec<u8>),
+    String(String),
+    Unit,
+    Bool(bool),
+    /// the element type is kept so empty lists can be encoded
+    List(Type, Vec<Constant>),
+    Pair(Box<Constant>, Box<Constant>),
+    Data(PlutusData),
+}
+
+impl Constant {
+    pub fn typ(&self) -> Type {
+        match self {
+            Constant::Integer(_) => Type::Integer,
+            Constant::ByteString(_) => Type::ByteString,
+            Constant::String(_) => Type::String,
+            Constant::Unit => Type::Unit,
+            Constant::Bool(_) => Type::Bool,
+            Constant::List(typ, _) => Type::List(Box::new(typ.clone())),
+            Constant::Pair(fst, snd) => Type::Pair(Box::new(fst.typ()), Box::new(snd.typ())),
+            Constant::Data(_) => Type::Data,
+        }
+    }
+}
+
+/// Variables are DeBruijn indices (starting at 1 for the innermost lambda) so lambdas don't name their parameter
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Term {
+    Var(u64),
+    Delay(Box<Term>),
+    Lambda(Box<Term>),
+    Apply(Box<Term>, Box<Term>),
+    Constant(Constant),
+    Force(Box<Term>),
+    Error,
+    /// flat tag of the builtin function
+    Builtin(u8),
+    /// since UPLC 1.1.0 (PlutusV3)
+    Constr(u64, Vec<Term>),
+    /// since UPLC 1.1.0 (PlutusV3)
+    Case(Box<Term>, Vec<Term>),
+}
+
+/// Where the bytes of a script go, to find what to optimize
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SizeBreakdown {
+    /// the flat program and its CBOR bytestring header i.e. what counts towards the transaction size
+    pub script_bytes: usize,
+    pub flat_bytes: usize,
+    pub terms: usize,
+    pub constants: usize,
+    /// bits spent on constant terms, including their tags and types
+    pub constant_bits: usize,
+    pub builtins: usize,
+    /// bits spent on builtin terms, including their tags
+    pub builtin_bits: usize,
+}
+
+/// The textual syntax is supported with Display and FromStr:
+/// `format!("{}", program)` prints it on one line and `format!("{:#}", program)` indents it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Program {
+    pub version: (u64, u64, u64),
+    pub term: Term,
+}
+
+impl Program {
+    pub fn from_flat(bytes: &[u8]) -> Result<Program, Error> {
+        flat::Decoder::new(bytes).program()
+    }
+
+    pub fn to_flat(&self) -> Vec<u8> {
+        flat::Encoder::default().program(self)
+    }
+
+    /// Decodes the bytes of a PlutusV1Script/PlutusV2Script/PlutusV3Script i.e. the flat program wrapped in a CBOR bytestring
+    pub fn from_script_bytes(bytes: &[u8]) -> Result<Program, Error> {
+        let mut raw = Deserializer::from(std::io::Cursor::new(bytes));
+        let flat = raw.bytes().map_err(|e| Error::from_str(&format!("script is not a CBOR bytestring: {}", e)))?;
+        Self::from_flat(&flat)
+    }
+
+    pub fn to_script_bytes(&self) -> Vec<u8> {
+        let mut serializer = Serializer::new_vec();
+        serializer.write_bytes(self.to_flat()).unwrap();
+        serializer.finalize()
+    }
+
+    /// e.g. 1.0.0 for PlutusV1/PlutusV2 scripts and 1.1.0 for PlutusV3 scripts using constr/case
+    pub fn version(&self) -> String {
+        format!("{}.{}.{}", self.version.0, self.version.1, self.version.2)
+    }
+
+    pub fn size_breakdown(&self) -> SizeBreakdown {
+        let mut encoder = flat::Encoder::default();
+        encoder.write_program(self);
+        SizeBreakdown {
+            script_bytes: self.to_script_bytes().len(),
+            ..encoder.sizes
+        }
+    }
+
+    /// Applies the program to `data` as a constant, like parameters are applied to blueprint validators
+    pub fn apply_data(&mut self, data: &PlutusData) {
+        let term = std::mem::replace(&mut self.term, Term::Error);
+        self.term = Term::Apply(Box::new(term), Box::new(Term::Constant(Constant::Data(data.clone()))));
+    }
+}
+
+/// Applies `params` in order to the script in `script_bytes` (flat program wrapped in a CBOR bytestring)
+pub(crate) fn apply_params(script_bytes: &[u8], params: &PlutusList) -> Result<Vec<u8>, Error> {
+    let mut program = Program::from_script_bytes(script_bytes)?;
+    for param in &params.elems {
+        program.apply_data(param);
+    }
+    Ok(program.to_script_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_params_to_identity() {
+        // (program 1.0.0 (lam x x))
+        let identity = hex::decode("46010000200101").unwrap();
+        let program = Program::from_script_bytes(&identity).unwrap();
+        assert_eq!(program.term, Term::Lambda(Box::new(Term::Var(1))));
+        assert_eq!(program.to_script_bytes(), identity);
+
+        let mut params = PlutusList::new();
+        params.add(&PlutusData::new_integer(&crate::ledger::common::value::BigInt::from_str("42").unwrap()));
+        let applied = apply_params(&identity, &params).unwrap();
+        // (program 1.0.0 [(lam x x) (con data (I 42))])
+        assert_eq!(hex::encode(&applied), "4c010000320014c102182a0001");
+        assert_eq!(
+            Program::from_script_bytes(&applied).unwrap().term,
+            Term::Apply(
+                Box::new(Term::Lambda(Box::new(Term::Var(1)))),
+                Box::new(Term::Constant(Constant::Data(PlutusData::from_bytes(vec![0x18, 0x2a]).unwrap()))),
+            ),
+        );
+    }
+
+    #[test]
+    fn flat_roundtrip_constants() {
+        let big = BigInt::parse_bytes(b"-123456789012345678901234567890", 10).unwrap();
+        let program = Program {
+            version: (1, 1, 0),
+            term: Term::Case(
+                Box::new(Term::Constr(3, vec![
+                    Term::Constant(Constant::Integer(big)),
+                    Term::Constant(Constant::ByteString(vec![7; 300])),
+                    Term::Constant(Constant::String(String::from("uplc"))),
+                    Term::Constant(Constant::Unit),
+                    Term::Constant(Constant::List(Type::Bool, vec![Constant::Bool(true), Constant::Bool(false)])),
+                    Term::Constant(Constant::List(Type::Integer, vec![])),
+                    Term::Constant(Constant::Pair(
+                        Box::new(Constant::Integer(BigInt::from(0u8))),
+                        Box::new(Constant::Data(PlutusData::from_bytes(vec![0x42, 1, 2]).unwrap())),
+                    )),
+                ])),
+                vec![Term::Force(Box::new(Term::Delay(Box::new(Term::Builtin(0))))), Term::Error],
+            ),
+        };
+        assert_eq!(Program::from_flat(&program.to_flat()).unwrap(), program);
+    }
+
+    #[test]
+    fn flat_nesting_limit() {
+        // version 1.0.0 then `n` delay tags (0001) around an error (0110), padded with filler
+        let nested = |n: usize| {
+            let mut bits = "0001".repeat(n) + "0110";
+            bits += &"0".repeat(7 - bits.len() % 8);
+            bits += "1";
+            let mut flat = vec![1, 0, 0];
+            for byte in bits.as_bytes().chunks(8) {
+                flat.push(u8::from_str_radix(std::str::from_utf8(byte).unwrap(), 2).unwrap());
+            }
+            flat
+        };
+        // the error term sits at the maximum depth of 1024
+        let mut term = Term::Error;
+        for _ in 0..1023 {
+            term = Term::Delay(Box::new(term));
+        }
+        assert_eq!(Program::from_flat(&nested(1023)).unwrap().term, term);
+        assert_eq!(Program::from_flat(&nested(1024)).unwrap_err().to_string(), "flat: terms nested too deep");
+        assert!(Program::from_flat(&nested(100_000)).is_err());
+    }
+
+    #[test]
+    fn text_roundtrip() {
+        let text = "(program 1.1.0 (lam i_0 (lam i_1 [[(builtin addInteger) i_0] (force (delay (case (constr 2 i_1 (con integer -5)) (error) (con (list (pair bool data)) [(True, (Constr 0 [I 1, B #00, List [], Map [(I 1, I 2)]]))]) (con string \"a \\\"q\\\"\\n\") (con unit ()) (con bytestring #cafe))))])))";
+        let program = text.parse::<Program>().unwrap();
+        assert_eq!(program.to_string(), text);
+        assert_eq!(Program::from_flat(&program.to_flat()).unwrap().to_string(), text);
+        // applications of several arguments and any variable names are accepted
+        let applied = "(program 1.0.0 (lam f (lam x [f x x])))".parse::<Program>().unwrap();
+        assert_eq!(applied.to_string(), "(program 1.0.0 (lam i_0 (lam i_1 [[i_0 i_1] i_1])))");
+        assert_eq!(format!("{:#}", applied), "(program 1.0.0\n  (lam i_0\n    (lam i_1\n      [[i_0\n          i_1]\n        i_1])))");
+
+        assert!("(program 1.0.0 (lam x y))".parse::<Program>().is_err());
+        assert!("(program 1.0.0 (builtin notABuiltin))".parse::<Program>().is_err());
+        assert!("(program 1.0 (error))".parse::<Program>().is_err());
+    }
+
+    #[test]
+    fn size_breakdown() {
+        let program = "(program 1.0.0 [(lam x x) (con data (I 42))])".parse::<Program>().unwrap();
+        assert_eq!(program.version(), "1.0.0");
+        let sizes = program.size_breakdown();
+        assert_eq!(sizes.script_bytes, 13);
+        assert_eq!(sizes.flat_bytes, 12);
+        assert_eq!(sizes.terms, 4);
+        assert_eq!(sizes.constants, 1);
+        // tag, type list, alignment and the 2 bytes of data in a single chunk
+        assert_eq!(sizes.constant_bits, 4 + 6 + 2 + 8 * 4);
+        assert_eq!(sizes.builtins, 0);
+    }
+}
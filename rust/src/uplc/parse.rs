@@ -0,0 +1,336 @@
+use std::str::FromStr;
+
+use crate::ledger::common::value::{self, to_bignum};
+use crate::plutus::{ConstrPlutusData, PlutusMap};
+
+use super::*;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    /// keywords, names, numbers, versions and #bytestrings
+    Word(String),
+    Str(String),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => tokens.push(Token::LParen),
+            ')' => tokens.push(Token::RParen),
+            '[' => tokens.push(Token::LBracket),
+            ']' => tokens.push(Token::RBracket),
+            ',' => tokens.push(Token::Comma),
+            '"' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => string.push('\n'),
+                            Some('r') => string.push('\r'),
+                            Some('t') => string.push('\t'),
+                            Some(c @ ('"' | '\\')) => string.push(c),
+                            other => return Err(Error::from_str(&format!("uplc: invalid string escape {:?}", other))),
+                        },
+                        Some(c) => string.push(c),
+                        None => return Err(Error::from_str("uplc: unterminated string")),
+                    }
+                }
+                tokens.push(Token::Str(string));
+            },
+            c if c.is_whitespace() => {},
+            c => {
+                let mut word = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "()[],\"".contains(c) {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            },
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// names bound by the enclosing lambdas, innermost last
+    scope: Vec<String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, Error> {
+        let token = self.tokens.get(self.pos).cloned().ok_or_else(|| Error::from_str("uplc: unexpected end of input"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), Error> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(Error::from_str(&format!("uplc: expected {:?}, found {:?}", expected, token))),
+        }
+    }
+
+    fn word(&mut self) -> Result<String, Error> {
+        match self.next()? {
+            Token::Word(word) => Ok(word),
+            token => Err(Error::from_str(&format!("uplc: expected a name or number, found {:?}", token))),
+        }
+    }
+
+    fn natural(&mut self) -> Result<u64, Error> {
+        let word = self.word()?;
+        word.parse().map_err(|_| Error::from_str(&format!("uplc: invalid natural {}", word)))
+    }
+
+    fn integer(&mut self) -> Result<BigInt, Error> {
+        let word = self.word()?;
+        BigInt::parse_bytes(word.as_bytes(), 10).ok_or_else(|| Error::from_str(&format!("uplc: invalid integer {}", word)))
+    }
+
+    fn bytestring(&mut self) -> Result<Vec<u8>, Error> {
+        let word = self.word()?;
+        match word.strip_prefix('#') {
+            Some(bytes) => hex::decode(bytes).map_err(|e| Error::from_str(&format!("uplc: invalid bytestring {}: {}", word, e))),
+            None => Err(Error::from_str(&format!("uplc: expected a #bytestring, found {}", word))),
+        }
+    }
+
+    /// comma separated elements between brackets
+    fn list<T>(&mut self, mut elem: impl FnMut(&mut Self) -> Result<T, Error>) -> Result<Vec<T>, Error> {
+        self.expect(Token::LBracket)?;
+        let mut elems = Vec::new();
+        if self.peek() == Some(&Token::RBracket) {
+            self.pos += 1;
+            return Ok(elems);
+        }
+        loop {
+            elems.push(elem(self)?);
+            match self.next()? {
+                Token::Comma => {},
+                Token::RBracket => return Ok(elems),
+                token => return Err(Error::from_str(&format!("uplc: expected , or ], found {:?}", token))),
+            }
+        }
+    }
+
+    fn program(&mut self) -> Result<Program, Error> {
+        self.expect(Token::LParen)?;
+        self.keyword("program")?;
+        let version_str = self.word()?;
+        let version = version_str
+            .split('.')
+            .map(|n| n.parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+            .filter(|version| version.len() == 3)
+            .ok_or_else(|| Error::from_str(&format!("uplc: invalid version {}", version_str)))?;
+        let term = self.term()?;
+        self.expect(Token::RParen)?;
+        if self.pos != self.tokens.len() {
+            return Err(Error::from_str("uplc: trailing input after program"));
+        }
+        Ok(Program {
+            version: (version[0], version[1], version[2]),
+            term,
+        })
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), Error> {
+        match self.word()? {
+            word if word == keyword => Ok(()),
+            word => Err(Error::from_str(&format!("uplc: expected {}, found {}", keyword, word))),
+        }
+    }
+
+    fn term(&mut self) -> Result<Term, Error> {
+        match self.next()? {
+            Token::Word(name) => match self.scope.iter().rev().position(|bound| *bound == name) {
+                Some(index) => Ok(Term::Var(index as u64 + 1)),
+                None => Err(Error::from_str(&format!("uplc: unbound variable {}", name))),
+            },
+            Token::LBracket => {
+                let mut term = self.term()?;
+                let mut arguments = 0;
+                while self.peek() != Some(&Token::RBracket) {
+                    term = Term::Apply(Box::new(term), Box::new(self.term()?));
+                    arguments += 1;
+                }
+                self.pos += 1;
+                match arguments {
+                    0 => Err(Error::from_str("uplc: application without arguments")),
+                    _ => Ok(term),
+                }
+            },
+            Token::LParen => {
+                let keyword = self.word()?;
+                let term = match keyword.as_str() {
+                    "lam" => {
+                        let name = self.word()?;
+                        self.scope.push(name);
+                        let body = self.term();
+                        self.scope.pop();
+                        Term::Lambda(Box::new(body?))
+                    },
+                    "delay" => Term::Delay(Box::new(self.term()?)),
+                    "force" => Term::Force(Box::new(self.term()?)),
+                    "error" => Term::Error,
+                    "builtin" => {
+                        let name = self.word()?;
+                        Term::Builtin(builtin_tag(&name).ok_or_else(|| Error::from_str(&format!("uplc: unknown builtin {}", name)))?)
+                    },
+                    "con" => {
+                        let typ = self.typ()?;
+                        Term::Constant(self.constant(&typ)?)
+                    },
+                    "constr" => {
+                        let tag = self.natural()?;
+                        Term::Constr(tag, self.terms_until_paren()?)
+                    },
+                    "case" => {
+                        let scrutinee = self.term()?;
+                        Term::Case(Box::new(scrutinee), self.terms_until_paren()?)
+                    },
+                    keyword => return Err(Error::from_str(&format!("uplc: unknown term {}", keyword))),
+                };
+                self.expect(Token::RParen)?;
+                Ok(term)
+            },
+            token => Err(Error::from_str(&format!("uplc: expected a term, found {:?}", token))),
+        }
+    }
+
+    fn terms_until_paren(&mut self) -> Result<Vec<Term>, Error> {
+        let mut terms = Vec::new();
+        while self.peek() != Some(&Token::RParen) {
+            terms.push(self.term()?);
+        }
+        Ok(terms)
+    }
+
+    fn typ(&mut self) -> Result<Type, Error> {
+        match self.next()? {
+            Token::Word(name) => match name.as_str() {
+                "integer" => Ok(Type::Integer),
+                "bytestring" => Ok(Type::ByteString),
+                "string" => Ok(Type::String),
+                "unit" => Ok(Type::Unit),
+                "bool" => Ok(Type::Bool),
+                "data" => Ok(Type::Data),
+                name => Err(Error::from_str(&format!("uplc: unknown type {}", name))),
+            },
+            Token::LParen => {
+                let typ = match self.word()?.as_str() {
+                    "list" => Type::List(Box::new(self.typ()?)),
+                    "pair" => Type::Pair(Box::new(self.typ()?), Box::new(self.typ()?)),
+                    name => return Err(Error::from_str(&format!("uplc: unknown type {}", name))),
+                };
+                self.expect(Token::RParen)?;
+                Ok(typ)
+            },
+            token => Err(Error::from_str(&format!("uplc: expected a type, found {:?}", token))),
+        }
+    }
+
+    fn constant(&mut self, typ: &Type) -> Result<Constant, Error> {
+        Ok(match typ {
+            Type::Integer => Constant::Integer(self.integer()?),
+            Type::ByteString => Constant::ByteString(self.bytestring()?),
+            Type::String => match self.next()? {
+                Token::Str(string) => Constant::String(string),
+                token => return Err(Error::from_str(&format!("uplc: expected a string, found {:?}", token))),
+            },
+            Type::Unit => {
+                self.expect(Token::LParen)?;
+                self.expect(Token::RParen)?;
+                Constant::Unit
+            },
+            Type::Bool => match self.word()?.as_str() {
+                "True" => Constant::Bool(true),
+                "False" => Constant::Bool(false),
+                word => return Err(Error::from_str(&format!("uplc: expected True or False, found {}", word))),
+            },
+            Type::List(elem) => Constant::List((**elem).clone(), self.list(|p| p.constant(elem))?),
+            Type::Pair(fst, snd) => {
+                self.expect(Token::LParen)?;
+                let fst = self.constant(fst)?;
+                self.expect(Token::Comma)?;
+                let snd = self.constant(snd)?;
+                self.expect(Token::RParen)?;
+                Constant::Pair(Box::new(fst), Box::new(snd))
+            },
+            Type::Data => Constant::Data(self.data()?),
+        })
+    }
+
+    /// parentheses around data are optional
+    fn data(&mut self) -> Result<PlutusData, Error> {
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let data = self.data()?;
+            self.expect(Token::RParen)?;
+            return Ok(data);
+        }
+        Ok(match self.word()?.as_str() {
+            "I" => PlutusData::new_integer(&value::BigInt::from_str(&self.integer()?.to_string())?),
+            "B" => PlutusData::new_bytes(self.bytestring()?),
+            "List" => PlutusData::new_list(&self.data_list()?),
+            "Map" => {
+                let mut map = PlutusMap::new();
+                for (key, value) in self.list(|p| {
+                    p.expect(Token::LParen)?;
+                    let key = p.data()?;
+                    p.expect(Token::Comma)?;
+                    let value = p.data()?;
+                    p.expect(Token::RParen)?;
+                    Ok((key, value))
+                })? {
+                    map.insert(&key, &value);
+                }
+                PlutusData::new_map(&map)
+            },
+            "Constr" => {
+                let alternative = self.natural()?;
+                PlutusData::new_constr_plutus_data(&ConstrPlutusData::new(&to_bignum(alternative), &self.data_list()?))
+            },
+            word => return Err(Error::from_str(&format!("uplc: unknown data constructor {}", word))),
+        })
+    }
+
+    fn data_list(&mut self) -> Result<PlutusList, Error> {
+        let mut list = PlutusList::new();
+        for elem in self.list(|p| p.data())? {
+            list.add(&elem);
+        }
+        Ok(list)
+    }
+}
+
+impl FromStr for Program {
+    type Err = Error;
+
+    /// Parses the textual syntax e.g. (program 1.0.0 (lam x x))
+    fn from_str(text: &str) -> Result<Program, Error> {
+        Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+            scope: Vec::new(),
+        }.program()
+    }
+}
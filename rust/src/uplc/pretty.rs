@@ -0,0 +1,187 @@
+use std::fmt;
+
+use crate::plutus::{PlutusData, PlutusDataKind};
+
+use super::*;
+
+// Variables are named after the depth of the lambda binding them: i_0 for the outermost one
+
+struct Printer<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    /// indent nested terms on new lines
+    pretty: bool,
+}
+
+impl<'a, 'b> Printer<'a, 'b> {
+    fn separator(&mut self, indent: usize) -> fmt::Result {
+        if self.pretty {
+            write!(self.f, "\n{:width$}", "", width = indent * 2)
+        } else {
+            write!(self.f, " ")
+        }
+    }
+
+    fn term(&mut self, term: &Term, level: u64, indent: usize) -> fmt::Result {
+        match term {
+            Term::Var(index) => match level.checked_sub(*index) {
+                Some(binder) if *index > 0 => write!(self.f, "i_{}", binder),
+                _ => write!(self.f, "free_{}", index),
+            },
+            Term::Delay(body) => self.compound("delay", &[body], level, indent),
+            Term::Force(body) => self.compound("force", &[body], level, indent),
+            Term::Lambda(body) => {
+                write!(self.f, "(lam i_{}", level)?;
+                self.separator(indent + 1)?;
+                self.term(body, level + 1, indent + 1)?;
+                write!(self.f, ")")
+            },
+            Term::Apply(function, argument) => {
+                write!(self.f, "[")?;
+                self.term(function, level, indent + 1)?;
+                self.separator(indent + 1)?;
+                self.term(argument, level, indent + 1)?;
+                write!(self.f, "]")
+            },
+            Term::Constant(constant) => write!(self.f, "(con {} {})", constant.typ(), ConstantValue(constant)),
+            Term::Error => write!(self.f, "(error)"),
+            Term::Builtin(builtin) => write!(self.f, "(builtin {})", builtin_name(*builtin).unwrap_or("unknown")),
+            Term::Constr(tag, fields) => {
+                let fields = fields.iter().collect::<Vec<_>>();
+                self.compound(&format!("constr {}", tag), &fields, level, indent)
+            },
+            Term::Case(scrutinee, branches) => {
+                let terms = std::iter::once(&**scrutinee).chain(branches.iter()).collect::<Vec<_>>();
+                self.compound("case", &terms, level, indent)
+            },
+        }
+    }
+
+    fn compound(&mut self, keyword: &str, terms: &[&Term], level: u64, indent: usize) -> fmt::Result {
+        write!(self.f, "({}", keyword)?;
+        for term in terms {
+            self.separator(indent + 1)?;
+            self.term(term, level, indent + 1)?;
+        }
+        write!(self.f, ")")
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pretty = f.alternate();
+        write!(f, "(program {}", self.version())?;
+        let mut printer = Printer { f, pretty };
+        printer.separator(1)?;
+        printer.term(&self.term, 0, 1)?;
+        write!(printer.f, ")")
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pretty = f.alternate();
+        Printer { f, pretty }.term(self, 0, 0)
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Integer => write!(f, "integer"),
+            Type::ByteString => write!(f, "bytestring"),
+            Type::String => write!(f, "string"),
+            Type::Unit => write!(f, "unit"),
+            Type::Bool => write!(f, "bool"),
+            Type::List(elem) => write!(f, "(list {})", elem),
+            Type::Pair(fst, snd) => write!(f, "(pair {} {})", fst, snd),
+            Type::Data => write!(f, "data"),
+        }
+    }
+}
+
+/// The value of a constant without its type
+struct ConstantValue<'a>(&'a Constant);
+
+impl<'a> fmt::Display for ConstantValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Constant::Integer(integer) => write!(f, "{}", integer),
+            Constant::ByteString(bytes) => write!(f, "#{}", hex::encode(bytes)),
+            Constant::String(string) => {
+                write!(f, "\"")?;
+                for c in string.chars() {
+                    match c {
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        '\r' => write!(f, "\\r")?,
+                        '\t' => write!(f, "\\t")?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                write!(f, "\"")
+            },
+            Constant::Unit => write!(f, "()"),
+            Constant::Bool(true) => write!(f, "True"),
+            Constant::Bool(false) => write!(f, "False"),
+            Constant::List(_typ, elems) => {
+                write!(f, "[")?;
+                for (i, elem) in elems.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", ConstantValue(elem))?;
+                }
+                write!(f, "]")
+            },
+            Constant::Pair(fst, snd) => write!(f, "({}, {})", ConstantValue(fst), ConstantValue(snd)),
+            Constant::Data(data) => {
+                write!(f, "(")?;
+                write_data(f, data)?;
+                write!(f, ")")
+            },
+        }
+    }
+}
+
+/// e.g. Constr 0 [I 1, B #00, List [], Map [(I 1, I 2)]]
+fn write_data(f: &mut fmt::Formatter<'_>, data: &PlutusData) -> fmt::Result {
+    let write_list = |f: &mut fmt::Formatter<'_>, list: &PlutusList| -> fmt::Result {
+        write!(f, "[")?;
+        for (i, elem) in list.elems.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write_data(f, elem)?;
+        }
+        write!(f, "]")
+    };
+    match data.kind() {
+        PlutusDataKind::ConstrPlutusData => {
+            let constr = data.as_constr_plutus_data().unwrap();
+            write!(f, "Constr {} ", constr.alternative().to_str())?;
+            write_list(f, &constr.data())
+        },
+        PlutusDataKind::Map => {
+            let map = data.as_map().unwrap();
+            write!(f, "Map [")?;
+            for (i, key) in map.keys().elems.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "(")?;
+                write_data(f, key)?;
+                write!(f, ", ")?;
+                write_data(f, &map.get(key).unwrap())?;
+                write!(f, ")")?;
+            }
+            write!(f, "]")
+        },
+        PlutusDataKind::List => {
+            write!(f, "List ")?;
+            write_list(f, &data.as_list().unwrap())
+        },
+        PlutusDataKind::Integer => write!(f, "I {}", data.as_integer().unwrap().to_str()),
+        PlutusDataKind::Bytes => write!(f, "B #{}", hex::encode(data.as_bytes().unwrap())),
+    }
+}